@@ -0,0 +1,311 @@
+use super::helpers::{get_advance_by_id, insert_recovery, map_advance, ADVANCE_SELECT};
+use super::types::{
+    AdvanceLedgerEntry, AdvanceRecoveryInput, OutstandingAdvance, SalaryAdvance, SalaryAdvanceInput,
+};
+use crate::dates::{format_date, month_bounds, parse_date};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::round2;
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use tauri::State;
+
+/// Validate an advance and return its disbursement date and recovery start
+/// month in canonical form
+fn validate_advance(advance: &SalaryAdvanceInput) -> Result<(String, String), String> {
+    let disbursed_on = parse_date(&advance.disbursed_on, "disbursement date")?;
+    let (start, _) = month_bounds(&advance.recovery_start_month)?;
+
+    if !advance.amount.is_finite() || advance.amount <= 0.0 {
+        return Err("Advance amount must be greater than zero".to_string());
+    }
+    match advance.recovery_type.as_str() {
+        "fixed" if advance.recovery_value > 0.0 => {}
+        "percentage" if advance.recovery_value > 0.0 && advance.recovery_value <= 100.0 => {}
+        "fixed" | "percentage" => {
+            return Err("Recovery instalment must be a positive amount or percentage".to_string())
+        }
+        other => return Err(format!("Unknown recovery type '{other}'")),
+    }
+    if start.format("%Y-%m").to_string() < disbursed_on.format("%Y-%m").to_string() {
+        return Err("Recovery cannot start before the advance is disbursed".to_string());
+    }
+
+    Ok((format_date(disbursed_on), start.format("%Y-%m").to_string()))
+}
+
+#[tauri::command]
+pub fn get_salary_advances(
+    _state: State<AppState>,
+    employee_id: Option<i64>,
+    outstanding_only: Option<bool>,
+) -> Result<Vec<SalaryAdvance>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ADVANCE_SELECT}
+            WHERE (?1 IS NULL OR a.employee_id = ?1)
+            ORDER BY a.disbursed_on DESC, a.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let advances = stmt
+        .query_map(params![employee_id], map_advance)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(advances
+        .into_iter()
+        .filter(|a| !outstanding_only.unwrap_or(false) || a.outstanding > 0.0)
+        .collect())
+}
+
+#[tauri::command]
+pub fn create_salary_advance(
+    _state: State<AppState>,
+    advance: SalaryAdvanceInput,
+) -> Result<SalaryAdvance, String> {
+    let conn = db::get_connection()?;
+
+    let (disbursed_on, start_month) = validate_advance(&advance)?;
+    get_employee_by_id(&conn, advance.employee_id)?.ok_or("Employee not found")?;
+
+    conn.execute(
+        "INSERT INTO salary_advances (
+            employee_id, disbursed_on, amount, reason, recovery_type, recovery_value,
+            recovery_start_month
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            advance.employee_id,
+            disbursed_on,
+            advance.amount,
+            advance.reason,
+            advance.recovery_type,
+            advance.recovery_value,
+            start_month,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(advance)) = get_advance_by_id(&conn, id) {
+        Ok(advance)
+    } else {
+        Err("Failed to retrieve advance".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_salary_advance(
+    _state: State<AppState>,
+    id: i64,
+    advance: SalaryAdvanceInput,
+) -> Result<SalaryAdvance, String> {
+    let conn = db::get_connection()?;
+
+    let (disbursed_on, start_month) = validate_advance(&advance)?;
+    let existing = get_advance_by_id(&conn, id)?.ok_or("Advance not found")?;
+    if existing.employee_id != advance.employee_id && existing.recovered > 0.0 {
+        return Err("Advance has recoveries and cannot be moved to another employee".to_string());
+    }
+    if advance.amount < existing.recovered {
+        return Err(format!(
+            "Amount cannot be less than the {} already recovered",
+            existing.recovered
+        ));
+    }
+
+    conn.execute(
+        "UPDATE salary_advances SET
+            employee_id = ?1, disbursed_on = ?2, amount = ?3, reason = ?4, recovery_type = ?5,
+            recovery_value = ?6, recovery_start_month = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            advance.employee_id,
+            disbursed_on,
+            advance.amount,
+            advance.reason,
+            advance.recovery_type,
+            advance.recovery_value,
+            start_month,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(advance)) = get_advance_by_id(&conn, id) {
+        Ok(advance)
+    } else {
+        Err("Failed to retrieve advance".to_string())
+    }
+}
+
+/// Delete an advance that nothing has been recovered against yet
+#[tauri::command]
+pub fn delete_salary_advance(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let advance = get_advance_by_id(&conn, id)?.ok_or("Advance not found")?;
+    if advance.recovered > 0.0 {
+        return Err("Advance has recoveries and cannot be deleted".to_string());
+    }
+
+    conn.execute("DELETE FROM salary_advances WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Record a recovery made outside payroll, such as a cash repayment
+#[tauri::command]
+pub fn add_advance_recovery(
+    _state: State<AppState>,
+    recovery: AdvanceRecoveryInput,
+) -> Result<SalaryAdvance, String> {
+    let conn = db::get_connection()?;
+
+    let advance = get_advance_by_id(&conn, recovery.advance_id)?.ok_or("Advance not found")?;
+    let recovered_on = parse_date(&recovery.recovered_on, "recovery date")?;
+    if !recovery.amount.is_finite() || recovery.amount <= 0.0 {
+        return Err("Recovery amount must be greater than zero".to_string());
+    }
+    if recovery.amount > advance.outstanding {
+        return Err(format!(
+            "Recovery exceeds the outstanding balance of {}",
+            advance.outstanding
+        ));
+    }
+
+    insert_recovery(
+        &conn,
+        advance.id,
+        None,
+        &format_date(recovered_on),
+        recovery.amount,
+        recovery.remarks.as_deref(),
+    )?;
+
+    if let Ok(Some(advance)) = get_advance_by_id(&conn, advance.id) {
+        Ok(advance)
+    } else {
+        Err("Failed to retrieve advance".to_string())
+    }
+}
+
+/// Disbursements and recoveries of an employee in date order with the
+/// running outstanding balance
+#[tauri::command]
+pub fn get_advance_ledger(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Vec<AdvanceLedgerEntry>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT disbursed_on, id, 'disbursement', IFNULL(reason, ''), amount, NULL, 0, id
+            FROM salary_advances WHERE employee_id = ?1
+            UNION ALL
+            SELECT r.recovered_on, r.advance_id, 'recovery', IFNULL(r.remarks, ''), r.amount,
+                p.month, 1, r.id
+            FROM advance_recoveries r
+            JOIN salary_advances a ON a.id = r.advance_id
+            LEFT JOIN pay_run_lines l ON l.id = r.line_id
+            LEFT JOIN pay_runs p ON p.id = l.pay_run_id
+            WHERE a.employee_id = ?1
+            ORDER BY 1, 7, 8",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([employee_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut balance = 0.0;
+    let mut entries = Vec::new();
+    for (date, advance_id, kind, note, amount, pay_run_month) in rows {
+        let (debit, credit, description) = if kind == "disbursement" {
+            balance += amount;
+            (amount, 0.0, format!("Advance #{advance_id}"))
+        } else {
+            balance -= amount;
+            let source = match &pay_run_month {
+                Some(month) => format!("Recovered in payroll for {month}"),
+                None => "Recovered".to_string(),
+            };
+            (0.0, amount, source)
+        };
+        entries.push(AdvanceLedgerEntry {
+            date,
+            advance_id,
+            kind,
+            description: if note.is_empty() {
+                description
+            } else {
+                format!("{description} - {note}")
+            },
+            debit,
+            credit,
+            balance: round2(balance),
+            pay_run_month,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Employees marked past who still owe advances, to be recovered at full and
+/// final settlement
+#[tauri::command]
+pub fn get_past_employee_outstanding_advances(
+    _state: State<AppState>,
+) -> Result<Vec<OutstandingAdvance>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.name, e.essid, e.exit_date, COUNT(a.id), SUM(a.amount),
+                SUM(IFNULL((SELECT SUM(r.amount) FROM advance_recoveries r
+                    WHERE r.advance_id = a.id), 0))
+            FROM employees e
+            JOIN salary_advances a ON a.employee_id = e.id
+            WHERE e.employment_status = 'past'
+            GROUP BY e.id
+            ORDER BY e.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            let total_advanced: f64 = row.get(5)?;
+            let total_recovered: f64 = row.get(6)?;
+            Ok(OutstandingAdvance {
+                employee_id: row.get(0)?,
+                employee_name: row.get(1)?,
+                essid: row.get(2)?,
+                exit_date: row.get(3)?,
+                advance_count: row.get(4)?,
+                total_advanced,
+                total_recovered: round2(total_recovered),
+                outstanding: round2(total_advanced - total_recovered),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows.into_iter().filter(|r| r.outstanding > 0.0).collect())
+}
@@ -0,0 +1,189 @@
+use rusqlite::{params, Row};
+
+use super::types::SalaryAdvance;
+use crate::payroll::helpers::round2;
+
+pub const ADVANCE_SELECT: &str = "SELECT a.id, a.employee_id, e.name, e.essid, a.disbursed_on,
+        a.amount, a.reason, a.recovery_type, a.recovery_value, a.recovery_start_month,
+        IFNULL((SELECT SUM(r.amount) FROM advance_recoveries r WHERE r.advance_id = a.id), 0),
+        a.created_at, a.updated_at
+    FROM salary_advances a
+    JOIN employees e ON e.id = a.employee_id";
+
+/// Pay line item code of advance recoveries
+pub const RECOVERY_CODE: &str = "ADV";
+
+pub fn map_advance(row: &Row) -> rusqlite::Result<SalaryAdvance> {
+    let amount: f64 = row.get(5)?;
+    let recovered: f64 = row.get(10)?;
+    Ok(SalaryAdvance {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        disbursed_on: row.get(4)?,
+        amount,
+        reason: row.get(6)?,
+        recovery_type: row.get(7)?,
+        recovery_value: row.get(8)?,
+        recovery_start_month: row.get(9)?,
+        recovered: round2(recovered),
+        outstanding: round2(amount - recovered),
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+pub fn get_advance_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<SalaryAdvance>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ADVANCE_SELECT} WHERE a.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_advance) {
+        Ok(advance) => Ok(Some(advance)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Advances of an employee that still have a balance, oldest first
+pub fn open_advances(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Vec<SalaryAdvance>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ADVANCE_SELECT} WHERE a.employee_id = ?1 ORDER BY a.disbursed_on, a.id"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let advances = stmt
+        .query_map([employee_id], map_advance)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(advances
+        .into_iter()
+        .filter(|a| a.outstanding > 0.0)
+        .collect())
+}
+
+/// Instalments payroll should recover from an employee in a `YYYY-MM` month
+/// as `(advance id, amount)`. Percentage schedules apply to the net pay before
+/// any recovery, and the total never exceeds that net pay.
+pub fn advance_instalments(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    month: &str,
+    net_pay: f64,
+) -> Result<Vec<(i64, f64)>, String> {
+    let mut available = net_pay.max(0.0);
+    let mut instalments = Vec::new();
+
+    for advance in open_advances(conn, employee_id)? {
+        if advance.recovery_start_month.as_str() > month || available <= 0.0 {
+            continue;
+        }
+        let scheduled = match advance.recovery_type.as_str() {
+            "percentage" => (net_pay * advance.recovery_value / 100.0).round(),
+            _ => advance.recovery_value,
+        };
+        let amount = round2(scheduled.min(advance.outstanding).min(available));
+        if amount > 0.0 {
+            available -= amount;
+            instalments.push((advance.id, amount));
+        }
+    }
+
+    Ok(instalments)
+}
+
+pub fn insert_recovery(
+    conn: &rusqlite::Connection,
+    advance_id: i64,
+    line_id: Option<i64>,
+    recovered_on: &str,
+    amount: f64,
+    remarks: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO advance_recoveries (advance_id, line_id, recovered_on, amount, remarks)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![advance_id, line_id, recovered_on, amount, remarks],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn advance(conn: &rusqlite::Connection, amount: f64, kind: &str, value: f64, start: &str) {
+        conn.execute(
+            "INSERT INTO salary_advances (
+                employee_id, disbursed_on, amount, recovery_type, recovery_value,
+                recovery_start_month
+            ) VALUES (1, '2024-06-01', ?1, ?2, ?3, ?4)",
+            params![amount, kind, value, start],
+        )
+        .unwrap();
+    }
+
+    fn with_advances() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        advance(&conn, 5000.0, "fixed", 2000.0, "2024-06");
+        advance(&conn, 1500.0, "percentage", 10.0, "2024-07");
+        advance(&conn, 3000.0, "fixed", 1000.0, "2024-09");
+        conn
+    }
+
+    #[test]
+    fn instalments_follow_each_schedule_from_its_start_month() {
+        let conn = with_advances();
+
+        assert_eq!(
+            advance_instalments(&conn, 1, "2024-06", 15000.0).unwrap(),
+            vec![(1, 2000.0)]
+        );
+        assert_eq!(
+            advance_instalments(&conn, 1, "2024-07", 15000.0).unwrap(),
+            vec![(1, 2000.0), (2, 1500.0)]
+        );
+    }
+
+    #[test]
+    fn instalments_stop_at_the_balance_and_the_net_pay() {
+        let conn = with_advances();
+        insert_recovery(&conn, 1, None, "2024-06-30", 4000.0, None).unwrap();
+
+        let advance = get_advance_by_id(&conn, 1).unwrap().unwrap();
+        assert_eq!((advance.recovered, advance.outstanding), (4000.0, 1000.0));
+        assert_eq!(
+            advance_instalments(&conn, 1, "2024-07", 15000.0).unwrap(),
+            vec![(1, 1000.0), (2, 1500.0)]
+        );
+        assert_eq!(
+            advance_instalments(&conn, 1, "2024-07", 1100.0).unwrap(),
+            vec![(1, 1000.0), (2, 100.0)]
+        );
+
+        insert_recovery(&conn, 1, None, "2024-07-31", 1000.0, None).unwrap();
+        let open: Vec<i64> = open_advances(&conn, 1)
+            .unwrap()
+            .iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(open, vec![2, 3]);
+    }
+}
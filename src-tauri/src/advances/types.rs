@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalaryAdvance {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub disbursed_on: String,
+    pub amount: f64,
+    pub reason: Option<String>,
+    /// `fixed` amount per month or `percentage` of the net pay
+    pub recovery_type: String,
+    pub recovery_value: f64,
+    pub recovery_start_month: String,
+    pub recovered: f64,
+    pub outstanding: f64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalaryAdvanceInput {
+    pub employee_id: i64,
+    pub disbursed_on: String,
+    pub amount: f64,
+    pub reason: Option<String>,
+    pub recovery_type: String,
+    pub recovery_value: f64,
+    pub recovery_start_month: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvanceRecoveryInput {
+    pub advance_id: i64,
+    pub recovered_on: String,
+    pub amount: f64,
+    pub remarks: Option<String>,
+}
+
+/// One row of an employee's advance ledger, oldest first
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvanceLedgerEntry {
+    pub date: String,
+    pub advance_id: i64,
+    /// `disbursement` or `recovery`
+    pub kind: String,
+    pub description: String,
+    pub debit: f64,
+    pub credit: f64,
+    pub balance: f64,
+    /// Month of the pay run a recovery was deducted in
+    pub pay_run_month: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutstandingAdvance {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub exit_date: Option<String>,
+    pub advance_count: i64,
+    pub total_advanced: f64,
+    pub total_recovered: f64,
+    pub outstanding: f64,
+}
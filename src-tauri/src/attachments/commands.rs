@@ -0,0 +1,96 @@
+use super::helpers::{get_attachment_by_id, map_attachment};
+use super::types::{AttachmentInput, EmployeeAttachment};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::files::{delete_attachment_file, save_attachment_file};
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use std::path::Path;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_employee_attachments(
+    _state: State<AppState>,
+    employee_id: i64,
+    category: Option<String>,
+) -> Result<Vec<EmployeeAttachment>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM employee_attachments
+            WHERE employee_id = ?1 AND (?2 IS NULL OR category = ?2)
+            ORDER BY created_at DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let attachments = stmt
+        .query_map(params![employee_id, category], map_attachment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(attachments)
+}
+
+#[tauri::command]
+pub fn add_employee_attachment(
+    _state: State<AppState>,
+    attachment: AttachmentInput,
+) -> Result<EmployeeAttachment, String> {
+    let conn = db::get_connection()?;
+
+    if attachment.category.trim().is_empty() {
+        return Err("Attachment category is required".to_string());
+    }
+    if attachment.title.trim().is_empty() {
+        return Err("Attachment title is required".to_string());
+    }
+    get_employee_by_id(&conn, attachment.employee_id)?.ok_or("Employee not found")?;
+
+    let source_path = Path::new(&attachment.source_path);
+    let file_name = source_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stored_path = save_attachment_file(source_path, attachment.employee_id)?;
+
+    conn.execute(
+        "INSERT INTO employee_attachments (
+            employee_id, category, title, file_path, file_name, reference, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            attachment.employee_id,
+            attachment.category.trim().to_lowercase(),
+            attachment.title.trim(),
+            stored_path.to_string_lossy(),
+            file_name,
+            attachment.reference,
+            attachment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(attachment)) = get_attachment_by_id(&conn, id) {
+        Ok(attachment)
+    } else {
+        Err("Failed to retrieve attachment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_employee_attachment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let attachment = get_attachment_by_id(&conn, id)?.ok_or("Attachment not found")?;
+
+    conn.execute(
+        "DELETE FROM employee_attachments WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    delete_attachment_file(Path::new(&attachment.file_path))?;
+
+    Ok(())
+}
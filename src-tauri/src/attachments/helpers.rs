@@ -0,0 +1,94 @@
+use rusqlite::{params, Row};
+use std::path::Path;
+
+use super::types::EmployeeAttachment;
+use crate::files::{delete_attachment_file, write_attachment_file};
+
+pub fn map_attachment(row: &Row) -> rusqlite::Result<EmployeeAttachment> {
+    Ok(EmployeeAttachment {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        category: row.get(2)?,
+        title: row.get(3)?,
+        file_path: row.get(4)?,
+        file_name: row.get(5)?,
+        reference: row.get(6)?,
+        remarks: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+pub fn get_attachment_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<EmployeeAttachment>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM employee_attachments WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_attachment) {
+        Ok(attachment) => Ok(Some(attachment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Save generated content as an attachment, replacing the earlier attachment
+/// of the same category and reference together with its file
+pub fn replace_generated_attachment(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    category: &str,
+    reference: &str,
+    title: &str,
+    file_name: &str,
+    content: &[u8],
+) -> Result<EmployeeAttachment, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_path FROM employee_attachments
+            WHERE employee_id = ?1 AND category = ?2 AND reference = ?3",
+        )
+        .map_err(|e| e.to_string())?;
+    let previous = stmt
+        .query_map(params![employee_id, category, reference], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin");
+    let path = write_attachment_file(employee_id, extension, content)?;
+
+    conn.execute(
+        "DELETE FROM employee_attachments
+         WHERE employee_id = ?1 AND category = ?2 AND reference = ?3",
+        params![employee_id, category, reference],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO employee_attachments (
+            employee_id, category, title, file_path, file_name, reference
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            employee_id,
+            category,
+            title,
+            path.to_string_lossy(),
+            file_name,
+            reference,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+
+    for old_path in previous {
+        delete_attachment_file(Path::new(&old_path))?;
+    }
+
+    get_attachment_by_id(conn, id)?.ok_or_else(|| "Failed to retrieve attachment".to_string())
+}
@@ -0,0 +1,3 @@
+pub mod commands;
+pub mod helpers;
+pub mod types;
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployeeAttachment {
+    pub id: i64,
+    pub employee_id: i64,
+    pub category: String,
+    pub title: String,
+    pub file_path: String,
+    pub file_name: String,
+    /// What the attachment belongs to, such as the month of a payslip
+    pub reference: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInput {
+    pub employee_id: i64,
+    pub category: String,
+    pub title: String,
+    pub source_path: String,
+    pub reference: Option<String>,
+    pub remarks: Option<String>,
+}
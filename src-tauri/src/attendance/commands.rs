@@ -0,0 +1,317 @@
+use super::helpers::{
+    attendance_summaries, map_attendance, normalize_status, upsert_attendance, NewAttendance,
+    ATTENDANCE_SELECT,
+};
+use super::types::{
+    AttendanceFilter, AttendanceImportResult, AttendanceRecord, AttendanceSummary, ImportError,
+    SiteAttendanceInput,
+};
+use crate::clients::helpers::get_site_by_id;
+use crate::csv::{header_index, parse_csv};
+use crate::dates::{format_date, month_bounds, parse_date, parse_date_flexible};
+use crate::db;
+use crate::deployments::helpers::ACTIVE_ON_DATE;
+use crate::payroll::helpers::ensure_month_unlocked;
+use crate::state::AppState;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
+use std::fs;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_attendance(
+    _state: State<AppState>,
+    filter: AttendanceFilter,
+) -> Result<Vec<AttendanceRecord>, String> {
+    let conn = db::get_connection()?;
+
+    let date_from = format_date(parse_date(&filter.date_from, "from date")?);
+    let date_to = format_date(parse_date(&filter.date_to, "to date")?);
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ATTENDANCE_SELECT}
+            WHERE a.attendance_date BETWEEN ?1 AND ?2
+              AND (?3 IS NULL OR a.employee_id = ?3)
+              AND (?4 IS NULL OR a.site_id = ?4)
+            ORDER BY a.attendance_date, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let records = stmt
+        .query_map(
+            params![date_from, date_to, filter.employee_id, filter.site_id],
+            map_attendance,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(records)
+}
+
+/// Mark attendance for a site and date in one go. Employees deployed at the
+/// site without an explicit entry get `default_status`, when given.
+#[tauri::command]
+pub fn mark_site_attendance(
+    _state: State<AppState>,
+    attendance: SiteAttendanceInput,
+) -> Result<i64, String> {
+    let mut conn = db::get_connection()?;
+
+    let date = format_date(parse_date(&attendance.attendance_date, "attendance date")?);
+    ensure_month_unlocked(&conn, &date[..7])?;
+    get_site_by_id(&conn, attendance.site_id)?.ok_or("Site not found")?;
+
+    let mut statuses: Vec<(i64, &'static str, i64, Option<String>)> = Vec::new();
+    for entry in &attendance.entries {
+        statuses.push((
+            entry.employee_id,
+            normalize_status(&entry.status)?,
+            entry.ot_shifts.unwrap_or(0).max(0),
+            entry.remarks.clone(),
+        ));
+    }
+
+    if let Some(default_status) = attendance
+        .default_status
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+    {
+        let default_status = normalize_status(default_status)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT d.employee_id FROM deployments d WHERE {ACTIVE_ON_DATE} AND d.site_id = ?2"
+            ))
+            .map_err(|e| e.to_string())?;
+        let deployed = stmt
+            .query_map(params![date, attendance.site_id], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for employee_id in deployed {
+            if !statuses.iter().any(|(id, ..)| *id == employee_id) {
+                statuses.push((employee_id, default_status, 0, None));
+            }
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (employee_id, status, ot_shifts, remarks) in &statuses {
+        upsert_attendance(
+            &tx,
+            &NewAttendance {
+                employee_id: *employee_id,
+                attendance_date: date.clone(),
+                status: status.to_string(),
+                site_id: Some(attendance.site_id),
+                shift_template_id: attendance.shift_template_id,
+                ot_shifts: *ot_shifts,
+                source: "manual",
+                remarks: remarks.clone(),
+            },
+        )?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(statuses.len() as i64)
+}
+
+#[tauri::command]
+pub fn delete_attendance(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let date: Option<String> = conn
+        .query_row(
+            "SELECT attendance_date FROM attendance WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(date) = date {
+        ensure_month_unlocked(&conn, &date[..7])?;
+    }
+
+    conn.execute("DELETE FROM attendance WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+struct AttendanceColumns {
+    essid: usize,
+    date: usize,
+    status: usize,
+    site: Option<usize>,
+    shift: Option<usize>,
+    ot_shifts: Option<usize>,
+    remarks: Option<usize>,
+}
+
+/// Lookups already resolved during an import, keyed by the ESSID as given and
+/// by the lowercased site and shift names, matching how each is looked up
+#[derive(Default)]
+struct ImportLookups {
+    employees: HashMap<String, Option<i64>>,
+    sites: HashMap<String, Result<i64, String>>,
+    shifts: HashMap<String, Option<i64>>,
+}
+
+fn import_attendance_row(
+    conn: &Connection,
+    columns: &AttendanceColumns,
+    row: &[String],
+    lookups: &mut ImportLookups,
+) -> Result<(), String> {
+    let cell = |col: usize| row.get(col).map(|v| v.trim()).unwrap_or("");
+    let optional = |col: Option<usize>| col.map(cell).filter(|v| !v.is_empty());
+
+    let essid = cell(columns.essid);
+    if essid.is_empty() {
+        return Err("ESSID is empty".to_string());
+    }
+    if !lookups.employees.contains_key(essid) {
+        let id = conn
+            .query_row(
+                "SELECT id FROM employees WHERE essid = ?1",
+                [essid],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        lookups.employees.insert(essid.to_string(), id);
+    }
+    let employee_id =
+        lookups.employees[essid].ok_or_else(|| format!("No employee with ESSID '{essid}'"))?;
+
+    let date = parse_date_flexible(cell(columns.date), "date")?;
+    let status = normalize_status(cell(columns.status))?;
+
+    let site_id = match optional(columns.site) {
+        Some(name) => {
+            let key = name.to_lowercase();
+            if !lookups.sites.contains_key(&key) {
+                let mut stmt = conn
+                    .prepare("SELECT id FROM sites WHERE LOWER(name) = ?1")
+                    .map_err(|e| e.to_string())?;
+                let ids = stmt
+                    .query_map([&key], |row| row.get::<_, i64>(0))
+                    .map_err(|e| e.to_string())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?;
+                let site = match ids.as_slice() {
+                    [id] => Ok(*id),
+                    [] => Err(format!("No site named '{name}'")),
+                    _ => Err(format!("More than one site is named '{name}'")),
+                };
+                lookups.sites.insert(key.clone(), site);
+            }
+            Some(lookups.sites[&key].clone()?)
+        }
+        None => None,
+    };
+
+    let shift_template_id = match optional(columns.shift) {
+        Some(shift) => {
+            let key = shift.to_lowercase();
+            if !lookups.shifts.contains_key(&key) {
+                let id = conn
+                    .query_row(
+                        "SELECT id FROM shift_templates WHERE LOWER(code) = ?1 OR LOWER(name) = ?1",
+                        [&key],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?;
+                lookups.shifts.insert(key.clone(), id);
+            }
+            Some(lookups.shifts[&key].ok_or_else(|| format!("No shift named '{shift}'"))?)
+        }
+        None => None,
+    };
+
+    let ot_shifts = match optional(columns.ot_shifts) {
+        Some(value) => value
+            .parse::<i64>()
+            .ok()
+            .filter(|v| *v >= 0)
+            .ok_or_else(|| format!("Invalid OT shifts '{value}'"))?,
+        None => 0,
+    };
+
+    upsert_attendance(
+        conn,
+        &NewAttendance {
+            employee_id,
+            attendance_date: format_date(date),
+            status: status.to_string(),
+            site_id,
+            shift_template_id,
+            ot_shifts,
+            source: "csv",
+            remarks: optional(columns.remarks).map(str::to_string),
+        },
+    )
+}
+
+/// Import attendance from a CSV with `ESSID`, `Date` and `Status` columns and
+/// optional `Site`, `Shift`, `OT Shifts` and `Remarks` columns. Rows that fail
+/// validation are reported and skipped; the rest are imported.
+#[tauri::command]
+pub fn import_attendance_csv(
+    _state: State<AppState>,
+    file_path: String,
+) -> Result<AttendanceImportResult, String> {
+    let mut conn = db::get_connection()?;
+
+    let content =
+        fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+    let mut rows = parse_csv(&content).into_iter();
+    let (_, header) = rows.next().ok_or("The CSV file is empty")?;
+
+    let columns = AttendanceColumns {
+        essid: header_index(&header, &["essid", "employee id", "emp id"])
+            .ok_or("The CSV file has no ESSID column")?,
+        date: header_index(&header, &["date", "attendance date"])
+            .ok_or("The CSV file has no Date column")?,
+        status: header_index(&header, &["status", "attendance"])
+            .ok_or("The CSV file has no Status column")?,
+        site: header_index(&header, &["site", "site name"]),
+        shift: header_index(&header, &["shift"]),
+        ot_shifts: header_index(&header, &["ot shifts", "ot", "overtime shifts"]),
+        remarks: header_index(&header, &["remarks", "note", "notes"]),
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut lookups = ImportLookups::default();
+    let mut imported = 0;
+    let mut errors = Vec::new();
+
+    for (line, row) in rows {
+        match import_attendance_row(&tx, &columns, &row, &mut lookups) {
+            Ok(()) => imported += 1,
+            Err(message) => errors.push(ImportError { line, message }),
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(AttendanceImportResult { imported, errors })
+}
+
+/// Days present, absences and OT shifts per employee for a `YYYY-MM` month
+#[tauri::command]
+pub fn get_monthly_attendance_summary(
+    _state: State<AppState>,
+    month: String,
+    site_id: Option<i64>,
+) -> Result<Vec<AttendanceSummary>, String> {
+    let conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    attendance_summaries(&conn, first, last, site_id, None)
+}
@@ -0,0 +1,255 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Row};
+
+use super::types::{AttendanceRecord, AttendanceSummary};
+use crate::dates::format_date;
+use crate::payroll::helpers::ensure_month_unlocked;
+
+pub const ATTENDANCE_SELECT: &str = "SELECT a.id, a.employee_id, e.name, e.essid,
+        a.attendance_date, a.status, a.site_id, s.name, a.shift_template_id, t.code,
+        a.ot_shifts, a.source, a.remarks
+    FROM attendance a
+    JOIN employees e ON e.id = a.employee_id
+    LEFT JOIN sites s ON s.id = a.site_id
+    LEFT JOIN shift_templates t ON t.id = a.shift_template_id";
+
+pub struct NewAttendance {
+    pub employee_id: i64,
+    pub attendance_date: String,
+    pub status: String,
+    pub site_id: Option<i64>,
+    pub shift_template_id: Option<i64>,
+    pub ot_shifts: i64,
+    pub source: &'static str,
+    pub remarks: Option<String>,
+}
+
+pub fn map_attendance(row: &Row) -> rusqlite::Result<AttendanceRecord> {
+    Ok(AttendanceRecord {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        attendance_date: row.get(4)?,
+        status: row.get(5)?,
+        site_id: row.get(6)?,
+        site_name: row.get(7)?,
+        shift_template_id: row.get(8)?,
+        shift_code: row.get(9)?,
+        ot_shifts: row.get(10)?,
+        source: row.get(11)?,
+        remarks: row.get(12)?,
+    })
+}
+
+/// Map the usual register abbreviations and spellings to a stored status
+pub fn normalize_status(raw: &str) -> Result<&'static str, String> {
+    let key: String = raw
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    match key.as_str() {
+        "p" | "present" => Ok("present"),
+        "a" | "ab" | "absent" => Ok("absent"),
+        "l" | "lv" | "leave" => Ok("leave"),
+        "wo" | "off" | "weeklyoff" => Ok("weekly_off"),
+        "h" | "ph" | "holiday" => Ok("holiday"),
+        _ => Err(format!(
+            "Invalid attendance status '{raw}', expected present, absent, leave, weekly off or holiday"
+        )),
+    }
+}
+
+/// Insert or replace the attendance of an employee for a date, unless the
+/// payroll for its month is locked
+pub fn upsert_attendance(
+    conn: &rusqlite::Connection,
+    attendance: &NewAttendance,
+) -> Result<(), String> {
+    ensure_month_unlocked(conn, &attendance.attendance_date[..7])?;
+
+    conn.execute(
+        "INSERT INTO attendance (
+            employee_id, attendance_date, status, site_id, shift_template_id, ot_shifts,
+            source, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT (employee_id, attendance_date) DO UPDATE SET
+            status = excluded.status,
+            site_id = excluded.site_id,
+            shift_template_id = excluded.shift_template_id,
+            ot_shifts = excluded.ot_shifts,
+            source = excluded.source,
+            remarks = excluded.remarks,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            attendance.employee_id,
+            attendance.attendance_date,
+            attendance.status,
+            attendance.site_id,
+            attendance.shift_template_id,
+            attendance.ot_shifts,
+            attendance.source,
+            attendance.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Per-employee attendance totals between two dates (inclusive)
+pub fn attendance_summaries(
+    conn: &rusqlite::Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    site_id: Option<i64>,
+    employee_id: Option<i64>,
+) -> Result<Vec<AttendanceSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.name, e.essid,
+                SUM(a.status = 'present'),
+                SUM(a.status = 'absent'),
+                SUM(a.status = 'leave'),
+                SUM(a.status = 'weekly_off'),
+                SUM(a.status = 'holiday'),
+                SUM(a.ot_shifts),
+                COUNT(a.id)
+            FROM attendance a
+            JOIN employees e ON e.id = a.employee_id
+            WHERE a.attendance_date BETWEEN ?1 AND ?2
+              AND (?3 IS NULL OR a.site_id = ?3)
+              AND (?4 IS NULL OR a.employee_id = ?4)
+            GROUP BY e.id
+            ORDER BY e.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let summaries = stmt
+        .query_map(
+            params![format_date(from), format_date(to), site_id, employee_id],
+            |row| {
+                Ok(AttendanceSummary {
+                    employee_id: row.get(0)?,
+                    employee_name: row.get(1)?,
+                    essid: row.get(2)?,
+                    present_days: row.get(3)?,
+                    absent_days: row.get(4)?,
+                    leave_days: row.get(5)?,
+                    weekly_offs: row.get(6)?,
+                    holidays: row.get(7)?,
+                    ot_shifts: row.get(8)?,
+                    marked_days: row.get(9)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn employee(conn: &rusqlite::Connection, essid: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES (?1, ?2, 'current')",
+            params![format!("Emp {essid}"), essid],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn present(employee_id: i64, date: &str) -> NewAttendance {
+        NewAttendance {
+            employee_id,
+            attendance_date: date.to_string(),
+            status: "present".to_string(),
+            site_id: None,
+            shift_template_id: None,
+            ot_shifts: 0,
+            source: "manual",
+            remarks: None,
+        }
+    }
+
+    #[test]
+    fn locked_months_cannot_be_marked() {
+        let conn = open_test_db();
+        let employee_id = employee(&conn, "E1");
+        conn.execute(
+            "INSERT INTO pay_runs (month, status) VALUES ('2024-07', 'locked')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(
+            upsert_attendance(&conn, &present(employee_id, "2024-07-15")),
+            Err("Payroll for 2024-07 is locked".to_string())
+        );
+        upsert_attendance(&conn, &present(employee_id, "2024-08-01")).unwrap();
+
+        let marked: i64 = conn
+            .query_row("SELECT COUNT(*) FROM attendance", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(marked, 1);
+    }
+
+    #[test]
+    fn register_abbreviations_are_normalized() {
+        assert_eq!(normalize_status(" P "), Ok("present"));
+        assert_eq!(normalize_status("Ab"), Ok("absent"));
+        assert_eq!(normalize_status("W/O"), Ok("weekly_off"));
+        assert_eq!(normalize_status("PH"), Ok("holiday"));
+        assert!(normalize_status("half day").is_err());
+    }
+
+    #[test]
+    fn summaries_count_each_status_within_the_range() {
+        let conn = open_test_db();
+        let ravi = employee(&conn, "E1");
+        let anil = employee(&conn, "E2");
+        let mut marked = vec![
+            present(ravi, "2024-06-30"),
+            present(ravi, "2024-07-01"),
+            present(ravi, "2024-07-02"),
+            present(anil, "2024-07-01"),
+        ];
+        marked[2].ot_shifts = 1;
+        for (date, status) in [("2024-07-03", "absent"), ("2024-07-07", "weekly_off")] {
+            let mut attendance = present(ravi, date);
+            attendance.status = status.to_string();
+            marked.push(attendance);
+        }
+        for attendance in &marked {
+            upsert_attendance(&conn, attendance).unwrap();
+        }
+
+        let from = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 7, 31).unwrap();
+        let summaries = attendance_summaries(&conn, from, to, None, None).unwrap();
+        assert_eq!(summaries.len(), 2);
+        let summary = summaries.iter().find(|s| s.employee_id == ravi).unwrap();
+        assert_eq!(
+            (
+                summary.present_days,
+                summary.absent_days,
+                summary.weekly_offs,
+                summary.ot_shifts,
+                summary.marked_days
+            ),
+            (2, 1, 1, 1, 4)
+        );
+
+        let only_anil = attendance_summaries(&conn, from, to, None, Some(anil)).unwrap();
+        assert_eq!(only_anil.len(), 1);
+        assert_eq!(only_anil[0].present_days, 1);
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceRecord {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub attendance_date: String,
+    pub status: String,
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub shift_template_id: Option<i64>,
+    pub shift_code: Option<String>,
+    pub ot_shifts: i64,
+    pub source: String,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceFilter {
+    pub employee_id: Option<i64>,
+    pub site_id: Option<i64>,
+    pub date_from: String,
+    pub date_to: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceEntry {
+    pub employee_id: i64,
+    pub status: String,
+    pub ot_shifts: Option<i64>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteAttendanceInput {
+    pub site_id: i64,
+    pub attendance_date: String,
+    pub shift_template_id: Option<i64>,
+    /// Status for employees deployed at the site that have no entry of their own
+    pub default_status: Option<String>,
+    pub entries: Vec<AttendanceEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceImportResult {
+    pub imported: i64,
+    pub errors: Vec<ImportError>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceSummary {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub present_days: i64,
+    pub absent_days: i64,
+    pub leave_days: i64,
+    pub weekly_offs: i64,
+    pub holidays: i64,
+    pub ot_shifts: i64,
+    pub marked_days: i64,
+}
@@ -0,0 +1,329 @@
+use super::helpers::{
+    get_bank_account_by_employee, get_layout_by_id, map_bank_account, map_layout,
+    normalize_account_number, normalize_ifsc, render_payment_file, validate_bank_account,
+    validate_layout, BANK_ACCOUNT_SELECT,
+};
+use super::types::{
+    BankAccount, BankAccountInput, BankPaymentLayout, BankPaymentLayoutInput, BankPaymentPreview,
+    BankPaymentRow, InvalidBankAccount,
+};
+use crate::dates::{parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::{get_pay_run_by_id, get_pay_run_lines};
+use crate::payslips::helpers::month_title;
+use crate::state::AppState;
+use rusqlite::{params, Connection, Result};
+use std::fs;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_bank_accounts(_state: State<AppState>) -> Result<Vec<BankAccount>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{BANK_ACCOUNT_SELECT} ORDER BY e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let accounts = stmt
+        .query_map([], map_bank_account)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(accounts)
+}
+
+#[tauri::command]
+pub fn get_employee_bank_account(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Option<BankAccount>, String> {
+    let conn = db::get_connection()?;
+    get_bank_account_by_employee(&conn, employee_id)
+}
+
+/// Create or replace the salary account of an employee
+#[tauri::command]
+pub fn save_employee_bank_account(
+    _state: State<AppState>,
+    account: BankAccountInput,
+) -> Result<BankAccount, String> {
+    let conn = db::get_connection()?;
+
+    if account.account_holder_name.trim().is_empty() {
+        return Err("Account holder name is required".to_string());
+    }
+    let account_number = normalize_account_number(&account.account_number)?;
+    let ifsc = normalize_ifsc(&account.ifsc)?;
+    get_employee_by_id(&conn, account.employee_id)?.ok_or("Employee not found")?;
+
+    conn.execute(
+        "INSERT INTO employee_bank_accounts (
+            employee_id, account_holder_name, account_number, ifsc, bank_name, branch
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(employee_id) DO UPDATE SET
+            account_holder_name = excluded.account_holder_name,
+            account_number = excluded.account_number,
+            ifsc = excluded.ifsc,
+            bank_name = excluded.bank_name,
+            branch = excluded.branch,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            account.employee_id,
+            account.account_holder_name.trim(),
+            account_number,
+            ifsc,
+            account.bank_name,
+            account.branch,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(account)) = get_bank_account_by_employee(&conn, account.employee_id) {
+        Ok(account)
+    } else {
+        Err("Failed to retrieve bank account".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_employee_bank_account(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM employee_bank_accounts WHERE employee_id = ?1",
+        params![employee_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_bank_payment_layouts(_state: State<AppState>) -> Result<Vec<BankPaymentLayout>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, delimiter, include_header, debit_account, transaction_type,
+            narration, date_format, columns, created_at, updated_at
+            FROM bank_payment_layouts ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let layouts = stmt
+        .query_map([], map_layout)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(layouts)
+}
+
+fn check_layout_input(layout: &BankPaymentLayoutInput) -> Result<String, String> {
+    if layout.name.trim().is_empty() {
+        return Err("Layout name is required".to_string());
+    }
+    validate_layout(&layout.delimiter, &layout.date_format, &layout.columns)?;
+    serde_json::to_string(&layout.columns).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_bank_payment_layout(
+    _state: State<AppState>,
+    layout: BankPaymentLayoutInput,
+) -> Result<BankPaymentLayout, String> {
+    let conn = db::get_connection()?;
+
+    let columns = check_layout_input(&layout)?;
+
+    conn.execute(
+        "INSERT INTO bank_payment_layouts (
+            name, delimiter, include_header, debit_account, transaction_type, narration,
+            date_format, columns
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            layout.name.trim(),
+            layout.delimiter,
+            layout.include_header,
+            layout.debit_account,
+            layout.transaction_type,
+            layout.narration,
+            layout.date_format,
+            columns,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Layout with the same name already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(layout)) = get_layout_by_id(&conn, id) {
+        Ok(layout)
+    } else {
+        Err("Failed to retrieve layout".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_bank_payment_layout(
+    _state: State<AppState>,
+    id: i64,
+    layout: BankPaymentLayoutInput,
+) -> Result<BankPaymentLayout, String> {
+    let conn = db::get_connection()?;
+
+    let columns = check_layout_input(&layout)?;
+
+    conn.execute(
+        "UPDATE bank_payment_layouts SET
+            name = ?1, delimiter = ?2, include_header = ?3, debit_account = ?4,
+            transaction_type = ?5, narration = ?6, date_format = ?7, columns = ?8,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?9",
+        params![
+            layout.name.trim(),
+            layout.delimiter,
+            layout.include_header,
+            layout.debit_account,
+            layout.transaction_type,
+            layout.narration,
+            layout.date_format,
+            columns,
+            id,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Layout with the same name already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    if let Ok(Some(layout)) = get_layout_by_id(&conn, id) {
+        Ok(layout)
+    } else {
+        Err("Failed to retrieve layout".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_bank_payment_layout(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM bank_payment_layouts WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Split the net pay of a pay run into payable rows and employees whose bank
+/// details are missing or fail validation
+fn build_payment_preview(conn: &Connection, pay_run_id: i64) -> Result<BankPaymentPreview, String> {
+    let pay_run = get_pay_run_by_id(conn, pay_run_id)?.ok_or("Pay run not found")?;
+
+    let mut rows = Vec::new();
+    let mut invalid = Vec::new();
+    for line in get_pay_run_lines(conn, pay_run_id, None)? {
+        if line.net_pay <= 0.0 {
+            continue;
+        }
+        let account = match line.employee_id {
+            Some(employee_id) => get_bank_account_by_employee(conn, employee_id)?,
+            None => None,
+        };
+        let messages = match &account {
+            Some(account) => validate_bank_account(account),
+            None => vec!["No bank account recorded".to_string()],
+        };
+
+        match account {
+            Some(account) if messages.is_empty() => rows.push(BankPaymentRow {
+                employee_id: account.employee_id,
+                employee_name: line.employee_name,
+                essid: line.essid,
+                beneficiary_name: account.account_holder_name.trim().to_string(),
+                account_number: normalize_account_number(&account.account_number)?,
+                ifsc: normalize_ifsc(&account.ifsc)?,
+                bank_name: account.bank_name,
+                amount: line.net_pay,
+            }),
+            _ => invalid.push(InvalidBankAccount {
+                employee_id: line.employee_id,
+                employee_name: line.employee_name,
+                essid: line.essid,
+                amount: line.net_pay,
+                message: messages.join("; "),
+            }),
+        }
+    }
+
+    Ok(BankPaymentPreview {
+        month: pay_run.month,
+        total_amount: rows.iter().map(|r| r.amount).sum(),
+        rows,
+        invalid,
+    })
+}
+
+/// Preview the salary transfers of a pay run before writing the bank file
+#[tauri::command]
+pub fn get_bank_payment_preview(
+    _state: State<AppState>,
+    pay_run_id: i64,
+) -> Result<BankPaymentPreview, String> {
+    let conn = db::get_connection()?;
+    build_payment_preview(&conn, pay_run_id)
+}
+
+/// Write the bulk-payment upload of a pay run in the chosen layout. Employees
+/// listed as invalid in the preview are left out and have to be paid
+/// separately.
+#[tauri::command]
+pub fn export_bank_payment_file(
+    _state: State<AppState>,
+    pay_run_id: i64,
+    layout_id: i64,
+    payment_date: Option<String>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let layout = get_layout_by_id(&conn, layout_id)?.ok_or("Layout not found")?;
+    validate_layout(&layout.delimiter, &layout.date_format, &layout.columns)?;
+    let payment_date = match payment_date.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date(date, "payment date")?,
+        None => today(),
+    };
+
+    let preview = build_payment_preview(&conn, pay_run_id)?;
+    if preview.rows.is_empty() {
+        return Err("No employees with valid bank accounts to pay".to_string());
+    }
+
+    let content = render_payment_file(
+        &layout,
+        &preview.rows,
+        &month_title(&preview.month),
+        payment_date,
+    );
+    fs::write(&export_path, content).map_err(|e| format!("Failed to write file: {e}"))?;
+
+    Ok(export_path)
+}
@@ -0,0 +1,327 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use rusqlite::Row;
+
+use super::types::{BankAccount, BankPaymentLayout, BankPaymentRow, LayoutColumn};
+use crate::csv::csv_field;
+
+/// Fields a payment layout column can be filled from
+pub const PAYMENT_FIELDS: [&str; 10] = [
+    "beneficiary_name",
+    "account_number",
+    "ifsc",
+    "bank_name",
+    "amount",
+    "narration",
+    "transaction_type",
+    "debit_account",
+    "payment_date",
+    "essid",
+];
+
+pub const BANK_ACCOUNT_SELECT: &str = "SELECT b.id, b.employee_id, e.name, e.essid,
+    b.account_holder_name, b.account_number, b.ifsc, b.bank_name, b.branch,
+    b.created_at, b.updated_at
+    FROM employee_bank_accounts b
+    JOIN employees e ON e.id = b.employee_id";
+
+pub fn map_bank_account(row: &Row) -> rusqlite::Result<BankAccount> {
+    Ok(BankAccount {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        account_holder_name: row.get(4)?,
+        account_number: row.get(5)?,
+        ifsc: row.get(6)?,
+        bank_name: row.get(7)?,
+        branch: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn map_layout(row: &Row) -> rusqlite::Result<BankPaymentLayout> {
+    let columns: String = row.get(8)?;
+    Ok(BankPaymentLayout {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        delimiter: row.get(2)?,
+        include_header: row.get(3)?,
+        debit_account: row.get(4)?,
+        transaction_type: row.get(5)?,
+        narration: row.get(6)?,
+        date_format: row.get(7)?,
+        columns: serde_json::from_str(&columns).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn get_bank_account_by_employee(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Option<BankAccount>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{BANK_ACCOUNT_SELECT} WHERE b.employee_id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([employee_id], map_bank_account) {
+        Ok(account) => Ok(Some(account)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_layout_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<BankPaymentLayout>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, delimiter, include_header, debit_account, transaction_type,
+            narration, date_format, columns, created_at, updated_at
+            FROM bank_payment_layouts WHERE id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_layout) {
+        Ok(layout) => Ok(Some(layout)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// IFSC is four letters for the bank, a zero and six characters for the branch
+pub fn normalize_ifsc(ifsc: &str) -> Result<String, String> {
+    let ifsc = ifsc.trim().to_uppercase();
+    let valid = ifsc.len() == 11
+        && ifsc.chars().enumerate().all(|(i, c)| match i {
+            0..=3 => c.is_ascii_uppercase(),
+            4 => c == '0',
+            _ => c.is_ascii_uppercase() || c.is_ascii_digit(),
+        });
+    if valid {
+        Ok(ifsc)
+    } else {
+        Err(format!(
+            "IFSC '{ifsc}' must be 4 letters, a zero and 6 letters or digits"
+        ))
+    }
+}
+
+/// Account numbers are 9 to 18 digits across Indian banks
+pub fn normalize_account_number(account_number: &str) -> Result<String, String> {
+    let account_number: String = account_number
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if (9..=18).contains(&account_number.len())
+        && account_number.chars().all(|c| c.is_ascii_digit())
+    {
+        Ok(account_number)
+    } else {
+        Err(format!(
+            "Account number '{account_number}' must be 9 to 18 digits"
+        ))
+    }
+}
+
+/// Reasons a stored account cannot be paid into, empty when it is usable
+pub fn validate_bank_account(account: &BankAccount) -> Vec<String> {
+    let mut messages = Vec::new();
+    if account.account_holder_name.trim().is_empty() {
+        messages.push("Account holder name is empty".to_string());
+    }
+    if let Err(e) = normalize_account_number(&account.account_number) {
+        messages.push(e);
+    }
+    if let Err(e) = normalize_ifsc(&account.ifsc) {
+        messages.push(e);
+    }
+    messages
+}
+
+pub fn validate_layout(
+    delimiter: &str,
+    date_format: &str,
+    columns: &[LayoutColumn],
+) -> Result<(), String> {
+    if delimiter.is_empty() {
+        return Err("Delimiter is required".to_string());
+    }
+    if StrftimeItems::new(date_format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Date format '{date_format}' is not valid"));
+    }
+    if columns.is_empty() {
+        return Err("Layout needs at least one column".to_string());
+    }
+    for column in columns {
+        if !PAYMENT_FIELDS.contains(&column.field.as_str()) {
+            return Err(format!(
+                "Unknown field '{}', expected one of: {}",
+                column.field,
+                PAYMENT_FIELDS.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn field_value(
+    field: &str,
+    row: &BankPaymentRow,
+    layout: &BankPaymentLayout,
+    month: &str,
+    payment_date: NaiveDate,
+) -> String {
+    match field {
+        "beneficiary_name" => row.beneficiary_name.clone(),
+        "account_number" => row.account_number.clone(),
+        "ifsc" => row.ifsc.clone(),
+        "bank_name" => row.bank_name.clone().unwrap_or_default(),
+        "amount" => format!("{:.2}", row.amount),
+        "narration" => layout.narration.replace("{month}", month),
+        "transaction_type" => layout.transaction_type.clone(),
+        "debit_account" => layout.debit_account.clone().unwrap_or_default(),
+        "payment_date" => payment_date.format(&layout.date_format).to_string(),
+        "essid" => row.essid.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Render payment rows in a layout. Comma separated files are quoted like any
+/// other CSV; other delimiters are stripped from values since bank parsers
+/// for them rarely understand quoting.
+pub fn render_payment_file(
+    layout: &BankPaymentLayout,
+    rows: &[BankPaymentRow],
+    month: &str,
+    payment_date: NaiveDate,
+) -> String {
+    let delimiter = layout.delimiter.as_str();
+    let escape = |value: String| {
+        if delimiter == "," {
+            csv_field(Some(value))
+        } else {
+            value.replace(delimiter, " ")
+        }
+    };
+
+    let mut lines = Vec::new();
+    if layout.include_header != 0 {
+        lines.push(
+            layout
+                .columns
+                .iter()
+                .map(|c| escape(c.header.clone()))
+                .collect::<Vec<_>>()
+                .join(delimiter),
+        );
+    }
+    for row in rows {
+        lines.push(
+            layout
+                .columns
+                .iter()
+                .map(|c| escape(field_value(&c.field, row, layout, month, payment_date)))
+                .collect::<Vec<_>>()
+                .join(delimiter),
+        );
+    }
+
+    let mut content = lines.join("\r\n");
+    content.push_str("\r\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(header: &str, field: &str) -> LayoutColumn {
+        LayoutColumn {
+            header: header.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    #[test]
+    fn ifsc_is_upper_cased_and_checked() {
+        assert_eq!(
+            normalize_ifsc(" sbin0001234 "),
+            Ok("SBIN0001234".to_string())
+        );
+        assert_eq!(normalize_ifsc("HDFC0ABC123"), Ok("HDFC0ABC123".to_string()));
+        assert!(normalize_ifsc("SBIN1001234").is_err());
+        assert!(normalize_ifsc("SBI00001234").is_err());
+        assert!(normalize_ifsc("SBIN000123").is_err());
+    }
+
+    #[test]
+    fn account_numbers_drop_spaces_and_need_9_to_18_digits() {
+        assert_eq!(
+            normalize_account_number("1234 5678 9012"),
+            Ok("123456789012".to_string())
+        );
+        assert!(normalize_account_number("12345678").is_err());
+        assert!(normalize_account_number("1234567890123456789").is_err());
+        assert!(normalize_account_number("12345678A").is_err());
+    }
+
+    #[test]
+    fn layouts_only_use_known_fields() {
+        let columns = vec![column("Name", "beneficiary_name"), column("Amt", "amount")];
+        assert!(validate_layout(",", "%d/%m/%Y", &columns).is_ok());
+        assert!(validate_layout("", "%d/%m/%Y", &columns).is_err());
+        assert!(validate_layout(",", "%d/%m/%Y", &[]).is_err());
+        assert!(validate_layout(",", "%d/%m/%Y", &[column("PAN", "pan")]).is_err());
+    }
+
+    #[test]
+    fn payment_file_strips_the_delimiter_from_values() {
+        let layout = BankPaymentLayout {
+            id: 1,
+            name: "Pipe".to_string(),
+            delimiter: "|".to_string(),
+            include_header: 1,
+            debit_account: Some("000111222333".to_string()),
+            transaction_type: "NEFT".to_string(),
+            narration: "Salary {month}".to_string(),
+            date_format: "%d/%m/%Y".to_string(),
+            columns: vec![
+                column("Name", "beneficiary_name"),
+                column("Account", "account_number"),
+                column("Amount", "amount"),
+                column("Narration", "narration"),
+                column("Date", "payment_date"),
+            ],
+            created_at: None,
+            updated_at: None,
+        };
+        let rows = vec![BankPaymentRow {
+            employee_id: 1,
+            employee_name: "Ravi".to_string(),
+            essid: "E1".to_string(),
+            beneficiary_name: "Ravi|Kumar".to_string(),
+            account_number: "123456789012".to_string(),
+            ifsc: "SBIN0001234".to_string(),
+            bank_name: None,
+            amount: 18780.4,
+        }];
+
+        let content = render_payment_file(
+            &layout,
+            &rows,
+            "2024-07",
+            NaiveDate::from_ymd_opt(2024, 8, 1).unwrap(),
+        );
+        assert_eq!(
+            content,
+            "Name|Account|Amount|Narration|Date\r\n\
+             Ravi Kumar|123456789012|18780.40|Salary 2024-07|01/08/2024\r\n"
+        );
+    }
+}
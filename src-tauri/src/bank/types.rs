@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankAccount {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub account_holder_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+    pub bank_name: Option<String>,
+    pub branch: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankAccountInput {
+    pub employee_id: i64,
+    pub account_holder_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+    pub bank_name: Option<String>,
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutColumn {
+    pub header: String,
+    /// One of the fields in `PAYMENT_FIELDS`
+    pub field: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankPaymentLayout {
+    pub id: i64,
+    pub name: String,
+    pub delimiter: String,
+    pub include_header: i32,
+    pub debit_account: Option<String>,
+    pub transaction_type: String,
+    /// `{month}` is replaced with the pay run month
+    pub narration: String,
+    pub date_format: String,
+    pub columns: Vec<LayoutColumn>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankPaymentLayoutInput {
+    pub name: String,
+    pub delimiter: String,
+    pub include_header: i32,
+    pub debit_account: Option<String>,
+    pub transaction_type: String,
+    pub narration: String,
+    pub date_format: String,
+    pub columns: Vec<LayoutColumn>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankPaymentRow {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub beneficiary_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+    pub bank_name: Option<String>,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidBankAccount {
+    pub employee_id: Option<i64>,
+    pub employee_name: String,
+    pub essid: String,
+    pub amount: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankPaymentPreview {
+    pub month: String,
+    pub rows: Vec<BankPaymentRow>,
+    pub invalid: Vec<InvalidBankAccount>,
+    pub total_amount: f64,
+}
@@ -0,0 +1,368 @@
+use super::helpers::{
+    map_enrolment, minutes_apart, pair_punches, parse_punch_line, PunchState, ENROLMENT_SELECT,
+};
+use super::types::{
+    AttendanceConflict, BiometricEnrolment, BiometricEnrolmentInput, BiometricImportOptions,
+    BiometricImportResult, PunchShift,
+};
+use crate::attendance::helpers::{upsert_attendance, NewAttendance};
+use crate::attendance::types::ImportError;
+use crate::clients::helpers::get_site_by_id;
+use crate::dates::format_date;
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::ensure_month_unlocked;
+use crate::roster::helpers::parse_time;
+use crate::state::AppState;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use tauri::State;
+
+const DEFAULT_DUPLICATE_WINDOW_MINUTES: i64 = 5;
+const DEFAULT_MAX_SHIFT_HOURS: i64 = 16;
+/// How far an in punch may be from a shift's start time to count as that shift
+const SHIFT_MATCH_MINUTES: i64 = 180;
+
+/// Shifts of one employee that started on the same day
+#[derive(Default)]
+struct PunchDay {
+    /// Shifts with both an in and an out punch
+    shift_count: i64,
+    shift_template_id: Option<i64>,
+    flags: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_biometric_enrolments(
+    _state: State<AppState>,
+    site_id: Option<i64>,
+) -> Result<Vec<BiometricEnrolment>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ENROLMENT_SELECT}
+            WHERE ?1 IS NULL OR b.site_id = ?1 OR b.site_id IS NULL
+            ORDER BY s.name COLLATE NOCASE, b.enrolment_number"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let enrolments = stmt
+        .query_map([site_id], map_enrolment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(enrolments)
+}
+
+/// Map an enrolment number to an employee, replacing any existing mapping of
+/// that number for the same site (or for all sites when no site is given)
+#[tauri::command]
+pub fn save_biometric_enrolment(
+    _state: State<AppState>,
+    enrolment: BiometricEnrolmentInput,
+) -> Result<BiometricEnrolment, String> {
+    let mut conn = db::get_connection()?;
+
+    let enrolment_number = enrolment.enrolment_number.trim();
+    if enrolment_number.is_empty() {
+        return Err("Enrolment number is required".to_string());
+    }
+    get_employee_by_id(&conn, enrolment.employee_id)?.ok_or("Employee not found")?;
+    if let Some(site_id) = enrolment.site_id {
+        get_site_by_id(&conn, site_id)?.ok_or("Site not found")?;
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM biometric_enrolments
+         WHERE IFNULL(site_id, 0) = IFNULL(?1, 0) AND enrolment_number = ?2",
+        params![enrolment.site_id, enrolment_number],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO biometric_enrolments (site_id, enrolment_number, employee_id)
+         VALUES (?1, ?2, ?3)",
+        params![enrolment.site_id, enrolment_number, enrolment.employee_id],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    tx.commit().map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        &format!("{ENROLMENT_SELECT} WHERE b.id = ?1"),
+        [id],
+        map_enrolment,
+    )
+    .map_err(|_| "Failed to retrieve biometric enrolment".to_string())
+}
+
+#[tauri::command]
+pub fn delete_biometric_enrolment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM biometric_enrolments WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Employee for an enrolment number: a site mapping first, then a mapping for
+/// all sites, then an employee whose ESSID is the enrolment number
+fn resolve_enrolment(
+    conn: &Connection,
+    site_id: Option<i64>,
+    enrolment_number: &str,
+) -> Result<Option<(i64, String, String)>, String> {
+    let mapped: Option<i64> = conn
+        .query_row(
+            "SELECT employee_id FROM biometric_enrolments
+             WHERE enrolment_number = ?2 AND (site_id = ?1 OR site_id IS NULL)
+             ORDER BY site_id IS NULL
+             LIMIT 1",
+            params![site_id, enrolment_number],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let unpadded = enrolment_number.trim_start_matches('0');
+    conn.query_row(
+        "SELECT id, name, essid FROM employees
+         WHERE id = ?1 OR (?1 IS NULL AND (essid = ?2 OR essid = ?3))
+         LIMIT 1",
+        params![mapped, enrolment_number, unpadded],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Import a fingerprint terminal log, pair the punches into shifts and mark
+/// the employees present for the day their shift started. Days with more than
+/// one complete shift record the extra shifts as OT shifts. Shifts missing a
+/// punch are flagged, and a day without a complete shift is not marked. Days
+/// already entered another way or in a locked payroll month are reported as
+/// conflicts and left as they are.
+#[tauri::command]
+pub fn import_biometric_log(
+    _state: State<AppState>,
+    file_path: String,
+    options: BiometricImportOptions,
+) -> Result<BiometricImportResult, String> {
+    let mut conn = db::get_connection()?;
+
+    if let Some(site_id) = options.site_id {
+        get_site_by_id(&conn, site_id)?.ok_or("Site not found")?;
+    }
+    let duplicate_window = Duration::minutes(
+        options
+            .duplicate_window_minutes
+            .unwrap_or(DEFAULT_DUPLICATE_WINDOW_MINUTES),
+    );
+    let max_shift = Duration::hours(options.max_shift_hours.unwrap_or(DEFAULT_MAX_SHIFT_HOURS));
+
+    let bytes = fs::read(&file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+    let content = String::from_utf8_lossy(&bytes);
+
+    let mut errors = Vec::new();
+    let mut punches_read = 0;
+    let mut resolved: HashMap<String, Option<(i64, String, String)>> = HashMap::new();
+    let mut unmapped_enrolments = Vec::new();
+    let mut punches_by_employee: BTreeMap<i64, Vec<(NaiveDateTime, PunchState)>> = BTreeMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let punch = match parse_punch_line(line) {
+            Ok(Some(punch)) => punch,
+            Ok(None) => continue,
+            Err(message) => {
+                errors.push(ImportError {
+                    line: index + 1,
+                    message,
+                });
+                continue;
+            }
+        };
+        punches_read += 1;
+
+        if !resolved.contains_key(&punch.enrolment_number) {
+            let employee = resolve_enrolment(&conn, options.site_id, &punch.enrolment_number)?;
+            if employee.is_none() {
+                unmapped_enrolments.push(punch.enrolment_number.clone());
+            }
+            resolved.insert(punch.enrolment_number.clone(), employee);
+        }
+
+        if let Some((employee_id, ..)) = &resolved[&punch.enrolment_number] {
+            punches_by_employee
+                .entry(*employee_id)
+                .or_default()
+                .push((punch.time, punch.state));
+        }
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT id, code, start_time FROM shift_templates WHERE is_active = 1")
+        .map_err(|e| e.to_string())?;
+    let templates = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    drop(stmt);
+
+    let employees: HashMap<i64, (String, String)> = resolved
+        .into_values()
+        .flatten()
+        .map(|(id, name, essid)| (id, (name, essid)))
+        .collect();
+
+    let mut shifts = Vec::new();
+    let mut missing_punches = 0;
+    let mut days: BTreeMap<(i64, NaiveDate), PunchDay> = BTreeMap::new();
+
+    for (employee_id, mut punches) in punches_by_employee {
+        let (name, essid) = &employees[&employee_id];
+
+        for pair in pair_punches(&mut punches, duplicate_window, max_shift) {
+            let anchor = pair
+                .in_time
+                .or(pair.out_time)
+                .expect("a paired shift has at least one punch");
+            let date = anchor.date();
+
+            let template = pair.in_time.and_then(|in_time| {
+                templates
+                    .iter()
+                    .filter_map(|(id, code, start)| {
+                        let start = parse_time(start, "shift start time").ok()?;
+                        let apart = minutes_apart(in_time.time(), start);
+                        (apart <= SHIFT_MATCH_MINUTES).then_some((apart, *id, code.clone()))
+                    })
+                    .min_by_key(|(apart, ..)| *apart)
+            });
+
+            let flag = match (pair.in_time, pair.out_time) {
+                (Some(_), None) => Some("Missing out punch".to_string()),
+                (None, Some(_)) => Some("Missing in punch".to_string()),
+                _ => None,
+            };
+            if flag.is_some() {
+                missing_punches += 1;
+            }
+
+            let day = days.entry((employee_id, date)).or_default();
+            if flag.is_none() {
+                day.shift_count += 1;
+            }
+            if day.shift_template_id.is_none() {
+                day.shift_template_id = template.as_ref().map(|(_, id, _)| *id);
+            }
+            if let Some(flag) = &flag {
+                day.flags.push(flag.clone());
+            }
+
+            shifts.push(PunchShift {
+                employee_id,
+                employee_name: name.clone(),
+                essid: essid.clone(),
+                attendance_date: format_date(date),
+                in_time: pair.in_time.map(|t| t.format("%Y-%m-%d %H:%M").to_string()),
+                out_time: pair
+                    .out_time
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string()),
+                hours: pair.in_time.zip(pair.out_time).map(|(start, end)| {
+                    ((end - start).num_minutes() as f64 / 60.0 * 100.0).round() / 100.0
+                }),
+                shift_code: template.map(|(_, _, code)| code),
+                flag,
+            });
+        }
+    }
+
+    // A day with no complete shift is not proof of work, so it is left for
+    // the supervisor to mark
+    let unverified_days = days.values().filter(|d| d.shift_count == 0).count() as i64;
+    days.retain(|_, day| day.shift_count > 0);
+
+    // Only days without attendance or marked by an earlier biometric import
+    // are written, so manual, CSV and leave entries are never overwritten
+    let mut conflicts = Vec::new();
+    let mut locked_months: HashMap<String, Option<String>> = HashMap::new();
+    for (employee_id, date) in days.keys().copied().collect::<Vec<_>>() {
+        let month = date.format("%Y-%m").to_string();
+        if !locked_months.contains_key(&month) {
+            let locked = ensure_month_unlocked(&conn, &month).err();
+            locked_months.insert(month.clone(), locked);
+        }
+        let message = match &locked_months[&month] {
+            Some(message) => Some(message.clone()),
+            None => conn
+                .query_row(
+                    "SELECT source FROM attendance WHERE employee_id = ?1 AND attendance_date = ?2",
+                    params![employee_id, format_date(date)],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .filter(|source| source != "biometric")
+                .map(|source| format!("Attendance already entered ({source})")),
+        };
+        if let Some(message) = message {
+            let (name, essid) = &employees[&employee_id];
+            conflicts.push(AttendanceConflict {
+                employee_id,
+                employee_name: name.clone(),
+                essid: essid.clone(),
+                attendance_date: format_date(date),
+                message,
+            });
+            days.remove(&(employee_id, date));
+        }
+    }
+
+    let mut attendance_marked = 0;
+    if !options.dry_run.unwrap_or(false) {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for ((employee_id, date), day) in days {
+            upsert_attendance(
+                &tx,
+                &NewAttendance {
+                    employee_id,
+                    attendance_date: format_date(date),
+                    status: "present".to_string(),
+                    site_id: options.site_id,
+                    shift_template_id: day.shift_template_id,
+                    ot_shifts: day.shift_count - 1,
+                    source: "biometric",
+                    remarks: (!day.flags.is_empty()).then(|| day.flags.join(", ")),
+                },
+            )?;
+            attendance_marked += 1;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(BiometricImportResult {
+        punches_read,
+        shifts,
+        missing_punches,
+        attendance_marked,
+        unverified_days,
+        conflicts,
+        unmapped_enrolments,
+        errors,
+    })
+}
@@ -0,0 +1,258 @@
+use chrono::{Duration, NaiveDateTime, NaiveTime, Timelike};
+use rusqlite::Row;
+
+use super::types::BiometricEnrolment;
+
+pub const ENROLMENT_SELECT: &str = "SELECT b.id, b.site_id, s.name, b.enrolment_number,
+        b.employee_id, e.name, e.essid
+    FROM biometric_enrolments b
+    JOIN employees e ON e.id = b.employee_id
+    LEFT JOIN sites s ON s.id = b.site_id";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PunchState {
+    In,
+    Out,
+    Unknown,
+}
+
+#[derive(Debug)]
+pub struct Punch {
+    pub enrolment_number: String,
+    pub time: NaiveDateTime,
+    pub state: PunchState,
+}
+
+/// A pair of punches, either of which may be missing
+#[derive(Debug)]
+pub struct PairedShift {
+    pub in_time: Option<NaiveDateTime>,
+    pub out_time: Option<NaiveDateTime>,
+}
+
+pub fn map_enrolment(row: &Row) -> rusqlite::Result<BiometricEnrolment> {
+    Ok(BiometricEnrolment {
+        id: row.get(0)?,
+        site_id: row.get(1)?,
+        site_name: row.get(2)?,
+        enrolment_number: row.get(3)?,
+        employee_id: row.get(4)?,
+        employee_name: row.get(5)?,
+        essid: row.get(6)?,
+    })
+}
+
+/// Parse one line of a terminal attendance log. The usual layout is
+/// tab-separated: enrolment number, `YYYY-MM-DD HH:MM:SS`, verify mode,
+/// punch state (0 check-in, 1 check-out, 4 OT-in, 5 OT-out), work code.
+/// Some exports separate the fields with spaces instead. Blank lines yield
+/// `None`.
+pub fn parse_punch_line(line: &str) -> Result<Option<Punch>, String> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let (enrolment_number, time, state) = if line.contains('\t') {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        (
+            fields[0],
+            fields.get(1).map(|t| t.to_string()).unwrap_or_default(),
+            fields.get(3).copied(),
+        )
+    } else {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (
+            fields[0],
+            fields.get(1..3).map(|t| t.join(" ")).unwrap_or_default(),
+            fields.get(4).copied(),
+        )
+    };
+
+    if enrolment_number.is_empty() {
+        return Err("Missing enrolment number".to_string());
+    }
+    if time.is_empty() {
+        return Err("Missing punch time".to_string());
+    }
+
+    let time = NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M"))
+        .map_err(|_| format!("Invalid punch time '{time}'"))?;
+
+    let state = match state {
+        Some("0") | Some("4") => PunchState::In,
+        Some("1") | Some("5") => PunchState::Out,
+        _ => PunchState::Unknown,
+    };
+
+    Ok(Some(Punch {
+        enrolment_number: enrolment_number.to_string(),
+        time,
+        state,
+    }))
+}
+
+/// Pair the punches of one employee into shifts. Repeated punches within
+/// `duplicate_window` are dropped. A check-in with no check-out within
+/// `max_shift` is closed as a shift with a missing out punch. Punches without
+/// a state alternate between in and out.
+pub fn pair_punches(
+    punches: &mut [(NaiveDateTime, PunchState)],
+    duplicate_window: Duration,
+    max_shift: Duration,
+) -> Vec<PairedShift> {
+    punches.sort_by_key(|(time, _)| *time);
+
+    let mut shifts = Vec::new();
+    let mut open: Option<NaiveDateTime> = None;
+    let mut last: Option<(NaiveDateTime, PunchState)> = None;
+
+    for &(time, state) in punches.iter() {
+        if let Some((last_time, last_state)) = last {
+            if last_state == state && time - last_time <= duplicate_window {
+                continue;
+            }
+        }
+        last = Some((time, state));
+
+        if let Some(start) = open {
+            if time - start > max_shift {
+                shifts.push(PairedShift {
+                    in_time: Some(start),
+                    out_time: None,
+                });
+                open = None;
+            }
+        }
+
+        match (state, open.take()) {
+            (PunchState::In, previous) => {
+                if let Some(start) = previous {
+                    shifts.push(PairedShift {
+                        in_time: Some(start),
+                        out_time: None,
+                    });
+                }
+                open = Some(time);
+            }
+            (PunchState::Out, previous) => shifts.push(PairedShift {
+                in_time: previous,
+                out_time: Some(time),
+            }),
+            (PunchState::Unknown, Some(start)) => shifts.push(PairedShift {
+                in_time: Some(start),
+                out_time: Some(time),
+            }),
+            (PunchState::Unknown, None) => open = Some(time),
+        }
+    }
+
+    if let Some(start) = open {
+        shifts.push(PairedShift {
+            in_time: Some(start),
+            out_time: None,
+        });
+    }
+
+    shifts
+}
+
+/// Minutes between two times of day, going the short way around midnight
+pub fn minutes_apart(a: NaiveTime, b: NaiveTime) -> i64 {
+    let a = (a.hour() * 60 + a.minute()) as i64;
+    let b = (b.hour() * 60 + b.minute()) as i64;
+    let diff = (a - b).abs();
+    diff.min(24 * 60 - diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn pair(punches: &[(&str, PunchState)]) -> Vec<(Option<NaiveDateTime>, Option<NaiveDateTime>)> {
+        let mut punches: Vec<_> = punches.iter().map(|&(t, s)| (at(t), s)).collect();
+        pair_punches(&mut punches, Duration::minutes(5), Duration::hours(16))
+            .into_iter()
+            .map(|shift| (shift.in_time, shift.out_time))
+            .collect()
+    }
+
+    #[test]
+    fn tab_and_space_separated_lines_are_parsed() {
+        let punch = parse_punch_line("  17\t2024-07-01 20:01:12\t1\t0\t0")
+            .unwrap()
+            .unwrap();
+        assert_eq!(punch.enrolment_number, "17");
+        assert_eq!(punch.time, at("2024-07-01 20:01") + Duration::seconds(12));
+        assert_eq!(punch.state, PunchState::In);
+
+        let punch = parse_punch_line("17 2024-07-02 08:03 1 1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(punch.state, PunchState::Out);
+
+        assert!(parse_punch_line("   ").unwrap().is_none());
+        assert!(parse_punch_line("17\t02/07/2024 08:03").is_err());
+    }
+
+    #[test]
+    fn night_shift_punches_pair_across_midnight() {
+        use PunchState::*;
+        let shifts = pair(&[
+            ("2024-07-02 08:02", Out),
+            ("2024-07-01 20:01", In),
+            ("2024-07-01 20:03", In),
+        ]);
+        assert_eq!(
+            shifts,
+            vec![(Some(at("2024-07-01 20:01")), Some(at("2024-07-02 08:02")))]
+        );
+    }
+
+    #[test]
+    fn missing_punches_leave_half_open_shifts() {
+        use PunchState::*;
+        let shifts = pair(&[
+            ("2024-07-01 08:00", In),
+            ("2024-07-02 08:00", In),
+            ("2024-07-02 20:00", Out),
+            ("2024-07-03 20:00", Out),
+        ]);
+        assert_eq!(
+            shifts,
+            vec![
+                (Some(at("2024-07-01 08:00")), None),
+                (Some(at("2024-07-02 08:00")), Some(at("2024-07-02 20:00"))),
+                (None, Some(at("2024-07-03 20:00"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn punches_without_a_state_alternate() {
+        use PunchState::*;
+        let shifts = pair(&[
+            ("2024-07-01 09:00", Unknown),
+            ("2024-07-01 18:00", Unknown),
+            ("2024-07-02 09:00", Unknown),
+        ]);
+        assert_eq!(
+            shifts,
+            vec![
+                (Some(at("2024-07-01 09:00")), Some(at("2024-07-01 18:00"))),
+                (Some(at("2024-07-02 09:00")), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn minutes_apart_wraps_around_midnight() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(minutes_apart(time(23, 50), time(0, 10)), 20);
+        assert_eq!(minutes_apart(time(8, 0), time(8, 45)), 45);
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::attendance::types::ImportError;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiometricEnrolment {
+    pub id: i64,
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub enrolment_number: String,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiometricEnrolmentInput {
+    pub site_id: Option<i64>,
+    pub enrolment_number: String,
+    pub employee_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiometricImportOptions {
+    pub site_id: Option<i64>,
+    /// Parse and pair the punches without writing attendance
+    pub dry_run: Option<bool>,
+    pub duplicate_window_minutes: Option<i64>,
+    pub max_shift_hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PunchShift {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub attendance_date: String,
+    pub in_time: Option<String>,
+    pub out_time: Option<String>,
+    pub hours: Option<f64>,
+    pub shift_code: Option<String>,
+    pub flag: Option<String>,
+}
+
+/// A day the import did not mark because attendance was already entered for
+/// it another way or its payroll month is locked
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceConflict {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub attendance_date: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiometricImportResult {
+    pub punches_read: i64,
+    pub shifts: Vec<PunchShift>,
+    pub missing_punches: i64,
+    pub attendance_marked: i64,
+    /// Days left unmarked because none of their shifts has both punches
+    pub unverified_days: i64,
+    pub conflicts: Vec<AttendanceConflict>,
+    pub unmapped_enrolments: Vec<String>,
+    pub errors: Vec<ImportError>,
+}
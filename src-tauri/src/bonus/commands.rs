@@ -0,0 +1,389 @@
+use super::helpers::{
+    accounting_year_bounds, bonus_amount, employee_bonus_wages, get_bonus_run_by_id, map_bonus_run,
+    validate_bonus_input, BONUS_RUN_SELECT, MINIMUM_DAYS_WORKED,
+};
+use super::types::{BonusInput, BonusRun};
+use crate::company::helpers::load_company_profile;
+use crate::csv::write_csv;
+use crate::dates::{age_on, format_date, parse_date};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::round2;
+use crate::payslips::helpers::format_amount;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use rusqlite::{params, OptionalExtension, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_bonus_runs(_state: State<AppState>) -> Result<Vec<BonusRun>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{BONUS_RUN_SELECT} GROUP BY b.id ORDER BY b.accounting_year DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let runs = stmt
+        .query_map([], map_bonus_run)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(runs)
+}
+
+#[tauri::command]
+pub fn get_bonus_run(_state: State<AppState>, id: i64) -> Result<BonusRun, String> {
+    let conn = db::get_connection()?;
+    get_bonus_run_by_id(&conn, id)?.ok_or_else(|| "Bonus run not found".to_string())
+}
+
+/// Compute, or recompute, the bonus of every employee who was paid or marked
+/// attendance during an accounting year
+#[tauri::command]
+pub fn compute_bonus(_state: State<AppState>, input: BonusInput) -> Result<BonusRun, String> {
+    let mut conn = db::get_connection()?;
+
+    let (first, last, accounting_year) = accounting_year_bounds(&input.accounting_year)?;
+    validate_bonus_input(&input)?;
+
+    let paid_lines: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM bonus_run_lines l JOIN bonus_runs b ON b.id = l.bonus_run_id
+            WHERE b.accounting_year = ?1 AND l.paid_on IS NOT NULL",
+            [&accounting_year],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if paid_lines > 0 {
+        return Err(format!(
+            "Bonus for {accounting_year} has already been paid and cannot be recomputed"
+        ));
+    }
+
+    let (first_date, last_date) = (format_date(first), format_date(last));
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM employees
+            WHERE id IN (
+                SELECT l.employee_id FROM pay_run_lines l JOIN pay_runs p ON p.id = l.pay_run_id
+                WHERE p.month BETWEEN substr(?1, 1, 7) AND substr(?2, 1, 7)
+                UNION
+                SELECT employee_id FROM attendance WHERE attendance_date BETWEEN ?1 AND ?2
+            )
+            ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let employee_ids = stmt
+        .query_map(params![first_date, last_date], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    drop(stmt);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO bonus_runs (
+            accounting_year, bonus_rate, calculation_ceiling, eligibility_threshold
+        ) VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT (accounting_year) DO UPDATE SET
+            bonus_rate = excluded.bonus_rate,
+            calculation_ceiling = excluded.calculation_ceiling,
+            eligibility_threshold = excluded.eligibility_threshold,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            accounting_year,
+            input.bonus_rate,
+            input.calculation_ceiling,
+            input.eligibility_threshold,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let bonus_run_id: i64 = tx
+        .query_row(
+            "SELECT id FROM bonus_runs WHERE accounting_year = ?1",
+            [&accounting_year],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM bonus_run_lines WHERE bonus_run_id = ?1",
+        params![bonus_run_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for employee_id in employee_ids {
+        let employee = get_employee_by_id(&tx, employee_id)?.ok_or("Employee not found")?;
+        let (days_worked, bonus_wages, above_threshold) = employee_bonus_wages(
+            &tx,
+            &employee,
+            first,
+            input.calculation_ceiling,
+            input.eligibility_threshold,
+        )?;
+
+        let remarks = if days_worked < MINIMUM_DAYS_WORKED {
+            if above_threshold && days_worked == 0 {
+                Some("Salary above the eligibility threshold".to_string())
+            } else {
+                Some(format!("Worked fewer than {MINIMUM_DAYS_WORKED} days"))
+            }
+        } else if bonus_wages <= 0.0 {
+            Some("No basic or DA paid in the year".to_string())
+        } else {
+            None
+        };
+        let eligible = remarks.is_none();
+        let bonus_amount = if eligible {
+            bonus_amount(bonus_wages, input.bonus_rate)
+        } else {
+            0.0
+        };
+
+        tx.execute(
+            "INSERT INTO bonus_run_lines (
+                bonus_run_id, employee_id, employee_name, father_name, essid, job_post,
+                date_of_birth, days_worked, bonus_wages, bonus_amount, eligible, remarks
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                bonus_run_id,
+                employee_id,
+                employee.name,
+                employee.father_name,
+                employee.essid,
+                employee.job_post,
+                employee.date_of_birth,
+                days_worked,
+                bonus_wages,
+                bonus_amount,
+                eligible as i32,
+                remarks,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_bonus_run_by_id(&conn, bonus_run_id)?
+        .ok_or_else(|| "Failed to retrieve bonus run".to_string())
+}
+
+/// Record the bonus of a run as paid on `paid_on`, for the given employees or
+/// for every eligible employee not yet paid
+#[tauri::command]
+pub fn mark_bonus_paid(
+    _state: State<AppState>,
+    bonus_run_id: i64,
+    paid_on: String,
+    employee_ids: Option<Vec<i64>>,
+) -> Result<BonusRun, String> {
+    let mut conn = db::get_connection()?;
+
+    get_bonus_run_by_id(&conn, bonus_run_id)?.ok_or("Bonus run not found")?;
+    let paid_on = format_date(parse_date(&paid_on, "paid on date")?);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    match employee_ids {
+        Some(employee_ids) => {
+            for employee_id in employee_ids {
+                tx.execute(
+                    "UPDATE bonus_run_lines SET paid_on = ?1
+                    WHERE bonus_run_id = ?2 AND employee_id = ?3 AND eligible = 1
+                      AND paid_on IS NULL",
+                    params![paid_on, bonus_run_id, employee_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        None => {
+            tx.execute(
+                "UPDATE bonus_run_lines SET paid_on = ?1
+                WHERE bonus_run_id = ?2 AND eligible = 1 AND paid_on IS NULL",
+                params![paid_on, bonus_run_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_bonus_run_by_id(&conn, bonus_run_id)?
+        .ok_or_else(|| "Failed to retrieve bonus run".to_string())
+}
+
+#[tauri::command]
+pub fn delete_bonus_run(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let bonus_run = get_bonus_run_by_id(&conn, id)?.ok_or("Bonus run not found")?;
+    if bonus_run.lines.iter().any(|l| l.paid_on.is_some()) {
+        return Err(format!(
+            "Bonus for {} has already been paid and cannot be deleted",
+            bonus_run.accounting_year
+        ));
+    }
+
+    conn.execute("DELETE FROM bonus_runs WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Register of bonus paid in Form C of the Payment of Bonus Rules
+#[tauri::command]
+pub fn export_bonus_form_c(
+    _state: State<AppState>,
+    id: i64,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let bonus_run = get_bonus_run_by_id(&conn, id)?.ok_or("Bonus run not found")?;
+    let profile = load_company_profile(&conn)?;
+    let (first, last, _) = accounting_year_bounds(&bonus_run.accounting_year)?;
+
+    // Columns 1 to 16 of Form C
+    let columns = vec![
+        TableColumn::new("Sl", 20.0, Align::Right),
+        TableColumn::new("Name", 100.0, Align::Left),
+        TableColumn::new("Father's name", 90.0, Align::Left),
+        TableColumn::new("Age 15+", 44.0, Align::Left),
+        TableColumn::new("Designation", 80.0, Align::Left),
+        TableColumn::new("Days", 32.0, Align::Right),
+        TableColumn::new("Wages", 58.0, Align::Right),
+        TableColumn::new("Bonus", 52.0, Align::Right),
+        TableColumn::new("Puja", 38.0, Align::Right),
+        TableColumn::new("Interim", 40.0, Align::Right),
+        TableColumn::new("Tax", 34.0, Align::Right),
+        TableColumn::new("Loss", 34.0, Align::Right),
+        TableColumn::new("Deducted", 54.0, Align::Right),
+        TableColumn::new("Net", 52.0, Align::Right),
+        TableColumn::new("Paid", 52.0, Align::Right),
+        TableColumn::new("Date paid", 52.0, Align::Left),
+        TableColumn::new("Signature", 60.0, Align::Left),
+    ];
+    let mut report = TableReport::new("Form C - Bonus Paid to Employees", columns);
+    report.subtitle_lines = vec![
+        "[See rule 4(c)]".to_string(),
+        format!(
+            "Name of the establishment: {}    Accounting year: {}",
+            profile.name, bonus_run.accounting_year
+        ),
+        format!(
+            "Period: {} to {}    Bonus rate: {}%",
+            format_date(first),
+            format_date(last),
+            bonus_run.bonus_rate
+        ),
+    ];
+    report.rows = bonus_run
+        .lines
+        .iter()
+        .filter(|l| l.eligible != 0)
+        .enumerate()
+        .map(|(i, line)| {
+            let over_15 = age_on(line.date_of_birth.as_deref(), first)
+                .map_or("-".to_string(), |age| {
+                    if age >= 15 { "Yes" } else { "No" }.to_string()
+                });
+            vec![
+                (i + 1).to_string(),
+                line.employee_name.clone(),
+                line.father_name.clone().unwrap_or_default(),
+                over_15,
+                line.job_post.clone().unwrap_or_default(),
+                line.days_worked.to_string(),
+                format_amount(line.bonus_wages),
+                format_amount(line.bonus_amount),
+                "0.00".to_string(),
+                "0.00".to_string(),
+                "0.00".to_string(),
+                "0.00".to_string(),
+                "0.00".to_string(),
+                format_amount(line.bonus_amount),
+                line.paid_on
+                    .as_ref()
+                    .map_or(String::new(), |_| format_amount(line.bonus_amount)),
+                line.paid_on.clone().unwrap_or_default(),
+                String::new(),
+            ]
+        })
+        .collect();
+    report.footer_lines = vec![
+        format!(
+            "Total bonus payable: Rs. {}",
+            format_amount(round2(bonus_run.total_bonus))
+        ),
+        "Wages: salary or wage for the year. Puja: customary bonus. Interim: bonus paid in advance. Loss: deduction for financial loss caused by misconduct.".to_string(),
+    ];
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
+
+/// Bonus payable to each eligible employee with their bank details, for
+/// disbursement
+#[tauri::command]
+pub fn export_bonus_disbursement_csv(
+    _state: State<AppState>,
+    id: i64,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let bonus_run = get_bonus_run_by_id(&conn, id)?.ok_or("Bonus run not found")?;
+
+    let mut rows = Vec::new();
+    for line in bonus_run.lines.iter().filter(|l| l.eligible != 0) {
+        let account: Option<(String, String, String)> = match line.employee_id {
+            Some(employee_id) => conn
+                .query_row(
+                    "SELECT account_holder_name, account_number, ifsc
+                    FROM employee_bank_accounts WHERE employee_id = ?1",
+                    [employee_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?,
+            None => None,
+        };
+        let (holder, account_number, ifsc) = account.unwrap_or_default();
+        rows.push(vec![
+            line.essid.clone(),
+            line.employee_name.clone(),
+            line.days_worked.to_string(),
+            format!("{:.2}", line.bonus_wages),
+            format!("{:.2}", line.bonus_amount),
+            holder,
+            account_number,
+            ifsc,
+            line.paid_on.clone().unwrap_or_default(),
+        ]);
+    }
+    if rows.is_empty() {
+        return Err(format!(
+            "No eligible employees in the bonus for {}",
+            bonus_run.accounting_year
+        ));
+    }
+
+    write_csv(
+        &PathBuf::from(&export_path),
+        &[
+            "ESSID",
+            "Name",
+            "Days Worked",
+            "Bonus Wages",
+            "Bonus Amount",
+            "Account Holder",
+            "Account Number",
+            "IFSC",
+            "Paid On",
+        ],
+        &rows,
+    )
+}
@@ -0,0 +1,361 @@
+use chrono::{Datelike, Months, NaiveDate};
+use rusqlite::{params, Row};
+
+use super::types::{BonusInput, BonusLine, BonusRun};
+use crate::attendance::helpers::attendance_summaries;
+use crate::employees::types::Employee;
+use crate::payroll::helpers::{
+    find_wage_structure, get_pay_run_by_month, get_pay_run_lines, line_item_amount, round2,
+};
+
+/// Minimum bonus under section 10 of the Payment of Bonus Act
+pub const MINIMUM_BONUS: f64 = 100.0;
+pub const MINIMUM_RATE: f64 = 8.33;
+pub const MAXIMUM_RATE: f64 = 20.0;
+/// Days an employee must have worked in the year to qualify
+pub const MINIMUM_DAYS_WORKED: i64 = 30;
+
+pub const BONUS_RUN_SELECT: &str = "SELECT b.id, b.accounting_year, b.bonus_rate,
+        b.calculation_ceiling, b.eligibility_threshold, IFNULL(SUM(l.eligible), 0),
+        IFNULL(SUM(l.bonus_amount), 0), b.created_at, b.updated_at
+    FROM bonus_runs b
+    LEFT JOIN bonus_run_lines l ON l.bonus_run_id = b.id";
+
+pub fn map_bonus_run(row: &Row) -> rusqlite::Result<BonusRun> {
+    Ok(BonusRun {
+        id: row.get(0)?,
+        accounting_year: row.get(1)?,
+        bonus_rate: row.get(2)?,
+        calculation_ceiling: row.get(3)?,
+        eligibility_threshold: row.get(4)?,
+        eligible_count: row.get(5)?,
+        total_bonus: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        lines: Vec::new(),
+    })
+}
+
+pub fn map_bonus_line(row: &Row) -> rusqlite::Result<BonusLine> {
+    Ok(BonusLine {
+        id: row.get(0)?,
+        employee_id: row.get(2)?,
+        employee_name: row.get(3)?,
+        father_name: row.get(4)?,
+        essid: row.get(5)?,
+        job_post: row.get(6)?,
+        date_of_birth: row.get(7)?,
+        days_worked: row.get(8)?,
+        bonus_wages: row.get(9)?,
+        bonus_amount: row.get(10)?,
+        eligible: row.get(11)?,
+        remarks: row.get(12)?,
+        paid_on: row.get(13)?,
+    })
+}
+
+pub fn get_bonus_run_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<BonusRun>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{BONUS_RUN_SELECT} WHERE b.id = ?1 GROUP BY b.id"))
+        .map_err(|e| e.to_string())?;
+
+    let mut bonus_run = match stmt.query_row([id], map_bonus_run) {
+        Ok(bonus_run) => bonus_run,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM bonus_run_lines WHERE bonus_run_id = ?1
+            ORDER BY employee_name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    bonus_run.lines = stmt
+        .query_map([id], map_bonus_line)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some(bonus_run))
+}
+
+/// First and last day of an April to March accounting year given as
+/// `2023-24`, `2023-2024` or `2023`, with the year in `YYYY-YY` form
+pub fn accounting_year_bounds(year: &str) -> Result<(NaiveDate, NaiveDate, String), String> {
+    let invalid = || format!("Invalid accounting year '{year}', expected e.g. 2023-24");
+    let (start, end) = match year.trim().split_once('-') {
+        Some((start, end)) => (start, Some(end)),
+        None => (year.trim(), None),
+    };
+    if start.len() != 4 {
+        return Err(invalid());
+    }
+    let start_year: i32 = start.parse().map_err(|_| invalid())?;
+    // The year after the start, in two or four digits
+    let next_year = start_year + 1;
+    if let Some(end) = end {
+        let valid = match end.len() {
+            2 => end.parse::<i32>().ok() == Some(next_year % 100),
+            4 => end.parse::<i32>().ok() == Some(next_year),
+            _ => false,
+        };
+        if !valid {
+            return Err(invalid());
+        }
+    }
+    let first = NaiveDate::from_ymd_opt(start_year, 4, 1).ok_or_else(invalid)?;
+    let last = NaiveDate::from_ymd_opt(start_year + 1, 3, 31).ok_or_else(invalid)?;
+    Ok((
+        first,
+        last,
+        format!("{start_year}-{:02}", (start_year + 1) % 100),
+    ))
+}
+
+/// Check the rate, ceiling and threshold of a bonus computation
+pub fn validate_bonus_input(input: &BonusInput) -> Result<(), String> {
+    if !(MINIMUM_RATE..=MAXIMUM_RATE).contains(&input.bonus_rate) {
+        return Err(format!(
+            "Bonus rate must be between {MINIMUM_RATE}% and {MAXIMUM_RATE}%"
+        ));
+    }
+    if input.calculation_ceiling <= 0.0 || input.eligibility_threshold <= 0.0 {
+        return Err("Wage ceiling and eligibility threshold must be greater than zero".to_string());
+    }
+    Ok(())
+}
+
+/// Bonus of an eligible employee at `bonus_rate` percent of their bonus
+/// wages, but not less than the statutory minimum
+pub fn bonus_amount(bonus_wages: f64, bonus_rate: f64) -> f64 {
+    (bonus_wages * bonus_rate / 100.0)
+        .round()
+        .max(MINIMUM_BONUS)
+}
+
+/// Days worked and bonus wages of an employee over an accounting year.
+/// Months with a pay run line use the basic and DA actually paid; other months
+/// fall back to attendance and the wage structure. Months in which the full
+/// monthly basic and DA exceed the threshold are not covered.
+pub fn employee_bonus_wages(
+    conn: &rusqlite::Connection,
+    employee: &Employee,
+    first: NaiveDate,
+    calculation_ceiling: f64,
+    eligibility_threshold: f64,
+) -> Result<(i64, f64, bool), String> {
+    let mut days_worked = 0;
+    let mut bonus_wages = 0.0;
+    let mut above_threshold = false;
+
+    for offset in 0..12 {
+        let month_start = first
+            .checked_add_months(Months::new(offset))
+            .ok_or("Invalid month")?;
+        let month_end = month_start
+            .checked_add_months(Months::new(1))
+            .and_then(|d| d.pred_opt())
+            .ok_or("Invalid month")?;
+        let days_in_month = month_end.day() as i64;
+        let month = month_start.format("%Y-%m").to_string();
+
+        let line = match get_pay_run_by_month(conn, &month)? {
+            Some(pay_run) => get_pay_run_lines(conn, pay_run.id, Some(employee.id))?
+                .into_iter()
+                .next(),
+            None => None,
+        };
+
+        let (job_post, paid_days, earned) = match line {
+            Some(line) => (
+                line.job_post.clone().unwrap_or_default(),
+                line.paid_days,
+                line_item_amount(&line, "BASIC") + line_item_amount(&line, "DA"),
+            ),
+            None => {
+                let job_post = employee.job_post.clone().unwrap_or_default();
+                let Some(summary) =
+                    attendance_summaries(conn, month_start, month_end, None, Some(employee.id))?
+                        .into_iter()
+                        .next()
+                else {
+                    continue;
+                };
+                let paid_days = (summary.present_days
+                    + summary.leave_days
+                    + summary.weekly_offs
+                    + summary.holidays)
+                    .min(days_in_month);
+                let earned = find_wage_structure(conn, &job_post, month_end)?.map_or(0.0, |s| {
+                    ((s.basic + s.da) * paid_days as f64 / days_in_month as f64).round()
+                });
+                (job_post, paid_days, earned)
+            }
+        };
+        if paid_days == 0 {
+            continue;
+        }
+
+        let monthly_rate = match find_wage_structure(conn, &job_post, month_end)? {
+            Some(structure) => structure.basic + structure.da,
+            None => earned * days_in_month as f64 / paid_days as f64,
+        };
+        if monthly_rate > eligibility_threshold {
+            above_threshold = true;
+            continue;
+        }
+
+        days_worked += paid_days;
+        bonus_wages += earned.min(calculation_ceiling * paid_days as f64 / days_in_month as f64);
+    }
+
+    Ok((days_worked, round2(bonus_wages), above_threshold))
+}
+
+/// Unpaid bonus of an employee across all accounting years, as
+/// `(line id, accounting year, amount)`
+pub fn unpaid_bonus(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Vec<(i64, String, f64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.id, b.accounting_year, l.bonus_amount
+            FROM bonus_run_lines l JOIN bonus_runs b ON b.id = l.bonus_run_id
+            WHERE l.employee_id = ?1 AND l.eligible = 1 AND l.bonus_amount > 0
+              AND l.paid_on IS NULL
+            ORDER BY b.accounting_year",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![employee_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+    use crate::employees::helpers::get_employee_by_id;
+
+    fn input(bonus_rate: f64) -> BonusInput {
+        BonusInput {
+            accounting_year: "2023-24".to_string(),
+            bonus_rate,
+            calculation_ceiling: 7000.0,
+            eligibility_threshold: 21000.0,
+        }
+    }
+
+    #[test]
+    fn accounting_year_forms() {
+        let expected = (
+            NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            "2023-24".to_string(),
+        );
+        assert_eq!(accounting_year_bounds("2023-24").unwrap(), expected);
+        assert_eq!(accounting_year_bounds(" 2023-2024 ").unwrap(), expected);
+        assert_eq!(accounting_year_bounds("2023").unwrap(), expected);
+        assert_eq!(accounting_year_bounds("1999-00").unwrap().2, "1999-00");
+    }
+
+    #[test]
+    fn accounting_year_rejects_a_mismatched_end() {
+        for year in [
+            "2023-99",
+            "2023-23",
+            "2023-2025",
+            "2023-024",
+            "2023-",
+            "23-24",
+            "x",
+        ] {
+            assert!(accounting_year_bounds(year).is_err(), "{year} was accepted");
+        }
+    }
+
+    #[test]
+    fn bonus_rate_bounds() {
+        assert!(validate_bonus_input(&input(MINIMUM_RATE)).is_ok());
+        assert!(validate_bonus_input(&input(MAXIMUM_RATE)).is_ok());
+        assert!(validate_bonus_input(&input(8.32)).is_err());
+        assert!(validate_bonus_input(&input(20.01)).is_err());
+
+        let mut no_ceiling = input(8.33);
+        no_ceiling.calculation_ceiling = 0.0;
+        assert!(validate_bonus_input(&no_ceiling).is_err());
+    }
+
+    #[test]
+    fn bonus_amount_has_a_floor() {
+        assert_eq!(bonus_amount(84000.0, 8.33), 6997.0);
+        assert_eq!(bonus_amount(84000.0, 20.0), 16800.0);
+        // 8.33% of 1000 is below the minimum bonus
+        assert_eq!(bonus_amount(1000.0, 8.33), MINIMUM_BONUS);
+    }
+
+    #[test]
+    fn bonus_wages_are_prorated_to_the_ceiling() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status, job_post)
+            VALUES ('Guard', 'E1', 'current', 'Security Guard')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO wage_structures (job_post, effective_from, basic, da)
+            VALUES ('Security Guard', '2023-01-01', 8000, 2000)",
+            [],
+        )
+        .unwrap();
+        // Half of April and all of May
+        for day in 1..=15 {
+            conn.execute(
+                "INSERT INTO attendance (employee_id, attendance_date, status)
+                VALUES (1, ?1, 'present')",
+                [format!("2023-04-{day:02}")],
+            )
+            .unwrap();
+        }
+        for day in 1..=31 {
+            conn.execute(
+                "INSERT INTO attendance (employee_id, attendance_date, status)
+                VALUES (1, ?1, 'present')",
+                [format!("2023-05-{day:02}")],
+            )
+            .unwrap();
+        }
+        let employee = get_employee_by_id(&conn, 1).unwrap().unwrap();
+        let (first, _, _) = accounting_year_bounds("2023-24").unwrap();
+
+        // 10,000 a month is paid on 7,000 at most, for the days worked
+        let (days_worked, bonus_wages, above_threshold) =
+            employee_bonus_wages(&conn, &employee, first, 7000.0, 21000.0).unwrap();
+        assert_eq!(days_worked, 46);
+        assert_eq!(bonus_wages, 3500.0 + 7000.0);
+        assert!(!above_threshold);
+
+        // Under a higher ceiling the wages earned are counted in full
+        let (_, bonus_wages, _) =
+            employee_bonus_wages(&conn, &employee, first, 12000.0, 21000.0).unwrap();
+        assert_eq!(bonus_wages, 5000.0 + 10000.0);
+
+        // Months above the threshold are not covered at all
+        let (days_worked, bonus_wages, above_threshold) =
+            employee_bonus_wages(&conn, &employee, first, 7000.0, 9000.0).unwrap();
+        assert_eq!((days_worked, bonus_wages, above_threshold), (0, 0.0, true));
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusRun {
+    pub id: i64,
+    pub accounting_year: String,
+    pub bonus_rate: f64,
+    pub calculation_ceiling: f64,
+    pub eligibility_threshold: f64,
+    pub eligible_count: i64,
+    pub total_bonus: f64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub lines: Vec<BonusLine>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusLine {
+    pub id: i64,
+    pub employee_id: Option<i64>,
+    pub employee_name: String,
+    pub father_name: Option<String>,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub days_worked: i64,
+    /// Basic and DA of the covered months, limited to the calculation ceiling
+    pub bonus_wages: f64,
+    pub bonus_amount: f64,
+    pub eligible: i32,
+    pub remarks: Option<String>,
+    pub paid_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusInput {
+    /// `YYYY-YY`, April to March
+    pub accounting_year: String,
+    /// Percentage between 8.33 and 20
+    pub bonus_rate: f64,
+    /// Monthly wage the bonus is calculated on at most
+    pub calculation_ceiling: f64,
+    /// Monthly basic and DA above which an employee is not covered
+    pub eligibility_threshold: f64,
+}
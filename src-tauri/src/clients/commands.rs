@@ -0,0 +1,320 @@
+use super::helpers::{
+    get_client_by_id, get_site_by_id, map_client, map_site, normalize_gstin, SITE_SELECT,
+};
+use super::types::{Client, ClientInput, Site, SiteInput};
+use crate::db;
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use tauri::State;
+
+#[tauri::command]
+pub fn get_clients(
+    _state: State<AppState>,
+    include_inactive: Option<bool>,
+) -> Result<Vec<Client>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM clients WHERE ?1 OR is_active = 1 ORDER BY name COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+
+    let clients = stmt
+        .query_map([include_inactive.unwrap_or(false)], map_client)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(clients)
+}
+
+#[tauri::command]
+pub fn create_client(_state: State<AppState>, client: ClientInput) -> Result<Client, String> {
+    let conn = db::get_connection()?;
+
+    if client.name.trim().is_empty() {
+        return Err("Client name is required".to_string());
+    }
+    let gstin = normalize_gstin(client.gstin.as_deref())?;
+
+    conn.execute(
+        "INSERT INTO clients (
+            name, contact_person, contact_phone, email, address, is_active, gstin
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            client.name.trim(),
+            client.contact_person,
+            client.contact_phone,
+            client.email,
+            client.address,
+            client.is_active,
+            gstin,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Client with the same name already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(client)) = get_client_by_id(&conn, id) {
+        Ok(client)
+    } else {
+        Err("Failed to retrieve client".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_client(
+    _state: State<AppState>,
+    id: i64,
+    client: ClientInput,
+) -> Result<Client, String> {
+    let conn = db::get_connection()?;
+
+    if client.name.trim().is_empty() {
+        return Err("Client name is required".to_string());
+    }
+    let gstin = normalize_gstin(client.gstin.as_deref())?;
+
+    conn.execute(
+        "UPDATE clients SET
+            name = ?1, contact_person = ?2, contact_phone = ?3, email = ?4, address = ?5,
+            is_active = ?6, gstin = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            client.name.trim(),
+            client.contact_person,
+            client.contact_phone,
+            client.email,
+            client.address,
+            client.is_active,
+            gstin,
+            id,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Client with the same name already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    if let Ok(Some(client)) = get_client_by_id(&conn, id) {
+        Ok(client)
+    } else {
+        Err("Failed to retrieve client".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_client(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let site_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sites WHERE client_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if site_count > 0 {
+        return Err("Client has sites, delete them or mark the client inactive".to_string());
+    }
+
+    // Issued invoices are kept for the accounts
+    let has_invoices: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM invoices WHERE client_id = ?1)",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_invoices {
+        return Err("Client has been invoiced, mark it inactive instead".to_string());
+    }
+
+    conn.execute("DELETE FROM clients WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_sites(
+    _state: State<AppState>,
+    client_id: Option<i64>,
+    include_inactive: Option<bool>,
+) -> Result<Vec<Site>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{SITE_SELECT}
+            WHERE (?1 IS NULL OR s.client_id = ?1) AND (?2 OR s.is_active = 1)
+            ORDER BY c.name COLLATE NOCASE, s.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let sites = stmt
+        .query_map(
+            params![client_id, include_inactive.unwrap_or(false)],
+            map_site,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(sites)
+}
+
+#[tauri::command]
+pub fn create_site(_state: State<AppState>, site: SiteInput) -> Result<Site, String> {
+    let conn = db::get_connection()?;
+
+    if site.name.trim().is_empty() {
+        return Err("Site name is required".to_string());
+    }
+    if get_client_by_id(&conn, site.client_id)?.is_none() {
+        return Err("Client not found".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO sites (
+            client_id, name, place, address, contact_person, contact_phone, is_active, state,
+            wage_zone
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            site.client_id,
+            site.name.trim(),
+            site.place,
+            site.address,
+            site.contact_person,
+            site.contact_phone,
+            site.is_active,
+            site.state,
+            site.wage_zone,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Site with the same name already exists for this client".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(site)) = get_site_by_id(&conn, id) {
+        Ok(site)
+    } else {
+        Err("Failed to retrieve site".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_site(_state: State<AppState>, id: i64, site: SiteInput) -> Result<Site, String> {
+    let conn = db::get_connection()?;
+
+    if site.name.trim().is_empty() {
+        return Err("Site name is required".to_string());
+    }
+    if get_client_by_id(&conn, site.client_id)?.is_none() {
+        return Err("Client not found".to_string());
+    }
+
+    conn.execute(
+        "UPDATE sites SET
+            client_id = ?1, name = ?2, place = ?3, address = ?4, contact_person = ?5,
+            contact_phone = ?6, is_active = ?7, state = ?8, wage_zone = ?9,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?10",
+        params![
+            site.client_id,
+            site.name.trim(),
+            site.place,
+            site.address,
+            site.contact_person,
+            site.contact_phone,
+            site.is_active,
+            site.state,
+            site.wage_zone,
+            id,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Site with the same name already exists for this client".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    if let Ok(Some(site)) = get_site_by_id(&conn, id) {
+        Ok(site)
+    } else {
+        Err("Failed to retrieve site".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_site(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let deployment_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM deployments WHERE site_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if deployment_count > 0 {
+        return Err("Site has deployment history, mark it inactive instead".to_string());
+    }
+
+    for (table, message) in [
+        (
+            "roster_assignments",
+            "Site has roster assignments, mark it inactive instead",
+        ),
+        (
+            "attendance",
+            "Site has attendance records, mark it inactive instead",
+        ),
+        (
+            "weapon_assignments",
+            "Site appears in the weapon register, mark it inactive instead",
+        ),
+        (
+            "contract_rates",
+            "Site has contract rates, remove them or mark the site inactive",
+        ),
+        (
+            "site_requirements",
+            "Site has strength requirements, remove them or mark the site inactive",
+        ),
+    ] {
+        let in_use: bool = conn
+            .query_row(
+                &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE site_id = ?1)"),
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if in_use {
+            return Err(message.to_string());
+        }
+    }
+
+    conn.execute("DELETE FROM sites WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
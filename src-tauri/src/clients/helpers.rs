@@ -0,0 +1,82 @@
+use rusqlite::Row;
+
+use super::types::{Client, Site};
+
+pub const SITE_SELECT: &str = "SELECT s.id, s.client_id, c.name, s.name, s.place, s.address,
+        s.contact_person, s.contact_phone, s.is_active, s.state, s.wage_zone, s.created_at,
+        s.updated_at
+    FROM sites s
+    JOIN clients c ON c.id = s.client_id";
+
+pub fn map_client(row: &Row) -> rusqlite::Result<Client> {
+    Ok(Client {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        contact_person: row.get(2)?,
+        contact_phone: row.get(3)?,
+        email: row.get(4)?,
+        address: row.get(5)?,
+        is_active: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        gstin: row.get(9)?,
+    })
+}
+
+pub fn map_site(row: &Row) -> rusqlite::Result<Site> {
+    Ok(Site {
+        id: row.get(0)?,
+        client_id: row.get(1)?,
+        client_name: row.get(2)?,
+        name: row.get(3)?,
+        place: row.get(4)?,
+        address: row.get(5)?,
+        contact_person: row.get(6)?,
+        contact_phone: row.get(7)?,
+        is_active: row.get(8)?,
+        state: row.get(9)?,
+        wage_zone: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+/// Trimmed, upper-cased GSTIN, empty when none is given. A GSTIN is 15
+/// letters and digits starting with the two digit state code.
+pub fn normalize_gstin(gstin: Option<&str>) -> Result<Option<String>, String> {
+    let Some(gstin) = gstin.map(str::trim).filter(|g| !g.is_empty()) else {
+        return Ok(None);
+    };
+    let gstin = gstin.to_uppercase();
+    if gstin.len() != 15
+        || !gstin.chars().all(|c| c.is_ascii_alphanumeric())
+        || !gstin[..2].chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(format!("Invalid GSTIN: {gstin}"));
+    }
+    Ok(Some(gstin))
+}
+
+pub fn get_client_by_id(conn: &rusqlite::Connection, id: i64) -> Result<Option<Client>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM clients WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_client) {
+        Ok(client) => Ok(Some(client)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_site_by_id(conn: &rusqlite::Connection, id: i64) -> Result<Option<Site>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{SITE_SELECT} WHERE s.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_site) {
+        Ok(site) => Ok(Some(site)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Client {
+    pub id: i64,
+    pub name: String,
+    pub contact_person: Option<String>,
+    pub contact_phone: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub is_active: i32,
+    /// Printed on invoices and decides between IGST and CGST with SGST
+    pub gstin: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInput {
+    pub name: String,
+    pub contact_person: Option<String>,
+    pub contact_phone: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub is_active: i32,
+    pub gstin: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Site {
+    pub id: i64,
+    pub client_id: i64,
+    pub client_name: String,
+    pub name: String,
+    pub place: Option<String>,
+    pub address: Option<String>,
+    pub contact_person: Option<String>,
+    pub contact_phone: Option<String>,
+    pub is_active: i32,
+    pub state: Option<String>,
+    /// Zone of the state for minimum wages, e.g. Zone A
+    pub wage_zone: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteInput {
+    pub client_id: i64,
+    pub name: String,
+    pub place: Option<String>,
+    pub address: Option<String>,
+    pub contact_person: Option<String>,
+    pub contact_phone: Option<String>,
+    pub is_active: i32,
+    pub state: Option<String>,
+    pub wage_zone: Option<String>,
+}
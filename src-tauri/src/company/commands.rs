@@ -0,0 +1,46 @@
+use super::helpers::load_company_profile;
+use super::types::CompanyProfile;
+use crate::clients::helpers::normalize_gstin;
+use crate::db;
+use crate::state::AppState;
+use rusqlite::params;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_company_profile(_state: State<AppState>) -> Result<CompanyProfile, String> {
+    let conn = db::get_connection()?;
+    load_company_profile(&conn)
+}
+
+#[tauri::command]
+pub fn update_company_profile(
+    _state: State<AppState>,
+    profile: CompanyProfile,
+) -> Result<CompanyProfile, String> {
+    let conn = db::get_connection()?;
+
+    if profile.name.trim().is_empty() {
+        return Err("Company name is required".to_string());
+    }
+    let gstin = normalize_gstin(profile.gstin.as_deref())?;
+
+    conn.execute(
+        "UPDATE company_profile SET
+            name = ?1, address = ?2, phone = ?3, email = ?4, pf_code = ?5, esic_code = ?6,
+            gstin = ?7, pan = ?8, updated_at = CURRENT_TIMESTAMP
+        WHERE id = 1",
+        params![
+            profile.name.trim(),
+            profile.address,
+            profile.phone,
+            profile.email,
+            profile.pf_code,
+            profile.esic_code,
+            gstin,
+            profile.pan,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    load_company_profile(&conn)
+}
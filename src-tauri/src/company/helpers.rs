@@ -0,0 +1,79 @@
+use super::types::CompanyProfile;
+use crate::pdf::{text_width, Font, Page, MARGIN};
+
+pub fn load_company_profile(conn: &rusqlite::Connection) -> Result<CompanyProfile, String> {
+    conn.query_row(
+        "SELECT name, address, phone, email, pf_code, esic_code, gstin, pan
+        FROM company_profile WHERE id = 1",
+        [],
+        |row| {
+            Ok(CompanyProfile {
+                name: row.get(0)?,
+                address: row.get(1)?,
+                phone: row.get(2)?,
+                email: row.get(3)?,
+                pf_code: row.get(4)?,
+                esic_code: row.get(5)?,
+                gstin: row.get(6)?,
+                pan: row.get(7)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to load company profile: {e}"))
+}
+
+fn present(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn centered(page: &mut Page, page_width: f32, y: f32, font: Font, size: f32, text: &str) {
+    let x = (page_width - text_width(text, font, size)) / 2.0;
+    page.text(x.max(MARGIN), y, font, size, text);
+}
+
+/// Print the company name, address and registration numbers centred at the
+/// top of a page and return the y position below the rule under them
+pub fn draw_letterhead(page: &mut Page, page_width: f32, profile: &CompanyProfile) -> f32 {
+    let mut y = MARGIN + 14.0;
+    centered(page, page_width, y, Font::Bold, 16.0, &profile.name);
+    y += 14.0;
+
+    for line in profile
+        .address
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        centered(page, page_width, y, Font::Regular, 9.0, line);
+        y += 11.0;
+    }
+
+    let contact = [
+        present(&profile.phone).map(|v| format!("Phone: {v}")),
+        present(&profile.email).map(|v| format!("Email: {v}")),
+    ];
+    let registrations = [
+        present(&profile.pf_code).map(|v| format!("PF Code: {v}")),
+        present(&profile.esic_code).map(|v| format!("ESIC Code: {v}")),
+        present(&profile.gstin).map(|v| format!("GSTIN: {v}")),
+        present(&profile.pan).map(|v| format!("PAN: {v}")),
+    ];
+    for parts in [&contact[..], &registrations[..]] {
+        let line = parts
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("  |  ");
+        if !line.is_empty() {
+            centered(page, page_width, y, Font::Regular, 8.5, &line);
+            y += 11.0;
+        }
+    }
+
+    y += 2.0;
+    page.line(MARGIN, y, page_width - MARGIN, y, 1.0);
+    y + 8.0
+}
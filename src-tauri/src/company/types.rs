@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyProfile {
+    pub name: String,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    /// EPFO establishment code
+    pub pf_code: Option<String>,
+    pub esic_code: Option<String>,
+    pub gstin: Option<String>,
+    pub pan: Option<String>,
+}
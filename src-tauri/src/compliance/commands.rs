@@ -0,0 +1,131 @@
+use super::helpers::{evaluate_compliance, CHECKS, FAIL, MISSING, PASS};
+use super::types::{
+    ComplianceCheckSummary, ComplianceDashboard, EmployeeCompliance, SiteCompliance,
+};
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::state::AppState;
+use chrono::NaiveDate;
+use tauri::State;
+
+fn compliance_date(date: Option<String>) -> Result<NaiveDate, String> {
+    match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date(&date, "date"),
+        None => Ok(today()),
+    }
+}
+
+/// PSARA compliance of an employee on the given date (today by default):
+/// police verification, training certificate, medical fitness and age
+#[tauri::command]
+pub fn get_employee_compliance(
+    _state: State<AppState>,
+    employee_id: i64,
+    date: Option<String>,
+) -> Result<EmployeeCompliance, String> {
+    let conn = db::get_connection()?;
+
+    let date = compliance_date(date)?;
+    evaluate_compliance(&conn, date, Some(employee_id))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Employee not found".to_string())
+}
+
+/// Compliance of all current employees, with the non-compliant ones grouped
+/// by the site they are deployed at
+#[tauri::command]
+pub fn get_compliance_dashboard(
+    _state: State<AppState>,
+    date: Option<String>,
+    client_id: Option<i64>,
+) -> Result<ComplianceDashboard, String> {
+    let conn = db::get_connection()?;
+
+    let date = compliance_date(date)?;
+    let mut employees = evaluate_compliance(&conn, date, None)?;
+    if client_id.is_some() {
+        employees.retain(|e| e.client_id == client_id);
+    }
+
+    let mut dashboard = ComplianceDashboard {
+        date: format_date(date),
+        total: 0,
+        compliant: 0,
+        failing: 0,
+        missing: 0,
+        checks: CHECKS
+            .iter()
+            .map(|(check, label)| ComplianceCheckSummary {
+                check: check.to_string(),
+                label: label.to_string(),
+                failing: 0,
+                missing: 0,
+            })
+            .collect(),
+        sites: Vec::new(),
+    };
+
+    for employee in employees {
+        for (summary, check) in dashboard.checks.iter_mut().zip(&employee.checks) {
+            match check.status.as_str() {
+                FAIL => summary.failing += 1,
+                MISSING => summary.missing += 1,
+                _ => {}
+            }
+        }
+
+        let position = dashboard
+            .sites
+            .iter()
+            .position(|s| s.site_id == employee.site_id);
+        let site = match position {
+            Some(position) => &mut dashboard.sites[position],
+            None => {
+                dashboard.sites.push(SiteCompliance {
+                    site_id: employee.site_id,
+                    site_name: employee.site_name.clone(),
+                    client_name: employee.client_name.clone(),
+                    total: 0,
+                    compliant: 0,
+                    failing: 0,
+                    missing: 0,
+                    employees: Vec::new(),
+                });
+                dashboard.sites.last_mut().expect("site was just pushed")
+            }
+        };
+
+        dashboard.total += 1;
+        site.total += 1;
+        match employee.status.as_str() {
+            PASS => {
+                dashboard.compliant += 1;
+                site.compliant += 1;
+            }
+            FAIL => {
+                dashboard.failing += 1;
+                site.failing += 1;
+            }
+            _ => {
+                dashboard.missing += 1;
+                site.missing += 1;
+            }
+        }
+        if employee.status != PASS {
+            site.employees.push(employee);
+        }
+    }
+
+    dashboard.sites.retain(|s| !s.employees.is_empty());
+    dashboard.sites.sort_by(|a, b| {
+        b.failing
+            .cmp(&a.failing)
+            .then(b.missing.cmp(&a.missing))
+            .then(a.site_id.is_none().cmp(&b.site_id.is_none()))
+            .then(a.client_name.cmp(&b.client_name))
+            .then(a.site_name.cmp(&b.site_name))
+    });
+
+    Ok(dashboard)
+}
@@ -0,0 +1,368 @@
+use chrono::NaiveDate;
+use rusqlite::params;
+use std::collections::HashMap;
+
+use super::types::{ComplianceCheck, EmployeeCompliance};
+use crate::dates::{age_on, format_date, DATE_FORMAT};
+use crate::deployments::helpers::ACTIVE_ON_DATE;
+
+/// Attachment category of security training certificates, including those
+/// stored with training attendance
+pub const TRAINING_CATEGORY: &str = "training_certificate";
+/// Attachment category of medical and physical fitness records
+pub const MEDICAL_CATEGORY: &str = "medical_fitness";
+/// Age limits for private security guards under PSARA
+pub const MIN_AGE: i32 = 18;
+pub const MAX_AGE: i32 = 65;
+/// A medical fitness record older than this no longer counts
+pub const MEDICAL_VALIDITY_MONTHS: u32 = 12;
+
+pub const PASS: &str = "pass";
+pub const FAIL: &str = "fail";
+pub const MISSING: &str = "missing";
+
+pub const CHECKS: [(&str, &str); 4] = [
+    ("police_verification", "Police verification"),
+    ("training", "Security training certificate"),
+    ("medical_fitness", "Medical and physical fitness"),
+    ("age", "Age criteria"),
+];
+
+/// Employee details the checks are evaluated from
+struct Subject {
+    compliance: EmployeeCompliance,
+    police_station: Option<String>,
+    date_of_birth: Option<String>,
+    verification_status: Option<String>,
+    verification_station: Option<String>,
+    application_date: Option<String>,
+    certificate_date: Option<String>,
+    renewal_due_date: Option<String>,
+}
+
+/// Attachment as `(reference, created_at)`
+type AttachmentDates = (Option<String>, Option<String>);
+
+/// Certification as `(program name, certificate number, expiry date)`
+type CertificationDates = (String, Option<String>, Option<String>);
+
+fn present(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn check(index: usize, status: &str, detail: String) -> ComplianceCheck {
+    let (check, label) = CHECKS[index];
+    ComplianceCheck {
+        check: check.to_string(),
+        label: label.to_string(),
+        status: status.to_string(),
+        detail: Some(detail),
+    }
+}
+
+fn police_check(subject: &Subject, date: &str) -> ComplianceCheck {
+    let station = present(subject.verification_station.as_deref())
+        .or(present(subject.police_station.as_deref()));
+
+    match subject.verification_status.as_deref() {
+        None => check(0, MISSING, "No police verification on record".to_string()),
+        Some("adverse") => check(0, FAIL, "Adverse police verification report".to_string()),
+        Some("cleared") => match present(subject.renewal_due_date.as_deref()) {
+            Some(due) if due < date => check(0, FAIL, format!("Renewal was due on {due}")),
+            _ => match station {
+                None => check(0, MISSING, "Police station is not recorded".to_string()),
+                Some(station) => {
+                    let detail = match present(subject.certificate_date.as_deref()) {
+                        Some(certified) => format!("Cleared by {station} on {certified}"),
+                        None => format!("Cleared by {station}"),
+                    };
+                    check(0, PASS, detail)
+                }
+            },
+        },
+        Some(_) => {
+            let detail = match present(subject.application_date.as_deref()) {
+                Some(applied) => format!("Applied on {applied}, pending"),
+                None => "Verification is pending".to_string(),
+            };
+            check(0, MISSING, detail)
+        }
+    }
+}
+
+/// Date of an attachment: its reference when that is a date, otherwise the
+/// day it was added
+fn attachment_date(attachment: &AttachmentDates) -> Option<NaiveDate> {
+    let (reference, created_at) = attachment;
+    present(reference.as_deref())
+        .and_then(|r| NaiveDate::parse_from_str(r, DATE_FORMAT).ok())
+        .or_else(|| {
+            created_at
+                .as_deref()
+                .and_then(|c| c.get(..10))
+                .and_then(|c| NaiveDate::parse_from_str(c, DATE_FORMAT).ok())
+        })
+}
+
+/// A certification from the training records that is valid on the date
+/// passes; without training records an attached certificate does
+fn training_check(
+    certifications: Option<&Vec<CertificationDates>>,
+    attachments: Option<&Vec<AttachmentDates>>,
+    date: &str,
+) -> ComplianceCheck {
+    let certifications = certifications.map(Vec::as_slice).unwrap_or_default();
+    let valid = certifications
+        .iter()
+        .find(|(_, _, expiry)| present(expiry.as_deref()).is_none_or(|e| e >= date));
+    if let Some((program, number, expiry)) = valid {
+        let mut detail = format!("Certified in {program}");
+        if let Some(number) = present(number.as_deref()) {
+            detail.push_str(&format!(", certificate {number}"));
+        }
+        if let Some(expiry) = present(expiry.as_deref()) {
+            detail.push_str(&format!(", valid until {expiry}"));
+        }
+        return check(1, PASS, detail);
+    }
+    if let Some((program, _, expiry)) = certifications.first() {
+        return check(
+            1,
+            FAIL,
+            format!(
+                "{program} certification expired on {}",
+                expiry.as_deref().unwrap_or_default()
+            ),
+        );
+    }
+
+    match attachments.and_then(|a| a.first()) {
+        None => check(1, MISSING, "No training certificate attached".to_string()),
+        Some((reference, _)) => {
+            let detail = match present(reference.as_deref()) {
+                Some(reference) => format!("Certificate {reference} attached"),
+                None => "Certificate attached".to_string(),
+            };
+            check(1, PASS, detail)
+        }
+    }
+}
+
+fn medical_check(attachments: Option<&Vec<AttachmentDates>>, date: NaiveDate) -> ComplianceCheck {
+    let latest = attachments
+        .into_iter()
+        .flatten()
+        .filter_map(attachment_date)
+        .max();
+    let Some(latest) = latest else {
+        return check(2, MISSING, "No medical fitness record attached".to_string());
+    };
+
+    let valid_from = date
+        .checked_sub_months(chrono::Months::new(MEDICAL_VALIDITY_MONTHS))
+        .unwrap_or(date);
+    if latest < valid_from {
+        check(
+            2,
+            FAIL,
+            format!(
+                "Last record dated {} is older than {MEDICAL_VALIDITY_MONTHS} months",
+                format_date(latest)
+            ),
+        )
+    } else {
+        check(2, PASS, format!("Record dated {}", format_date(latest)))
+    }
+}
+
+fn age_check(date_of_birth: Option<&str>, date: NaiveDate) -> ComplianceCheck {
+    if present(date_of_birth).is_none() {
+        return check(3, MISSING, "Date of birth is not recorded".to_string());
+    }
+    match age_on(date_of_birth.map(str::trim), date) {
+        None => check(3, MISSING, "Date of birth is not a valid date".to_string()),
+        Some(age) if !(MIN_AGE..=MAX_AGE).contains(&age) => check(
+            3,
+            FAIL,
+            format!("Aged {age}, must be between {MIN_AGE} and {MAX_AGE}"),
+        ),
+        Some(age) => check(3, PASS, format!("Aged {age}")),
+    }
+}
+
+/// Worst status of the checks: fail, then missing, then pass
+fn overall_status(checks: &[ComplianceCheck]) -> &'static str {
+    if checks.iter().any(|c| c.status == FAIL) {
+        FAIL
+    } else if checks.iter().any(|c| c.status == MISSING) {
+        MISSING
+    } else {
+        PASS
+    }
+}
+
+/// Attachments of the checked categories per employee and category, latest
+/// first. Certificates of training attendance count through the training
+/// records instead.
+fn compliance_attachments(
+    conn: &rusqlite::Connection,
+    employee_id: Option<i64>,
+) -> Result<HashMap<(i64, String), Vec<AttachmentDates>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT employee_id, category, reference, created_at FROM employee_attachments
+            WHERE category IN (?1, ?2) AND (?3 IS NULL OR employee_id = ?3)
+              AND id NOT IN (
+                SELECT attachment_id FROM training_attendance WHERE attachment_id IS NOT NULL
+              )
+            ORDER BY created_at DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            params![TRAINING_CATEGORY, MEDICAL_CATEGORY, employee_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut attachments: HashMap<(i64, String), Vec<AttachmentDates>> = HashMap::new();
+    for (employee_id, category, reference, created_at) in rows {
+        attachments
+            .entry((employee_id, category))
+            .or_default()
+            .push((reference, created_at));
+    }
+    Ok(attachments)
+}
+
+/// Latest certification per employee and training program earned by `date`,
+/// the one expiring last first
+fn compliance_certifications(
+    conn: &rusqlite::Connection,
+    date: &str,
+    employee_id: Option<i64>,
+) -> Result<HashMap<i64, Vec<CertificationDates>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT lc.employee_id, p.name, lc.certificate_number, lc.expiry_date
+            FROM latest_certifications lc
+            JOIN training_programs p ON p.id = lc.program_id
+            WHERE lc.certified_on <= ?1 AND (?2 IS NULL OR lc.employee_id = ?2)
+            ORDER BY lc.expiry_date IS NOT NULL, lc.expiry_date DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![date, employee_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                (
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ),
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut certifications: HashMap<i64, Vec<CertificationDates>> = HashMap::new();
+    for (employee_id, certification) in rows {
+        certifications
+            .entry(employee_id)
+            .or_default()
+            .push(certification);
+    }
+    Ok(certifications)
+}
+
+/// PSARA compliance of one employee (`employee_id`) or of all current
+/// employees on `date`, with the site each is deployed at on that date
+pub fn evaluate_compliance(
+    conn: &rusqlite::Connection,
+    date: NaiveDate,
+    employee_id: Option<i64>,
+) -> Result<Vec<EmployeeCompliance>, String> {
+    let date_text = format_date(date);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT e.id, e.name, e.essid, e.job_post, e.employment_status,
+                s.id, s.name, c.id, c.name,
+                e.police_station, e.date_of_birth,
+                lpv.status, lpv.police_station, lpv.application_date,
+                lpv.certificate_date, lpv.renewal_due_date
+            FROM employees e
+            LEFT JOIN deployments dep ON dep.id = (
+                SELECT d.id FROM deployments d
+                WHERE d.employee_id = e.id AND {ACTIVE_ON_DATE}
+                ORDER BY d.start_date DESC, d.id DESC LIMIT 1
+            )
+            LEFT JOIN sites s ON s.id = dep.site_id
+            LEFT JOIN clients c ON c.id = s.client_id
+            LEFT JOIN latest_police_verifications lpv ON lpv.employee_id = e.id
+            WHERE (?2 IS NULL AND e.employment_status = 'current') OR e.id = ?2
+            ORDER BY e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+    let subjects = stmt
+        .query_map(params![date_text, employee_id], |row| {
+            Ok(Subject {
+                compliance: EmployeeCompliance {
+                    employee_id: row.get(0)?,
+                    employee_name: row.get(1)?,
+                    essid: row.get(2)?,
+                    job_post: row.get(3)?,
+                    employment_status: row.get(4)?,
+                    site_id: row.get(5)?,
+                    site_name: row.get(6)?,
+                    client_id: row.get(7)?,
+                    client_name: row.get(8)?,
+                    status: String::new(),
+                    checks: Vec::new(),
+                },
+                police_station: row.get(9)?,
+                date_of_birth: row.get(10)?,
+                verification_status: row.get(11)?,
+                verification_station: row.get(12)?,
+                application_date: row.get(13)?,
+                certificate_date: row.get(14)?,
+                renewal_due_date: row.get(15)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let attachments = compliance_attachments(conn, employee_id)?;
+    let certifications = compliance_certifications(conn, &date_text, employee_id)?;
+
+    let mut evaluated = Vec::new();
+    for subject in subjects {
+        let id = subject.compliance.employee_id;
+        let checks = vec![
+            police_check(&subject, &date_text),
+            training_check(
+                certifications.get(&id),
+                attachments.get(&(id, TRAINING_CATEGORY.to_string())),
+                &date_text,
+            ),
+            medical_check(attachments.get(&(id, MEDICAL_CATEGORY.to_string())), date),
+            age_check(subject.date_of_birth.as_deref(), date),
+        ];
+
+        let mut compliance = subject.compliance;
+        compliance.status = overall_status(&checks).to_string();
+        compliance.checks = checks;
+        evaluated.push(compliance);
+    }
+    Ok(evaluated)
+}
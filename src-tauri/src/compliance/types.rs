@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceCheck {
+    /// police_verification, training, medical_fitness or age
+    pub check: String,
+    pub label: String,
+    /// pass, fail or missing
+    pub status: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployeeCompliance {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub employment_status: String,
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    /// fail if any check fails, otherwise missing if any check lacks data
+    pub status: String,
+    pub checks: Vec<ComplianceCheck>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteCompliance {
+    /// None for current employees who are not deployed
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub client_name: Option<String>,
+    pub total: i64,
+    pub compliant: i64,
+    pub failing: i64,
+    pub missing: i64,
+    /// Non-compliant employees only
+    pub employees: Vec<EmployeeCompliance>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceCheckSummary {
+    pub check: String,
+    pub label: String,
+    pub failing: i64,
+    pub missing: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceDashboard {
+    pub date: String,
+    pub total: i64,
+    pub compliant: i64,
+    pub failing: i64,
+    pub missing: i64,
+    pub checks: Vec<ComplianceCheckSummary>,
+    /// Sites with at least one non-compliant employee, worst first
+    pub sites: Vec<SiteCompliance>,
+}
@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub fn csv_field(value: Option<String>) -> String {
+    match value {
+        None => String::new(),
+        Some(v) => {
+            if v.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", v.replace('"', "\"\""))
+            } else {
+                v
+            }
+        }
+    }
+}
+
+/// Write a header and rows of already formatted values to a CSV file
+pub fn write_csv(
+    export_path: &Path,
+    header: &[&str],
+    rows: &[Vec<String>],
+) -> Result<String, String> {
+    let file = File::create(export_path).map_err(|e| format!("Failed to create file: {e}"))?;
+    let mut writer = BufWriter::new(file);
+
+    let header = header
+        .iter()
+        .map(|h| csv_field(Some(h.to_string())))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{header}").map_err(|e| format!("Failed to write header: {e}"))?;
+
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|v| csv_field(Some(v.clone())))
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(writer, "{line}").map_err(|e| format!("Failed to write row: {e}"))?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush: {e}"))?;
+    Ok(export_path.to_string_lossy().to_string())
+}
+
+/// Parse CSV text into rows of fields, handling quoted fields, escaped quotes
+/// and CRLF line endings. Each row comes with the line of the file it starts
+/// on, counting from 1, so that errors can point at it even though blank
+/// lines are skipped and quoted fields may span lines.
+pub fn parse_csv(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.trim().is_empty()) {
+                    rows.push((row_line, std::mem::take(&mut row)));
+                } else {
+                    row.clear();
+                }
+                line += 1;
+                row_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    row.push(field);
+    if row.iter().any(|f| !f.trim().is_empty()) {
+        rows.push((row_line, row));
+    }
+
+    rows
+}
+
+/// Position of the header column matching any of `names`, compared
+/// case-insensitively and ignoring spaces, dashes and underscores
+pub fn header_index(header: &[String], names: &[&str]) -> Option<usize> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_lowercase()
+    };
+    header.iter().position(|h| {
+        let h = normalize(h);
+        names.iter().any(|n| normalize(n) == h)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_keep_the_line_they_start_on() {
+        let rows = parse_csv(
+            "\u{feff}ESSID,Remarks\r\n\r\nE1,\"night\r\nshift\"\r\n,\n\nE2,\"said \"\"ok\"\"\"",
+        );
+        assert_eq!(
+            rows,
+            vec![
+                (1, vec!["ESSID".to_string(), "Remarks".to_string()]),
+                (3, vec!["E1".to_string(), "night\r\nshift".to_string()]),
+                (7, vec!["E2".to_string(), "said \"ok\"".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_with_separators_are_quoted() {
+        assert_eq!(csv_field(None), "");
+        assert_eq!(csv_field(Some("Kochi".to_string())), "Kochi");
+        assert_eq!(csv_field(Some("a,b".to_string())), "\"a,b\"");
+        assert_eq!(
+            csv_field(Some("say \"hi\"".to_string())),
+            "\"say \"\"hi\"\"\""
+        );
+        assert_eq!(csv_field(Some("a\rb".to_string())), "\"a\rb\"");
+        assert_eq!(
+            parse_csv(&csv_field(Some("a\r\nb".to_string()))),
+            vec![(1, vec!["a\r\nb".to_string()])]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{Datelike, NaiveDate};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parse a `YYYY-MM-DD` date, naming the offending field on failure
+pub fn parse_date(value: &str, field: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT)
+        .map_err(|_| format!("Invalid {field} '{value}', expected YYYY-MM-DD"))
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Age in completed years on `date`, if the birth date is known
+pub fn age_on(date_of_birth: Option<&str>, date: NaiveDate) -> Option<i32> {
+    let dob = NaiveDate::parse_from_str(date_of_birth?, DATE_FORMAT).ok()?;
+    let mut age = date.year() - dob.year();
+    if (date.month(), date.day()) < (dob.month(), dob.day()) {
+        age -= 1;
+    }
+    Some(age)
+}
+
+/// First and last day of a `YYYY-MM` month
+pub fn month_bounds(month: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), DATE_FORMAT)
+        .map_err(|_| format!("Invalid month '{month}', expected YYYY-MM"))?;
+    let next_month = first
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| format!("Invalid month '{month}'"))?;
+    Ok((first, next_month.pred_opt().unwrap_or(first)))
+}
+
+/// Parse a date as written in spreadsheets: `YYYY-MM-DD`, `DD-MM-YYYY` or
+/// `DD/MM/YYYY`
+pub fn parse_date_flexible(value: &str, field: &str) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    [DATE_FORMAT, "%d-%m-%Y", "%d/%m/%Y", "%Y/%m/%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .ok_or_else(|| format!("Invalid {field} '{value}', expected YYYY-MM-DD or DD-MM-YYYY"))
+}
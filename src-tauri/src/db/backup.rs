@@ -1,4 +1,5 @@
 use super::DB_NAME;
+use crate::csv::csv_field;
 use chrono::Local;
 use rusqlite::Connection;
 use std::fs::{self, File};
@@ -114,16 +115,3 @@ pub fn export_to_csv(conn: &Connection, export_path: &Path) -> Result<String, St
         .map_err(|e| format!("Failed to flush: {e}"))?;
     Ok(export_path.to_string_lossy().to_string())
 }
-
-fn csv_field(value: Option<String>) -> String {
-    match value {
-        None => String::new(),
-        Some(v) => {
-            if v.contains(',') || v.contains('"') || v.contains('\n') {
-                format!("\"{}\"", v.replace('"', "\"\""))
-            } else {
-                v
-            }
-        }
-    }
-}
@@ -114,7 +114,27 @@ pub fn run_migrations(conn: &Connection) -> Result<(), String> {
 
         match version {
             1 => migration_v1(conn)?,
-            // 2 => migration_v2(conn)?,
+            2 => migration_v2(conn)?,
+            3 => migration_v3(conn)?,
+            4 => migration_v4(conn)?,
+            5 => migration_v5(conn)?,
+            6 => migration_v6(conn)?,
+            7 => migration_v7(conn)?,
+            8 => migration_v8(conn)?,
+            9 => migration_v9(conn)?,
+            10 => migration_v10(conn)?,
+            11 => migration_v11(conn)?,
+            12 => migration_v12(conn)?,
+            13 => migration_v13(conn)?,
+            14 => migration_v14(conn)?,
+            15 => migration_v15(conn)?,
+            16 => migration_v16(conn)?,
+            17 => migration_v17(conn)?,
+            18 => migration_v18(conn)?,
+            19 => migration_v19(conn)?,
+            20 => migration_v20(conn)?,
+            21 => migration_v21(conn)?,
+            22 => migration_v22(conn)?,
             _ => return Err(format!("Unknown migration version: {version}")),
         }
 
@@ -130,3 +150,1271 @@ pub fn run_migrations(conn: &Connection) -> Result<(), String> {
 fn migration_v1(conn: &Connection) -> Result<(), String> {
     run_initial_migration(conn)
 }
+
+/// Migration to version 2: police verification history per employee
+fn migration_v2(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS police_verifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            application_date TEXT,
+            police_station TEXT,
+            reference_number TEXT,
+            status TEXT NOT NULL DEFAULT 'pending'
+                CHECK (status IN ('pending', 'cleared', 'adverse')),
+            certificate_date TEXT,
+            renewal_due_date TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create police_verifications table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_police_verifications_employee
+         ON police_verifications(employee_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create police_verifications employee index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_police_verifications_station
+         ON police_verifications(police_station)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create police_verifications station index: {e}"))?;
+
+    // The most recent application per employee is the one that counts
+    conn.execute(
+        "CREATE VIEW IF NOT EXISTS latest_police_verifications AS
+         SELECT pv.* FROM police_verifications pv
+         WHERE pv.id = (
+            SELECT p2.id FROM police_verifications p2
+            WHERE p2.employee_id = pv.employee_id
+            ORDER BY COALESCE(p2.application_date, p2.created_at) DESC, p2.id DESC
+            LIMIT 1
+         )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create latest_police_verifications view: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 3: clients, their sites and employee deployments
+fn migration_v3(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS clients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            contact_person TEXT,
+            contact_phone TEXT,
+            email TEXT,
+            address TEXT,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create clients table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sites (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            client_id INTEGER NOT NULL REFERENCES clients(id),
+            name TEXT NOT NULL,
+            place TEXT,
+            address TEXT,
+            contact_person TEXT,
+            contact_phone TEXT,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (client_id, name)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create sites table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS deployments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            site_id INTEGER NOT NULL REFERENCES sites(id),
+            role TEXT,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create deployments table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sites_client ON sites(client_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create sites client index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_deployments_employee ON deployments(employee_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create deployments employee index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_deployments_site_dates
+         ON deployments(site_id, start_date, end_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create deployments site index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 4: shift templates and the per-site roster
+fn migration_v4(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS shift_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            code TEXT NOT NULL UNIQUE,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create shift_templates table: {e}"))?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO shift_templates (name, code, start_time, end_time) VALUES
+            ('Day', 'D', '08:00', '20:00'),
+            ('Night', 'N', '20:00', '08:00'),
+            ('General', 'G', '09:00', '18:00')",
+        [],
+    )
+    .map_err(|e| format!("Failed to seed shift templates: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS roster_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            site_id INTEGER NOT NULL REFERENCES sites(id),
+            shift_template_id INTEGER NOT NULL REFERENCES shift_templates(id),
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            roster_date TEXT NOT NULL,
+            role TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (employee_id, roster_date, shift_template_id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create roster_assignments table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_roster_site_date
+         ON roster_assignments(site_id, roster_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create roster site index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_roster_employee_date
+         ON roster_assignments(employee_id, roster_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create roster employee index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 5: daily attendance register
+fn migration_v5(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS attendance (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            attendance_date TEXT NOT NULL,
+            status TEXT NOT NULL
+                CHECK (status IN ('present', 'absent', 'leave', 'weekly_off', 'holiday')),
+            site_id INTEGER REFERENCES sites(id),
+            shift_template_id INTEGER REFERENCES shift_templates(id),
+            ot_shifts INTEGER NOT NULL DEFAULT 0,
+            source TEXT NOT NULL DEFAULT 'manual',
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (employee_id, attendance_date)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create attendance table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_attendance_date ON attendance(attendance_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create attendance date index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_attendance_site_date
+         ON attendance(site_id, attendance_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create attendance site index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 6: biometric enrolment number mapping
+fn migration_v6(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS biometric_enrolments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            site_id INTEGER REFERENCES sites(id) ON DELETE CASCADE,
+            enrolment_number TEXT NOT NULL,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create biometric_enrolments table: {e}"))?;
+
+    // A mapping without a site applies to every device
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_biometric_enrolments_number
+         ON biometric_enrolments(IFNULL(site_id, 0), enrolment_number)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create biometric_enrolments index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 7: wage structures, statutory rates and pay runs
+fn migration_v7(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS wage_structures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_post TEXT NOT NULL COLLATE NOCASE,
+            effective_from TEXT NOT NULL,
+            basic REAL NOT NULL DEFAULT 0,
+            da REAL NOT NULL DEFAULT 0,
+            hra REAL NOT NULL DEFAULT 0,
+            other_allowances REAL NOT NULL DEFAULT 0,
+            ot_rate REAL NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (job_post, effective_from)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create wage_structures table: {e}"))?;
+
+    // Single row holding the PF and ESI rates and ceilings
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payroll_settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            pf_employee_rate REAL NOT NULL DEFAULT 12,
+            pf_employer_rate REAL NOT NULL DEFAULT 12,
+            eps_rate REAL NOT NULL DEFAULT 8.33,
+            pf_wage_ceiling REAL NOT NULL DEFAULT 15000,
+            restrict_pf_to_ceiling INTEGER NOT NULL DEFAULT 1,
+            esi_employee_rate REAL NOT NULL DEFAULT 0.75,
+            esi_employer_rate REAL NOT NULL DEFAULT 3.25,
+            esi_wage_ceiling REAL NOT NULL DEFAULT 21000,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create payroll_settings table: {e}"))?;
+
+    conn.execute("INSERT OR IGNORE INTO payroll_settings (id) VALUES (1)", [])
+        .map_err(|e| format!("Failed to seed payroll settings: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS professional_tax_slabs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            min_gross REAL NOT NULL,
+            max_gross REAL,
+            amount REAL NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create professional_tax_slabs table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pay_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month TEXT NOT NULL UNIQUE,
+            status TEXT NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'locked')),
+            locked_at TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create pay_runs table: {e}"))?;
+
+    // Employee details are copied onto the line so a locked month keeps
+    // showing what was paid even after the employee record changes
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pay_run_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pay_run_id INTEGER NOT NULL REFERENCES pay_runs(id) ON DELETE CASCADE,
+            employee_id INTEGER REFERENCES employees(id) ON DELETE SET NULL,
+            employee_name TEXT NOT NULL,
+            essid TEXT NOT NULL,
+            job_post TEXT,
+            uan TEXT,
+            esiip TEXT,
+            days_in_month INTEGER NOT NULL,
+            paid_days INTEGER NOT NULL,
+            ot_shifts INTEGER NOT NULL DEFAULT 0,
+            gross_pay REAL NOT NULL,
+            pf_wages REAL NOT NULL DEFAULT 0,
+            eps_wages REAL NOT NULL DEFAULT 0,
+            esi_wages REAL NOT NULL DEFAULT 0,
+            total_deductions REAL NOT NULL DEFAULT 0,
+            net_pay REAL NOT NULL,
+            UNIQUE (pay_run_id, employee_id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create pay_run_lines table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pay_run_line_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            line_id INTEGER NOT NULL REFERENCES pay_run_lines(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL CHECK (kind IN ('earning', 'deduction', 'employer')),
+            code TEXT NOT NULL,
+            label TEXT NOT NULL,
+            amount REAL NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create pay_run_line_items table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pay_run_lines_employee ON pay_run_lines(employee_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create pay run lines index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pay_run_line_items_line ON pay_run_line_items(line_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create pay run line items index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 8: company letterhead and employee attachments
+fn migration_v8(conn: &Connection) -> Result<(), String> {
+    // Single row printed as the letterhead of payslips and statements
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS company_profile (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            name TEXT NOT NULL DEFAULT '',
+            address TEXT,
+            phone TEXT,
+            email TEXT,
+            pf_code TEXT,
+            esic_code TEXT,
+            gstin TEXT,
+            pan TEXT,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create company_profile table: {e}"))?;
+
+    conn.execute("INSERT OR IGNORE INTO company_profile (id) VALUES (1)", [])
+        .map_err(|e| format!("Failed to seed company profile: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS employee_attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            category TEXT NOT NULL,
+            title TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            file_name TEXT NOT NULL,
+            reference TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create employee_attachments table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_employee_attachments_employee
+         ON employee_attachments(employee_id, category)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create employee attachments index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 9: employee bank accounts and bulk payment layouts
+fn migration_v9(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS employee_bank_accounts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL UNIQUE REFERENCES employees(id) ON DELETE CASCADE,
+            account_holder_name TEXT NOT NULL,
+            account_number TEXT NOT NULL,
+            ifsc TEXT NOT NULL,
+            bank_name TEXT,
+            branch TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create employee_bank_accounts table: {e}"))?;
+
+    // Columns are a JSON array of {"header", "field"} objects
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bank_payment_layouts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            delimiter TEXT NOT NULL DEFAULT ',',
+            include_header INTEGER NOT NULL DEFAULT 1,
+            debit_account TEXT,
+            transaction_type TEXT NOT NULL DEFAULT 'NEFT',
+            narration TEXT NOT NULL DEFAULT 'Salary {month}',
+            date_format TEXT NOT NULL DEFAULT '%d/%m/%Y',
+            columns TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create bank_payment_layouts table: {e}"))?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO bank_payment_layouts (name, delimiter, include_header, columns)
+         VALUES
+            ('Generic NEFT (CSV)', ',', 1, '[
+                {\"header\": \"Beneficiary Name\", \"field\": \"beneficiary_name\"},
+                {\"header\": \"Beneficiary Account Number\", \"field\": \"account_number\"},
+                {\"header\": \"IFSC\", \"field\": \"ifsc\"},
+                {\"header\": \"Amount\", \"field\": \"amount\"},
+                {\"header\": \"Narration\", \"field\": \"narration\"}
+            ]'),
+            ('Debit account with transaction type (CSV)', ',', 1, '[
+                {\"header\": \"Transaction Type\", \"field\": \"transaction_type\"},
+                {\"header\": \"Debit Account Number\", \"field\": \"debit_account\"},
+                {\"header\": \"Beneficiary Account Number\", \"field\": \"account_number\"},
+                {\"header\": \"Beneficiary Name\", \"field\": \"beneficiary_name\"},
+                {\"header\": \"IFSC\", \"field\": \"ifsc\"},
+                {\"header\": \"Amount\", \"field\": \"amount\"},
+                {\"header\": \"Value Date\", \"field\": \"payment_date\"},
+                {\"header\": \"Employee Code\", \"field\": \"essid\"},
+                {\"header\": \"Remarks\", \"field\": \"narration\"}
+            ]'),
+            ('Pipe delimited, no header', '|', 0, '[
+                {\"header\": \"Account Number\", \"field\": \"account_number\"},
+                {\"header\": \"Amount\", \"field\": \"amount\"},
+                {\"header\": \"Beneficiary Name\", \"field\": \"beneficiary_name\"},
+                {\"header\": \"IFSC\", \"field\": \"ifsc\"},
+                {\"header\": \"Narration\", \"field\": \"narration\"}
+            ]')",
+        [],
+    )
+    .map_err(|e| format!("Failed to seed bank payment layouts: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 10: salary advances and their recoveries
+fn migration_v10(conn: &Connection) -> Result<(), String> {
+    // Recovery is either a fixed amount per month or a percentage of the net
+    // pay, starting from recovery_start_month (YYYY-MM)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS salary_advances (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            disbursed_on TEXT NOT NULL,
+            amount REAL NOT NULL CHECK (amount > 0),
+            reason TEXT,
+            recovery_type TEXT NOT NULL DEFAULT 'fixed'
+                CHECK (recovery_type IN ('fixed', 'percentage')),
+            recovery_value REAL NOT NULL CHECK (recovery_value > 0),
+            recovery_start_month TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create salary_advances table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_salary_advances_employee
+         ON salary_advances (employee_id, disbursed_on)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create salary advances index: {e}"))?;
+
+    // Recoveries made by payroll belong to a pay run line and go away with it
+    // when the month is recomputed; manual ones have no line
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS advance_recoveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            advance_id INTEGER NOT NULL REFERENCES salary_advances(id) ON DELETE CASCADE,
+            line_id INTEGER REFERENCES pay_run_lines(id) ON DELETE CASCADE,
+            recovered_on TEXT NOT NULL,
+            amount REAL NOT NULL CHECK (amount > 0),
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create advance_recoveries table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_advance_recoveries_advance
+         ON advance_recoveries (advance_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create advance recoveries index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 11: full and final exit settlements
+fn migration_v11(conn: &Connection) -> Result<(), String> {
+    // The outstanding items value is what the employee still held when the
+    // settlement was computed, so that a return or issue before settling is
+    // noticed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS exit_settlements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL UNIQUE REFERENCES employees(id) ON DELETE CASCADE,
+            exit_date TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'settled')),
+            total_earnings REAL NOT NULL DEFAULT 0,
+            total_deductions REAL NOT NULL DEFAULT 0,
+            net_payable REAL NOT NULL DEFAULT 0,
+            outstanding_items_value REAL NOT NULL DEFAULT 0,
+            remarks TEXT,
+            settled_at TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create exit_settlements table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS exit_settlement_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            settlement_id INTEGER NOT NULL REFERENCES exit_settlements(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL CHECK (kind IN ('earning', 'deduction')),
+            code TEXT NOT NULL,
+            label TEXT NOT NULL,
+            amount REAL NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create exit_settlement_items table: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 12: gratuity ceiling in the payroll settings
+fn migration_v12(conn: &Connection) -> Result<(), String> {
+    // Maximum gratuity payable under the Payment of Gratuity Act
+    conn.execute(
+        "ALTER TABLE payroll_settings ADD COLUMN gratuity_ceiling REAL NOT NULL DEFAULT 2000000",
+        [],
+    )
+    .map_err(|e| format!("Failed to add gratuity ceiling: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 13: statutory bonus runs per accounting year
+fn migration_v13(conn: &Connection) -> Result<(), String> {
+    // One bonus computation per accounting year, e.g. 2023-24 for April 2023
+    // to March 2024
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bonus_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            accounting_year TEXT NOT NULL UNIQUE,
+            bonus_rate REAL NOT NULL,
+            calculation_ceiling REAL NOT NULL,
+            eligibility_threshold REAL NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create bonus_runs table: {e}"))?;
+
+    // Employee details are copied so the register stays as computed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bonus_run_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bonus_run_id INTEGER NOT NULL REFERENCES bonus_runs(id) ON DELETE CASCADE,
+            employee_id INTEGER REFERENCES employees(id) ON DELETE SET NULL,
+            employee_name TEXT NOT NULL,
+            father_name TEXT,
+            essid TEXT NOT NULL,
+            job_post TEXT,
+            date_of_birth TEXT,
+            days_worked INTEGER NOT NULL,
+            bonus_wages REAL NOT NULL,
+            bonus_amount REAL NOT NULL,
+            eligible INTEGER NOT NULL,
+            remarks TEXT,
+            paid_on TEXT
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create bonus_run_lines table: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 14: minimum wage rates, job post wage categories and
+/// the state and zone of sites
+fn migration_v14(conn: &Connection) -> Result<(), String> {
+    // Sites decide which state's minimum wages apply to the guards posted there
+    for column in ["state", "wage_zone"] {
+        conn.execute(&format!("ALTER TABLE sites ADD COLUMN {column} TEXT"), [])
+            .map_err(|e| format!("Failed to add {column} to sites: {e}"))?;
+    }
+
+    // An empty zone applies to every zone of the state without its own rate.
+    // Some states notify minimum wages per day rather than per month.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS minimum_wage_rates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            state TEXT NOT NULL COLLATE NOCASE,
+            zone TEXT NOT NULL DEFAULT '' COLLATE NOCASE,
+            category TEXT NOT NULL COLLATE NOCASE,
+            effective_from TEXT NOT NULL,
+            basic REAL NOT NULL,
+            vda REAL NOT NULL DEFAULT 0,
+            rate_basis TEXT NOT NULL DEFAULT 'month' CHECK (rate_basis IN ('day', 'month')),
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (state, zone, category, effective_from)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create minimum_wage_rates table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS job_post_wage_categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_post TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            category TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create job_post_wage_categories table: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 15: leave types, applications and balance adjustments
+fn migration_v15(conn: &Connection) -> Result<(), String> {
+    // Leave is credited at `accrual_days` for every `per_days_worked` days
+    // present, and the balance carried forward never exceeds `max_balance`
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS leave_types (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            name TEXT NOT NULL,
+            paid INTEGER NOT NULL DEFAULT 1,
+            encashable INTEGER NOT NULL DEFAULT 0,
+            accrual_days REAL NOT NULL DEFAULT 0,
+            per_days_worked REAL NOT NULL DEFAULT 0,
+            max_balance REAL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create leave_types table: {e}"))?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO leave_types (
+            code, name, paid, encashable, accrual_days, per_days_worked, max_balance
+        ) VALUES
+            ('CL', 'Casual leave', 1, 0, 1, 30, 12),
+            ('SL', 'Sick leave', 1, 0, 1, 30, 12),
+            ('EL', 'Earned leave', 1, 1, 1, 20, 30)",
+        [],
+    )
+    .map_err(|e| format!("Failed to seed leave types: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS leave_applications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            leave_type_id INTEGER NOT NULL REFERENCES leave_types(id),
+            from_date TEXT NOT NULL,
+            to_date TEXT NOT NULL,
+            days REAL NOT NULL,
+            reason TEXT,
+            status TEXT NOT NULL DEFAULT 'pending'
+                CHECK (status IN ('pending', 'approved', 'rejected', 'cancelled')),
+            decided_on TEXT,
+            decision_remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create leave_applications table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_leave_applications_employee
+            ON leave_applications(employee_id, from_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create leave applications index: {e}"))?;
+
+    // Opening balances and corrections, negative to debit
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS leave_adjustments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            leave_type_id INTEGER NOT NULL REFERENCES leave_types(id),
+            adjusted_on TEXT NOT NULL,
+            days REAL NOT NULL,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create leave_adjustments table: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 16: overtime limits and derived overtime records
+fn migration_v16(conn: &Connection) -> Result<(), String> {
+    // Hours beyond either limit are overtime, paid at a multiple of the
+    // ordinary hourly rate
+    for (column, default) in [
+        ("ot_daily_hours", "8"),
+        ("ot_weekly_hours", "48"),
+        ("ot_rate_multiplier", "2"),
+    ] {
+        conn.execute(
+            &format!(
+                "ALTER TABLE payroll_settings ADD COLUMN {column} REAL NOT NULL DEFAULT {default}"
+            ),
+            [],
+        )
+        .map_err(|e| format!("Failed to add {column} to payroll_settings: {e}"))?;
+    }
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS overtime_records (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            work_date TEXT NOT NULL,
+            site_id INTEGER REFERENCES sites(id) ON DELETE SET NULL,
+            hours_worked REAL NOT NULL,
+            ot_hours REAL NOT NULL,
+            source TEXT NOT NULL CHECK (source IN ('attendance', 'roster', 'manual')),
+            status TEXT NOT NULL DEFAULT 'pending'
+                CHECK (status IN ('pending', 'approved', 'rejected')),
+            approved_on TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (employee_id, work_date)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create overtime_records table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_overtime_records_date ON overtime_records(work_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create overtime records index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 17: client contracts, their rates and GST invoices
+fn migration_v17(conn: &Connection) -> Result<(), String> {
+    conn.execute("ALTER TABLE clients ADD COLUMN gstin TEXT", [])
+        .map_err(|e| format!("Failed to add gstin to clients: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS client_contracts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+            reference TEXT,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            service_charge_rate REAL NOT NULL DEFAULT 0,
+            gst_rate REAL NOT NULL DEFAULT 18,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create client_contracts table: {e}"))?;
+
+    // A rate without a site applies to every site of the client without its
+    // own rate for the role
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS contract_rates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            contract_id INTEGER NOT NULL REFERENCES client_contracts(id) ON DELETE CASCADE,
+            site_id INTEGER REFERENCES sites(id) ON DELETE CASCADE,
+            role TEXT NOT NULL COLLATE NOCASE,
+            billing_basis TEXT NOT NULL CHECK (billing_basis IN ('guard_day', 'post_month')),
+            rate REAL NOT NULL,
+            posts INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create contract_rates table: {e}"))?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_contract_rates_role
+         ON contract_rates(contract_id, IFNULL(site_id, 0), role)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create contract_rates index: {e}"))?;
+
+    // Invoices keep a copy of the supplier and client details and amounts they
+    // were issued with, so later changes to the company profile, client or
+    // contract do not alter them
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS invoices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_number TEXT NOT NULL UNIQUE,
+            financial_year TEXT NOT NULL,
+            sequence INTEGER NOT NULL,
+            supplier_name TEXT NOT NULL,
+            supplier_address TEXT,
+            supplier_phone TEXT,
+            supplier_email TEXT,
+            supplier_pf_code TEXT,
+            supplier_esic_code TEXT,
+            supplier_gstin TEXT,
+            supplier_pan TEXT,
+            client_id INTEGER NOT NULL REFERENCES clients(id),
+            client_name TEXT NOT NULL,
+            client_address TEXT,
+            client_gstin TEXT,
+            month TEXT NOT NULL,
+            invoice_date TEXT NOT NULL,
+            subtotal REAL NOT NULL,
+            service_charge_rate REAL NOT NULL,
+            service_charge REAL NOT NULL,
+            taxable_value REAL NOT NULL,
+            gst_rate REAL NOT NULL,
+            cgst REAL NOT NULL,
+            sgst REAL NOT NULL,
+            igst REAL NOT NULL,
+            total REAL NOT NULL,
+            status TEXT NOT NULL DEFAULT 'issued' CHECK (status IN ('issued', 'cancelled')),
+            cancelled_on TEXT,
+            cancel_reason TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (financial_year, sequence)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create invoices table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS invoice_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+            site_id INTEGER,
+            site_name TEXT,
+            role TEXT NOT NULL,
+            billing_basis TEXT NOT NULL,
+            quantity REAL NOT NULL,
+            rate REAL NOT NULL,
+            amount REAL NOT NULL,
+            description TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create invoice_lines table: {e}"))?;
+
+    // Issued invoices can only be cancelled, never edited or deleted; a cancel
+    // may only set the status, date and reason
+    for trigger in [
+        "CREATE TRIGGER IF NOT EXISTS invoices_immutable BEFORE UPDATE ON invoices
+         WHEN OLD.status <> 'issued' OR NEW.status <> 'cancelled'
+           OR NEW.id IS NOT OLD.id OR NEW.invoice_number IS NOT OLD.invoice_number
+           OR NEW.financial_year IS NOT OLD.financial_year OR NEW.sequence IS NOT OLD.sequence
+           OR NEW.client_id IS NOT OLD.client_id OR NEW.client_name IS NOT OLD.client_name
+           OR NEW.client_address IS NOT OLD.client_address
+           OR NEW.client_gstin IS NOT OLD.client_gstin OR NEW.month IS NOT OLD.month
+           OR NEW.invoice_date IS NOT OLD.invoice_date OR NEW.subtotal IS NOT OLD.subtotal
+           OR NEW.service_charge_rate IS NOT OLD.service_charge_rate
+           OR NEW.service_charge IS NOT OLD.service_charge
+           OR NEW.taxable_value IS NOT OLD.taxable_value OR NEW.gst_rate IS NOT OLD.gst_rate
+           OR NEW.cgst IS NOT OLD.cgst OR NEW.sgst IS NOT OLD.sgst OR NEW.igst IS NOT OLD.igst
+           OR NEW.total IS NOT OLD.total OR NEW.created_at IS NOT OLD.created_at
+           OR NEW.supplier_name IS NOT OLD.supplier_name
+           OR NEW.supplier_address IS NOT OLD.supplier_address
+           OR NEW.supplier_phone IS NOT OLD.supplier_phone
+           OR NEW.supplier_email IS NOT OLD.supplier_email
+           OR NEW.supplier_pf_code IS NOT OLD.supplier_pf_code
+           OR NEW.supplier_esic_code IS NOT OLD.supplier_esic_code
+           OR NEW.supplier_gstin IS NOT OLD.supplier_gstin
+           OR NEW.supplier_pan IS NOT OLD.supplier_pan
+         BEGIN SELECT RAISE(ABORT, 'Issued invoices cannot be changed'); END",
+        "CREATE TRIGGER IF NOT EXISTS invoices_no_delete BEFORE DELETE ON invoices
+         BEGIN SELECT RAISE(ABORT, 'Issued invoices cannot be deleted'); END",
+        "CREATE TRIGGER IF NOT EXISTS invoice_lines_immutable BEFORE UPDATE ON invoice_lines
+         BEGIN SELECT RAISE(ABORT, 'Issued invoices cannot be changed'); END",
+        "CREATE TRIGGER IF NOT EXISTS invoice_lines_no_delete BEFORE DELETE ON invoice_lines
+         BEGIN SELECT RAISE(ABORT, 'Issued invoices cannot be changed'); END",
+    ] {
+        conn.execute(trigger, [])
+            .map_err(|e| format!("Failed to create invoice trigger: {e}"))?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 18: site strength requirements per role and shift
+fn migration_v18(conn: &Connection) -> Result<(), String> {
+    // Strength a site needs per role, per shift or for the whole day when no
+    // shift is given
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS site_requirements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            site_id INTEGER NOT NULL REFERENCES sites(id) ON DELETE CASCADE,
+            role TEXT NOT NULL COLLATE NOCASE,
+            shift_template_id INTEGER REFERENCES shift_templates(id) ON DELETE CASCADE,
+            required INTEGER NOT NULL CHECK (required > 0),
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create site_requirements table: {e}"))?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_site_requirements_role
+         ON site_requirements(site_id, role, IFNULL(shift_template_id, 0))",
+        [],
+    )
+    .map_err(|e| format!("Failed to create site_requirements index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 19: weapon register, ammunition and armed roles
+fn migration_v19(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS weapons (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            weapon_type TEXT NOT NULL,
+            weapon_number TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            calibre TEXT,
+            licence_number TEXT NOT NULL,
+            issuing_authority TEXT,
+            licence_expiry TEXT NOT NULL,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create weapons table: {e}"))?;
+
+    // A weapon is with one employee at a time; the register keeps every
+    // handover, so assignments are never deleted with the weapon
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS weapon_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            weapon_id INTEGER NOT NULL REFERENCES weapons(id) ON DELETE RESTRICT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE RESTRICT,
+            site_id INTEGER NOT NULL REFERENCES sites(id) ON DELETE RESTRICT,
+            issued_on TEXT NOT NULL,
+            returned_on TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create weapon_assignments table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_weapon_assignments_weapon
+         ON weapon_assignments(weapon_id, issued_on)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create weapon_assignments weapon index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_weapon_assignments_employee
+         ON weapon_assignments(employee_id, issued_on)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create weapon_assignments employee index: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ammunition_movements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            weapon_id INTEGER NOT NULL REFERENCES weapons(id) ON DELETE RESTRICT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE RESTRICT,
+            movement_date TEXT NOT NULL,
+            movement TEXT NOT NULL CHECK (movement IN ('issue', 'return')),
+            rounds INTEGER NOT NULL CHECK (rounds > 0),
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create ammunition_movements table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_ammunition_movements_weapon
+         ON ammunition_movements(weapon_id, movement_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create ammunition_movements index: {e}"))?;
+
+    // Deployments in these roles need a weapon with a valid licence
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS armed_roles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            role TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create armed_roles table: {e}"))?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO armed_roles (role) VALUES ('Gunman'), ('Armed Guard')",
+        [],
+    )
+    .map_err(|e| format!("Failed to seed armed roles: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 20: uniform and equipment stock
+fn migration_v20(conn: &Connection) -> Result<(), String> {
+    // Uniform and equipment items; `unit_cost` is what is recovered when an
+    // issued item is not returned at exit
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS inventory_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            name TEXT NOT NULL,
+            category TEXT,
+            has_sizes INTEGER NOT NULL DEFAULT 0,
+            unit_cost REAL NOT NULL DEFAULT 0 CHECK (unit_cost >= 0),
+            reorder_level INTEGER NOT NULL DEFAULT 0 CHECK (reorder_level >= 0),
+            is_active INTEGER NOT NULL DEFAULT 1,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create inventory_items table: {e}"))?;
+
+    // Stock ledger. Receipts, returns and adjustments change the stock,
+    // issues take from it; issues, returns and exit settlements change what
+    // an employee holds. Only adjustments may be negative.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS inventory_movements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_id INTEGER NOT NULL REFERENCES inventory_items(id) ON DELETE RESTRICT,
+            size TEXT NOT NULL DEFAULT '',
+            movement_date TEXT NOT NULL,
+            movement TEXT NOT NULL
+                CHECK (movement IN ('receipt', 'issue', 'return', 'adjustment', 'settled')),
+            quantity INTEGER NOT NULL CHECK (quantity <> 0),
+            employee_id INTEGER REFERENCES employees(id) ON DELETE RESTRICT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            CHECK (movement = 'adjustment' OR quantity > 0),
+            CHECK ((employee_id IS NULL) = (movement IN ('receipt', 'adjustment')))
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create inventory_movements table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_inventory_movements_item
+         ON inventory_movements(item_id, size)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create inventory_movements item index: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_inventory_movements_employee
+         ON inventory_movements(employee_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create inventory_movements employee index: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 21: training programs, sessions and certifications
+fn migration_v21(conn: &Connection) -> Result<(), String> {
+    // A certification from a program lapses `validity_months` after it is
+    // earned and is renewed by a refresher session; no validity means it
+    // never lapses
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS training_programs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            name TEXT NOT NULL,
+            description TEXT,
+            validity_months INTEGER CHECK (validity_months IS NULL OR validity_months > 0),
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create training_programs table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS training_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            program_id INTEGER NOT NULL REFERENCES training_programs(id) ON DELETE RESTRICT,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            trainer TEXT,
+            venue TEXT,
+            is_refresher INTEGER NOT NULL DEFAULT 0,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            CHECK (end_date >= start_date)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create training_sessions table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_training_sessions_program
+         ON training_sessions(program_id, start_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create training_sessions index: {e}"))?;
+
+    // Employees who complete a session hold the program's certification
+    // until `expiry_date`
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS training_attendance (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL REFERENCES training_sessions(id) ON DELETE CASCADE,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            status TEXT NOT NULL DEFAULT 'enrolled'
+                CHECK (status IN ('enrolled', 'completed', 'absent', 'failed')),
+            certificate_number TEXT,
+            certified_on TEXT,
+            expiry_date TEXT,
+            attachment_id INTEGER REFERENCES employee_attachments(id) ON DELETE SET NULL,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (session_id, employee_id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create training_attendance table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_training_attendance_employee
+         ON training_attendance(employee_id, status)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create training_attendance index: {e}"))?;
+
+    // The most recent completion per employee and program is the
+    // certification that counts
+    conn.execute(
+        "CREATE VIEW IF NOT EXISTS latest_certifications AS
+         SELECT ta.*, ts.program_id FROM training_attendance ta
+         JOIN training_sessions ts ON ts.id = ta.session_id
+         WHERE ta.status = 'completed' AND ta.id = (
+            SELECT t2.id FROM training_attendance t2
+            JOIN training_sessions s2 ON s2.id = t2.session_id
+            WHERE t2.employee_id = ta.employee_id AND s2.program_id = ts.program_id
+              AND t2.status = 'completed'
+            ORDER BY t2.certified_on DESC, t2.id DESC
+            LIMIT 1
+         )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create latest_certifications view: {e}"))?;
+
+    Ok(())
+}
+
+/// Migration to version 22: previous employments and ex-servicemen
+fn migration_v22(conn: &Connection) -> Result<(), String> {
+    // Each period before joining, with its length in whole months kept so
+    // that prior experience can be summed and filtered on. Service in the
+    // armed forces is recorded as a period flagged as ex-serviceman.
+    // `employees.experience` stays as free text for older records.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS previous_employments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            employee_id INTEGER NOT NULL REFERENCES employees(id) ON DELETE CASCADE,
+            employer TEXT NOT NULL,
+            role TEXT,
+            from_date TEXT NOT NULL,
+            to_date TEXT NOT NULL CHECK (to_date >= from_date),
+            months INTEGER NOT NULL DEFAULT 0 CHECK (months >= 0),
+            reason_for_leaving TEXT,
+            is_ex_serviceman INTEGER NOT NULL DEFAULT 0,
+            service_number TEXT,
+            remarks TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create previous_employments table: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_previous_employments_employee
+         ON previous_employments(employee_id, from_date)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create previous_employments index: {e}"))?;
+
+    Ok(())
+}
@@ -8,7 +8,7 @@ pub mod backup;
 mod migrations;
 
 const DB_NAME: &str = "exgroup_app.db";
-const CURRENT_VERSION: i32 = 1;
+const CURRENT_VERSION: i32 = 22;
 
 /// Get the database path in the app's data directory
 pub fn get_db_path() -> Result<PathBuf, String> {
@@ -38,16 +38,22 @@ pub fn init_db() -> Result<Connection, String> {
         migrations::run_initial_migration(&conn)?;
     } else {
         println!("Database exists, checking version...");
-        migrations::run_migrations(&conn)?;
     }
 
+    migrations::run_migrations(&conn)?;
+
     Ok(conn)
 }
 
 /// Get a connection to the database
 pub fn get_connection() -> Result<Connection, String> {
     let db_path = get_db_path()?;
-    Connection::open(&db_path).map_err(|e| format!("Failed to open database: {e}"))
+    let conn = Connection::open(&db_path).map_err(|e| format!("Failed to open database: {e}"))?;
+
+    conn.execute("PRAGMA foreign_keys = ON", [])
+        .map_err(|e| format!("Failed to enable foreign keys: {e}"))?;
+
+    Ok(conn)
 }
 
 /// Get current database version
@@ -78,3 +84,14 @@ pub fn set_db_version(conn: &Connection, version: i32) -> Result<(), String> {
 
     Ok(())
 }
+
+/// An in-memory database with every migration applied
+#[cfg(test)]
+pub fn open_test_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("open in-memory database");
+    conn.execute("PRAGMA foreign_keys = ON", [])
+        .expect("enable foreign keys");
+    migrations::run_initial_migration(&conn).expect("run initial migration");
+    migrations::run_migrations(&conn).expect("run migrations");
+    conn
+}
@@ -0,0 +1,474 @@
+use super::helpers::{
+    ensure_deployable, get_deployment_by_id, get_requirement_by_id, map_deployment,
+    map_requirement, release_deployment, strength_gaps, transfer_deployment, ACTIVE_ON_DATE,
+    DEPLOYMENT_SELECT, REQUIREMENT_SELECT,
+};
+use super::types::{
+    AssignmentInput, Deployment, RoleHeadcount, SiteHeadcount, SiteRequirement,
+    SiteRequirementInput, StrengthGap, TransferInput,
+};
+use crate::clients::helpers::get_site_by_id;
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::roster::helpers::get_shift_template_by_id;
+use crate::state::AppState;
+use rusqlite::{params, Connection, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+#[tauri::command]
+pub fn assign_employee(
+    _state: State<AppState>,
+    assignment: AssignmentInput,
+) -> Result<Deployment, String> {
+    let conn = db::get_connection()?;
+
+    let start_date = format_date(parse_date(&assignment.start_date, "start date")?);
+    ensure_deployable(
+        &conn,
+        assignment.employee_id,
+        assignment.site_id,
+        assignment.role.as_deref(),
+        &start_date,
+    )?;
+
+    let overlapping: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM deployments
+             WHERE employee_id = ?1 AND (end_date IS NULL OR end_date >= ?2)",
+            params![assignment.employee_id, start_date],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if overlapping > 0 {
+        return Err(
+            "Employee is already deployed on or after this date, transfer or release them first"
+                .to_string(),
+        );
+    }
+
+    conn.execute(
+        "INSERT INTO deployments (employee_id, site_id, role, start_date, remarks)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            assignment.employee_id,
+            assignment.site_id,
+            assignment.role,
+            start_date,
+            assignment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(deployment)) = get_deployment_by_id(&conn, id) {
+        Ok(deployment)
+    } else {
+        Err("Failed to retrieve deployment".to_string())
+    }
+}
+
+/// Close the current deployment the day before `transfer_date` and open a new
+/// one at the target site from `transfer_date`
+#[tauri::command]
+pub fn transfer_employee(
+    _state: State<AppState>,
+    transfer: TransferInput,
+) -> Result<Deployment, String> {
+    let mut conn = db::get_connection()?;
+
+    let id = transfer_deployment(&mut conn, &transfer)?;
+
+    if let Ok(Some(deployment)) = get_deployment_by_id(&conn, id) {
+        Ok(deployment)
+    } else {
+        Err("Failed to retrieve deployment".to_string())
+    }
+}
+
+/// End the employee's current deployment with `release_date` as the last day
+#[tauri::command]
+pub fn release_employee(
+    _state: State<AppState>,
+    employee_id: i64,
+    release_date: String,
+    remarks: Option<String>,
+) -> Result<Deployment, String> {
+    let conn = db::get_connection()?;
+
+    let id = release_deployment(&conn, employee_id, &release_date, remarks)?;
+
+    if let Ok(Some(deployment)) = get_deployment_by_id(&conn, id) {
+        Ok(deployment)
+    } else {
+        Err("Failed to retrieve deployment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_deployment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM deployments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Every site the employee has been deployed to, most recent first
+#[tauri::command]
+pub fn get_employee_deployments(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Vec<Deployment>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{DEPLOYMENT_SELECT} WHERE d.employee_id = ?1 ORDER BY d.start_date DESC, d.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let deployments = stmt
+        .query_map([employee_id], map_deployment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(deployments)
+}
+
+/// Employees deployed at a site on the given date (today by default)
+#[tauri::command]
+pub fn get_site_deployments(
+    _state: State<AppState>,
+    site_id: i64,
+    date: Option<String>,
+) -> Result<Vec<Deployment>, String> {
+    let conn = db::get_connection()?;
+
+    let date = match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => format_date(parse_date(&date, "date")?),
+        None => format_date(today()),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{DEPLOYMENT_SELECT} WHERE {ACTIVE_ON_DATE} AND d.site_id = ?2
+            ORDER BY d.role COLLATE NOCASE, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let deployments = stmt
+        .query_map(params![date, site_id], map_deployment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(deployments)
+}
+
+/// Headcount per active site on the given date (today by default), broken
+/// down by role
+#[tauri::command]
+pub fn get_site_headcounts(
+    _state: State<AppState>,
+    date: Option<String>,
+    client_id: Option<i64>,
+) -> Result<Vec<SiteHeadcount>, String> {
+    let conn = db::get_connection()?;
+
+    let date = match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => format_date(parse_date(&date, "date")?),
+        None => format_date(today()),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT s.id, s.name, c.id, c.name, d.role, COUNT(d.id)
+            FROM sites s
+            JOIN clients c ON c.id = s.client_id
+            LEFT JOIN deployments d ON d.site_id = s.id AND {ACTIVE_ON_DATE}
+            WHERE s.is_active = 1 AND (?2 IS NULL OR s.client_id = ?2)
+            GROUP BY s.id, d.role COLLATE NOCASE
+            ORDER BY c.name COLLATE NOCASE, s.name COLLATE NOCASE, d.role COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![date, client_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut headcounts: Vec<SiteHeadcount> = Vec::new();
+    for (site_id, site_name, client_id, client_name, role, count) in rows {
+        if headcounts.last().map(|h| h.site_id) != Some(site_id) {
+            headcounts.push(SiteHeadcount {
+                site_id,
+                site_name,
+                client_id,
+                client_name,
+                headcount: 0,
+                roles: Vec::new(),
+            });
+        }
+
+        // Sites without deployments still produce one row with a zero count
+        if count > 0 {
+            let headcount = headcounts.last_mut().expect("headcount was just pushed");
+            headcount.headcount += count;
+            headcount.roles.push(RoleHeadcount { role, count });
+        }
+    }
+
+    Ok(headcounts)
+}
+
+fn validate_requirement(
+    conn: &Connection,
+    requirement: &SiteRequirementInput,
+) -> Result<(), String> {
+    get_site_by_id(conn, requirement.site_id)?.ok_or("Site not found")?;
+    if requirement.role.trim().is_empty() {
+        return Err("Role is required".to_string());
+    }
+    if let Some(shift_id) = requirement.shift_template_id {
+        get_shift_template_by_id(conn, shift_id)?.ok_or("Shift not found")?;
+    }
+    if requirement.required <= 0 {
+        return Err("Required strength must be at least one".to_string());
+    }
+    Ok(())
+}
+
+fn map_requirement_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "The site already has a requirement for this role and shift".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_site_requirements(
+    _state: State<AppState>,
+    site_id: Option<i64>,
+    client_id: Option<i64>,
+) -> Result<Vec<SiteRequirement>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{REQUIREMENT_SELECT}
+            WHERE (?1 IS NULL OR q.site_id = ?1) AND (?2 IS NULL OR s.client_id = ?2)
+            ORDER BY c.name COLLATE NOCASE, s.name COLLATE NOCASE, q.role COLLATE NOCASE,
+                t.start_time"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let requirements = stmt
+        .query_map(params![site_id, client_id], map_requirement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(requirements)
+}
+
+#[tauri::command]
+pub fn create_site_requirement(
+    _state: State<AppState>,
+    requirement: SiteRequirementInput,
+) -> Result<SiteRequirement, String> {
+    let conn = db::get_connection()?;
+
+    validate_requirement(&conn, &requirement)?;
+
+    conn.execute(
+        "INSERT INTO site_requirements (site_id, role, shift_template_id, required, remarks)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            requirement.site_id,
+            requirement.role.trim(),
+            requirement.shift_template_id,
+            requirement.required,
+            requirement.remarks,
+        ],
+    )
+    .map_err(map_requirement_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(requirement)) = get_requirement_by_id(&conn, id) {
+        Ok(requirement)
+    } else {
+        Err("Failed to retrieve requirement".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_site_requirement(
+    _state: State<AppState>,
+    id: i64,
+    requirement: SiteRequirementInput,
+) -> Result<SiteRequirement, String> {
+    let conn = db::get_connection()?;
+
+    validate_requirement(&conn, &requirement)?;
+
+    conn.execute(
+        "UPDATE site_requirements SET
+            site_id = ?1, role = ?2, shift_template_id = ?3, required = ?4, remarks = ?5,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?6",
+        params![
+            requirement.site_id,
+            requirement.role.trim(),
+            requirement.shift_template_id,
+            requirement.required,
+            requirement.remarks,
+            id,
+        ],
+    )
+    .map_err(map_requirement_error)?;
+
+    if let Ok(Some(requirement)) = get_requirement_by_id(&conn, id) {
+        Ok(requirement)
+    } else {
+        Err("Failed to retrieve requirement".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_site_requirement(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM site_requirements WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Required strength against deployments and roster on the given date (today
+/// by default), with available employees suggested for understaffed posts
+#[tauri::command]
+pub fn get_strength_gaps(
+    _state: State<AppState>,
+    date: Option<String>,
+    client_id: Option<i64>,
+    understaffed_only: Option<bool>,
+) -> Result<Vec<StrengthGap>, String> {
+    let conn = db::get_connection()?;
+
+    let date = match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => format_date(parse_date(&date, "date")?),
+        None => format_date(today()),
+    };
+
+    let mut gaps = strength_gaps(&conn, &date, client_id)?;
+    if understaffed_only.unwrap_or(false) {
+        gaps.retain(|g| g.understaffed);
+    }
+    Ok(gaps)
+}
+
+#[tauri::command]
+pub fn export_strength_gap_report(
+    _state: State<AppState>,
+    date: Option<String>,
+    client_id: Option<i64>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let date = match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => format_date(parse_date(&date, "date")?),
+        None => format_date(today()),
+    };
+    let gaps = strength_gaps(&conn, &date, client_id)?;
+    if gaps.is_empty() {
+        return Err("No required strength has been set for any active site".to_string());
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Manpower Requirement vs Deployment",
+        vec![
+            TableColumn::new("Client", 90.0, Align::Left),
+            TableColumn::new("Site", 90.0, Align::Left),
+            TableColumn::new("Role", 70.0, Align::Left),
+            TableColumn::new("Required", 45.0, Align::Right),
+            TableColumn::new("Deployed", 45.0, Align::Right),
+            TableColumn::new("Short", 35.0, Align::Right),
+            TableColumn::new("Roster cover", 110.0, Align::Left),
+            TableColumn::new("Status", 75.0, Align::Left),
+            TableColumn::new("Available to deploy", 155.0, Align::Left),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    report.subtitle_lines.push(format!("As on {date}"));
+
+    let understaffed = gaps.iter().filter(|g| g.understaffed).count();
+    for gap in &gaps {
+        let cover = gap
+            .shifts
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {}/{}",
+                    s.shift_name.as_deref().unwrap_or("All shifts"),
+                    s.rostered,
+                    s.required
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut available = gap
+            .candidates
+            .iter()
+            .take(3)
+            .map(|c| format!("{} ({})", c.employee_name, c.essid))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if gap.candidates.len() > 3 {
+            available.push_str(&format!(" +{} more", gap.candidates.len() - 3));
+        }
+        report.rows.push(vec![
+            gap.client_name.clone(),
+            gap.site_name.clone(),
+            gap.role.clone(),
+            gap.required.to_string(),
+            gap.deployed.to_string(),
+            gap.deployment_shortfall.to_string(),
+            cover,
+            if gap.understaffed {
+                "UNDERSTAFFED".to_string()
+            } else {
+                "OK".to_string()
+            },
+            available,
+        ]);
+    }
+    report.footer_lines.push(format!(
+        "Posts: {}    Understaffed: {understaffed}",
+        gaps.len()
+    ));
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
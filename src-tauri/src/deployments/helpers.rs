@@ -0,0 +1,545 @@
+use chrono::Duration;
+use rusqlite::{params, Row};
+use std::collections::HashMap;
+
+use super::types::{
+    AvailableEmployee, Deployment, ShiftCoverage, SiteRequirement, StrengthGap, TransferInput,
+};
+use crate::clients::helpers::get_site_by_id;
+use crate::dates::{format_date, parse_date};
+use crate::employees::helpers::get_employee_by_id;
+use crate::weapons::helpers::ensure_armed_licence;
+
+pub const DEPLOYMENT_SELECT: &str = "SELECT d.id, d.employee_id, e.name, e.essid, d.site_id,
+        s.name, s.client_id, c.name, d.role, d.start_date, d.end_date, d.remarks,
+        d.created_at, d.updated_at
+    FROM deployments d
+    JOIN employees e ON e.id = d.employee_id
+    JOIN sites s ON s.id = d.site_id
+    JOIN clients c ON c.id = s.client_id";
+
+pub const REQUIREMENT_SELECT: &str = "SELECT q.id, q.site_id, s.name, s.client_id, c.name,
+        q.role, q.shift_template_id, t.name, q.required, q.remarks, q.created_at, q.updated_at
+    FROM site_requirements q
+    JOIN sites s ON s.id = q.site_id
+    JOIN clients c ON c.id = s.client_id
+    LEFT JOIN shift_templates t ON t.id = q.shift_template_id";
+
+/// SQL condition for a deployment that is active on the date bound to `?1`
+pub const ACTIVE_ON_DATE: &str = "d.start_date <= ?1 AND (d.end_date IS NULL OR d.end_date >= ?1)";
+
+pub fn map_deployment(row: &Row) -> rusqlite::Result<Deployment> {
+    Ok(Deployment {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        site_id: row.get(4)?,
+        site_name: row.get(5)?,
+        client_id: row.get(6)?,
+        client_name: row.get(7)?,
+        role: row.get(8)?,
+        start_date: row.get(9)?,
+        end_date: row.get(10)?,
+        remarks: row.get(11)?,
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
+}
+
+pub fn get_deployment_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<Deployment>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{DEPLOYMENT_SELECT} WHERE d.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_deployment) {
+        Ok(deployment) => Ok(Some(deployment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The deployment of an employee that covers `date`, if any
+pub fn get_active_deployment(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    date: &str,
+) -> Result<Option<Deployment>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{DEPLOYMENT_SELECT} WHERE {ACTIVE_ON_DATE} AND d.employee_id = ?2
+            ORDER BY d.start_date DESC LIMIT 1"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row(params![date, employee_id], map_deployment) {
+        Ok(deployment) => Ok(Some(deployment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Checks before deploying an employee from `date`. A deployment without a
+/// role is in the employee's job post.
+pub fn ensure_deployable(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    site_id: i64,
+    role: Option<&str>,
+    date: &str,
+) -> Result<(), String> {
+    let employee = get_employee_by_id(conn, employee_id)?.ok_or("Employee not found")?;
+    if employee.employment_status != "current" {
+        return Err(format!(
+            "{} is not a current employee and cannot be deployed",
+            employee.name
+        ));
+    }
+
+    let site = get_site_by_id(conn, site_id)?.ok_or("Site not found")?;
+    if site.is_active == 0 {
+        return Err(format!("Site {} is inactive", site.name));
+    }
+
+    let role = role
+        .filter(|r| !r.trim().is_empty())
+        .or(employee.job_post.as_deref())
+        .unwrap_or_default();
+    ensure_armed_licence(conn, employee.id, &employee.name, role, date)?;
+
+    Ok(())
+}
+
+/// Close the employee's current deployment the day before the transfer date
+/// and open one at the target site from it, returning the new deployment
+pub fn transfer_deployment(
+    conn: &mut rusqlite::Connection,
+    transfer: &TransferInput,
+) -> Result<i64, String> {
+    let transfer_date = parse_date(&transfer.transfer_date, "transfer date")?;
+    let transfer_date_str = format_date(transfer_date);
+    ensure_deployable(
+        conn,
+        transfer.employee_id,
+        transfer.site_id,
+        transfer.role.as_deref(),
+        &transfer_date_str,
+    )?;
+
+    let current = get_active_deployment(conn, transfer.employee_id, &transfer_date_str)?
+        .ok_or("Employee is not deployed on the transfer date")?;
+    if current.site_id == transfer.site_id && current.role == transfer.role {
+        return Err("Employee is already deployed at this site in the same role".to_string());
+    }
+    if current.start_date >= transfer_date_str {
+        return Err(format!(
+            "Transfer date must be after the current deployment start date {}",
+            current.start_date
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE deployments SET end_date = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![format_date(transfer_date - Duration::days(1)), current.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO deployments (employee_id, site_id, role, start_date, end_date, remarks)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            transfer.employee_id,
+            transfer.site_id,
+            transfer.role,
+            transfer_date_str,
+            current.end_date,
+            transfer.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = tx.last_insert_rowid();
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+/// End the employee's current deployment with `release_date` as the last
+/// day, returning the deployment
+pub fn release_deployment(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    release_date: &str,
+    remarks: Option<String>,
+) -> Result<i64, String> {
+    let release_date = format_date(parse_date(release_date, "release date")?);
+
+    let current = get_active_deployment(conn, employee_id, &release_date)?
+        .ok_or("Employee is not deployed on the release date")?;
+
+    conn.execute(
+        "UPDATE deployments SET
+            end_date = ?1, remarks = COALESCE(?2, remarks), updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?3",
+        params![release_date, remarks, current.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(current.id)
+}
+
+pub fn map_requirement(row: &Row) -> rusqlite::Result<SiteRequirement> {
+    Ok(SiteRequirement {
+        id: row.get(0)?,
+        site_id: row.get(1)?,
+        site_name: row.get(2)?,
+        client_id: row.get(3)?,
+        client_name: row.get(4)?,
+        role: row.get(5)?,
+        shift_template_id: row.get(6)?,
+        shift_name: row.get(7)?,
+        required: row.get(8)?,
+        remarks: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+pub fn get_requirement_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<SiteRequirement>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{REQUIREMENT_SELECT} WHERE q.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_requirement) {
+        Ok(requirement) => Ok(Some(requirement)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Site, shift and lower-cased role
+type PostKey = (i64, Option<i64>, String);
+
+/// Count per key of rows returning a site, an optional shift and a role
+fn role_counts(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    date: &str,
+) -> Result<HashMap<PostKey, i64>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([date], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Roles differing only in case are the same post
+    let mut counts: HashMap<PostKey, i64> = HashMap::new();
+    for (site_id, shift_id, role, count) in rows {
+        *counts
+            .entry((site_id, shift_id, role.to_lowercase()))
+            .or_insert(0) += count;
+    }
+    Ok(counts)
+}
+
+/// Current employees per lower-cased job post who have no deployment and no
+/// approved leave on `date`
+pub fn available_employees(
+    conn: &rusqlite::Connection,
+    date: &str,
+) -> Result<HashMap<String, Vec<AvailableEmployee>>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT e.id, e.name, e.essid, e.job_post, e.phone_numbers
+            FROM employees e
+            WHERE e.employment_status = 'current' AND TRIM(IFNULL(e.job_post, '')) <> ''
+              AND NOT EXISTS (
+                SELECT 1 FROM deployments d WHERE d.employee_id = e.id AND {ACTIVE_ON_DATE}
+              )
+              AND NOT EXISTS (
+                SELECT 1 FROM leave_applications l
+                WHERE l.employee_id = e.id AND l.status = 'approved'
+                  AND l.from_date <= ?1 AND l.to_date >= ?1
+              )
+            ORDER BY e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+    let employees = stmt
+        .query_map([date], |row| {
+            Ok(AvailableEmployee {
+                employee_id: row.get(0)?,
+                employee_name: row.get(1)?,
+                essid: row.get(2)?,
+                job_post: row.get(3)?,
+                phone_numbers: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut available: HashMap<String, Vec<AvailableEmployee>> = HashMap::new();
+    for employee in employees {
+        let post = employee
+            .job_post
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        available.entry(post).or_default().push(employee);
+    }
+    Ok(available)
+}
+
+/// Required strength of every role at the active sites against the
+/// deployments and roster on `date`. A deployment or roster entry without a
+/// role counts under the employee's job post. Requirements for a shift are
+/// met by the roster of that shift, whole day requirements by the roster of
+/// all shifts.
+pub fn strength_gaps(
+    conn: &rusqlite::Connection,
+    date: &str,
+    client_id: Option<i64>,
+) -> Result<Vec<StrengthGap>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{REQUIREMENT_SELECT}
+            WHERE s.is_active = 1 AND (?1 IS NULL OR s.client_id = ?1)
+            ORDER BY c.name COLLATE NOCASE, s.name COLLATE NOCASE, q.role COLLATE NOCASE,
+                t.start_time"
+        ))
+        .map_err(|e| e.to_string())?;
+    let requirements = stmt
+        .query_map(params![client_id], map_requirement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let deployed = role_counts(
+        conn,
+        &format!(
+            "SELECT d.site_id, NULL,
+                IFNULL(NULLIF(TRIM(d.role), ''), TRIM(e.job_post)) AS post_role, COUNT(*)
+            FROM deployments d
+            JOIN employees e ON e.id = d.employee_id
+            WHERE {ACTIVE_ON_DATE}
+            GROUP BY 1, 3 HAVING post_role IS NOT NULL"
+        ),
+        date,
+    )?;
+    let rostered = role_counts(
+        conn,
+        &format!(
+            "SELECT r.site_id, r.shift_template_id,
+                IFNULL(NULLIF(TRIM(r.role), ''), IFNULL((
+                    SELECT NULLIF(TRIM(d.role), '') FROM deployments d
+                    WHERE d.employee_id = r.employee_id AND d.site_id = r.site_id
+                      AND {ACTIVE_ON_DATE}
+                    ORDER BY d.start_date DESC LIMIT 1
+                ), TRIM(e.job_post))) AS post_role,
+                COUNT(*)
+            FROM roster_assignments r
+            JOIN employees e ON e.id = r.employee_id
+            WHERE r.roster_date = ?1
+            GROUP BY 1, 2, 3 HAVING post_role IS NOT NULL"
+        ),
+        date,
+    )?;
+    let available = available_employees(conn, date)?;
+
+    let mut gaps: Vec<StrengthGap> = Vec::new();
+    for requirement in requirements {
+        let role = requirement.role.trim().to_lowercase();
+        let same_post = gaps
+            .last()
+            .is_some_and(|g| g.site_id == requirement.site_id && g.role.to_lowercase() == role);
+        if !same_post {
+            gaps.push(StrengthGap {
+                site_id: requirement.site_id,
+                site_name: requirement.site_name.clone(),
+                client_id: requirement.client_id,
+                client_name: requirement.client_name.clone(),
+                role: requirement.role.clone(),
+                required: 0,
+                deployed: deployed
+                    .get(&(requirement.site_id, None, role.clone()))
+                    .copied()
+                    .unwrap_or(0),
+                deployment_shortfall: 0,
+                shifts: Vec::new(),
+                understaffed: false,
+                candidates: Vec::new(),
+            });
+        }
+
+        let rostered = match requirement.shift_template_id {
+            Some(shift_id) => rostered
+                .get(&(requirement.site_id, Some(shift_id), role.clone()))
+                .copied()
+                .unwrap_or(0),
+            None => rostered
+                .iter()
+                .filter(|((site_id, _, r), _)| *site_id == requirement.site_id && *r == role)
+                .map(|(_, count)| count)
+                .sum(),
+        };
+        let gap = gaps.last_mut().expect("gap was just pushed");
+        gap.required += requirement.required;
+        gap.shifts.push(ShiftCoverage {
+            shift_template_id: requirement.shift_template_id,
+            shift_name: requirement.shift_name,
+            required: requirement.required,
+            rostered,
+            shortfall: (requirement.required - rostered).max(0),
+        });
+    }
+
+    for gap in &mut gaps {
+        gap.deployment_shortfall = (gap.required - gap.deployed).max(0);
+        gap.understaffed =
+            gap.deployment_shortfall > 0 || gap.shifts.iter().any(|s| s.shortfall > 0);
+        if gap.understaffed {
+            gap.candidates = available
+                .get(&gap.role.trim().to_lowercase())
+                .cloned()
+                .unwrap_or_default();
+        }
+    }
+
+    Ok(gaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn deployed_employee() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status, job_post)
+            VALUES ('Ravi', 'E1', 'current', 'Guard')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO clients (name) VALUES ('Acme')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO sites (client_id, name) VALUES (1, 'Gate'), (1, 'Warehouse')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO deployments (employee_id, site_id, role, start_date)
+            VALUES (1, 1, 'Guard', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn transfer(site_id: i64, date: &str) -> TransferInput {
+        TransferInput {
+            employee_id: 1,
+            site_id,
+            role: Some("Guard".to_string()),
+            transfer_date: date.to_string(),
+            remarks: None,
+        }
+    }
+
+    #[test]
+    fn transfer_closes_the_current_deployment_the_day_before() {
+        let mut conn = deployed_employee();
+
+        let id = transfer_deployment(&mut conn, &transfer(2, "2024-07-01")).unwrap();
+
+        let old = get_deployment_by_id(&conn, 1).unwrap().unwrap();
+        assert_eq!(old.end_date.as_deref(), Some("2024-06-30"));
+        let new = get_deployment_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!((new.site_id, new.start_date.as_str()), (2, "2024-07-01"));
+        assert_eq!(new.end_date, None);
+        let active = get_active_deployment(&conn, 1, "2024-07-01").unwrap();
+        assert_eq!(active.map(|d| d.id), Some(id));
+    }
+
+    #[test]
+    fn transfer_is_refused_to_the_same_post_or_on_the_start_date() {
+        let mut conn = deployed_employee();
+
+        assert!(transfer_deployment(&mut conn, &transfer(1, "2024-07-01")).is_err());
+        assert!(transfer_deployment(&mut conn, &transfer(2, "2024-01-01")).is_err());
+        assert!(transfer_deployment(&mut conn, &transfer(2, "2023-12-31")).is_err());
+    }
+
+    #[test]
+    fn release_ends_the_deployment_on_the_release_date() {
+        let conn = deployed_employee();
+
+        let id = release_deployment(&conn, 1, "2024-03-31", Some("Resigned".to_string())).unwrap();
+
+        let released = get_deployment_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!(released.end_date.as_deref(), Some("2024-03-31"));
+        assert_eq!(released.remarks.as_deref(), Some("Resigned"));
+        assert!(get_active_deployment(&conn, 1, "2024-04-01")
+            .unwrap()
+            .is_none());
+        assert!(release_deployment(&conn, 1, "2024-04-01", None).is_err());
+    }
+
+    #[test]
+    fn gaps_count_deployments_and_rosters_per_shift() {
+        let conn = deployed_employee();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status, job_post)
+            VALUES ('Anil', 'E2', 'current', 'GUARD')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO site_requirements (site_id, role, shift_template_id, required)
+            SELECT 1, 'guard', id, 1 FROM shift_templates WHERE code IN ('D', 'N')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO roster_assignments (site_id, shift_template_id, employee_id, roster_date)
+            SELECT 1, id, 1, '2024-07-01' FROM shift_templates WHERE code = 'D'",
+            [],
+        )
+        .unwrap();
+
+        let gaps = strength_gaps(&conn, "2024-07-01", None).unwrap();
+        assert_eq!(gaps.len(), 1);
+        let gap = &gaps[0];
+        assert_eq!(
+            (gap.required, gap.deployed, gap.deployment_shortfall),
+            (2, 1, 1)
+        );
+        let shifts: Vec<_> = gap
+            .shifts
+            .iter()
+            .map(|s| (s.rostered, s.shortfall))
+            .collect();
+        assert_eq!(shifts, vec![(1, 0), (0, 1)]);
+        assert!(gap.understaffed);
+        let candidates: Vec<_> = gap.candidates.iter().map(|c| c.essid.as_str()).collect();
+        assert_eq!(candidates, vec!["E2"]);
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub site_id: i64,
+    pub site_name: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub role: Option<String>,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignmentInput {
+    pub employee_id: i64,
+    pub site_id: i64,
+    pub role: Option<String>,
+    pub start_date: String,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferInput {
+    pub employee_id: i64,
+    pub site_id: i64,
+    pub role: Option<String>,
+    pub transfer_date: String,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleHeadcount {
+    pub role: Option<String>,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteHeadcount {
+    pub site_id: i64,
+    pub site_name: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub headcount: i64,
+    pub roles: Vec<RoleHeadcount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteRequirement {
+    pub id: i64,
+    pub site_id: i64,
+    pub site_name: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub role: String,
+    /// Empty when the strength is for the whole day rather than one shift
+    pub shift_template_id: Option<i64>,
+    pub shift_name: Option<String>,
+    pub required: i64,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteRequirementInput {
+    pub site_id: i64,
+    pub role: String,
+    pub shift_template_id: Option<i64>,
+    pub required: i64,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableEmployee {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub phone_numbers: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShiftCoverage {
+    pub shift_template_id: Option<i64>,
+    pub shift_name: Option<String>,
+    pub required: i64,
+    pub rostered: i64,
+    pub shortfall: i64,
+}
+
+/// Required strength of a role at a site against the employees deployed and
+/// rostered there
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrengthGap {
+    pub site_id: i64,
+    pub site_name: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub role: String,
+    /// Total over all shifts
+    pub required: i64,
+    pub deployed: i64,
+    pub deployment_shortfall: i64,
+    pub shifts: Vec<ShiftCoverage>,
+    pub understaffed: bool,
+    /// Current employees of the role who are neither deployed nor on leave,
+    /// listed for understaffed posts only
+    pub candidates: Vec<AvailableEmployee>,
+}
@@ -1,4 +1,6 @@
-use super::helpers::{get_employee_by_essid, get_employee_by_id, remove_employee_photo};
+use super::helpers::{
+    filter_conditions, get_employee_by_essid, get_employee_by_id, remove_employee_photo,
+};
 use super::types::Employee;
 use super::types::{DbInfo, EmployeeInput, EmployeeListResponse, Filter};
 use crate::db;
@@ -17,49 +19,8 @@ pub fn get_all_employees(
     filter: Filter,
 ) -> Result<EmployeeListResponse, String> {
     let conn = db::get_connection()?;
-    let mut query = String::from("SELECT * FROM employees WHERE 1=1");
-    if let Some(search_query) = &filter.query {
-        if !search_query.trim().is_empty() {
-            query.push_str(&format!(
-                " AND name LIKE '%{search_query}%' OR essid LIKE '%{search_query}%'"
-            ));
-        }
-    }
-    if let Some(job_post) = &filter.job_post {
-        if !job_post.trim().is_empty() {
-            query.push_str(&format!(" AND job_post LIKE '%{job_post}%'"));
-        }
-    }
-    if let Some(employment_status) = &filter.employment_status {
-        if !employment_status.trim().is_empty() {
-            query.push_str(&format!(" AND employment_status = '{employment_status}'"));
-        }
-    }
-    if let Some(from) = &filter.joining_date_from {
-        if !from.trim().is_empty() {
-            query.push_str(&format!(" AND joining_date >= '{from}'"));
-        }
-    }
-    if let Some(to) = &filter.joining_date_to {
-        if !to.trim().is_empty() {
-            query.push_str(&format!(" AND joining_date <= '{to}'"));
-        }
-    }
-    if let Some(from) = &filter.exit_date_from {
-        if !from.trim().is_empty() {
-            query.push_str(&format!(" AND exit_date >= '{from}'"));
-        }
-    }
-    if let Some(to) = &filter.exit_date_to {
-        if !to.trim().is_empty() {
-            query.push_str(&format!(" AND exit_date <= '{to}'"));
-        }
-    }
-    if let Some(post) = &filter.post {
-        if !post.trim().is_empty() {
-            query.push_str(&format!(" AND permanent_post LIKE '%{post}%'"));
-        }
-    }
+    let conditions = filter_conditions(&filter);
+    let mut query = format!("SELECT * FROM employees WHERE 1=1{conditions}");
 
     query.push_str(" ORDER BY created_at DESC");
 
@@ -108,7 +69,11 @@ pub fn get_all_employees(
         .map_err(|e| e.to_string())?;
 
     let total_count: i64 = conn
-        .query_row("SELECT COUNT(*) FROM employees", [], |row| row.get(0))
+        .query_row(
+            &format!("SELECT COUNT(*) FROM employees WHERE 1=1{conditions}"),
+            [],
+            |row| row.get(0),
+        )
         .map_err(|e| e.to_string())?;
 
     Ok(EmployeeListResponse {
@@ -260,6 +225,37 @@ pub fn update_employee(
 pub fn delete_employee(_state: State<AppState>, id: i64) -> Result<(), String> {
     let conn = db::get_connection()?;
 
+    // The weapon register keeps every handover, so it blocks the delete
+    let has_weapon_history: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM weapon_assignments WHERE employee_id = ?1)
+                OR EXISTS (SELECT 1 FROM ammunition_movements WHERE employee_id = ?1)",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_weapon_history {
+        return Err(
+            "This employee appears in the weapon register and cannot be deleted, mark them as past instead"
+                .to_string(),
+        );
+    }
+
+    // So does the stock ledger, which records who was issued uniform
+    let has_inventory_history: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM inventory_movements WHERE employee_id = ?1)",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_inventory_history {
+        return Err(
+            "This employee appears in the stock ledger and cannot be deleted, mark them as past instead"
+                .to_string(),
+        );
+    }
+
     conn.execute("DELETE FROM employees WHERE id = ?1", params![id])
         .map_err(|e| e.to_string())?;
 
@@ -4,7 +4,7 @@ use rusqlite::params;
 
 use crate::files::delete_image;
 
-use super::types::Employee;
+use super::types::{Employee, Filter};
 
 pub fn get_employee_by_id(
     conn: &rusqlite::Connection,
@@ -118,3 +118,258 @@ pub fn remove_employee_photo(conn: &rusqlite::Connection, employee_id: i64) -> R
 
     Ok(())
 }
+
+/// SQL conditions of the employee list filter, each starting with ` AND `, so
+/// the listed page and the total count select the same employees
+pub fn filter_conditions(filter: &Filter) -> String {
+    let mut query = String::new();
+    if let Some(search_query) = &filter.query {
+        if !search_query.trim().is_empty() {
+            query.push_str(&format!(
+                " AND (name LIKE '%{search_query}%' OR essid LIKE '%{search_query}%')"
+            ));
+        }
+    }
+    if let Some(job_post) = &filter.job_post {
+        if !job_post.trim().is_empty() {
+            query.push_str(&format!(" AND job_post LIKE '%{job_post}%'"));
+        }
+    }
+    if let Some(employment_status) = &filter.employment_status {
+        if !employment_status.trim().is_empty() {
+            query.push_str(&format!(" AND employment_status = '{employment_status}'"));
+        }
+    }
+    if let Some(from) = &filter.joining_date_from {
+        if !from.trim().is_empty() {
+            query.push_str(&format!(" AND joining_date >= '{from}'"));
+        }
+    }
+    if let Some(to) = &filter.joining_date_to {
+        if !to.trim().is_empty() {
+            query.push_str(&format!(" AND joining_date <= '{to}'"));
+        }
+    }
+    if let Some(from) = &filter.exit_date_from {
+        if !from.trim().is_empty() {
+            query.push_str(&format!(" AND exit_date >= '{from}'"));
+        }
+    }
+    if let Some(to) = &filter.exit_date_to {
+        if !to.trim().is_empty() {
+            query.push_str(&format!(" AND exit_date <= '{to}'"));
+        }
+    }
+    if let Some(post) = &filter.post {
+        if !post.trim().is_empty() {
+            query.push_str(&format!(" AND permanent_post LIKE '%{post}%'"));
+        }
+    }
+    if let Some(verification) = &filter.police_verification {
+        let clause = match verification.trim() {
+            "none" => Some(" AND id NOT IN (SELECT employee_id FROM police_verifications)"),
+            "not_cleared" => Some(
+                " AND id NOT IN (SELECT employee_id FROM latest_police_verifications WHERE status = 'cleared')",
+            ),
+            "pending" => Some(
+                " AND id IN (SELECT employee_id FROM latest_police_verifications WHERE status = 'pending')",
+            ),
+            "cleared" => Some(
+                " AND id IN (SELECT employee_id FROM latest_police_verifications WHERE status = 'cleared')",
+            ),
+            "adverse" => Some(
+                " AND id IN (SELECT employee_id FROM latest_police_verifications WHERE status = 'adverse')",
+            ),
+            _ => None,
+        };
+        if let Some(clause) = clause {
+            query.push_str(clause);
+        }
+    }
+    if let Some(certification) = &filter.certification {
+        if !certification.trim().is_empty() {
+            let program = certification.trim().replace('\'', "''");
+            query.push_str(&format!(
+                " AND id IN (SELECT lc.employee_id FROM latest_certifications lc
+                    JOIN training_programs tp ON tp.id = lc.program_id
+                    WHERE (tp.code = '{program}' COLLATE NOCASE OR tp.name = '{program}' COLLATE NOCASE)
+                      AND (lc.expiry_date IS NULL OR lc.expiry_date >= date('now', 'localtime')))"
+            ));
+        }
+    }
+
+    if let Some(months) = filter.min_experience_months.filter(|m| *m > 0) {
+        let role_clause = match &filter.experience_role {
+            Some(role) if !role.trim().is_empty() => {
+                format!(" AND role LIKE '%{}%'", role.trim().replace('\'', "''"))
+            }
+            _ => String::new(),
+        };
+        query.push_str(&format!(
+            " AND id IN (SELECT employee_id FROM previous_employments WHERE 1=1{role_clause}
+                GROUP BY employee_id HAVING SUM(months) >= {months})"
+        ));
+    }
+    match filter.ex_serviceman {
+        Some(true) => query.push_str(
+            " AND id IN (SELECT employee_id FROM previous_employments WHERE is_ex_serviceman != 0)",
+        ),
+        Some(false) => query.push_str(
+            " AND id NOT IN (SELECT employee_id FROM previous_employments WHERE is_ex_serviceman != 0)",
+        ),
+        None => {}
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn employee(conn: &rusqlite::Connection, name: &str, essid: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES (?1, ?2, 'current')",
+            params![name, essid],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    /// ESSIDs the filter selects, checked against the count of the list
+    fn matching(conn: &rusqlite::Connection, filter: &Filter) -> Vec<String> {
+        let conditions = filter_conditions(filter);
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT essid FROM employees WHERE 1=1{conditions} ORDER BY essid"
+            ))
+            .unwrap();
+        let essids = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<String>, _>>()
+            .unwrap();
+        let count: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM employees WHERE 1=1{conditions}"),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count as usize, essids.len());
+        essids
+    }
+
+    #[test]
+    fn search_is_combined_with_the_other_filters() {
+        let conn = open_test_db();
+        let ravi = employee(&conn, "Ravi", "E1");
+        employee(&conn, "Ravindran", "E2");
+        employee(&conn, "Suresh", "RAVI3");
+        conn.execute(
+            "INSERT INTO police_verifications (employee_id, status) VALUES (?1, 'pending')",
+            [ravi],
+        )
+        .unwrap();
+
+        let search = Filter {
+            query: Some("ravi".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matching(&conn, &search), vec!["E1", "E2", "RAVI3"]);
+
+        let pending = Filter {
+            police_verification: Some("pending".to_string()),
+            ..search
+        };
+        assert_eq!(matching(&conn, &pending), vec!["E1"]);
+    }
+
+    #[test]
+    fn certification_filter_needs_a_valid_certificate() {
+        let conn = open_test_db();
+        let valid = employee(&conn, "Ravi", "E1");
+        let expired = employee(&conn, "Ravindran", "E2");
+        employee(&conn, "Ravi Kumar", "E3");
+        conn.execute(
+            "INSERT INTO training_programs (code, name) VALUES ('FF', 'Fire fighting')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO training_sessions (program_id, start_date, end_date)
+            VALUES (1, '2024-01-10', '2024-01-12')",
+            [],
+        )
+        .unwrap();
+        for (employee_id, expiry_date) in [(valid, "2999-01-12"), (expired, "2025-01-12")] {
+            conn.execute(
+                "INSERT INTO training_attendance (
+                    session_id, employee_id, status, certified_on, expiry_date
+                ) VALUES (1, ?1, 'completed', '2024-01-12', ?2)",
+                params![employee_id, expiry_date],
+            )
+            .unwrap();
+        }
+
+        let certified = Filter {
+            certification: Some("fire fighting".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matching(&conn, &certified), vec!["E1"]);
+
+        let searched = Filter {
+            query: Some("ravi".to_string()),
+            certification: Some("ff".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matching(&conn, &searched), vec!["E1"]);
+    }
+
+    #[test]
+    fn experience_filter_sums_matching_roles() {
+        let conn = open_test_db();
+        let guard = employee(&conn, "Ravi", "E1");
+        let driver = employee(&conn, "Ravindran", "E2");
+        for (employee_id, role, months, ex_serviceman) in [
+            (guard, "Security Guard", 20, 0),
+            (guard, "Supervisor", 10, 1),
+            (driver, "Driver", 40, 0),
+        ] {
+            conn.execute(
+                "INSERT INTO previous_employments (
+                    employee_id, employer, role, from_date, to_date, months, is_ex_serviceman
+                ) VALUES (?1, 'Alpha', ?2, '2010-01-01', '2019-12-31', ?3, ?4)",
+                params![employee_id, role, months, ex_serviceman],
+            )
+            .unwrap();
+        }
+
+        let experienced = Filter {
+            query: Some("ravi".to_string()),
+            min_experience_months: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(matching(&conn, &experienced), vec!["E1", "E2"]);
+
+        let guards = Filter {
+            experience_role: Some("guard".to_string()),
+            ..experienced
+        };
+        assert_eq!(matching(&conn, &guards), Vec::<String>::new());
+
+        let guards = Filter {
+            min_experience_months: Some(12),
+            ..guards
+        };
+        assert_eq!(matching(&conn, &guards), vec!["E1"]);
+
+        let civilians = Filter {
+            query: Some("ravi".to_string()),
+            ex_serviceman: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(matching(&conn, &civilians), vec!["E2"]);
+    }
+}
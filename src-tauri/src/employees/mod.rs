@@ -1,3 +1,3 @@
 pub mod commands;
-mod helpers;
-mod types;
+pub mod helpers;
+pub mod types;
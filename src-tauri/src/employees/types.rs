@@ -19,6 +19,8 @@ pub struct Employee {
     pub emergency_contact_relation: Option<String>,
     pub emergency_contact_phone: Option<String>,
     pub police_station: Option<String>,
+    /// Free text kept for records from before previous employments were
+    /// recorded one by one
     pub experience: Option<String>,
     pub job_post: Option<String>,
     pub employment_status: String,
@@ -71,7 +73,7 @@ pub struct DbInfo {
     pub employee_count: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Filter {
     pub post: Option<String>,
@@ -82,6 +84,15 @@ pub struct Filter {
     pub joining_date_to: Option<String>,
     pub joining_date_from: Option<String>,
     pub employment_status: Option<String>,
+    pub police_verification: Option<String>,
+    /// Code or name of a training program the employee holds a valid
+    /// certification in
+    pub certification: Option<String>,
+    /// Least prior experience in months, counting only previous roles that
+    /// match `experience_role` when it is given
+    pub min_experience_months: Option<i64>,
+    pub experience_role: Option<String>,
+    pub ex_serviceman: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,289 @@
+use super::helpers::{
+    employee_employments, employment_months, get_employment_by_id, overlapping_employer,
+};
+use super::types::{EmploymentHistory, ExServiceman, PreviousEmployment, PreviousEmploymentInput};
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use rusqlite::{params, Connection, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+/// Check an employment and work out its dates and length in months
+fn validate_employment(
+    conn: &Connection,
+    id: Option<i64>,
+    employment: &PreviousEmploymentInput,
+) -> Result<(String, String, i64), String> {
+    get_employee_by_id(conn, employment.employee_id)?.ok_or("Employee not found")?;
+    if employment.employer.trim().is_empty() {
+        return Err("Employer is required".to_string());
+    }
+    let from_date = parse_date(&employment.from_date, "from date")?;
+    let to_date = parse_date(&employment.to_date, "to date")?;
+    if to_date < from_date {
+        return Err("To date must not be before the from date".to_string());
+    }
+    if to_date > today() {
+        return Err("Previous employment cannot end in the future".to_string());
+    }
+    if employment.is_ex_serviceman.unwrap_or(0) != 0
+        && employment
+            .service_number
+            .as_deref()
+            .is_none_or(|s| s.trim().is_empty())
+    {
+        return Err("Service number is required for ex-servicemen".to_string());
+    }
+
+    // Overlapping periods would count the same months twice
+    let months = employment_months(from_date, to_date);
+    let (from_date, to_date) = (format_date(from_date), format_date(to_date));
+    if let Some(employer) =
+        overlapping_employer(conn, employment.employee_id, id, &from_date, &to_date)?
+    {
+        return Err(format!(
+            "The period overlaps with the employment at {employer}"
+        ));
+    }
+
+    Ok((from_date, to_date, months))
+}
+
+/// Previous employments of an employee, latest first, with the total prior
+/// experience
+#[tauri::command]
+pub fn get_employment_history(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<EmploymentHistory, String> {
+    let conn = db::get_connection()?;
+
+    let employee = get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+    let employments = employee_employments(&conn, employee_id)?;
+    let service = employments.iter().find(|e| e.is_ex_serviceman != 0);
+
+    Ok(EmploymentHistory {
+        employee_id,
+        total_months: employments.iter().map(|e| e.months).sum(),
+        is_ex_serviceman: service.is_some(),
+        service_number: service.and_then(|e| e.service_number.clone()),
+        legacy_experience: employee.experience,
+        employments,
+    })
+}
+
+#[tauri::command]
+pub fn create_previous_employment(
+    _state: State<AppState>,
+    employment: PreviousEmploymentInput,
+) -> Result<PreviousEmployment, String> {
+    let conn = db::get_connection()?;
+
+    let (from_date, to_date, months) = validate_employment(&conn, None, &employment)?;
+
+    conn.execute(
+        "INSERT INTO previous_employments (
+            employee_id, employer, role, from_date, to_date, months, reason_for_leaving,
+            is_ex_serviceman, service_number, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            employment.employee_id,
+            employment.employer.trim(),
+            employment.role,
+            from_date,
+            to_date,
+            months,
+            employment.reason_for_leaving,
+            employment.is_ex_serviceman.unwrap_or(0),
+            employment.service_number,
+            employment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(employment)) = get_employment_by_id(&conn, id) {
+        Ok(employment)
+    } else {
+        Err("Failed to retrieve previous employment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_previous_employment(
+    _state: State<AppState>,
+    id: i64,
+    employment: PreviousEmploymentInput,
+) -> Result<PreviousEmployment, String> {
+    let conn = db::get_connection()?;
+
+    let existing = get_employment_by_id(&conn, id)?.ok_or("Previous employment not found")?;
+    if existing.employee_id != employment.employee_id {
+        return Err("Previous employment belongs to a different employee".to_string());
+    }
+    let (from_date, to_date, months) = validate_employment(&conn, Some(id), &employment)?;
+
+    conn.execute(
+        "UPDATE previous_employments SET
+            employer = ?1, role = ?2, from_date = ?3, to_date = ?4, months = ?5,
+            reason_for_leaving = ?6, is_ex_serviceman = ?7, service_number = ?8, remarks = ?9,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?10",
+        params![
+            employment.employer.trim(),
+            employment.role,
+            from_date,
+            to_date,
+            months,
+            employment.reason_for_leaving,
+            employment.is_ex_serviceman.unwrap_or(0),
+            employment.service_number,
+            employment.remarks,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(employment)) = get_employment_by_id(&conn, id) {
+        Ok(employment)
+    } else {
+        Err("Failed to retrieve previous employment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_previous_employment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM previous_employments WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn ex_servicemen(
+    conn: &Connection,
+    employment_status: Option<&str>,
+) -> Result<Vec<ExServiceman>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.name, e.essid, e.job_post, e.employment_status, pe.service_number,
+                pe.employer, pe.role, pe.from_date, pe.to_date, pe.months,
+                (SELECT SUM(p2.months) FROM previous_employments p2
+                 WHERE p2.employee_id = e.id)
+            FROM previous_employments pe
+            JOIN employees e ON e.id = pe.employee_id
+            WHERE pe.is_ex_serviceman != 0 AND (?1 IS NULL OR e.employment_status = ?1)
+            ORDER BY e.name COLLATE NOCASE, pe.from_date",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![employment_status], |row| {
+            Ok(ExServiceman {
+                employee_id: row.get(0)?,
+                name: row.get(1)?,
+                essid: row.get(2)?,
+                job_post: row.get(3)?,
+                employment_status: row.get(4)?,
+                service_number: row.get(5)?,
+                employer: row.get(6)?,
+                role: row.get(7)?,
+                from_date: row.get(8)?,
+                to_date: row.get(9)?,
+                service_months: row.get(10)?,
+                total_months: row.get(11)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Employees who served in the armed forces, with their service details
+#[tauri::command]
+pub fn get_ex_servicemen(
+    _state: State<AppState>,
+    employment_status: Option<String>,
+) -> Result<Vec<ExServiceman>, String> {
+    let conn = db::get_connection()?;
+
+    let employment_status = employment_status.filter(|s| !s.trim().is_empty());
+    ex_servicemen(&conn, employment_status.as_deref())
+}
+
+fn years_and_months(months: i64) -> String {
+    format!("{}y {}m", months / 12, months % 12)
+}
+
+#[tauri::command]
+pub fn export_ex_servicemen_report(
+    _state: State<AppState>,
+    employment_status: Option<String>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let employment_status = employment_status.filter(|s| !s.trim().is_empty());
+    let rows = ex_servicemen(&conn, employment_status.as_deref())?;
+    if rows.is_empty() {
+        return Err("No ex-servicemen found".to_string());
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Ex-Servicemen",
+        vec![
+            TableColumn::new("Employee", 120.0, Align::Left),
+            TableColumn::new("ESSID", 55.0, Align::Left),
+            TableColumn::new("Job post", 80.0, Align::Left),
+            TableColumn::new("Service no.", 75.0, Align::Left),
+            TableColumn::new("Served in", 100.0, Align::Left),
+            TableColumn::new("Rank", 80.0, Align::Left),
+            TableColumn::new("From", 60.0, Align::Left),
+            TableColumn::new("To", 60.0, Align::Left),
+            TableColumn::new("Service", 60.0, Align::Right),
+            TableColumn::new("Total exp.", 60.0, Align::Right),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    if let Some(status) = &employment_status {
+        report
+            .subtitle_lines
+            .push(format!("Employment status: {status}"));
+    }
+
+    for row in &rows {
+        report.rows.push(vec![
+            row.name.clone(),
+            row.essid.clone(),
+            row.job_post.clone().unwrap_or_default(),
+            row.service_number.clone().unwrap_or_default(),
+            row.employer.clone(),
+            row.role.clone().unwrap_or_default(),
+            row.from_date.clone(),
+            row.to_date.clone(),
+            years_and_months(row.service_months),
+            years_and_months(row.total_months),
+        ]);
+    }
+    let mut employees: Vec<i64> = rows.iter().map(|r| r.employee_id).collect();
+    employees.sort_unstable();
+    employees.dedup();
+    report
+        .footer_lines
+        .push(format!("Ex-servicemen: {}", employees.len()));
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
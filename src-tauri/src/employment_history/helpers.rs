@@ -0,0 +1,150 @@
+use chrono::NaiveDate;
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::types::PreviousEmployment;
+use crate::gratuity::helpers::service_length;
+
+pub const EMPLOYMENT_SELECT: &str = "SELECT id, employee_id, employer, role, from_date, to_date,
+        months, reason_for_leaving, is_ex_serviceman, service_number, remarks, created_at,
+        updated_at
+    FROM previous_employments";
+
+pub fn map_employment(row: &Row) -> rusqlite::Result<PreviousEmployment> {
+    Ok(PreviousEmployment {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employer: row.get(2)?,
+        role: row.get(3)?,
+        from_date: row.get(4)?,
+        to_date: row.get(5)?,
+        months: row.get(6)?,
+        reason_for_leaving: row.get(7)?,
+        is_ex_serviceman: row.get(8)?,
+        service_number: row.get(9)?,
+        remarks: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+pub fn get_employment_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<PreviousEmployment>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{EMPLOYMENT_SELECT} WHERE id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_employment) {
+        Ok(employment) => Ok(Some(employment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn employee_employments(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Vec<PreviousEmployment>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{EMPLOYMENT_SELECT} WHERE employee_id = ?1 ORDER BY from_date DESC, id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+    let employments = stmt
+        .query_map([employee_id], map_employment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(employments)
+}
+
+/// Whole months with both days included, so 2020-01-01 to 2021-12-31 is 24
+/// months
+pub fn employment_months(from: NaiveDate, to: NaiveDate) -> i64 {
+    let (years, months, _) = service_length(from, to);
+    years as i64 * 12 + months as i64
+}
+
+/// Employer of another recorded employment of the employee overlapping the
+/// `from` to `to` period, `exclude_id` being the employment being edited
+pub fn overlapping_employer(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    exclude_id: Option<i64>,
+    from: &str,
+    to: &str,
+) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT employer FROM previous_employments
+        WHERE employee_id = ?1 AND (?2 IS NULL OR id != ?2)
+          AND from_date <= ?4 AND to_date >= ?3
+        LIMIT 1",
+        params![employee_id, exclude_id, from, to],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::parse_date;
+    use crate::db::open_test_db;
+
+    fn months(from: &str, to: &str) -> i64 {
+        employment_months(
+            parse_date(from, "from date").unwrap(),
+            parse_date(to, "to date").unwrap(),
+        )
+    }
+
+    #[test]
+    fn months_count_both_end_days() {
+        assert_eq!(months("2020-01-01", "2021-12-31"), 24);
+        assert_eq!(months("2020-01-15", "2020-02-14"), 1);
+        assert_eq!(months("2020-01-15", "2020-02-13"), 0);
+        // A month from the 31st runs to the end of a shorter month
+        assert_eq!(months("2019-01-31", "2019-02-28"), 1);
+        assert_eq!(months("2019-01-31", "2019-03-30"), 2);
+    }
+
+    #[test]
+    fn overlapping_employments_are_found() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO previous_employments (employee_id, employer, from_date, to_date, months)
+            VALUES (1, 'Alpha Security', '2018-01-01', '2019-12-31', 24)",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+
+        let overlap = |from: &str, to: &str, exclude: Option<i64>| {
+            overlapping_employer(&conn, 1, exclude, from, to).unwrap()
+        };
+        assert_eq!(
+            overlap("2019-12-31", "2020-06-30", None).as_deref(),
+            Some("Alpha Security")
+        );
+        assert_eq!(
+            overlap("2017-01-01", "2021-01-01", None).as_deref(),
+            Some("Alpha Security")
+        );
+        assert_eq!(overlap("2020-01-01", "2020-06-30", None), None);
+        assert_eq!(overlap("2017-06-01", "2017-12-31", None), None);
+        // An employment being edited does not overlap itself
+        assert_eq!(overlap("2018-02-01", "2019-12-31", Some(id)), None);
+        // Nor with the employments of another employee
+        assert_eq!(
+            overlapping_employer(&conn, 2, None, "2018-06-01", "2018-07-01").unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousEmployment {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employer: String,
+    pub role: Option<String>,
+    pub from_date: String,
+    pub to_date: String,
+    /// Whole months between the two dates
+    pub months: i64,
+    pub reason_for_leaving: Option<String>,
+    pub is_ex_serviceman: i32,
+    pub service_number: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousEmploymentInput {
+    pub employee_id: i64,
+    pub employer: String,
+    pub role: Option<String>,
+    pub from_date: String,
+    pub to_date: String,
+    pub reason_for_leaving: Option<String>,
+    /// Service in the armed forces, which needs a service number
+    pub is_ex_serviceman: Option<i32>,
+    pub service_number: Option<String>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmploymentHistory {
+    pub employee_id: i64,
+    pub total_months: i64,
+    pub is_ex_serviceman: bool,
+    pub service_number: Option<String>,
+    /// Free text experience of records from before the history was kept
+    pub legacy_experience: Option<String>,
+    pub employments: Vec<PreviousEmployment>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExServiceman {
+    pub employee_id: i64,
+    pub name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub employment_status: String,
+    pub service_number: Option<String>,
+    /// Arm or unit served in
+    pub employer: String,
+    /// Rank or trade at discharge
+    pub role: Option<String>,
+    pub from_date: String,
+    pub to_date: String,
+    pub service_months: i64,
+    pub total_months: i64,
+}
@@ -0,0 +1,116 @@
+use super::helpers::{current_employees_without_uan, ecr_line, ecr_row, validate_ecr_row};
+use super::types::{EcrIssue, EcrReport, MissingUanEmployee};
+use crate::dates::month_bounds;
+use crate::db;
+use crate::payroll::helpers::{get_pay_run_by_month, get_pay_run_lines, load_payroll_settings};
+use crate::state::AppState;
+use rusqlite::Connection;
+use std::fs;
+use tauri::State;
+
+fn build_ecr_report(conn: &Connection, month: &str) -> Result<EcrReport, String> {
+    let (first, last) = month_bounds(month)?;
+    let month = first.format("%Y-%m").to_string();
+    let pay_run = get_pay_run_by_month(conn, &month)?
+        .ok_or_else(|| format!("Payroll for {month} has not been run"))?;
+    let settings = load_payroll_settings(conn)?;
+    let days_in_month = (last - first).num_days() + 1;
+
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+    let mut missing_uan = Vec::new();
+
+    for line in get_pay_run_lines(conn, pay_run.id, None)? {
+        let Some(uan) = line.uan.as_deref().filter(|u| !u.trim().is_empty()) else {
+            missing_uan.push(MissingUanEmployee {
+                employee_id: line.employee_id.unwrap_or_default(),
+                employee_name: line.employee_name.clone(),
+                essid: line.essid.clone(),
+                job_post: line.job_post.clone(),
+                joining_date: None,
+                in_pay_run: true,
+            });
+            continue;
+        };
+
+        let row = ecr_row(&line, uan, settings.pf_wage_ceiling);
+        for message in validate_ecr_row(&row, days_in_month) {
+            issues.push(EcrIssue {
+                essid: row.essid.clone(),
+                member_name: row.member_name.clone(),
+                message,
+            });
+        }
+        rows.push(row);
+    }
+
+    for employee in current_employees_without_uan(conn)? {
+        match missing_uan
+            .iter_mut()
+            .find(|m| m.in_pay_run && m.employee_id == employee.employee_id)
+        {
+            Some(listed) => listed.joining_date = employee.joining_date,
+            None => missing_uan.push(employee),
+        }
+    }
+
+    Ok(EcrReport {
+        month,
+        pay_run_status: pay_run.status,
+        total_epf_contribution: rows.iter().map(|r| r.epf_contribution).sum(),
+        total_eps_contribution: rows.iter().map(|r| r.eps_contribution).sum(),
+        total_epf_eps_difference: rows.iter().map(|r| r.epf_eps_difference).sum(),
+        rows,
+        issues,
+        missing_uan,
+    })
+}
+
+/// Preview the ECR lines of a `YYYY-MM` pay run along with validation issues
+/// and employees that cannot be included for want of a UAN
+#[tauri::command]
+pub fn get_ecr_report(_state: State<AppState>, month: String) -> Result<EcrReport, String> {
+    let conn = db::get_connection()?;
+    build_ecr_report(&conn, &month)
+}
+
+/// Write the ECR text file for upload to the EPFO portal. Nothing is written
+/// while any member line fails validation.
+#[tauri::command]
+pub fn export_ecr_file(
+    _state: State<AppState>,
+    month: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let report = build_ecr_report(&conn, &month)?;
+    if let Some(issue) = report.issues.first() {
+        return Err(format!(
+            "ECR has {} validation issue(s), first: {} ({}) {}",
+            report.issues.len(),
+            issue.member_name,
+            issue.essid,
+            issue.message
+        ));
+    }
+    if report.rows.is_empty() {
+        return Err(format!(
+            "No employees with a UAN in payroll for {}",
+            report.month
+        ));
+    }
+
+    let content: String = report.rows.iter().map(|row| ecr_line(row) + "\n").collect();
+    fs::write(&export_path, content).map_err(|e| format!("Failed to write ECR file: {e}"))?;
+
+    Ok(export_path)
+}
+
+#[tauri::command]
+pub fn get_employees_without_uan(
+    _state: State<AppState>,
+) -> Result<Vec<MissingUanEmployee>, String> {
+    let conn = db::get_connection()?;
+    current_employees_without_uan(&conn)
+}
@@ -0,0 +1,184 @@
+use super::types::{EcrRow, MissingUanEmployee};
+use crate::payroll::helpers::line_item_amount;
+use crate::payroll::types::PayRunLine;
+
+/// Field separator of the EPFO ECR text file
+pub const ECR_SEPARATOR: &str = "#~#";
+
+fn item_amount(line: &PayRunLine, code: &str) -> i64 {
+    line_item_amount(line, code).round() as i64
+}
+
+/// Build the ECR member line of a pay run line. EDLI wages are limited to the
+/// PF wage ceiling.
+pub fn ecr_row(line: &PayRunLine, uan: &str, edli_ceiling: f64) -> EcrRow {
+    EcrRow {
+        employee_id: line.employee_id,
+        essid: line.essid.clone(),
+        uan: uan.trim().to_string(),
+        member_name: line
+            .employee_name
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+        gross_wages: line.gross_pay.round() as i64,
+        epf_wages: line.pf_wages.round() as i64,
+        eps_wages: line.eps_wages.round() as i64,
+        edli_wages: line.pf_wages.min(edli_ceiling).round() as i64,
+        epf_contribution: item_amount(line, "PF"),
+        eps_contribution: item_amount(line, "EPS"),
+        epf_eps_difference: item_amount(line, "EPF_ER"),
+        ncp_days: (line.days_in_month - line.paid_days).max(0),
+        refund_of_advances: 0,
+    }
+}
+
+/// Problems that would make the EPFO portal reject the member line
+pub fn validate_ecr_row(row: &EcrRow, days_in_month: i64) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    if row.uan.len() != 12 || !row.uan.chars().all(|c| c.is_ascii_digit()) {
+        messages.push(format!("UAN '{}' must be 12 digits", row.uan));
+    }
+    if row.member_name.is_empty() {
+        messages.push("Member name is empty".to_string());
+    } else if row.member_name.contains(['#', '~']) {
+        messages.push("Member name cannot contain '#' or '~'".to_string());
+    }
+    if row.epf_wages > row.gross_wages {
+        messages.push("EPF wages exceed gross wages".to_string());
+    }
+    if row.eps_wages > row.epf_wages || row.edli_wages > row.epf_wages {
+        messages.push("EPS and EDLI wages cannot exceed EPF wages".to_string());
+    }
+    if row.epf_wages > 0 && row.epf_contribution == 0 {
+        messages.push("EPF contribution is missing".to_string());
+    }
+    if row.ncp_days > days_in_month {
+        messages.push(format!(
+            "NCP days {} exceed the {days_in_month} days of the month",
+            row.ncp_days
+        ));
+    }
+
+    messages
+}
+
+pub fn ecr_line(row: &EcrRow) -> String {
+    [
+        row.uan.clone(),
+        row.member_name.clone(),
+        row.gross_wages.to_string(),
+        row.epf_wages.to_string(),
+        row.eps_wages.to_string(),
+        row.edli_wages.to_string(),
+        row.epf_contribution.to_string(),
+        row.eps_contribution.to_string(),
+        row.epf_eps_difference.to_string(),
+        row.ncp_days.to_string(),
+        row.refund_of_advances.to_string(),
+    ]
+    .join(ECR_SEPARATOR)
+}
+
+/// Current employees that have no UAN recorded
+pub fn current_employees_without_uan(
+    conn: &rusqlite::Connection,
+) -> Result<Vec<MissingUanEmployee>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, essid, job_post, joining_date FROM employees
+            WHERE employment_status = 'current' AND TRIM(IFNULL(uan, '')) = ''
+            ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let employees = stmt
+        .query_map([], |row| {
+            Ok(MissingUanEmployee {
+                employee_id: row.get(0)?,
+                employee_name: row.get(1)?,
+                essid: row.get(2)?,
+                job_post: row.get(3)?,
+                joining_date: row.get(4)?,
+                in_pay_run: false,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(employees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payroll::types::PayLineItem;
+
+    fn item(kind: &str, code: &str, amount: f64) -> PayLineItem {
+        PayLineItem {
+            kind: kind.to_string(),
+            code: code.to_string(),
+            label: code.to_string(),
+            amount,
+        }
+    }
+
+    fn pay_run_line() -> PayRunLine {
+        PayRunLine {
+            id: 1,
+            pay_run_id: 1,
+            employee_id: Some(1),
+            employee_name: "  Ravi   Kumar ".to_string(),
+            essid: "E1".to_string(),
+            job_post: Some("Guard".to_string()),
+            uan: Some("100200300400".to_string()),
+            esiip: None,
+            days_in_month: 31,
+            paid_days: 27,
+            ot_shifts: 0,
+            gross_pay: 21000.4,
+            pf_wages: 18500.0,
+            eps_wages: 15000.0,
+            esi_wages: 21000.4,
+            total_deductions: 2220.0,
+            net_pay: 18780.4,
+            items: vec![
+                item("deduction", "PF", 2220.0),
+                item("employer", "EPS", 1249.5),
+                item("employer", "EPF_ER", 970.5),
+            ],
+        }
+    }
+
+    #[test]
+    fn member_line_follows_the_ecr_layout() {
+        let row = ecr_row(&pay_run_line(), " 100200300400 ", 15000.0);
+
+        assert!(validate_ecr_row(&row, 31).is_empty());
+        assert_eq!(
+            ecr_line(&row),
+            "100200300400#~#Ravi Kumar#~#21000#~#18500#~#15000#~#15000#~#2220#~#1250#~#971#~#4#~#0"
+        );
+    }
+
+    #[test]
+    fn rows_the_portal_would_reject_are_reported() {
+        let mut line = pay_run_line();
+        line.employee_name = "Ravi #1".to_string();
+        line.items.clear();
+        let mut row = ecr_row(&line, "10020030040", 15000.0);
+        row.ncp_days = 32;
+
+        assert_eq!(
+            validate_ecr_row(&row, 31),
+            vec![
+                "UAN '10020030040' must be 12 digits".to_string(),
+                "Member name cannot contain '#' or '~'".to_string(),
+                "EPF contribution is missing".to_string(),
+                "NCP days 32 exceed the 31 days of the month".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+
+/// One member line of the Electronic Challan cum Return, amounts in rupees
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcrRow {
+    pub employee_id: Option<i64>,
+    pub essid: String,
+    pub uan: String,
+    pub member_name: String,
+    pub gross_wages: i64,
+    pub epf_wages: i64,
+    pub eps_wages: i64,
+    pub edli_wages: i64,
+    pub epf_contribution: i64,
+    pub eps_contribution: i64,
+    pub epf_eps_difference: i64,
+    pub ncp_days: i64,
+    pub refund_of_advances: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcrIssue {
+    pub essid: String,
+    pub member_name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingUanEmployee {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub joining_date: Option<String>,
+    /// Whether the employee was paid in the pay run being exported
+    pub in_pay_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcrReport {
+    pub month: String,
+    pub pay_run_status: String,
+    pub rows: Vec<EcrRow>,
+    pub issues: Vec<EcrIssue>,
+    pub missing_uan: Vec<MissingUanEmployee>,
+    pub total_epf_contribution: i64,
+    pub total_eps_contribution: i64,
+    pub total_epf_eps_difference: i64,
+}
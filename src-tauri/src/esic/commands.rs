@@ -0,0 +1,198 @@
+use super::helpers::{
+    ceiling_message, contribution_sheet, ip_name, reason_code, reason_code_sheet, reason_label,
+    validate_esic_row,
+};
+use super::types::{EsicIssue, EsicReport, EsicRow, EsicThresholdEmployee};
+use crate::dates::{format_date, month_bounds};
+use crate::db;
+use crate::payroll::helpers::{
+    get_pay_run_by_month, get_pay_run_lines, line_item_amount, load_payroll_settings,
+};
+use crate::payroll::types::PayRunLine;
+use crate::state::AppState;
+use crate::xlsx::Workbook;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::State;
+
+fn item_amount(line: &PayRunLine, code: &str) -> i64 {
+    line_item_amount(line, code).round() as i64
+}
+
+fn build_esic_report(conn: &Connection, month: &str) -> Result<EsicReport, String> {
+    let (first, last) = month_bounds(month)?;
+    let month = first.format("%Y-%m").to_string();
+    let pay_run = get_pay_run_by_month(conn, &month)?
+        .ok_or_else(|| format!("Payroll for {month} has not been run"))?;
+    let settings = load_payroll_settings(conn)?;
+    let days_in_month = (last - first).num_days() + 1;
+    let (first, last) = (format_date(first), format_date(last));
+
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+    let mut above_threshold = Vec::new();
+    let mut paid = HashSet::new();
+
+    for line in get_pay_run_lines(conn, pay_run.id, None)? {
+        paid.extend(line.employee_id);
+
+        if let Some(message) =
+            ceiling_message(line.esi_wages, line.gross_pay, settings.esi_wage_ceiling)
+        {
+            above_threshold.push(EsicThresholdEmployee {
+                employee_id: line.employee_id,
+                employee_name: line.employee_name.clone(),
+                essid: line.essid.clone(),
+                esiip: line.esiip.clone(),
+                gross_pay: line.gross_pay,
+                message,
+            });
+        }
+        if line.esi_wages <= 0.0 {
+            continue;
+        }
+
+        let Some(ip_number) = line.esiip.as_deref().filter(|ip| !ip.trim().is_empty()) else {
+            issues.push(EsicIssue {
+                essid: line.essid.clone(),
+                ip_name: ip_name(&line.employee_name),
+                message: "ESI contribution is due but no IP number is recorded".to_string(),
+            });
+            continue;
+        };
+
+        // Employees leaving during the month are reported with their last day
+        let exit_date: Option<String> = match line.employee_id {
+            Some(employee_id) => conn
+                .query_row(
+                    "SELECT exit_date FROM employees
+                     WHERE id = ?1 AND employment_status = 'past' AND exit_date BETWEEN ?2 AND ?3",
+                    params![employee_id, first, last],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?,
+            None => None,
+        };
+        let reason_code = reason_code(exit_date.is_some(), true);
+
+        rows.push(EsicRow {
+            employee_id: line.employee_id,
+            essid: line.essid.clone(),
+            ip_number: ip_number.trim().to_string(),
+            ip_name: ip_name(&line.employee_name),
+            days_paid: line.paid_days,
+            total_wages: line.esi_wages.round() as i64,
+            reason_code,
+            reason: exit_date.as_ref().and_then(|_| reason_label(reason_code)),
+            last_working_day: exit_date,
+            employee_contribution: item_amount(&line, "ESI"),
+            employer_contribution: item_amount(&line, "ESI_ER"),
+        });
+    }
+
+    // Insured persons without wages this month still have to be reported
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, essid, esiip, exit_date FROM employees
+            WHERE TRIM(IFNULL(esiip, '')) <> ''
+              AND ((employment_status = 'current' AND IFNULL(joining_date, '') <= ?2)
+                OR (employment_status = 'past' AND exit_date BETWEEN ?1 AND ?2))
+            ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let unpaid = stmt
+        .query_map(params![first, last], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (employee_id, name, essid, esiip, exit_date) in unpaid {
+        if paid.contains(&employee_id) {
+            continue;
+        }
+        let reason_code = reason_code(exit_date.is_some(), false);
+        rows.push(EsicRow {
+            employee_id: Some(employee_id),
+            essid,
+            ip_number: esiip.trim().to_string(),
+            ip_name: ip_name(&name),
+            days_paid: 0,
+            total_wages: 0,
+            reason_code,
+            reason: reason_label(reason_code),
+            last_working_day: exit_date,
+            employee_contribution: 0,
+            employer_contribution: 0,
+        });
+    }
+
+    for row in &rows {
+        for message in validate_esic_row(row, days_in_month) {
+            issues.push(EsicIssue {
+                essid: row.essid.clone(),
+                ip_name: row.ip_name.clone(),
+                message,
+            });
+        }
+    }
+
+    Ok(EsicReport {
+        month,
+        pay_run_status: pay_run.status,
+        esi_wage_ceiling: settings.esi_wage_ceiling,
+        total_wages: rows.iter().map(|r| r.total_wages).sum(),
+        total_employee_contribution: rows.iter().map(|r| r.employee_contribution).sum(),
+        total_employer_contribution: rows.iter().map(|r| r.employer_contribution).sum(),
+        rows,
+        issues,
+        above_threshold,
+    })
+}
+
+/// Preview the ESIC contribution lines of a `YYYY-MM` pay run with validation
+/// issues and employees above the ESI wage ceiling
+#[tauri::command]
+pub fn get_esic_report(_state: State<AppState>, month: String) -> Result<EsicReport, String> {
+    let conn = db::get_connection()?;
+    build_esic_report(&conn, &month)
+}
+
+/// Write the monthly contribution upload in the ESIC template layout. Nothing
+/// is written while any line fails validation.
+#[tauri::command]
+pub fn export_esic_file(
+    _state: State<AppState>,
+    month: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let report = build_esic_report(&conn, &month)?;
+    if let Some(issue) = report.issues.first() {
+        return Err(format!(
+            "ESIC file has {} validation issue(s), first: {} ({}) {}",
+            report.issues.len(),
+            issue.ip_name,
+            issue.essid,
+            issue.message
+        ));
+    }
+    if report.rows.is_empty() {
+        return Err(format!("No insured persons to report for {}", report.month));
+    }
+
+    let mut workbook = Workbook::new();
+    workbook.add_sheet(contribution_sheet(&report.rows));
+    workbook.add_sheet(reason_code_sheet());
+    workbook.save(&PathBuf::from(&export_path))
+}
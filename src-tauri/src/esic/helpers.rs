@@ -0,0 +1,225 @@
+use super::types::EsicRow;
+use crate::dates::parse_date;
+use crate::xlsx::{Cell, Sheet};
+
+const REASON_ON_LEAVE: i64 = 1;
+const REASON_LEFT_SERVICE: i64 = 2;
+
+/// Reason codes of the ESIC contribution upload for IPs without working days
+const REASON_CODES: [(i64, &str); 14] = [
+    (0, "Without Reason"),
+    (1, "On Leave"),
+    (2, "Left Service"),
+    (3, "Retired"),
+    (4, "Out of Coverage"),
+    (5, "Expired"),
+    (6, "Non Implemented area"),
+    (7, "Compliance by Immediate Employer"),
+    (8, "Suspension of work"),
+    (9, "Strike/Lockout"),
+    (10, "Retrenchment"),
+    (11, "No Work"),
+    (12, "Doesnt Belong To This Employer"),
+    (13, "Duplicate IP"),
+];
+
+/// Reasons the portal only accepts together with a last working day
+const REASONS_NEEDING_LAST_DAY: [i64; 4] = [2, 3, 5, 10];
+
+const TEMPLATE_HEADER: [&str; 6] = [
+    "IP Number (10 Digits)",
+    "IP Name( Only Alphabets and Space )",
+    "No of Days for which wages paid/payable during the month",
+    "Total Monthly Wages",
+    "Reason Code for Zero workings days(numeric only; provide 0 for all other reasons- Click on the link for reference)",
+    "Last Working Day( Format DD/MM/YYYY  or DD-MM-YYYY)",
+];
+
+/// Reason code of an IP's line: left service when they exited during the
+/// month, on leave when nothing was paid, and none otherwise
+pub fn reason_code(exited: bool, paid: bool) -> i64 {
+    if exited {
+        REASON_LEFT_SERVICE
+    } else if paid {
+        0
+    } else {
+        REASON_ON_LEAVE
+    }
+}
+
+/// Why a pay run line with `gross_pay` is flagged against the ESI wage
+/// ceiling. Lines without ESI wages were above the ceiling when the wage
+/// period began and are not covered; covered lines may still go above it.
+pub fn ceiling_message(esi_wages: f64, gross_pay: f64, ceiling: f64) -> Option<String> {
+    if esi_wages <= 0.0 {
+        (gross_pay > 0.0)
+            .then(|| format!("Monthly wage is above the ESI ceiling of {ceiling}, not covered"))
+    } else {
+        (gross_pay > ceiling)
+            .then(|| format!("Wages this month exceed the ESI ceiling of {ceiling}"))
+    }
+}
+
+pub fn reason_label(code: i64) -> Option<String> {
+    REASON_CODES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, label)| label.to_string())
+}
+
+/// The portal accepts only letters and spaces in IP names
+pub fn ip_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphabetic() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `YYYY-MM-DD` as the `DD/MM/YYYY` the template asks for
+pub fn template_date(date: &str) -> String {
+    parse_date(date, "last working day")
+        .map(|d| d.format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+pub fn validate_esic_row(row: &EsicRow, days_in_month: i64) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    if row.ip_number.len() != 10 || !row.ip_number.chars().all(|c| c.is_ascii_digit()) {
+        messages.push(format!("IP number '{}' must be 10 digits", row.ip_number));
+    }
+    if row.ip_name.is_empty() {
+        messages.push("IP name is empty".to_string());
+    }
+    if row.days_paid > days_in_month {
+        messages.push(format!(
+            "{} days paid exceed the {days_in_month} days of the month",
+            row.days_paid
+        ));
+    }
+    if row.days_paid == 0 && row.total_wages > 0 {
+        messages.push("Wages are reported without any days paid".to_string());
+    }
+    if REASONS_NEEDING_LAST_DAY.contains(&row.reason_code) && row.last_working_day.is_none() {
+        messages.push(format!(
+            "Reason code {} needs a last working day",
+            row.reason_code
+        ));
+    }
+
+    messages
+}
+
+/// The contribution sheet in the column layout of the ESIC upload template
+pub fn contribution_sheet(rows: &[EsicRow]) -> Sheet {
+    let mut sheet = Sheet::new("Sheet1");
+    sheet.set_column_widths(&[16.0, 32.0, 18.0, 16.0, 22.0, 20.0]);
+    sheet.add_bold_row(TEMPLATE_HEADER.iter().map(|h| Cell::text(*h)).collect());
+
+    for row in rows {
+        sheet.add_row(vec![
+            Cell::text(row.ip_number.clone()),
+            Cell::text(row.ip_name.clone()),
+            Cell::number(row.days_paid as f64),
+            Cell::number(row.total_wages as f64),
+            Cell::number(row.reason_code as f64),
+            row.last_working_day
+                .as_deref()
+                .map_or(Cell::Empty, |d| Cell::text(template_date(d))),
+        ]);
+    }
+
+    sheet
+}
+
+pub fn reason_code_sheet() -> Sheet {
+    let mut sheet = Sheet::new("Instructions & Reason Codes");
+    sheet.set_column_widths(&[12.0, 36.0]);
+    sheet.add_bold_row(vec![Cell::text("Code"), Cell::text("Reason")]);
+    for (code, label) in REASON_CODES {
+        sheet.add_row(vec![Cell::number(code as f64), Cell::text(label)]);
+    }
+    sheet.add_row(Vec::new());
+    sheet.add_row(vec![Cell::text(
+        "Last working day is mandatory for reason codes 2, 3, 5 and 10",
+    )]);
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(reason_code: i64, last_working_day: Option<&str>) -> EsicRow {
+        EsicRow {
+            employee_id: Some(1),
+            essid: "E1".to_string(),
+            ip_number: "1234567890".to_string(),
+            ip_name: "Ravi Kumar".to_string(),
+            days_paid: 0,
+            total_wages: 0,
+            reason_code,
+            reason: reason_label(reason_code),
+            last_working_day: last_working_day.map(str::to_string),
+            employee_contribution: 0,
+            employer_contribution: 0,
+        }
+    }
+
+    #[test]
+    fn ceiling_flags_uncovered_and_exceeding_wages() {
+        assert_eq!(
+            ceiling_message(0.0, 25000.0, 21000.0).as_deref(),
+            Some("Monthly wage is above the ESI ceiling of 21000, not covered")
+        );
+        // Nothing paid at all is not a ceiling problem
+        assert_eq!(ceiling_message(0.0, 0.0, 21000.0), None);
+        // Covered from the start of the period, then paid above the ceiling
+        assert_eq!(
+            ceiling_message(23000.0, 23000.0, 21000.0).as_deref(),
+            Some("Wages this month exceed the ESI ceiling of 21000")
+        );
+        assert_eq!(ceiling_message(21000.0, 21000.0, 21000.0), None);
+    }
+
+    #[test]
+    fn reason_codes_follow_exit_and_payment() {
+        assert_eq!(reason_code(false, true), 0);
+        assert_eq!(reason_code(false, false), 1);
+        assert_eq!(reason_code(true, true), 2);
+        assert_eq!(reason_code(true, false), 2);
+        assert_eq!(reason_label(1).as_deref(), Some("On Leave"));
+        assert_eq!(reason_label(2).as_deref(), Some("Left Service"));
+        assert_eq!(reason_label(14), None);
+    }
+
+    #[test]
+    fn leaving_reasons_need_a_last_working_day() {
+        assert_eq!(
+            validate_esic_row(&row(2, None), 31),
+            vec!["Reason code 2 needs a last working day".to_string()]
+        );
+        assert!(validate_esic_row(&row(2, Some("2024-07-15")), 31).is_empty());
+        assert!(validate_esic_row(&row(1, None), 31).is_empty());
+
+        let mut paid = row(0, None);
+        paid.ip_number = "12345".to_string();
+        paid.days_paid = 32;
+        paid.total_wages = 18000;
+        assert_eq!(
+            validate_esic_row(&paid, 31),
+            vec![
+                "IP number '12345' must be 10 digits".to_string(),
+                "32 days paid exceed the 31 days of the month".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_and_dates_match_the_template() {
+        assert_eq!(ip_name("  A. K. Nair-2 "), "A K Nair");
+        assert_eq!(template_date("2024-07-05"), "05/07/2024");
+    }
+}
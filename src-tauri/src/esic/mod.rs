@@ -0,0 +1,3 @@
+pub mod commands;
+mod helpers;
+mod types;
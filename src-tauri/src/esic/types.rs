@@ -0,0 +1,52 @@
+use serde::Serialize;
+
+/// One insured person's line of the ESIC monthly contribution upload
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsicRow {
+    pub employee_id: Option<i64>,
+    pub essid: String,
+    pub ip_number: String,
+    pub ip_name: String,
+    pub days_paid: i64,
+    pub total_wages: i64,
+    pub reason_code: i64,
+    pub reason: Option<String>,
+    pub last_working_day: Option<String>,
+    pub employee_contribution: i64,
+    pub employer_contribution: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsicIssue {
+    pub essid: String,
+    pub ip_name: String,
+    pub message: String,
+}
+
+/// An employee whose wages are above the ESI ceiling
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsicThresholdEmployee {
+    pub employee_id: Option<i64>,
+    pub employee_name: String,
+    pub essid: String,
+    pub esiip: Option<String>,
+    pub gross_pay: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsicReport {
+    pub month: String,
+    pub pay_run_status: String,
+    pub esi_wage_ceiling: f64,
+    pub rows: Vec<EsicRow>,
+    pub issues: Vec<EsicIssue>,
+    pub above_threshold: Vec<EsicThresholdEmployee>,
+    pub total_wages: i64,
+    pub total_employee_contribution: i64,
+    pub total_employer_contribution: i64,
+}
@@ -54,3 +54,54 @@ pub fn delete_image(image_path: &Path) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Directory holding the attachments of an employee
+pub fn get_attachment_dir(employee_id: i64) -> Result<PathBuf, String> {
+    let directory = get_files_dir()?
+        .join("attachments")
+        .join(employee_id.to_string());
+    fs::create_dir_all(&directory)
+        .map_err(|e| format!("Failed to create attachments directory: {e}"))?;
+    Ok(directory)
+}
+
+/// Copy a file chosen by the user into the employee's attachments
+pub fn save_attachment_file(source_path: &Path, employee_id: i64) -> Result<PathBuf, String> {
+    if !source_path.exists() {
+        return Err(format!("File not found: {}", source_path.display()));
+    }
+    let extension = source_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin");
+
+    let destination_path =
+        get_attachment_dir(employee_id)?.join(format!("{}.{extension}", Uuid::new_v4()));
+
+    fs::copy(source_path, &destination_path)
+        .map_err(|e| format!("Failed to save attachment: {e}"))?;
+
+    Ok(destination_path)
+}
+
+/// Store generated content, such as a payslip, as an employee attachment
+pub fn write_attachment_file(
+    employee_id: i64,
+    extension: &str,
+    content: &[u8],
+) -> Result<PathBuf, String> {
+    let destination_path =
+        get_attachment_dir(employee_id)?.join(format!("{}.{extension}", Uuid::new_v4()));
+
+    fs::write(&destination_path, content).map_err(|e| format!("Failed to save attachment: {e}"))?;
+
+    Ok(destination_path)
+}
+
+/// Remove an attachment file, ignoring files that are already gone
+pub fn delete_attachment_file(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete attachment: {e}"))?;
+    }
+    Ok(())
+}
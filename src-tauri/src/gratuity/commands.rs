@@ -0,0 +1,136 @@
+use super::helpers::{calculate_gratuity, eligible_on};
+use super::types::{GratuityCalculation, GratuityEligibilityDue, GratuityLiability};
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::{load_payroll_settings, round2};
+use crate::state::AppState;
+use chrono::Months;
+use rusqlite::{params, Result};
+use tauri::State;
+
+/// Gratuity of one employee, up to their exit date once they have left and
+/// otherwise up to `as_on` (today when empty)
+#[tauri::command]
+pub fn get_gratuity_calculation(
+    _state: State<AppState>,
+    employee_id: i64,
+    as_on: Option<String>,
+) -> Result<GratuityCalculation, String> {
+    let conn = db::get_connection()?;
+
+    let employee = get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+    let settings = load_payroll_settings(&conn)?;
+    let service_end = match employee
+        .exit_date
+        .as_deref()
+        .filter(|d| employee.employment_status == "past" && !d.trim().is_empty())
+        .or(as_on.as_deref())
+    {
+        Some(date) => parse_date(date, "date")?,
+        None => today(),
+    };
+
+    calculate_gratuity(&conn, &settings, &employee, service_end)
+}
+
+/// Current employees who complete the qualifying service within
+/// `within_months` months (12 when empty), soonest first
+#[tauri::command]
+pub fn get_gratuity_eligibility_due(
+    _state: State<AppState>,
+    within_months: Option<u32>,
+) -> Result<Vec<GratuityEligibilityDue>, String> {
+    let conn = db::get_connection()?;
+
+    let today = today();
+    let until = today
+        .checked_add_months(Months::new(within_months.unwrap_or(12)))
+        .unwrap_or(today);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, essid, job_post, joining_date FROM employees
+            WHERE employment_status = 'current' AND TRIM(IFNULL(joining_date, '')) <> ''",
+        )
+        .map_err(|e| e.to_string())?;
+    let employees = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut due = Vec::new();
+    for (employee_id, name, essid, job_post, joining_date) in employees {
+        let Ok(joining) = parse_date(&joining_date, "joining date") else {
+            continue;
+        };
+        let eligible = eligible_on(joining);
+        if eligible <= today || eligible > until {
+            continue;
+        }
+        due.push(GratuityEligibilityDue {
+            employee_id,
+            employee_name: name,
+            essid,
+            job_post,
+            joining_date,
+            eligible_on: format_date(eligible),
+            days_remaining: (eligible - today).num_days(),
+        });
+    }
+    due.sort_by(|a, b| a.eligible_on.cmp(&b.eligible_on));
+
+    Ok(due)
+}
+
+/// Gratuity accrued by every current employee as on `as_on` (today when
+/// empty), and the part already payable to those past the qualifying service
+#[tauri::command]
+pub fn get_gratuity_liability(
+    _state: State<AppState>,
+    as_on: Option<String>,
+) -> Result<GratuityLiability, String> {
+    let conn = db::get_connection()?;
+
+    let as_on = match as_on.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date(date, "date")?,
+        None => today(),
+    };
+    let settings = load_payroll_settings(&conn)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM employees
+            WHERE employment_status = 'current' AND IFNULL(joining_date, '') <= ?1
+            ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let employee_ids = stmt
+        .query_map(params![format_date(as_on)], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut employees = Vec::new();
+    for employee_id in employee_ids {
+        let employee = get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+        employees.push(calculate_gratuity(&conn, &settings, &employee, as_on)?);
+    }
+
+    Ok(GratuityLiability {
+        as_on: format_date(as_on),
+        gratuity_ceiling: settings.gratuity_ceiling,
+        total_accrued: round2(employees.iter().map(|e| e.accrued_amount).sum()),
+        total_payable: round2(employees.iter().map(|e| e.payable_amount).sum()),
+        employees,
+    })
+}
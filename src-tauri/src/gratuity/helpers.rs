@@ -0,0 +1,283 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+use super::types::GratuityCalculation;
+use crate::dates::{format_date, parse_date};
+use crate::employees::types::Employee;
+use crate::payroll::helpers::{find_wage_structure, WORKING_DAYS_PER_MONTH};
+use crate::payroll::types::PayrollSettings;
+
+/// Years of continuous service before gratuity becomes payable
+pub const ELIGIBILITY_YEARS: i32 = 5;
+
+/// 15 days of wages for each counted year, a month being 26 working days
+const GRATUITY_DAYS_PER_YEAR: f64 = 15.0;
+
+/// Completed years, months and days from `joining` up to and including
+/// `last_day`
+pub fn service_length(joining: NaiveDate, last_day: NaiveDate) -> (i32, i32, i32) {
+    let end = last_day + Duration::days(1);
+    if end <= joining {
+        return (0, 0, 0);
+    }
+
+    // A month from the 31st ends on the last day of a shorter month
+    let month_after = |months: i32| {
+        joining
+            .checked_add_months(Months::new(months as u32))
+            .unwrap_or(end)
+    };
+    let mut months =
+        (end.year() - joining.year()) * 12 + end.month() as i32 - joining.month() as i32;
+    if month_after(months) > end {
+        months -= 1;
+    }
+    let days = (end - month_after(months)).num_days() as i32;
+
+    (months / 12, months % 12, days)
+}
+
+/// Service years counted for gratuity: a part year of more than six months
+/// counts as a full year
+pub fn counted_years(years: i32, months: i32, days: i32) -> i32 {
+    if months > 6 || (months == 6 && days > 0) {
+        years + 1
+    } else {
+        years
+    }
+}
+
+/// Date from which an employee who joined on `joining` has completed the
+/// qualifying service
+pub fn eligible_on(joining: NaiveDate) -> NaiveDate {
+    joining
+        .checked_add_months(Months::new(ELIGIBILITY_YEARS as u32 * 12))
+        .unwrap_or(joining)
+}
+
+/// Gratuity of an employee for service up to `service_end`, using the wage
+/// structure of their job post on that date as the last drawn wages
+pub fn calculate_gratuity(
+    conn: &rusqlite::Connection,
+    settings: &PayrollSettings,
+    employee: &Employee,
+    service_end: NaiveDate,
+) -> Result<GratuityCalculation, String> {
+    let mut calculation = GratuityCalculation {
+        employee_id: employee.id,
+        employee_name: employee.name.clone(),
+        essid: employee.essid.clone(),
+        job_post: employee.job_post.clone(),
+        joining_date: employee.joining_date.clone(),
+        service_end_date: format_date(service_end),
+        service_years: 0,
+        service_months: 0,
+        service_days: 0,
+        counted_years: 0,
+        eligible: false,
+        monthly_wages: 0.0,
+        accrued_amount: 0.0,
+        payable_amount: 0.0,
+        capped: false,
+        message: None,
+    };
+
+    let Some(joining) = employee
+        .joining_date
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+    else {
+        calculation.message = Some("No joining date".to_string());
+        return Ok(calculation);
+    };
+    let joining = parse_date(joining, "joining date")?;
+
+    let (years, months, days) = service_length(joining, service_end);
+    calculation.service_years = years as i64;
+    calculation.service_months = months as i64;
+    calculation.service_days = days as i64;
+    calculation.counted_years = counted_years(years, months, days) as i64;
+    calculation.eligible = years >= ELIGIBILITY_YEARS;
+
+    let job_post = employee.job_post.as_deref().unwrap_or_default();
+    let Some(structure) = find_wage_structure(conn, job_post, service_end)? else {
+        calculation.message = Some(format!("No wage structure for {job_post} on {service_end}"));
+        return Ok(calculation);
+    };
+    calculation.monthly_wages = structure.basic + structure.da;
+
+    let amount = (calculation.monthly_wages * GRATUITY_DAYS_PER_YEAR / WORKING_DAYS_PER_MONTH
+        * calculation.counted_years as f64)
+        .round();
+    calculation.capped = amount > settings.gratuity_ceiling;
+    calculation.accrued_amount = amount.min(settings.gratuity_ceiling);
+    if calculation.eligible {
+        calculation.payable_amount = calculation.accrued_amount;
+    }
+
+    Ok(calculation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+    use crate::payroll::helpers::load_payroll_settings;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_date(value, "date").unwrap()
+    }
+
+    fn employee(joining_date: &str) -> Employee {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "Guard",
+            "permanentSameAsCurrent": 0,
+            "employmentStatus": "past",
+            "essid": "E1",
+            "jobPost": "Security Guard",
+            "joiningDate": joining_date,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn service_length_counts_both_days() {
+        assert_eq!(
+            service_length(date("2019-01-10"), date("2024-01-09")),
+            (5, 0, 0)
+        );
+        assert_eq!(
+            service_length(date("2019-01-10"), date("2024-01-10")),
+            (5, 0, 1)
+        );
+        assert_eq!(
+            service_length(date("2019-01-10"), date("2019-01-10")),
+            (0, 0, 1)
+        );
+        assert_eq!(
+            service_length(date("2019-01-10"), date("2019-01-09")),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn service_length_from_month_end() {
+        // A month from 31 January ends with 27 or 28 February
+        assert_eq!(
+            service_length(date("2021-01-31"), date("2021-02-26")),
+            (0, 0, 27)
+        );
+        assert_eq!(
+            service_length(date("2021-01-31"), date("2021-02-27")),
+            (0, 1, 0)
+        );
+        assert_eq!(
+            service_length(date("2021-01-31"), date("2021-02-28")),
+            (0, 1, 1)
+        );
+        assert_eq!(
+            service_length(date("2020-01-31"), date("2020-02-28")),
+            (0, 1, 0)
+        );
+        assert_eq!(
+            service_length(date("2019-01-31"), date("2024-01-30")),
+            (5, 0, 0)
+        );
+    }
+
+    #[test]
+    fn part_year_over_six_months_counts() {
+        let joining = date("2019-01-10");
+        let exactly_six = service_length(joining, date("2024-07-09"));
+        assert_eq!(exactly_six, (5, 6, 0));
+        assert_eq!(
+            counted_years(exactly_six.0, exactly_six.1, exactly_six.2),
+            5
+        );
+
+        let one_day_more = service_length(joining, date("2024-07-10"));
+        assert_eq!(one_day_more, (5, 6, 1));
+        assert_eq!(
+            counted_years(one_day_more.0, one_day_more.1, one_day_more.2),
+            6
+        );
+
+        assert_eq!(counted_years(5, 7, 0), 6);
+        assert_eq!(counted_years(5, 5, 30), 5);
+    }
+
+    #[test]
+    fn gratuity_is_fifteen_days_per_counted_year() {
+        let conn = open_test_db();
+        let settings = load_payroll_settings(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO wage_structures (job_post, effective_from, basic, da, hra,
+                other_allowances, ot_rate)
+            VALUES ('Security Guard', '2019-01-01', 13000, 2600, 1000, 0, 0)",
+            [],
+        )
+        .unwrap();
+
+        let gratuity = calculate_gratuity(
+            &conn,
+            &settings,
+            &employee("2019-01-10"),
+            date("2024-07-10"),
+        )
+        .unwrap();
+        assert_eq!(gratuity.counted_years, 6);
+        assert!(gratuity.eligible);
+        assert_eq!(gratuity.monthly_wages, 15600.0);
+        assert_eq!(gratuity.payable_amount, 54000.0);
+        assert!(!gratuity.capped);
+
+        // Accrued but not payable before five years
+        let gratuity = calculate_gratuity(
+            &conn,
+            &settings,
+            &employee("2019-01-10"),
+            date("2024-01-08"),
+        )
+        .unwrap();
+        assert!(!gratuity.eligible);
+        assert_eq!(gratuity.accrued_amount, 45000.0);
+        assert_eq!(gratuity.payable_amount, 0.0);
+    }
+
+    #[test]
+    fn gratuity_is_capped_at_the_ceiling() {
+        let conn = open_test_db();
+        let settings = load_payroll_settings(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO wage_structures (job_post, effective_from, basic, da, hra,
+                other_allowances, ot_rate)
+            VALUES ('Security Guard', '1990-01-01', 150000, 50000, 0, 0, 0)",
+            [],
+        )
+        .unwrap();
+
+        let gratuity = calculate_gratuity(
+            &conn,
+            &settings,
+            &employee("1990-01-01"),
+            date("2024-12-31"),
+        )
+        .unwrap();
+        assert_eq!(gratuity.counted_years, 35);
+        assert!(gratuity.capped);
+        assert_eq!(gratuity.accrued_amount, settings.gratuity_ceiling);
+        assert_eq!(gratuity.payable_amount, settings.gratuity_ceiling);
+    }
+
+    #[test]
+    fn gratuity_needs_a_joining_date() {
+        let conn = open_test_db();
+        let settings = load_payroll_settings(&conn).unwrap();
+        let mut employee = employee("2019-01-10");
+        employee.joining_date = None;
+
+        let gratuity = calculate_gratuity(&conn, &settings, &employee, date("2024-07-10")).unwrap();
+        assert_eq!(gratuity.payable_amount, 0.0);
+        assert_eq!(gratuity.message.as_deref(), Some("No joining date"));
+    }
+}
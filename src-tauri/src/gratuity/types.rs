@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GratuityCalculation {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub joining_date: Option<String>,
+    /// Exit date of past employees, otherwise the date calculated for
+    pub service_end_date: String,
+    pub service_years: i64,
+    pub service_months: i64,
+    pub service_days: i64,
+    /// Completed years, plus one for a part year of more than six months
+    pub counted_years: i64,
+    pub eligible: bool,
+    /// Last drawn basic plus DA
+    pub monthly_wages: f64,
+    /// Gratuity earned so far whether or not it is payable yet
+    pub accrued_amount: f64,
+    /// Accrued amount once eligible, zero before that
+    pub payable_amount: f64,
+    pub capped: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GratuityEligibilityDue {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub joining_date: String,
+    pub eligible_on: String,
+    pub days_remaining: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GratuityLiability {
+    pub as_on: String,
+    pub gratuity_ceiling: f64,
+    pub employees: Vec<GratuityCalculation>,
+    /// Gratuity accrued by all current staff
+    pub total_accrued: f64,
+    /// Gratuity payable if every eligible employee left on `as_on`
+    pub total_payable: f64,
+}
@@ -0,0 +1,398 @@
+use super::helpers::{
+    get_item_by_id, get_movement_by_id, held_by, map_item, map_movement, normalize_size,
+    outstanding_items, stock_by_size, stock_of, ITEM_SELECT, MOVEMENTS, MOVEMENT_SELECT,
+};
+use super::types::{
+    InventoryItem, InventoryItemInput, InventoryMovement, InventoryMovementInput, LowStockItem,
+    OutstandingItem,
+};
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, month_bounds, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::round2;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+fn validate_item(item: &InventoryItemInput) -> Result<(), String> {
+    if item.code.trim().is_empty() {
+        return Err("Item code is required".to_string());
+    }
+    if item.name.trim().is_empty() {
+        return Err("Item name is required".to_string());
+    }
+    if !item.unit_cost.is_finite() || item.unit_cost < 0.0 {
+        return Err("Unit cost cannot be negative".to_string());
+    }
+    if item.reorder_level.unwrap_or(0) < 0 {
+        return Err("Reorder level cannot be negative".to_string());
+    }
+    Ok(())
+}
+
+fn map_item_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "An item with this code already exists".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_inventory_items(
+    _state: State<AppState>,
+    active_only: Option<bool>,
+) -> Result<Vec<InventoryItem>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ITEM_SELECT}
+            WHERE (?1 = 0 OR i.is_active = 1)
+            ORDER BY i.category IS NULL, i.category COLLATE NOCASE, i.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut items = stmt
+        .query_map(params![active_only.unwrap_or(false)], map_item)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for item in &mut items {
+        item.stock_by_size = stock_by_size(&conn, item.id)?;
+    }
+
+    Ok(items)
+}
+
+#[tauri::command]
+pub fn create_inventory_item(
+    _state: State<AppState>,
+    item: InventoryItemInput,
+) -> Result<InventoryItem, String> {
+    let conn = db::get_connection()?;
+
+    validate_item(&item)?;
+
+    conn.execute(
+        "INSERT INTO inventory_items (
+            code, name, category, has_sizes, unit_cost, reorder_level, is_active, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            item.code.trim(),
+            item.name.trim(),
+            item.category,
+            item.has_sizes.unwrap_or(0),
+            round2(item.unit_cost),
+            item.reorder_level.unwrap_or(0),
+            item.is_active.unwrap_or(1),
+            item.remarks,
+        ],
+    )
+    .map_err(map_item_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(item)) = get_item_by_id(&conn, id) {
+        Ok(item)
+    } else {
+        Err("Failed to retrieve item".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_inventory_item(
+    _state: State<AppState>,
+    id: i64,
+    item: InventoryItemInput,
+) -> Result<InventoryItem, String> {
+    let conn = db::get_connection()?;
+
+    validate_item(&item)?;
+    let existing = get_item_by_id(&conn, id)?.ok_or("Item not found")?;
+    let has_sizes = item.has_sizes.unwrap_or(existing.has_sizes);
+    let has_movements = !existing.stock_by_size.is_empty();
+    if has_movements && (has_sizes != 0) != (existing.has_sizes != 0) {
+        return Err(
+            "Sizes cannot be switched on or off for an item that already has stock entries"
+                .to_string(),
+        );
+    }
+
+    conn.execute(
+        "UPDATE inventory_items SET
+            code = ?1, name = ?2, category = ?3, has_sizes = ?4, unit_cost = ?5,
+            reorder_level = ?6, is_active = ?7, remarks = ?8, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?9",
+        params![
+            item.code.trim(),
+            item.name.trim(),
+            item.category,
+            has_sizes,
+            round2(item.unit_cost),
+            item.reorder_level.unwrap_or(existing.reorder_level),
+            item.is_active.unwrap_or(existing.is_active),
+            item.remarks,
+            id,
+        ],
+    )
+    .map_err(map_item_error)?;
+
+    if let Ok(Some(item)) = get_item_by_id(&conn, id) {
+        Ok(item)
+    } else {
+        Err("Failed to retrieve item".to_string())
+    }
+}
+
+/// Only items without stock entries can be deleted, others are marked
+/// inactive
+#[tauri::command]
+pub fn delete_inventory_item(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM inventory_items WHERE id = ?1", params![id])
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _)
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                "The item has stock entries, mark it inactive instead".to_string()
+            }
+            e => e.to_string(),
+        })?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_inventory_movements(
+    _state: State<AppState>,
+    item_id: Option<i64>,
+    employee_id: Option<i64>,
+    month: Option<String>,
+) -> Result<Vec<InventoryMovement>, String> {
+    let conn = db::get_connection()?;
+
+    let bounds = match month.filter(|m| !m.trim().is_empty()) {
+        Some(month) => {
+            let (first, last) = month_bounds(&month)?;
+            Some((format_date(first), format_date(last)))
+        }
+        None => None,
+    };
+    let (from, to) = bounds.unzip();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{MOVEMENT_SELECT}
+            WHERE (?1 IS NULL OR m.item_id = ?1) AND (?2 IS NULL OR m.employee_id = ?2)
+              AND (?3 IS NULL OR m.movement_date BETWEEN ?3 AND ?4)
+            ORDER BY m.movement_date DESC, m.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let movements = stmt
+        .query_map(params![item_id, employee_id, from, to], map_movement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(movements)
+}
+
+/// Record stock received, items issued to or returned by an employee, or a
+/// stock adjustment after a count. Issues cannot exceed the stock of the size
+/// and returns cannot exceed what the employee holds.
+#[tauri::command]
+pub fn record_inventory_movement(
+    _state: State<AppState>,
+    movement: InventoryMovementInput,
+) -> Result<InventoryMovement, String> {
+    let conn = db::get_connection()?;
+
+    let movement_date = format_date(parse_date(&movement.movement_date, "date")?);
+    let kind = movement.movement.trim().to_lowercase();
+    if !MOVEMENTS.contains(&kind.as_str()) {
+        return Err(format!(
+            "Invalid stock movement '{}', expected one of: {}",
+            movement.movement,
+            MOVEMENTS.join(", ")
+        ));
+    }
+    if movement.quantity == 0 || (movement.quantity < 0 && kind != "adjustment") {
+        return Err("Quantity must be at least one".to_string());
+    }
+
+    let item = get_item_by_id(&conn, movement.item_id)?.ok_or("Item not found")?;
+    let size = normalize_size(&item, movement.size.as_deref())?;
+    let label = if size.is_empty() {
+        item.name.clone()
+    } else {
+        format!("{} ({size})", item.name)
+    };
+
+    let employee_id = match kind.as_str() {
+        "issue" | "return" => {
+            let employee_id = movement
+                .employee_id
+                .ok_or("Employee is required for issues and returns")?;
+            let employee = get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+            if kind == "issue" {
+                if item.is_active == 0 {
+                    return Err(format!("{} is inactive", item.name));
+                }
+                if employee.employment_status != "current" {
+                    return Err(format!("{} is not a current employee", employee.name));
+                }
+            } else {
+                let held = held_by(&conn, employee_id, item.id, &size)?;
+                if movement.quantity > held {
+                    return Err(format!("{} holds only {held} of {label}", employee.name));
+                }
+            }
+            Some(employee_id)
+        }
+        _ => None,
+    };
+
+    let stock_change = match kind.as_str() {
+        "issue" => -movement.quantity,
+        _ => movement.quantity,
+    };
+    if stock_change < 0 {
+        let stock = stock_of(&conn, item.id, &size)?;
+        if stock + stock_change < 0 {
+            return Err(format!("Only {stock} of {label} in stock"));
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO inventory_movements (
+            item_id, size, movement_date, movement, quantity, employee_id, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            item.id,
+            size,
+            movement_date,
+            kind,
+            movement.quantity,
+            employee_id,
+            movement.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(movement)) = get_movement_by_id(&conn, id) {
+        Ok(movement)
+    } else {
+        Err("Failed to retrieve stock entry".to_string())
+    }
+}
+
+/// Items issued to an employee and not returned, valued at unit cost. The
+/// total is deducted in the full and final settlement.
+#[tauri::command]
+pub fn get_employee_outstanding_items(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Vec<OutstandingItem>, String> {
+    let conn = db::get_connection()?;
+    outstanding_items(&conn, employee_id)
+}
+
+fn low_stock_items(conn: &rusqlite::Connection) -> Result<Vec<LowStockItem>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ITEM_SELECT}
+            WHERE i.is_active = 1
+            ORDER BY i.category IS NULL, i.category COLLATE NOCASE, i.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+    let items = stmt
+        .query_map([], map_item)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut low = Vec::new();
+    for item in items.into_iter().filter(|i| i.stock <= i.reorder_level) {
+        low.push(LowStockItem {
+            item_id: item.id,
+            stock_by_size: stock_by_size(conn, item.id)?,
+            code: item.code,
+            name: item.name,
+            category: item.category,
+            stock: item.stock,
+            reorder_level: item.reorder_level,
+        });
+    }
+    Ok(low)
+}
+
+/// Active items whose stock is at or below their reorder level
+#[tauri::command]
+pub fn get_low_stock_items(_state: State<AppState>) -> Result<Vec<LowStockItem>, String> {
+    let conn = db::get_connection()?;
+    low_stock_items(&conn)
+}
+
+#[tauri::command]
+pub fn export_low_stock_report(
+    _state: State<AppState>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let items = low_stock_items(&conn)?;
+    if items.is_empty() {
+        return Err("No item is at or below its reorder level".to_string());
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Low Stock Report",
+        vec![
+            TableColumn::new("Code", 60.0, Align::Left),
+            TableColumn::new("Item", 120.0, Align::Left),
+            TableColumn::new("Category", 70.0, Align::Left),
+            TableColumn::new("Stock", 45.0, Align::Right),
+            TableColumn::new("Reorder at", 55.0, Align::Right),
+            TableColumn::new("Stock by size", 173.0, Align::Left),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    report
+        .subtitle_lines
+        .push(format!("As on {}", format_date(today())));
+
+    for item in &items {
+        let sizes = item
+            .stock_by_size
+            .iter()
+            .filter(|s| !s.size.is_empty())
+            .map(|s| format!("{} {}", s.size, s.quantity))
+            .collect::<Vec<_>>()
+            .join(", ");
+        report.rows.push(vec![
+            item.code.clone(),
+            item.name.clone(),
+            item.category.clone().unwrap_or_default(),
+            item.stock.to_string(),
+            item.reorder_level.to_string(),
+            sizes,
+        ]);
+    }
+    report
+        .footer_lines
+        .push(format!("Items to reorder: {}", items.len()));
+
+    report
+        .render(Orientation::Portrait)
+        .save(&PathBuf::from(&export_path))
+}
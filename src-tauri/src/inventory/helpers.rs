@@ -0,0 +1,313 @@
+use rusqlite::{params, Row};
+
+use super::types::{InventoryItem, InventoryMovement, OutstandingItem, SizeStock};
+use crate::payroll::helpers::round2;
+
+pub const MOVEMENTS: [&str; 4] = ["receipt", "issue", "return", "adjustment"];
+
+/// Change in stock of a movement `m`
+const STOCK_EFFECT: &str = "CASE m.movement WHEN 'issue' THEN -m.quantity WHEN 'settled' THEN 0
+    ELSE m.quantity END";
+
+/// Change in what the employee of a movement `m` holds
+const HELD_EFFECT: &str = "CASE m.movement WHEN 'issue' THEN m.quantity
+    WHEN 'return' THEN -m.quantity WHEN 'settled' THEN -m.quantity ELSE 0 END";
+
+/// Items with their stock and the quantity held by employees
+pub const ITEM_SELECT: &str = "SELECT i.id, i.code, i.name, i.category, i.has_sizes, i.unit_cost,
+        i.reorder_level, i.is_active, i.remarks,
+        (SELECT IFNULL(SUM(CASE m.movement WHEN 'issue' THEN -m.quantity
+                WHEN 'settled' THEN 0 ELSE m.quantity END), 0)
+         FROM inventory_movements m WHERE m.item_id = i.id),
+        (SELECT IFNULL(SUM(CASE m.movement WHEN 'issue' THEN m.quantity
+                WHEN 'return' THEN -m.quantity WHEN 'settled' THEN -m.quantity ELSE 0 END), 0)
+         FROM inventory_movements m WHERE m.item_id = i.id),
+        i.created_at, i.updated_at
+    FROM inventory_items i";
+
+pub const MOVEMENT_SELECT: &str = "SELECT m.id, m.item_id, i.code, i.name, m.size,
+        m.movement_date, m.movement, m.quantity, m.employee_id, e.name, e.essid, m.remarks,
+        m.created_at
+    FROM inventory_movements m
+    JOIN inventory_items i ON i.id = m.item_id
+    LEFT JOIN employees e ON e.id = m.employee_id";
+
+pub fn map_item(row: &Row) -> rusqlite::Result<InventoryItem> {
+    Ok(InventoryItem {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        name: row.get(2)?,
+        category: row.get(3)?,
+        has_sizes: row.get(4)?,
+        unit_cost: row.get(5)?,
+        reorder_level: row.get(6)?,
+        is_active: row.get(7)?,
+        remarks: row.get(8)?,
+        stock: row.get(9)?,
+        issued: row.get(10)?,
+        stock_by_size: Vec::new(),
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+/// Stock of an item per size, leaving out sizes that were never stocked
+pub fn stock_by_size(conn: &rusqlite::Connection, item_id: i64) -> Result<Vec<SizeStock>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT m.size, SUM({STOCK_EFFECT}) FROM inventory_movements m
+            WHERE m.item_id = ?1
+            GROUP BY m.size
+            ORDER BY m.size"
+        ))
+        .map_err(|e| e.to_string())?;
+    let sizes = stmt
+        .query_map([item_id], |row| {
+            Ok(SizeStock {
+                size: row.get(0)?,
+                quantity: row.get(1)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(sizes)
+}
+
+pub fn get_item_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<InventoryItem>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ITEM_SELECT} WHERE i.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_item) {
+        Ok(mut item) => {
+            item.stock_by_size = stock_by_size(conn, id)?;
+            Ok(Some(item))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn map_movement(row: &Row) -> rusqlite::Result<InventoryMovement> {
+    Ok(InventoryMovement {
+        id: row.get(0)?,
+        item_id: row.get(1)?,
+        item_code: row.get(2)?,
+        item_name: row.get(3)?,
+        size: row.get(4)?,
+        movement_date: row.get(5)?,
+        movement: row.get(6)?,
+        quantity: row.get(7)?,
+        employee_id: row.get(8)?,
+        employee_name: row.get(9)?,
+        essid: row.get(10)?,
+        remarks: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+pub fn get_movement_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<InventoryMovement>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{MOVEMENT_SELECT} WHERE m.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_movement) {
+        Ok(movement) => Ok(Some(movement)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Sizes are required for sized items and kept in upper case, so that `xl`
+/// and `XL` are the same stock
+pub fn normalize_size(item: &InventoryItem, size: Option<&str>) -> Result<String, String> {
+    let size = size.unwrap_or_default().trim().to_uppercase();
+    if item.has_sizes != 0 && size.is_empty() {
+        return Err(format!("Size is required for {}", item.name));
+    }
+    if item.has_sizes == 0 && !size.is_empty() {
+        return Err(format!("{} does not come in sizes", item.name));
+    }
+    Ok(size)
+}
+
+pub fn stock_of(conn: &rusqlite::Connection, item_id: i64, size: &str) -> Result<i64, String> {
+    conn.query_row(
+        &format!(
+            "SELECT IFNULL(SUM({STOCK_EFFECT}), 0) FROM inventory_movements m
+            WHERE m.item_id = ?1 AND m.size = ?2"
+        ),
+        params![item_id, size],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn held_by(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    item_id: i64,
+    size: &str,
+) -> Result<i64, String> {
+    conn.query_row(
+        &format!(
+            "SELECT IFNULL(SUM({HELD_EFFECT}), 0) FROM inventory_movements m
+            WHERE m.employee_id = ?1 AND m.item_id = ?2 AND m.size = ?3"
+        ),
+        params![employee_id, item_id, size],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Items issued to the employee and not yet returned, valued at unit cost
+pub fn outstanding_items(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Vec<OutstandingItem>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT i.id, i.code, i.name, m.size, SUM({HELD_EFFECT}) AS held, i.unit_cost,
+                MAX(CASE m.movement WHEN 'issue' THEN m.movement_date END)
+            FROM inventory_movements m
+            JOIN inventory_items i ON i.id = m.item_id
+            WHERE m.employee_id = ?1
+            GROUP BY i.id, m.size
+            HAVING held > 0
+            ORDER BY i.name COLLATE NOCASE, m.size"
+        ))
+        .map_err(|e| e.to_string())?;
+    let items = stmt
+        .query_map([employee_id], |row| {
+            let quantity: i64 = row.get(4)?;
+            let unit_cost: f64 = row.get(5)?;
+            Ok(OutstandingItem {
+                item_id: row.get(0)?,
+                code: row.get(1)?,
+                name: row.get(2)?,
+                size: row.get(3)?,
+                quantity,
+                unit_cost,
+                amount: round2(quantity as f64 * unit_cost),
+                last_issued_on: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(items)
+}
+
+/// Close what the employee still holds as recovered in the full and final
+/// settlement, without returning it to stock
+pub fn close_outstanding_items(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    date: &str,
+) -> Result<(), String> {
+    for item in outstanding_items(conn, employee_id)? {
+        conn.execute(
+            "INSERT INTO inventory_movements (
+                item_id, size, movement_date, movement, quantity, employee_id, remarks
+            ) VALUES (?1, ?2, ?3, 'settled', ?4, ?5, 'Full and final settlement')",
+            params![item.item_id, item.size, date, item.quantity, employee_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+    use crate::settlements::helpers::outstanding_value;
+
+    fn movement(conn: &rusqlite::Connection, item_id: i64, size: &str, kind: &str, qty: i64) {
+        let employee_id = (kind != "receipt").then_some(1);
+        conn.execute(
+            "INSERT INTO inventory_movements (
+                item_id, size, movement_date, movement, quantity, employee_id
+            ) VALUES (?1, ?2, '2024-06-01', ?3, ?4, ?5)",
+            params![item_id, size, kind, qty, employee_id],
+        )
+        .unwrap();
+    }
+
+    /// Ravi was issued two large shirts and one medium, returned a large one,
+    /// and holds a baton
+    fn with_issues() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO inventory_items (code, name, has_sizes, unit_cost) VALUES
+                ('SHIRT', 'Shirt', 1, 450), ('BATON', 'Baton', 0, 250.5)",
+            [],
+        )
+        .unwrap();
+        movement(&conn, 1, "L", "receipt", 10);
+        movement(&conn, 1, "M", "receipt", 10);
+        movement(&conn, 2, "", "receipt", 5);
+        movement(&conn, 1, "L", "issue", 2);
+        movement(&conn, 1, "M", "issue", 1);
+        movement(&conn, 1, "L", "return", 1);
+        movement(&conn, 2, "", "issue", 1);
+        conn
+    }
+
+    #[test]
+    fn unreturned_items_are_valued_at_unit_cost() {
+        let conn = with_issues();
+
+        let items: Vec<_> = outstanding_items(&conn, 1)
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.code, i.size, i.quantity, i.amount))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("BATON".to_string(), String::new(), 1, 250.5),
+                ("SHIRT".to_string(), "L".to_string(), 1, 450.0),
+                ("SHIRT".to_string(), "M".to_string(), 1, 450.0),
+            ]
+        );
+        assert_eq!(outstanding_value(&conn, 1).unwrap(), 1150.5);
+        assert_eq!(stock_of(&conn, 1, "L").unwrap(), 9);
+        assert_eq!(held_by(&conn, 1, 1, "L").unwrap(), 1);
+    }
+
+    #[test]
+    fn settled_items_stop_being_held_without_returning_to_stock() {
+        let conn = with_issues();
+
+        close_outstanding_items(&conn, 1, "2024-07-31").unwrap();
+        assert!(outstanding_items(&conn, 1).unwrap().is_empty());
+        assert_eq!(outstanding_value(&conn, 1).unwrap(), 0.0);
+        assert_eq!(stock_of(&conn, 1, "L").unwrap(), 9);
+        assert_eq!(stock_of(&conn, 2, "").unwrap(), 4);
+    }
+
+    #[test]
+    fn sizes_are_upper_cased_and_required_for_sized_items() {
+        let conn = with_issues();
+        let shirt = get_item_by_id(&conn, 1).unwrap().unwrap();
+        let baton = get_item_by_id(&conn, 2).unwrap().unwrap();
+
+        assert_eq!(normalize_size(&shirt, Some(" xl ")), Ok("XL".to_string()));
+        assert!(normalize_size(&shirt, None).is_err());
+        assert_eq!(normalize_size(&baton, Some("")), Ok(String::new()));
+        assert!(normalize_size(&baton, Some("L")).is_err());
+    }
+}
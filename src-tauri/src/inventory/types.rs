@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeStock {
+    /// Empty for items without sizes
+    pub size: String,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryItem {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    /// Free text grouping such as uniform or equipment
+    pub category: Option<String>,
+    pub has_sizes: i32,
+    /// Recovered from the employee when an issued item is not returned
+    pub unit_cost: f64,
+    /// Stock at or below this level is reported for purchase
+    pub reorder_level: i64,
+    pub is_active: i32,
+    pub remarks: Option<String>,
+    pub stock: i64,
+    /// Quantity held by employees
+    pub issued: i64,
+    pub stock_by_size: Vec<SizeStock>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryItemInput {
+    pub code: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub has_sizes: Option<i32>,
+    pub unit_cost: f64,
+    pub reorder_level: Option<i64>,
+    pub is_active: Option<i32>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryMovement {
+    pub id: i64,
+    pub item_id: i64,
+    pub item_code: String,
+    pub item_name: String,
+    pub size: String,
+    pub movement_date: String,
+    /// receipt, issue, return, adjustment or settled
+    pub movement: String,
+    pub quantity: i64,
+    pub employee_id: Option<i64>,
+    pub employee_name: Option<String>,
+    pub essid: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryMovementInput {
+    pub item_id: i64,
+    pub size: Option<String>,
+    pub movement_date: String,
+    pub movement: String,
+    /// Negative only for adjustments that reduce the stock
+    pub quantity: i64,
+    /// Required for issues and returns
+    pub employee_id: Option<i64>,
+    pub remarks: Option<String>,
+}
+
+/// Items issued to an employee and not returned
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutstandingItem {
+    pub item_id: i64,
+    pub code: String,
+    pub name: String,
+    pub size: String,
+    pub quantity: i64,
+    pub unit_cost: f64,
+    pub amount: f64,
+    pub last_issued_on: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowStockItem {
+    pub item_id: i64,
+    pub code: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub stock: i64,
+    pub reorder_level: i64,
+    pub stock_by_size: Vec<SizeStock>,
+}
@@ -0,0 +1,419 @@
+use super::helpers::{
+    basis_label, build_invoice, contract_rates, draw_invoice, financial_year, format_number,
+    get_contract_by_id, get_invoice_by_id, insert_contract_rates, invoice_totals, map_contract,
+    map_invoice, next_invoice_number, validate_contract, CONTRACT_SELECT, INVOICE_SELECT, SAC_CODE,
+};
+use super::types::{ClientContract, ClientContractInput, Invoice, InvoicePreview};
+use crate::clients::helpers::get_client_by_id;
+use crate::company::helpers::load_company_profile;
+use crate::csv::write_csv;
+use crate::dates::{format_date, month_bounds, parse_date, today};
+use crate::db;
+use crate::payslips::helpers::month_title;
+use crate::pdf::{Orientation, PdfDocument};
+use crate::state::AppState;
+use rusqlite::{params, OptionalExtension, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_client_contracts(
+    _state: State<AppState>,
+    client_id: Option<i64>,
+) -> Result<Vec<ClientContract>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{CONTRACT_SELECT} WHERE (?1 IS NULL OR k.client_id = ?1)
+            ORDER BY c.name COLLATE NOCASE, k.start_date DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut contracts = stmt
+        .query_map(params![client_id], map_contract)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for contract in &mut contracts {
+        contract.rates = contract_rates(&conn, contract.id)?;
+    }
+
+    Ok(contracts)
+}
+
+#[tauri::command]
+pub fn create_client_contract(
+    _state: State<AppState>,
+    contract: ClientContractInput,
+) -> Result<ClientContract, String> {
+    let mut conn = db::get_connection()?;
+
+    let (start, end) = validate_contract(&conn, &contract, None)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO client_contracts (
+            client_id, reference, start_date, end_date, service_charge_rate, gst_rate, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            contract.client_id,
+            contract.reference,
+            start,
+            end,
+            contract.service_charge_rate.unwrap_or(0.0),
+            contract.gst_rate.unwrap_or(18.0),
+            contract.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    insert_contract_rates(&tx, id, &contract)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(contract)) = get_contract_by_id(&conn, id) {
+        Ok(contract)
+    } else {
+        Err("Failed to retrieve contract".to_string())
+    }
+}
+
+/// Update a contract and replace its rates. Invoices already issued keep the
+/// amounts they were issued with.
+#[tauri::command]
+pub fn update_client_contract(
+    _state: State<AppState>,
+    id: i64,
+    contract: ClientContractInput,
+) -> Result<ClientContract, String> {
+    let mut conn = db::get_connection()?;
+
+    get_contract_by_id(&conn, id)?.ok_or("Contract not found")?;
+    let (start, end) = validate_contract(&conn, &contract, Some(id))?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE client_contracts SET
+            client_id = ?1, reference = ?2, start_date = ?3, end_date = ?4,
+            service_charge_rate = ?5, gst_rate = ?6, remarks = ?7,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            contract.client_id,
+            contract.reference,
+            start,
+            end,
+            contract.service_charge_rate.unwrap_or(0.0),
+            contract.gst_rate.unwrap_or(18.0),
+            contract.remarks,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM contract_rates WHERE contract_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    insert_contract_rates(&tx, id, &contract)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(contract)) = get_contract_by_id(&conn, id) {
+        Ok(contract)
+    } else {
+        Err("Failed to retrieve contract".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_client_contract(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM client_contracts WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Work out a client's invoice for a month without issuing it, along with
+/// the duties no contract rate covers
+#[tauri::command]
+pub fn preview_invoice(
+    _state: State<AppState>,
+    client_id: i64,
+    month: String,
+) -> Result<InvoicePreview, String> {
+    let conn = db::get_connection()?;
+    build_invoice(&conn, client_id, &month)
+}
+
+/// Issue a client's invoice for a month under the next number of the
+/// financial year of the invoice date. A month is invoiced once unless its
+/// invoice is cancelled.
+#[tauri::command]
+pub fn issue_invoice(
+    _state: State<AppState>,
+    client_id: i64,
+    month: String,
+    invoice_date: Option<String>,
+) -> Result<Invoice, String> {
+    let mut conn = db::get_connection()?;
+
+    let (first, _) = month_bounds(&month)?;
+    let invoice_date = match invoice_date.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date(date, "invoice date")?,
+        None => today(),
+    };
+    if invoice_date < first {
+        return Err("Invoice date cannot be before the month being billed".to_string());
+    }
+
+    let preview = build_invoice(&conn, client_id, &month)?;
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT invoice_number FROM invoices
+            WHERE client_id = ?1 AND month = ?2 AND status = 'issued'",
+            params![client_id, preview.month],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(number) = existing {
+        return Err(format!(
+            "Invoice {number} is already issued to {} for {}",
+            preview.client_name,
+            month_title(&preview.month)
+        ));
+    }
+    if preview.lines.is_empty() {
+        return Err(format!(
+            "Nothing to bill {} for {}",
+            preview.client_name,
+            month_title(&preview.month)
+        ));
+    }
+    let client = get_client_by_id(&conn, client_id)?.ok_or("Client not found")?;
+    let supplier = load_company_profile(&conn)?;
+
+    let financial_year = financial_year(invoice_date);
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let (sequence, invoice_number) = next_invoice_number(&tx, &financial_year)?;
+
+    tx.execute(
+        "INSERT INTO invoices (
+            invoice_number, financial_year, sequence, client_id, client_name, client_address,
+            client_gstin, month, invoice_date, subtotal, service_charge_rate, service_charge,
+            taxable_value, gst_rate, cgst, sgst, igst, total, supplier_name, supplier_address,
+            supplier_phone, supplier_email, supplier_pf_code, supplier_esic_code,
+            supplier_gstin, supplier_pan
+        ) VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+            ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26
+        )",
+        params![
+            invoice_number,
+            financial_year,
+            sequence,
+            client_id,
+            client.name,
+            client.address,
+            client.gstin,
+            preview.month,
+            format_date(invoice_date),
+            preview.subtotal,
+            preview.service_charge_rate,
+            preview.service_charge,
+            preview.taxable_value,
+            preview.gst_rate,
+            preview.cgst,
+            preview.sgst,
+            preview.igst,
+            preview.total,
+            supplier.name,
+            supplier.address,
+            supplier.phone,
+            supplier.email,
+            supplier.pf_code,
+            supplier.esic_code,
+            supplier.gstin,
+            supplier.pan,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+
+    for line in &preview.lines {
+        tx.execute(
+            "INSERT INTO invoice_lines (
+                invoice_id, site_id, site_name, role, billing_basis, quantity, rate, amount,
+                description
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                id,
+                line.site_id,
+                line.site_name,
+                line.role,
+                line.billing_basis,
+                line.quantity,
+                line.rate,
+                line.amount,
+                line.description,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(invoice)) = get_invoice_by_id(&conn, id) {
+        Ok(invoice)
+    } else {
+        Err("Failed to retrieve invoice".to_string())
+    }
+}
+
+/// Invoices without their lines, newest first
+#[tauri::command]
+pub fn get_invoices(
+    _state: State<AppState>,
+    client_id: Option<i64>,
+    month: Option<String>,
+    status: Option<String>,
+) -> Result<Vec<Invoice>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{INVOICE_SELECT}
+            WHERE (?1 IS NULL OR client_id = ?1) AND (?2 IS NULL OR month = ?2)
+              AND (?3 IS NULL OR status = ?3)
+            ORDER BY invoice_date DESC, id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let invoices = stmt
+        .query_map(params![client_id, month, status], map_invoice)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(invoices)
+}
+
+#[tauri::command]
+pub fn get_invoice(_state: State<AppState>, id: i64) -> Result<Option<Invoice>, String> {
+    let conn = db::get_connection()?;
+    get_invoice_by_id(&conn, id)
+}
+
+/// Cancel an issued invoice. It keeps its number and amounts, and the month
+/// can then be invoiced again under a new number.
+#[tauri::command]
+pub fn cancel_invoice(_state: State<AppState>, id: i64, reason: String) -> Result<Invoice, String> {
+    let conn = db::get_connection()?;
+
+    let invoice = get_invoice_by_id(&conn, id)?.ok_or("Invoice not found")?;
+    if invoice.status == "cancelled" {
+        return Err(format!(
+            "Invoice {} is already cancelled",
+            invoice.invoice_number
+        ));
+    }
+    if reason.trim().is_empty() {
+        return Err("A reason is required to cancel an invoice".to_string());
+    }
+
+    conn.execute(
+        "UPDATE invoices SET status = 'cancelled', cancelled_on = ?1, cancel_reason = ?2
+        WHERE id = ?3",
+        params![format_date(today()), reason.trim(), id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(invoice)) = get_invoice_by_id(&conn, id) {
+        Ok(invoice)
+    } else {
+        Err("Failed to retrieve invoice".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn export_invoice_pdf(
+    _state: State<AppState>,
+    id: i64,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let invoice = get_invoice_by_id(&conn, id)?.ok_or("Invoice not found")?;
+
+    let mut doc = PdfDocument::new(
+        &format!("Invoice {}", invoice.invoice_number),
+        Orientation::Portrait,
+    );
+    draw_invoice(&mut doc, &invoice.supplier, &invoice);
+
+    doc.save(&PathBuf::from(&export_path))
+}
+
+/// One row per invoice line followed by the totals, for importing into
+/// accounting software
+#[tauri::command]
+pub fn export_invoice_csv(
+    _state: State<AppState>,
+    id: i64,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let invoice = get_invoice_by_id(&conn, id)?.ok_or("Invoice not found")?;
+
+    let row = |description: String, basis: &str, quantity: String, rate: String, amount: f64| {
+        vec![
+            invoice.invoice_number.clone(),
+            invoice.invoice_date.clone(),
+            invoice.client_name.clone(),
+            invoice.client_gstin.clone().unwrap_or_default(),
+            SAC_CODE.to_string(),
+            description,
+            basis.to_string(),
+            quantity,
+            rate,
+            format!("{amount:.2}"),
+        ]
+    };
+
+    let mut rows: Vec<Vec<String>> = invoice
+        .lines
+        .iter()
+        .map(|line| {
+            row(
+                line.description.clone(),
+                basis_label(&line.billing_basis),
+                format_number(line.quantity),
+                format!("{:.2}", line.rate),
+                line.amount,
+            )
+        })
+        .collect();
+
+    let mut totals = invoice_totals(&invoice);
+    totals.push(("Invoice total".to_string(), invoice.total));
+    for (label, amount) in totals {
+        rows.push(row(label, "", String::new(), String::new(), amount));
+    }
+
+    write_csv(
+        &PathBuf::from(&export_path),
+        &[
+            "Invoice No",
+            "Invoice Date",
+            "Client",
+            "Client GSTIN",
+            "SAC",
+            "Description",
+            "Basis",
+            "Quantity",
+            "Rate",
+            "Amount",
+        ],
+        &rows,
+    )
+}
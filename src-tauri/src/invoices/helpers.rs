@@ -0,0 +1,1018 @@
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, OptionalExtension, Row};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::types::{
+    ClientContract, ClientContractInput, ContractRate, Invoice, InvoiceLine, InvoicePreview,
+    UnbilledDuty,
+};
+use crate::clients::helpers::get_client_by_id;
+use crate::company::helpers::{draw_letterhead, load_company_profile};
+use crate::company::types::CompanyProfile;
+use crate::dates::{format_date, month_bounds, parse_date};
+use crate::payroll::helpers::round2;
+use crate::payslips::helpers::{amount_in_words, format_amount, month_title};
+use crate::pdf::{text_width, Align, Font, Page, PdfDocument, MARGIN};
+
+pub const CONTRACT_SELECT: &str = "SELECT k.id, k.client_id, c.name, k.reference, k.start_date,
+        k.end_date, k.service_charge_rate, k.gst_rate, k.remarks, k.created_at, k.updated_at
+    FROM client_contracts k
+    JOIN clients c ON c.id = k.client_id";
+
+pub const INVOICE_SELECT: &str = "SELECT id, invoice_number, financial_year, client_id,
+        client_name, client_address, client_gstin, month, invoice_date, subtotal,
+        service_charge_rate, service_charge, taxable_value, gst_rate, cgst, sgst, igst, total,
+        status, cancelled_on, cancel_reason, created_at, supplier_name, supplier_address,
+        supplier_phone, supplier_email, supplier_pf_code, supplier_esic_code, supplier_gstin,
+        supplier_pan
+    FROM invoices";
+
+/// Services Accounting Code of security services
+pub const SAC_CODE: &str = "998525";
+
+pub const BILLING_BASES: [&str; 2] = ["guard_day", "post_month"];
+
+pub fn map_contract(row: &Row) -> rusqlite::Result<ClientContract> {
+    Ok(ClientContract {
+        id: row.get(0)?,
+        client_id: row.get(1)?,
+        client_name: row.get(2)?,
+        reference: row.get(3)?,
+        start_date: row.get(4)?,
+        end_date: row.get(5)?,
+        service_charge_rate: row.get(6)?,
+        gst_rate: row.get(7)?,
+        remarks: row.get(8)?,
+        rates: Vec::new(),
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn map_invoice(row: &Row) -> rusqlite::Result<Invoice> {
+    Ok(Invoice {
+        id: row.get(0)?,
+        invoice_number: row.get(1)?,
+        financial_year: row.get(2)?,
+        client_id: row.get(3)?,
+        client_name: row.get(4)?,
+        client_address: row.get(5)?,
+        client_gstin: row.get(6)?,
+        month: row.get(7)?,
+        invoice_date: row.get(8)?,
+        subtotal: row.get(9)?,
+        service_charge_rate: row.get(10)?,
+        service_charge: row.get(11)?,
+        taxable_value: row.get(12)?,
+        gst_rate: row.get(13)?,
+        cgst: row.get(14)?,
+        sgst: row.get(15)?,
+        igst: row.get(16)?,
+        total: row.get(17)?,
+        status: row.get(18)?,
+        cancelled_on: row.get(19)?,
+        cancel_reason: row.get(20)?,
+        lines: Vec::new(),
+        created_at: row.get(21)?,
+        supplier: CompanyProfile {
+            name: row.get(22)?,
+            address: row.get(23)?,
+            phone: row.get(24)?,
+            email: row.get(25)?,
+            pf_code: row.get(26)?,
+            esic_code: row.get(27)?,
+            gstin: row.get(28)?,
+            pan: row.get(29)?,
+        },
+    })
+}
+
+pub fn contract_rates(
+    conn: &rusqlite::Connection,
+    contract_id: i64,
+) -> Result<Vec<ContractRate>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.id, r.site_id, s.name, r.role, r.billing_basis, r.rate, r.posts
+            FROM contract_rates r
+            LEFT JOIN sites s ON s.id = r.site_id
+            WHERE r.contract_id = ?1
+            ORDER BY s.name COLLATE NOCASE, r.role COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rates = stmt
+        .query_map([contract_id], |row| {
+            Ok(ContractRate {
+                id: row.get(0)?,
+                site_id: row.get(1)?,
+                site_name: row.get(2)?,
+                role: row.get(3)?,
+                billing_basis: row.get(4)?,
+                rate: row.get(5)?,
+                posts: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rates)
+}
+
+pub fn get_contract_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<ClientContract>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{CONTRACT_SELECT} WHERE k.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_contract) {
+        Ok(mut contract) => {
+            contract.rates = contract_rates(conn, contract.id)?;
+            Ok(Some(contract))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn invoice_lines(
+    conn: &rusqlite::Connection,
+    invoice_id: i64,
+) -> Result<Vec<InvoiceLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT site_id, site_name, role, billing_basis, quantity, rate, amount, description
+            FROM invoice_lines WHERE invoice_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let lines = stmt
+        .query_map([invoice_id], |row| {
+            Ok(InvoiceLine {
+                site_id: row.get(0)?,
+                site_name: row.get(1)?,
+                role: row.get(2)?,
+                billing_basis: row.get(3)?,
+                quantity: row.get(4)?,
+                rate: row.get(5)?,
+                amount: row.get(6)?,
+                description: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(lines)
+}
+
+pub fn get_invoice_by_id(conn: &rusqlite::Connection, id: i64) -> Result<Option<Invoice>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{INVOICE_SELECT} WHERE id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_invoice) {
+        Ok(mut invoice) => {
+            invoice.lines = invoice_lines(conn, invoice.id)?;
+            Ok(Some(invoice))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Validate a contract and return its start and end dates. A client has one
+/// contract in force at a time, so a contract may not overlap another one of
+/// the same client.
+pub fn validate_contract(
+    conn: &rusqlite::Connection,
+    contract: &ClientContractInput,
+    exclude_id: Option<i64>,
+) -> Result<(String, Option<String>), String> {
+    get_client_by_id(conn, contract.client_id)?.ok_or("Client not found")?;
+
+    let start = parse_date(&contract.start_date, "start date")?;
+    let end = contract
+        .end_date
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| parse_date(d, "end date"))
+        .transpose()?;
+    if end.is_some_and(|end| end < start) {
+        return Err("End date cannot be before the start date".to_string());
+    }
+
+    for (label, rate) in [
+        ("Service charge", contract.service_charge_rate),
+        ("GST rate", contract.gst_rate),
+    ] {
+        if rate.is_some_and(|r| !r.is_finite() || !(0.0..=100.0).contains(&r)) {
+            return Err(format!("{label} must be between 0 and 100 percent"));
+        }
+    }
+
+    let (start, end) = (format_date(start), end.map(format_date));
+    let overlapping: Option<String> = conn
+        .query_row(
+            "SELECT start_date FROM client_contracts
+            WHERE client_id = ?1 AND id <> ?2
+              AND start_date <= IFNULL(?4, '9999-12-31')
+              AND IFNULL(end_date, '9999-12-31') >= ?3
+            LIMIT 1",
+            params![contract.client_id, exclude_id.unwrap_or(0), start, end],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(other_start) = overlapping {
+        return Err(format!(
+            "The client already has a contract starting {other_start} that overlaps these dates"
+        ));
+    }
+
+    if contract.rates.is_empty() {
+        return Err("Add at least one billing rate".to_string());
+    }
+    let mut seen = BTreeSet::new();
+    for rate in &contract.rates {
+        let role = rate.role.trim();
+        if role.is_empty() {
+            return Err("Every rate needs a role".to_string());
+        }
+        if !BILLING_BASES.contains(&rate.billing_basis.as_str()) {
+            return Err(format!("Invalid billing basis '{}'", rate.billing_basis));
+        }
+        if !rate.rate.is_finite() || rate.rate <= 0.0 {
+            return Err(format!("Rate for {role} must be greater than zero"));
+        }
+        if rate.posts.is_some_and(|p| p <= 0) {
+            return Err(format!("Posts for {role} must be at least one"));
+        }
+        if let Some(site_id) = rate.site_id {
+            let client_id: Option<i64> = conn
+                .query_row(
+                    "SELECT client_id FROM sites WHERE id = ?1",
+                    [site_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if client_id != Some(contract.client_id) {
+                return Err(format!(
+                    "Site of the {role} rate does not belong to the client"
+                ));
+            }
+        }
+        if !seen.insert((rate.site_id, role.to_lowercase())) {
+            return Err(format!("{role} has more than one rate for the same site"));
+        }
+    }
+
+    Ok((start, end))
+}
+
+pub fn insert_contract_rates(
+    conn: &rusqlite::Connection,
+    contract_id: i64,
+    contract: &ClientContractInput,
+) -> Result<(), String> {
+    for rate in &contract.rates {
+        conn.execute(
+            "INSERT INTO contract_rates (contract_id, site_id, role, billing_basis, rate, posts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                contract_id,
+                rate.site_id,
+                rate.role.trim(),
+                rate.billing_basis,
+                rate.rate,
+                rate.posts.unwrap_or(1),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Indian financial year of a date, e.g. `2024-25` for dates from April 2024
+/// to March 2025
+pub fn financial_year(date: NaiveDate) -> String {
+    let start = if date.month() >= 4 {
+        date.year()
+    } else {
+        date.year() - 1
+    };
+    format!("{start}-{:02}", (start + 1) % 100)
+}
+
+/// Sequence and number of the next invoice of a financial year, e.g.
+/// `INV/2024-25/0007`. Cancelled invoices keep their numbers, so the series
+/// has no gaps.
+pub fn next_invoice_number(
+    conn: &rusqlite::Connection,
+    financial_year: &str,
+) -> Result<(i64, String), String> {
+    let sequence: i64 = conn
+        .query_row(
+            "SELECT IFNULL(MAX(sequence), 0) + 1 FROM invoices WHERE financial_year = ?1",
+            [financial_year],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok((sequence, format!("INV/{financial_year}/{sequence:04}")))
+}
+
+/// Whether GST is charged as IGST, which is the case when the state codes
+/// that start both GSTINs differ
+fn is_inter_state(company_gstin: Option<&str>, client_gstin: Option<&str>) -> bool {
+    let state = |gstin: Option<&str>| {
+        gstin
+            .map(str::trim)
+            .and_then(|g| g.get(..2))
+            .map(str::to_string)
+    };
+    match (state(company_gstin), state(client_gstin)) {
+        (Some(company), Some(client)) => company != client,
+        _ => false,
+    }
+}
+
+/// Duties of one employee in one role at a site of a client
+struct ClientDuty {
+    employee_id: i64,
+    site_id: i64,
+    site_name: String,
+    role: Option<String>,
+    duties: f64,
+}
+
+/// Contracts of a client in force at any time between two dates
+fn contracts_between(
+    conn: &rusqlite::Connection,
+    client_id: i64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ClientContract>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{CONTRACT_SELECT} WHERE k.client_id = ?1 AND k.start_date <= ?3
+              AND (k.end_date IS NULL OR k.end_date >= ?2)
+            ORDER BY k.start_date"
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut contracts = stmt
+        .query_map(
+            params![client_id, format_date(from), format_date(to)],
+            map_contract,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for contract in &mut contracts {
+        contract.rates = contract_rates(conn, contract.id)?;
+    }
+    Ok(contracts)
+}
+
+/// Duties done at the sites of a client between two dates, one row per
+/// employee, site and role with the number of duties. Present days count one
+/// duty each plus their OT shifts. Attendance without a site counts at the
+/// site the employee was deployed to that day, and the role is that of the
+/// deployment at the site, falling back to the employee's job post.
+fn client_duties(
+    conn: &rusqlite::Connection,
+    client_id: i64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ClientDuty>, String> {
+    let mut stmt = conn
+        .prepare(
+            "WITH duty AS (
+                SELECT a.employee_id, a.attendance_date, a.ot_shifts,
+                    IFNULL(a.site_id, (
+                        SELECT d.site_id FROM deployments d
+                        WHERE d.employee_id = a.employee_id
+                          AND d.start_date <= a.attendance_date
+                          AND (d.end_date IS NULL OR d.end_date >= a.attendance_date)
+                        ORDER BY d.start_date DESC LIMIT 1
+                    )) AS site_id
+                FROM attendance a
+                WHERE a.status = 'present' AND a.attendance_date BETWEEN ?2 AND ?3
+            )
+            SELECT duty.employee_id, s.id, s.name,
+                IFNULL(NULLIF(TRIM((
+                    SELECT d.role FROM deployments d
+                    WHERE d.employee_id = duty.employee_id AND d.site_id = duty.site_id
+                      AND d.start_date <= duty.attendance_date
+                      AND (d.end_date IS NULL OR d.end_date >= duty.attendance_date)
+                    ORDER BY d.start_date DESC LIMIT 1
+                )), ''), NULLIF(TRIM(e.job_post), '')) AS role,
+                SUM(1 + duty.ot_shifts)
+            FROM duty
+            JOIN sites s ON s.id = duty.site_id
+            JOIN employees e ON e.id = duty.employee_id
+            WHERE s.client_id = ?1
+            GROUP BY 1, 2, 3, 4",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let duties = stmt
+        .query_map(
+            params![client_id, format_date(from), format_date(to)],
+            |row| {
+                Ok(ClientDuty {
+                    employee_id: row.get(0)?,
+                    site_id: row.get(1)?,
+                    site_name: row.get(2)?,
+                    role: row.get(3)?,
+                    duties: row.get(4)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(duties)
+}
+
+/// Rate of a role at a site, preferring one set for the site over one set for
+/// every site of the client
+fn find_rate<'a>(rates: &'a [ContractRate], site_id: i64, role: &str) -> Option<&'a ContractRate> {
+    let matches = |rate: &&ContractRate| rate.role.eq_ignore_ascii_case(role);
+    rates
+        .iter()
+        .filter(matches)
+        .find(|rate| rate.site_id == Some(site_id))
+        .or_else(|| {
+            rates
+                .iter()
+                .filter(matches)
+                .find(|rate| rate.site_id.is_none())
+        })
+}
+
+/// Add `quantity` to the line for the same site, role, basis and rate, or
+/// start a new one
+fn add_to_line(lines: &mut Vec<InvoiceLine>, line: InvoiceLine) {
+    match lines.iter_mut().find(|l| {
+        l.site_id == line.site_id
+            && l.role.eq_ignore_ascii_case(&line.role)
+            && l.billing_basis == line.billing_basis
+            && l.rate == line.rate
+    }) {
+        Some(existing) => existing.quantity += line.quantity,
+        None => lines.push(line),
+    }
+}
+
+/// Work out the invoice of a client for a month from its contracts and the
+/// attendance at its sites. Guard day rates bill the duties done within the
+/// contract period; post rates bill their posts prorated to the days of the
+/// month the contract was in force.
+pub fn build_invoice(
+    conn: &rusqlite::Connection,
+    client_id: i64,
+    month: &str,
+) -> Result<InvoicePreview, String> {
+    let client = get_client_by_id(conn, client_id)?.ok_or("Client not found")?;
+    let (first, last) = month_bounds(month)?;
+    let month = first.format("%Y-%m").to_string();
+
+    let contracts = contracts_between(conn, client_id, first, last)?;
+    let Some(contract) = contracts.first() else {
+        return Err(format!(
+            "{} has no contract in force during {}",
+            client.name,
+            month_title(&month)
+        ));
+    };
+    let (service_charge_rate, gst_rate) = (contract.service_charge_rate, contract.gst_rate);
+    if contracts
+        .iter()
+        .any(|c| c.service_charge_rate != service_charge_rate || c.gst_rate != gst_rate)
+    {
+        return Err(format!(
+            "Contracts of {} in force during {} have different service charge or GST rates",
+            client.name,
+            month_title(&month)
+        ));
+    }
+
+    let days_in_month = (last - first).num_days() + 1;
+    let mut lines = Vec::new();
+    // (site, site name, role) -> (employees, duties)
+    let mut unbilled: BTreeMap<_, (BTreeSet<i64>, f64)> = BTreeMap::new();
+
+    for contract in &contracts {
+        let from = parse_date(&contract.start_date, "contract start date")?.max(first);
+        let to = match contract.end_date.as_deref() {
+            Some(end) => parse_date(end, "contract end date")?.min(last),
+            None => last,
+        };
+
+        for duty in client_duties(conn, client_id, from, to)? {
+            let rate = duty
+                .role
+                .as_deref()
+                .and_then(|role| find_rate(&contract.rates, duty.site_id, role));
+            match rate {
+                Some(rate) if rate.billing_basis == "guard_day" => add_to_line(
+                    &mut lines,
+                    InvoiceLine {
+                        site_id: Some(duty.site_id),
+                        site_name: Some(duty.site_name.clone()),
+                        role: rate.role.clone(),
+                        billing_basis: rate.billing_basis.clone(),
+                        quantity: duty.duties,
+                        rate: rate.rate,
+                        amount: 0.0,
+                        description: format!("{} at {}", rate.role, duty.site_name),
+                    },
+                ),
+                // Covered by the monthly charge of the post
+                Some(_) => {}
+                None => {
+                    let entry = unbilled
+                        .entry((duty.site_id, duty.site_name, duty.role))
+                        .or_default();
+                    entry.0.insert(duty.employee_id);
+                    entry.1 += duty.duties;
+                }
+            }
+        }
+
+        let days = (to - from).num_days() + 1;
+        for rate in contract
+            .rates
+            .iter()
+            .filter(|r| r.billing_basis == "post_month")
+        {
+            let site = rate.site_name.as_deref().unwrap_or("all sites");
+            let mut description = format!("{} at {site}, {} post(s)", rate.role, rate.posts);
+            if days < days_in_month {
+                description.push_str(&format!(" for {days} of {days_in_month} days"));
+            }
+            add_to_line(
+                &mut lines,
+                InvoiceLine {
+                    site_id: rate.site_id,
+                    site_name: rate.site_name.clone(),
+                    role: rate.role.clone(),
+                    billing_basis: rate.billing_basis.clone(),
+                    quantity: rate.posts as f64 * days as f64 / days_in_month as f64,
+                    rate: rate.rate,
+                    amount: 0.0,
+                    description,
+                },
+            );
+        }
+    }
+
+    for line in &mut lines {
+        line.amount = round2(line.quantity * line.rate);
+        line.quantity = round2(line.quantity);
+    }
+    lines.sort_by(|a, b| {
+        a.site_name
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase()
+            .cmp(&b.site_name.as_deref().unwrap_or_default().to_lowercase())
+            .then_with(|| a.role.to_lowercase().cmp(&b.role.to_lowercase()))
+    });
+
+    let subtotal = round2(lines.iter().map(|l| l.amount).sum());
+    let service_charge = round2(subtotal * service_charge_rate / 100.0);
+    let taxable_value = round2(subtotal + service_charge);
+    let profile = load_company_profile(conn)?;
+    let (cgst, sgst, igst) = if is_inter_state(profile.gstin.as_deref(), client.gstin.as_deref()) {
+        (0.0, 0.0, round2(taxable_value * gst_rate / 100.0))
+    } else {
+        let half = round2(taxable_value * gst_rate / 200.0);
+        (half, half, 0.0)
+    };
+
+    Ok(InvoicePreview {
+        client_id,
+        client_name: client.name,
+        month,
+        lines,
+        subtotal,
+        service_charge_rate,
+        service_charge,
+        taxable_value,
+        gst_rate,
+        cgst,
+        sgst,
+        igst,
+        total: round2(taxable_value + cgst + sgst + igst),
+        unbilled: unbilled
+            .into_iter()
+            .map(
+                |((site_id, site_name, role), (employees, duties))| UnbilledDuty {
+                    site_id,
+                    site_name,
+                    role,
+                    employees: employees.len() as i64,
+                    duties,
+                },
+            )
+            .collect(),
+    })
+}
+
+/// Quantity or percentage without trailing zeros
+pub fn format_number(value: f64) -> String {
+    let formatted = format!("{value:.2}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+pub fn basis_label(basis: &str) -> &'static str {
+    match basis {
+        "post_month" => "Per post/month",
+        _ => "Per guard day",
+    }
+}
+
+/// Subtotal, service charge, taxable value and tax of an invoice with their
+/// labels, in the order they are printed
+pub fn invoice_totals(invoice: &Invoice) -> Vec<(String, f64)> {
+    let mut totals = vec![
+        ("Subtotal".to_string(), invoice.subtotal),
+        (
+            format!(
+                "Service charge @ {}%",
+                format_number(invoice.service_charge_rate)
+            ),
+            invoice.service_charge,
+        ),
+        ("Taxable value".to_string(), invoice.taxable_value),
+    ];
+    if invoice.igst > 0.0 {
+        totals.push((
+            format!("IGST @ {}%", format_number(invoice.gst_rate)),
+            invoice.igst,
+        ));
+    } else {
+        let half_rate = format_number(invoice.gst_rate / 2.0);
+        totals.push((format!("CGST @ {half_rate}%"), invoice.cgst));
+        totals.push((format!("SGST @ {half_rate}%"), invoice.sgst));
+    }
+    totals
+}
+
+/// Add the pages of a tax invoice to `doc`
+pub fn draw_invoice(doc: &mut PdfDocument, profile: &CompanyProfile, invoice: &Invoice) {
+    let width = doc.width;
+    let height = doc.height;
+    let printable_width = width - 2.0 * MARGIN;
+    let mut page = doc.add_page();
+
+    let mut y = draw_letterhead(page, width, profile);
+
+    let title = if invoice.status == "cancelled" {
+        "Tax Invoice (Cancelled)"
+    } else {
+        "Tax Invoice"
+    };
+    y += 8.0;
+    page.text(
+        (width - text_width(title, Font::Bold, 12.0)) / 2.0,
+        y,
+        Font::Bold,
+        12.0,
+        title,
+    );
+    y += 16.0;
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+
+    let top = y;
+    y += 13.0;
+    page.text(MARGIN + 4.0, y, Font::Regular, 8.5, "Bill to");
+    y += 12.0;
+    page.text_in_box(
+        MARGIN + 4.0,
+        y,
+        printable_width / 2.0 - 8.0,
+        Align::Left,
+        Font::Bold,
+        10.0,
+        &invoice.client_name,
+    );
+    for line in invoice
+        .client_address
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        y += 11.0;
+        page.text_in_box(
+            MARGIN + 4.0,
+            y,
+            printable_width / 2.0 - 8.0,
+            Align::Left,
+            Font::Regular,
+            9.0,
+            line,
+        );
+    }
+    if let Some(gstin) = invoice.client_gstin.as_deref() {
+        y += 11.0;
+        page.text(
+            MARGIN + 4.0,
+            y,
+            Font::Regular,
+            9.0,
+            &format!("GSTIN: {gstin}"),
+        );
+    }
+
+    let details = [
+        ("Invoice no.", invoice.invoice_number.clone()),
+        ("Invoice date", invoice.invoice_date.clone()),
+        ("Billing period", month_title(&invoice.month)),
+        ("SAC", format!("{SAC_CODE} (Security services)")),
+    ];
+    let mut detail_y = top;
+    for (label, value) in details {
+        detail_y += 13.0;
+        page.text(width / 2.0 + 10.0, detail_y, Font::Regular, 9.0, label);
+        page.text_in_box(
+            width / 2.0 + 90.0,
+            detail_y,
+            width / 2.0 - MARGIN - 94.0,
+            Align::Left,
+            Font::Bold,
+            9.0,
+            &value,
+        );
+    }
+    y = y.max(detail_y) + 10.0;
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+    y += 12.0;
+
+    // (title, width, alignment) with the description taking the rest
+    let columns = [
+        ("#", 22.0, Align::Right),
+        ("Description", 0.0, Align::Left),
+        ("Basis", 80.0, Align::Left),
+        ("Quantity", 55.0, Align::Right),
+        ("Rate (Rs.)", 70.0, Align::Right),
+        ("Amount (Rs.)", 85.0, Align::Right),
+    ];
+    let fixed: f32 = columns.iter().map(|c| c.1).sum();
+    let column_width = |w: f32| if w == 0.0 { printable_width - fixed } else { w };
+    let row_height = 15.0;
+
+    let draw_header = |page: &mut Page, y: f32| {
+        page.fill_rect(MARGIN, y, printable_width, row_height, 0.88);
+        let mut x = MARGIN;
+        for (title, w, align) in columns {
+            let w = column_width(w);
+            page.text_in_box(x + 3.0, y + 10.5, w - 6.0, align, Font::Bold, 8.5, title);
+            x += w;
+        }
+        y + row_height
+    };
+    y = draw_header(page, y);
+
+    for (i, line) in invoice.lines.iter().enumerate() {
+        if y + row_height > height - MARGIN - 20.0 {
+            page = doc.add_page();
+            y = draw_header(page, MARGIN + 14.0);
+        }
+        let values = [
+            (i + 1).to_string(),
+            line.description.clone(),
+            basis_label(&line.billing_basis).to_string(),
+            format_number(line.quantity),
+            format_amount(line.rate),
+            format_amount(line.amount),
+        ];
+        let mut x = MARGIN;
+        for ((_, w, align), value) in columns.iter().zip(values) {
+            let w = column_width(*w);
+            page.text_in_box(
+                x + 3.0,
+                y + 10.5,
+                w - 6.0,
+                *align,
+                Font::Regular,
+                8.5,
+                &value,
+            );
+            x += w;
+        }
+        y += row_height;
+    }
+    page.line(MARGIN, y, width - MARGIN, y, 0.6);
+    y += 6.0;
+
+    let totals = invoice_totals(invoice);
+    if y + row_height * (totals.len() as f32 + 6.0) > height - MARGIN {
+        page = doc.add_page();
+        y = MARGIN + 14.0;
+    }
+    let amount_width = 110.0;
+    for (label, amount) in totals {
+        y += 13.0;
+        page.text_in_box(
+            width - MARGIN - amount_width - 160.0,
+            y,
+            156.0,
+            Align::Right,
+            Font::Regular,
+            9.0,
+            &label,
+        );
+        page.text_in_box(
+            width - MARGIN - amount_width,
+            y,
+            amount_width - 4.0,
+            Align::Right,
+            Font::Regular,
+            9.0,
+            &format_amount(amount),
+        );
+    }
+    y += 6.0;
+    page.line(
+        width - MARGIN - amount_width - 160.0,
+        y,
+        width - MARGIN,
+        y,
+        0.6,
+    );
+    y += 13.0;
+    page.text_in_box(
+        width - MARGIN - amount_width - 160.0,
+        y,
+        156.0,
+        Align::Right,
+        Font::Bold,
+        10.0,
+        "Invoice total",
+    );
+    page.text_in_box(
+        width - MARGIN - amount_width,
+        y,
+        amount_width - 4.0,
+        Align::Right,
+        Font::Bold,
+        10.0,
+        &format_amount(invoice.total),
+    );
+    y += 18.0;
+    page.text_in_box(
+        MARGIN + 4.0,
+        y,
+        printable_width - 8.0,
+        Align::Left,
+        Font::Regular,
+        9.0,
+        &format!("({})", amount_in_words(invoice.total)),
+    );
+
+    let signature_y = (y + 60.0).min(height - MARGIN - 20.0);
+    let x = width - MARGIN - 180.0;
+    page.text_in_box(
+        x,
+        signature_y - 40.0,
+        176.0,
+        Align::Left,
+        Font::Regular,
+        9.0,
+        &format!("For {}", profile.name),
+    );
+    page.line(x, signature_y - 12.0, x + 176.0, signature_y - 12.0, 0.4);
+    page.text(x, signature_y, Font::Regular, 9.0, "Authorised signatory");
+
+    if invoice.status == "cancelled" {
+        let footer = format!(
+            "Cancelled on {}{}",
+            invoice.cancelled_on.as_deref().unwrap_or_default(),
+            invoice
+                .cancel_reason
+                .as_deref()
+                .map(|r| format!(": {r}"))
+                .unwrap_or_default()
+        );
+        page.text(MARGIN, height - MARGIN, Font::Regular, 7.5, &footer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    /// Two clients with a contract for two posts at 15,000 a month, 10%
+    /// service charge and 18% GST. The company is in Kerala, like the first
+    /// client; the second client is in Karnataka.
+    fn with_contracts() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "UPDATE company_profile SET name = 'Shield', gstin = '32AAACS1234A1Z5'",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO clients (name, gstin) VALUES
+                ('Acme', '32AABCA1111B1Z2'), ('Beta', '29AABCB2222C1Z3')",
+            [],
+        )
+        .unwrap();
+        for client_id in [1, 2] {
+            conn.execute(
+                "INSERT INTO client_contracts (client_id, start_date, service_charge_rate, gst_rate)
+                VALUES (?1, '2024-01-01', 10, 18)",
+                [client_id],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO contract_rates (contract_id, role, billing_basis, rate, posts)
+                VALUES (?1, 'Guard', 'post_month', 15000, 2)",
+                [client_id],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn gst_is_split_within_the_state_and_igst_across_states() {
+        let conn = with_contracts();
+
+        let local = build_invoice(&conn, 1, "2024-07").unwrap();
+        assert_eq!(
+            (local.subtotal, local.service_charge, local.taxable_value),
+            (30000.0, 3000.0, 33000.0)
+        );
+        assert_eq!((local.cgst, local.sgst, local.igst), (2970.0, 2970.0, 0.0));
+        assert_eq!(local.total, 38940.0);
+
+        let other_state = build_invoice(&conn, 2, "2024-07").unwrap();
+        assert_eq!(
+            (other_state.cgst, other_state.sgst, other_state.igst),
+            (0.0, 0.0, 5940.0)
+        );
+        assert_eq!(other_state.total, 38940.0);
+    }
+
+    #[test]
+    fn posts_are_prorated_to_the_contract_start() {
+        let conn = with_contracts();
+        conn.execute(
+            "UPDATE client_contracts SET start_date = '2024-07-17' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+
+        let invoice = build_invoice(&conn, 1, "2024-07").unwrap();
+        assert_eq!(invoice.lines.len(), 1);
+        assert_eq!(invoice.lines[0].quantity, 0.97);
+        assert_eq!(invoice.subtotal, 14516.13);
+        assert!(build_invoice(&conn, 1, "2024-06").is_err());
+    }
+
+    #[test]
+    fn invoice_numbers_run_per_financial_year() {
+        let conn = with_contracts();
+        let date = |value: &str| parse_date(value, "date").unwrap();
+        assert_eq!(financial_year(date("2024-03-31")), "2023-24");
+        assert_eq!(financial_year(date("2024-04-01")), "2024-25");
+        assert_eq!(financial_year(date("2099-12-01")), "2099-00");
+
+        assert_eq!(
+            next_invoice_number(&conn, "2024-25").unwrap(),
+            (1, "INV/2024-25/0001".to_string())
+        );
+        conn.execute(
+            "INSERT INTO invoices (
+                invoice_number, financial_year, sequence, supplier_name, client_id,
+                client_name, month, invoice_date, subtotal, service_charge_rate,
+                service_charge, taxable_value, gst_rate, cgst, sgst, igst, total, status
+            ) VALUES (
+                'INV/2024-25/0001', '2024-25', 1, 'Shield', 1, 'Acme', '2024-07',
+                '2024-08-01', 0, 0, 0, 0, 18, 0, 0, 0, 0, 'cancelled'
+            )",
+            [],
+        )
+        .unwrap();
+        assert_eq!(
+            next_invoice_number(&conn, "2024-25").unwrap(),
+            (2, "INV/2024-25/0002".to_string())
+        );
+        assert_eq!(next_invoice_number(&conn, "2025-26").unwrap().0, 1);
+    }
+}
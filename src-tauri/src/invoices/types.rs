@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use crate::company::types::CompanyProfile;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractRate {
+    pub id: i64,
+    /// Empty for a rate that applies at every site of the client
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub role: String,
+    /// `guard_day` or `post_month`
+    pub billing_basis: String,
+    pub rate: f64,
+    /// Posts billed per month for `post_month` rates
+    pub posts: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientContract {
+    pub id: i64,
+    pub client_id: i64,
+    pub client_name: String,
+    pub reference: Option<String>,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    /// Percent of the billed amount
+    pub service_charge_rate: f64,
+    /// Percent of the billed amount and service charge
+    pub gst_rate: f64,
+    pub remarks: Option<String>,
+    pub rates: Vec<ContractRate>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractRateInput {
+    pub site_id: Option<i64>,
+    pub role: String,
+    pub billing_basis: String,
+    pub rate: f64,
+    pub posts: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientContractInput {
+    pub client_id: i64,
+    pub reference: Option<String>,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub service_charge_rate: Option<f64>,
+    pub gst_rate: Option<f64>,
+    pub remarks: Option<String>,
+    pub rates: Vec<ContractRateInput>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceLine {
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub role: String,
+    pub billing_basis: String,
+    /// Guard days, or posts prorated to the days of the month under contract
+    pub quantity: f64,
+    pub rate: f64,
+    pub amount: f64,
+    pub description: String,
+}
+
+/// Duties at a client's sites that no contract rate covers
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnbilledDuty {
+    pub site_id: i64,
+    pub site_name: String,
+    pub role: Option<String>,
+    pub employees: i64,
+    pub duties: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoicePreview {
+    pub client_id: i64,
+    pub client_name: String,
+    pub month: String,
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal: f64,
+    pub service_charge_rate: f64,
+    pub service_charge: f64,
+    pub taxable_value: f64,
+    pub gst_rate: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub total: f64,
+    pub unbilled: Vec<UnbilledDuty>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    pub id: i64,
+    pub invoice_number: String,
+    pub financial_year: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub client_address: Option<String>,
+    pub client_gstin: Option<String>,
+    pub month: String,
+    pub invoice_date: String,
+    pub subtotal: f64,
+    pub service_charge_rate: f64,
+    pub service_charge: f64,
+    pub taxable_value: f64,
+    pub gst_rate: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub total: f64,
+    /// `issued` or `cancelled`
+    pub status: String,
+    pub cancelled_on: Option<String>,
+    pub cancel_reason: Option<String>,
+    pub lines: Vec<InvoiceLine>,
+    pub created_at: Option<String>,
+    /// Company details as they were when the invoice was issued
+    pub supplier: CompanyProfile,
+}
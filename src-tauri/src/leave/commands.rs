@@ -0,0 +1,510 @@
+use super::helpers::{
+    clear_leave_attendance, ensure_leave_unlocked, get_application_by_id, get_leave_type_by_id,
+    leave_balance, leave_ledger, map_application, map_leave_type, mark_leave_attendance,
+    APPLICATION_SELECT, LEAVE_TYPE_SELECT,
+};
+use super::types::{
+    LeaveAdjustment, LeaveAdjustmentInput, LeaveApplication, LeaveApplicationInput, LeaveBalance,
+    LeaveLedgerEntry, LeaveType, LeaveTypeInput,
+};
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::round2;
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use tauri::State;
+
+fn validate_leave_type(leave_type: &LeaveTypeInput) -> Result<(), String> {
+    if leave_type.code.trim().is_empty() || leave_type.name.trim().is_empty() {
+        return Err("Leave code and name are required".to_string());
+    }
+    let amounts = [leave_type.accrual_days, leave_type.per_days_worked];
+    if amounts
+        .iter()
+        .chain(leave_type.max_balance.iter())
+        .any(|amount| !amount.is_finite() || *amount < 0.0)
+    {
+        return Err("Accrual and maximum balance cannot be negative".to_string());
+    }
+    if leave_type.accrual_days > 0.0 && leave_type.per_days_worked <= 0.0 {
+        return Err("Days worked per accrual must be greater than zero".to_string());
+    }
+    Ok(())
+}
+
+fn map_leave_type_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "A leave type with this code already exists".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_leave_types(_state: State<AppState>) -> Result<Vec<LeaveType>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!("{LEAVE_TYPE_SELECT} ORDER BY code COLLATE NOCASE"))
+        .map_err(|e| e.to_string())?;
+
+    let leave_types = stmt
+        .query_map([], map_leave_type)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(leave_types)
+}
+
+#[tauri::command]
+pub fn create_leave_type(
+    _state: State<AppState>,
+    leave_type: LeaveTypeInput,
+) -> Result<LeaveType, String> {
+    let conn = db::get_connection()?;
+
+    validate_leave_type(&leave_type)?;
+
+    conn.execute(
+        "INSERT INTO leave_types (
+            code, name, paid, encashable, accrual_days, per_days_worked, max_balance
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            leave_type.code.trim().to_uppercase(),
+            leave_type.name.trim(),
+            leave_type.paid,
+            leave_type.encashable,
+            leave_type.accrual_days,
+            leave_type.per_days_worked,
+            leave_type.max_balance,
+        ],
+    )
+    .map_err(map_leave_type_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(leave_type)) = get_leave_type_by_id(&conn, id) {
+        Ok(leave_type)
+    } else {
+        Err("Failed to retrieve leave type".to_string())
+    }
+}
+
+/// Policy changes apply to balances computed afterwards, including accrual of
+/// months already past
+#[tauri::command]
+pub fn update_leave_type(
+    _state: State<AppState>,
+    id: i64,
+    leave_type: LeaveTypeInput,
+) -> Result<LeaveType, String> {
+    let conn = db::get_connection()?;
+
+    validate_leave_type(&leave_type)?;
+
+    conn.execute(
+        "UPDATE leave_types SET
+            code = ?1, name = ?2, paid = ?3, encashable = ?4, accrual_days = ?5,
+            per_days_worked = ?6, max_balance = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            leave_type.code.trim().to_uppercase(),
+            leave_type.name.trim(),
+            leave_type.paid,
+            leave_type.encashable,
+            leave_type.accrual_days,
+            leave_type.per_days_worked,
+            leave_type.max_balance,
+            id,
+        ],
+    )
+    .map_err(map_leave_type_error)?;
+
+    if let Ok(Some(leave_type)) = get_leave_type_by_id(&conn, id) {
+        Ok(leave_type)
+    } else {
+        Err("Failed to retrieve leave type".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_leave_type(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM leave_types WHERE id = ?1", params![id])
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _)
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                "This leave type has applications or adjustments and cannot be deleted".to_string()
+            }
+            e => e.to_string(),
+        })?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_leave_applications(
+    _state: State<AppState>,
+    employee_id: Option<i64>,
+    status: Option<String>,
+) -> Result<Vec<LeaveApplication>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{APPLICATION_SELECT}
+            WHERE (?1 IS NULL OR l.employee_id = ?1) AND (?2 IS NULL OR l.status = ?2)
+            ORDER BY l.from_date DESC, l.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let applications = stmt
+        .query_map(params![employee_id, status], map_application)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(applications)
+}
+
+/// Record a leave application awaiting approval. Days are calendar days from
+/// the first to the last day of leave.
+#[tauri::command]
+pub fn apply_leave(
+    _state: State<AppState>,
+    application: LeaveApplicationInput,
+) -> Result<LeaveApplication, String> {
+    let conn = db::get_connection()?;
+
+    let employee =
+        get_employee_by_id(&conn, application.employee_id)?.ok_or("Employee not found")?;
+    if employee.employment_status != "current" {
+        return Err(format!("{} is not a current employee", employee.name));
+    }
+    get_leave_type_by_id(&conn, application.leave_type_id)?.ok_or("Leave type not found")?;
+
+    let from = parse_date(&application.from_date, "from date")?;
+    let to = parse_date(&application.to_date, "to date")?;
+    if to < from {
+        return Err("Leave cannot end before it starts".to_string());
+    }
+    let days = (to - from).num_days() + 1;
+    let (from, to) = (format_date(from), format_date(to));
+
+    let overlapping: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM leave_applications
+            WHERE employee_id = ?1 AND status IN ('pending', 'approved')
+              AND from_date <= ?3 AND to_date >= ?2",
+            params![application.employee_id, from, to],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if overlapping > 0 {
+        return Err("Leave has already been applied for some of these days".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO leave_applications (
+            employee_id, leave_type_id, from_date, to_date, days, reason
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            application.employee_id,
+            application.leave_type_id,
+            from,
+            to,
+            days as f64,
+            application.reason,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(application)) = get_application_by_id(&conn, id) {
+        Ok(application)
+    } else {
+        Err("Failed to retrieve leave application".to_string())
+    }
+}
+
+/// Approve a pending application and mark its days in attendance. Paid leave
+/// cannot exceed the balance left after leave already approved.
+#[tauri::command]
+pub fn approve_leave(
+    _state: State<AppState>,
+    id: i64,
+    remarks: Option<String>,
+) -> Result<LeaveApplication, String> {
+    let mut conn = db::get_connection()?;
+
+    let application = get_application_by_id(&conn, id)?.ok_or("Leave application not found")?;
+    if application.status != "pending" {
+        return Err(format!(
+            "Leave application is already {}",
+            application.status
+        ));
+    }
+    let leave_type =
+        get_leave_type_by_id(&conn, application.leave_type_id)?.ok_or("Leave type not found")?;
+    let from = parse_date(&application.from_date, "from date")?;
+    let to = parse_date(&application.to_date, "to date")?;
+    ensure_leave_unlocked(&conn, from, to)?;
+
+    if leave_type.paid {
+        let available = leave_balance(&conn, application.employee_id, &leave_type, None)?;
+        if application.days > available {
+            return Err(format!(
+                "Only {available} days of {} are available",
+                leave_type.name.to_lowercase()
+            ));
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE leave_applications SET
+            status = 'approved', decided_on = ?1, decision_remarks = ?2,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?3",
+        params![format_date(today()), remarks, id],
+    )
+    .map_err(|e| e.to_string())?;
+    mark_leave_attendance(&tx, application.employee_id, &leave_type, from, to)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(application)) = get_application_by_id(&conn, id) {
+        Ok(application)
+    } else {
+        Err("Failed to retrieve leave application".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn reject_leave(
+    _state: State<AppState>,
+    id: i64,
+    remarks: Option<String>,
+) -> Result<LeaveApplication, String> {
+    let conn = db::get_connection()?;
+
+    let application = get_application_by_id(&conn, id)?.ok_or("Leave application not found")?;
+    if application.status != "pending" {
+        return Err(format!(
+            "Leave application is already {}",
+            application.status
+        ));
+    }
+
+    conn.execute(
+        "UPDATE leave_applications SET
+            status = 'rejected', decided_on = ?1, decision_remarks = ?2,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?3",
+        params![format_date(today()), remarks, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(application)) = get_application_by_id(&conn, id) {
+        Ok(application)
+    } else {
+        Err("Failed to retrieve leave application".to_string())
+    }
+}
+
+/// Withdraw a pending or approved application. Approved leave is removed
+/// from attendance and returns to the balance, unless payroll for its month
+/// is locked.
+#[tauri::command]
+pub fn cancel_leave(
+    _state: State<AppState>,
+    id: i64,
+    remarks: Option<String>,
+) -> Result<LeaveApplication, String> {
+    let mut conn = db::get_connection()?;
+
+    let application = get_application_by_id(&conn, id)?.ok_or("Leave application not found")?;
+    if !matches!(application.status.as_str(), "pending" | "approved") {
+        return Err(format!(
+            "Leave application is already {}",
+            application.status
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if application.status == "approved" {
+        ensure_leave_unlocked(
+            &tx,
+            parse_date(&application.from_date, "from date")?,
+            parse_date(&application.to_date, "to date")?,
+        )?;
+        clear_leave_attendance(
+            &tx,
+            application.employee_id,
+            &application.from_date,
+            &application.to_date,
+        )?;
+    }
+    tx.execute(
+        "UPDATE leave_applications SET
+            status = 'cancelled', decided_on = ?1, decision_remarks = ?2,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?3",
+        params![format_date(today()), remarks, id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(application)) = get_application_by_id(&conn, id) {
+        Ok(application)
+    } else {
+        Err("Failed to retrieve leave application".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_leave_adjustments(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Vec<LeaveAdjustment>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, employee_id, leave_type_id, adjusted_on, days, remarks, created_at
+            FROM leave_adjustments WHERE employee_id = ?1
+            ORDER BY adjusted_on DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let adjustments = stmt
+        .query_map([employee_id], |row| {
+            Ok(LeaveAdjustment {
+                id: row.get(0)?,
+                employee_id: row.get(1)?,
+                leave_type_id: row.get(2)?,
+                adjusted_on: row.get(3)?,
+                days: row.get(4)?,
+                remarks: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(adjustments)
+}
+
+/// Credit, or with negative days debit, a leave balance, e.g. the opening
+/// balance of an employee who joined before leave was tracked here
+#[tauri::command]
+pub fn add_leave_adjustment(
+    _state: State<AppState>,
+    adjustment: LeaveAdjustmentInput,
+) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    get_employee_by_id(&conn, adjustment.employee_id)?.ok_or("Employee not found")?;
+    get_leave_type_by_id(&conn, adjustment.leave_type_id)?.ok_or("Leave type not found")?;
+    let adjusted_on = parse_date(&adjustment.adjusted_on, "adjustment date")?;
+    if !adjustment.days.is_finite() || adjustment.days == 0.0 {
+        return Err("Adjustment days cannot be zero".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO leave_adjustments (employee_id, leave_type_id, adjusted_on, days, remarks)
+        VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            adjustment.employee_id,
+            adjustment.leave_type_id,
+            format_date(adjusted_on),
+            round2(adjustment.days),
+            adjustment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_leave_adjustment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM leave_adjustments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Balance of every leave type for an employee on `as_on`, or counting all
+/// approved leave including future days when empty
+#[tauri::command]
+pub fn get_leave_balances(
+    _state: State<AppState>,
+    employee_id: i64,
+    as_on: Option<String>,
+) -> Result<Vec<LeaveBalance>, String> {
+    let conn = db::get_connection()?;
+
+    get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+    let as_on = match as_on.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(date) => Some(parse_date(date, "date")?),
+        None => None,
+    };
+
+    let mut stmt = conn
+        .prepare(&format!("{LEAVE_TYPE_SELECT} ORDER BY code COLLATE NOCASE"))
+        .map_err(|e| e.to_string())?;
+    let leave_types = stmt
+        .query_map([], map_leave_type)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut balances = Vec::new();
+    for leave_type in leave_types {
+        let ledger = leave_ledger(&conn, employee_id, &leave_type, as_on)?;
+        let pending: f64 = conn
+            .query_row(
+                "SELECT IFNULL(SUM(days), 0) FROM leave_applications
+                WHERE employee_id = ?1 AND leave_type_id = ?2 AND status = 'pending'",
+                params![employee_id, leave_type.id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        balances.push(LeaveBalance {
+            leave_type_id: leave_type.id,
+            code: leave_type.code,
+            name: leave_type.name,
+            paid: leave_type.paid,
+            encashable: leave_type.encashable,
+            accrued: round2(ledger.iter().map(|e| e.accrued - e.lapsed).sum()),
+            adjusted: round2(ledger.iter().map(|e| e.adjusted).sum()),
+            taken: round2(ledger.iter().map(|e| e.taken).sum()),
+            balance: ledger.last().map_or(0.0, |e| e.balance),
+            pending,
+        });
+    }
+
+    Ok(balances)
+}
+
+#[tauri::command]
+pub fn get_leave_ledger(
+    _state: State<AppState>,
+    employee_id: i64,
+    leave_type_id: i64,
+) -> Result<Vec<LeaveLedgerEntry>, String> {
+    let conn = db::get_connection()?;
+
+    let leave_type = get_leave_type_by_id(&conn, leave_type_id)?.ok_or("Leave type not found")?;
+    leave_ledger(&conn, employee_id, &leave_type, None)
+}
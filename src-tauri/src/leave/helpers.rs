@@ -0,0 +1,493 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Row};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::types::{LeaveApplication, LeaveLedgerEntry, LeaveType};
+use crate::attendance::helpers::{upsert_attendance, NewAttendance};
+use crate::dates::{format_date, parse_date};
+use crate::payroll::helpers::{ensure_month_unlocked, round2};
+
+pub const LEAVE_TYPE_SELECT: &str = "SELECT id, code, name, paid, encashable, accrual_days,
+        per_days_worked, max_balance, created_at, updated_at
+    FROM leave_types";
+
+pub const APPLICATION_SELECT: &str = "SELECT l.id, l.employee_id, e.name, e.essid,
+        l.leave_type_id, t.code, t.name, l.from_date, l.to_date, l.days, l.reason, l.status,
+        l.decided_on, l.decision_remarks, l.created_at, l.updated_at
+    FROM leave_applications l
+    JOIN employees e ON e.id = l.employee_id
+    JOIN leave_types t ON t.id = l.leave_type_id";
+
+/// Attendance source of days marked from approved leave
+const LEAVE_SOURCE: &str = "leave";
+
+/// Upper bound for ledgers that take every recorded movement into account
+const NO_DATE_LIMIT: &str = "9999-12-31";
+
+pub fn map_leave_type(row: &Row) -> rusqlite::Result<LeaveType> {
+    Ok(LeaveType {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        name: row.get(2)?,
+        paid: row.get(3)?,
+        encashable: row.get(4)?,
+        accrual_days: row.get(5)?,
+        per_days_worked: row.get(6)?,
+        max_balance: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+pub fn map_application(row: &Row) -> rusqlite::Result<LeaveApplication> {
+    Ok(LeaveApplication {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        leave_type_id: row.get(4)?,
+        leave_code: row.get(5)?,
+        leave_name: row.get(6)?,
+        from_date: row.get(7)?,
+        to_date: row.get(8)?,
+        days: row.get(9)?,
+        reason: row.get(10)?,
+        status: row.get(11)?,
+        decided_on: row.get(12)?,
+        decision_remarks: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+pub fn get_leave_type_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<LeaveType>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{LEAVE_TYPE_SELECT} WHERE id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_leave_type) {
+        Ok(leave_type) => Ok(Some(leave_type)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_application_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<LeaveApplication>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{APPLICATION_SELECT} WHERE l.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_application) {
+        Ok(application) => Ok(Some(application)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Every date from `from` to `to`, both included
+pub fn leave_dates(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    from.iter_days().take_while(move |date| *date <= to)
+}
+
+/// Month by month accrual, adjustments and leave taken of one leave type up
+/// to `as_on`, or of everything recorded when `as_on` is empty. Approved
+/// leave counts in the months its days fall in.
+pub fn leave_ledger(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    leave_type: &LeaveType,
+    as_on: Option<NaiveDate>,
+) -> Result<Vec<LeaveLedgerEntry>, String> {
+    let as_on = as_on.map_or(NO_DATE_LIMIT.to_string(), format_date);
+    // (days worked, adjusted, taken) per month
+    let mut months: BTreeMap<String, (i64, f64, f64)> = BTreeMap::new();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(attendance_date, 1, 7), COUNT(*) FROM attendance
+            WHERE employee_id = ?1 AND status = 'present' AND attendance_date <= ?2
+            GROUP BY 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let worked = stmt
+        .query_map(params![employee_id, as_on], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (month, days) in worked {
+        months.entry(month).or_default().0 = days;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(adjusted_on, 1, 7), SUM(days) FROM leave_adjustments
+            WHERE employee_id = ?1 AND leave_type_id = ?2 AND adjusted_on <= ?3
+            GROUP BY 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let adjustments = stmt
+        .query_map(params![employee_id, leave_type.id, as_on], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (month, days) in adjustments {
+        months.entry(month).or_default().1 = days;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT from_date, to_date FROM leave_applications
+            WHERE employee_id = ?1 AND leave_type_id = ?2 AND status = 'approved'
+              AND from_date <= ?3",
+        )
+        .map_err(|e| e.to_string())?;
+    let taken = stmt
+        .query_map(params![employee_id, leave_type.id, as_on], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (from, to) in taken {
+        let (Ok(from), Ok(to)) = (parse_date(&from, "from date"), parse_date(&to, "to date"))
+        else {
+            continue;
+        };
+        for date in leave_dates(from, to).filter(|d| format_date(*d) <= as_on) {
+            months
+                .entry(date.format("%Y-%m").to_string())
+                .or_default()
+                .2 += 1.0;
+        }
+    }
+
+    let mut balance = 0.0;
+    let mut ledger = Vec::new();
+    for (month, (days_worked, adjusted, taken)) in months {
+        let accrued = if leave_type.per_days_worked > 0.0 {
+            round2(days_worked as f64 * leave_type.accrual_days / leave_type.per_days_worked)
+        } else {
+            0.0
+        };
+        let credited = match leave_type.max_balance {
+            Some(max) => accrued.min((max - balance).max(0.0)),
+            None => accrued,
+        };
+        balance = round2(balance + credited + adjusted - taken);
+        ledger.push(LeaveLedgerEntry {
+            month,
+            days_worked,
+            accrued,
+            lapsed: round2(accrued - credited),
+            adjusted,
+            taken,
+            balance,
+        });
+    }
+
+    Ok(ledger)
+}
+
+/// Days of leave an employee can still take or encash
+pub fn leave_balance(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    leave_type: &LeaveType,
+    as_on: Option<NaiveDate>,
+) -> Result<f64, String> {
+    Ok(leave_ledger(conn, employee_id, leave_type, as_on)?
+        .last()
+        .map_or(0.0, |entry| entry.balance))
+}
+
+/// Total balance of the encashable leave types on `as_on`
+pub fn encashable_leave_days(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    as_on: NaiveDate,
+) -> Result<f64, String> {
+    let mut stmt = conn
+        .prepare(&format!("{LEAVE_TYPE_SELECT} WHERE encashable = 1"))
+        .map_err(|e| e.to_string())?;
+    let leave_types = stmt
+        .query_map([], map_leave_type)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut days = 0.0;
+    for leave_type in &leave_types {
+        days += leave_balance(conn, employee_id, leave_type, Some(as_on))?.max(0.0);
+    }
+    Ok(round2(days))
+}
+
+/// Fail when payroll is locked for any month the leave falls in
+pub fn ensure_leave_unlocked(
+    conn: &rusqlite::Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(), String> {
+    let months: BTreeSet<String> = leave_dates(from, to)
+        .map(|date| date.format("%Y-%m").to_string())
+        .collect();
+    for month in months {
+        ensure_month_unlocked(conn, &month)?;
+    }
+    Ok(())
+}
+
+/// Mark the days of approved leave in attendance, as leave when it is paid
+/// and absent otherwise, so payroll counts them. Days already marked some
+/// other way are refused, since cancelling the leave would lose them.
+pub fn mark_leave_attendance(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    leave_type: &LeaveType,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT attendance_date FROM attendance
+            WHERE employee_id = ?1 AND attendance_date BETWEEN ?2 AND ?3 AND source <> ?4
+            ORDER BY attendance_date",
+        )
+        .map_err(|e| e.to_string())?;
+    let marked = stmt
+        .query_map(
+            params![
+                employee_id,
+                format_date(from),
+                format_date(to),
+                LEAVE_SOURCE
+            ],
+            |row| row.get::<_, String>(0),
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if !marked.is_empty() {
+        return Err(format!(
+            "Attendance is already marked on {}",
+            marked.join(", ")
+        ));
+    }
+
+    let status = if leave_type.paid { "leave" } else { "absent" };
+    for date in leave_dates(from, to) {
+        upsert_attendance(
+            conn,
+            &NewAttendance {
+                employee_id,
+                attendance_date: format_date(date),
+                status: status.to_string(),
+                site_id: None,
+                shift_template_id: None,
+                ot_shifts: 0,
+                source: LEAVE_SOURCE,
+                remarks: Some(leave_type.name.clone()),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Remove the attendance marked from leave that is cancelled
+pub fn clear_leave_attendance(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    from: &str,
+    to: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM attendance
+        WHERE employee_id = ?1 AND attendance_date BETWEEN ?2 AND ?3 AND source = ?4",
+        params![employee_id, from, to, LEAVE_SOURCE],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_date(value, "date").unwrap()
+    }
+
+    fn employee(conn: &rusqlite::Connection) -> i64 {
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn leave_type(conn: &rusqlite::Connection, code: &str) -> LeaveType {
+        let id: i64 = conn
+            .query_row(
+                "SELECT id FROM leave_types WHERE code = ?1",
+                [code],
+                |row| row.get(0),
+            )
+            .unwrap();
+        get_leave_type_by_id(conn, id).unwrap().unwrap()
+    }
+
+    fn mark_present(conn: &rusqlite::Connection, employee_id: i64, from: &str, to: &str) {
+        for day in leave_dates(date(from), date(to)) {
+            conn.execute(
+                "INSERT INTO attendance (employee_id, attendance_date, status, source)
+                VALUES (?1, ?2, 'present', 'manual')",
+                params![employee_id, format_date(day)],
+            )
+            .unwrap();
+        }
+    }
+
+    fn statuses(conn: &rusqlite::Connection, employee_id: i64) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT attendance_date, status FROM attendance
+                WHERE employee_id = ?1 ORDER BY attendance_date",
+            )
+            .unwrap();
+        stmt.query_map([employee_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn leave_accrues_per_days_worked() {
+        let conn = open_test_db();
+        let employee_id = employee(&conn);
+        mark_present(&conn, employee_id, "2024-07-01", "2024-07-20");
+        mark_present(&conn, employee_id, "2024-08-01", "2024-08-20");
+
+        // Earned leave accrues a day per 20 days worked
+        let ledger = leave_ledger(&conn, employee_id, &leave_type(&conn, "EL"), None).unwrap();
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger[0].days_worked, 20);
+        assert_eq!(ledger[0].accrued, 1.0);
+        assert_eq!(ledger[1].balance, 2.0);
+
+        // Casual leave accrues a day per 30 days worked
+        let casual = leave_type(&conn, "CL");
+        let ledger = leave_ledger(&conn, employee_id, &casual, None).unwrap();
+        assert_eq!(ledger[0].accrued, 0.67);
+        assert_eq!(ledger[1].balance, 1.34);
+        assert_eq!(
+            leave_balance(&conn, employee_id, &casual, Some(date("2024-07-31"))).unwrap(),
+            0.67
+        );
+    }
+
+    #[test]
+    fn balance_lapses_above_the_maximum_and_drops_with_leave_taken() {
+        let conn = open_test_db();
+        let employee_id = employee(&conn);
+        let casual = leave_type(&conn, "CL");
+        conn.execute(
+            "INSERT INTO leave_adjustments (employee_id, leave_type_id, adjusted_on, days)
+            VALUES (?1, ?2, '2024-06-01', 11.5)",
+            params![employee_id, casual.id],
+        )
+        .unwrap();
+        mark_present(&conn, employee_id, "2024-07-01", "2024-07-30");
+        conn.execute(
+            "INSERT INTO leave_applications (
+                employee_id, leave_type_id, from_date, to_date, days, status
+            ) VALUES (?1, ?2, '2024-08-05', '2024-08-06', 2, 'approved')",
+            params![employee_id, casual.id],
+        )
+        .unwrap();
+
+        let ledger = leave_ledger(&conn, employee_id, &casual, None).unwrap();
+        assert_eq!(ledger[1].month, "2024-07");
+        assert_eq!(ledger[1].accrued, 1.0);
+        assert_eq!(ledger[1].lapsed, 0.5);
+        assert_eq!(ledger[1].balance, 12.0);
+        assert_eq!(ledger[2].taken, 2.0);
+        assert_eq!(
+            leave_balance(&conn, employee_id, &casual, None).unwrap(),
+            10.0
+        );
+    }
+
+    #[test]
+    fn approved_leave_is_marked_and_cleared_in_attendance() {
+        let conn = open_test_db();
+        let employee_id = employee(&conn);
+        let casual = leave_type(&conn, "CL");
+
+        mark_leave_attendance(
+            &conn,
+            employee_id,
+            &casual,
+            date("2024-07-01"),
+            date("2024-07-02"),
+        )
+        .unwrap();
+        assert_eq!(
+            statuses(&conn, employee_id),
+            vec![
+                ("2024-07-01".to_string(), "leave".to_string()),
+                ("2024-07-02".to_string(), "leave".to_string()),
+            ]
+        );
+
+        mark_present(&conn, employee_id, "2024-07-03", "2024-07-03");
+        clear_leave_attendance(&conn, employee_id, "2024-07-01", "2024-07-03").unwrap();
+        assert_eq!(
+            statuses(&conn, employee_id),
+            vec![("2024-07-03".to_string(), "present".to_string())]
+        );
+    }
+
+    #[test]
+    fn leave_is_refused_over_marked_or_locked_days() {
+        let conn = open_test_db();
+        let employee_id = employee(&conn);
+        let casual = leave_type(&conn, "CL");
+        mark_present(&conn, employee_id, "2024-07-02", "2024-07-02");
+
+        assert_eq!(
+            mark_leave_attendance(
+                &conn,
+                employee_id,
+                &casual,
+                date("2024-07-01"),
+                date("2024-07-03"),
+            ),
+            Err("Attendance is already marked on 2024-07-02".to_string())
+        );
+        assert_eq!(statuses(&conn, employee_id).len(), 1);
+
+        conn.execute(
+            "INSERT INTO pay_runs (month, status) VALUES ('2024-08', 'locked')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(
+            ensure_leave_unlocked(&conn, date("2024-07-30"), date("2024-08-02")),
+            Err("Payroll for 2024-08 is locked".to_string())
+        );
+        ensure_leave_unlocked(&conn, date("2024-07-30"), date("2024-07-31")).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveType {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    /// Unpaid leave is marked absent in attendance
+    pub paid: bool,
+    /// Balance is paid out in the full and final settlement
+    pub encashable: bool,
+    pub accrual_days: f64,
+    /// Days present that earn `accrual_days`, zero for no accrual
+    pub per_days_worked: f64,
+    pub max_balance: Option<f64>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveTypeInput {
+    pub code: String,
+    pub name: String,
+    pub paid: bool,
+    pub encashable: bool,
+    pub accrual_days: f64,
+    pub per_days_worked: f64,
+    pub max_balance: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveApplication {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub leave_type_id: i64,
+    pub leave_code: String,
+    pub leave_name: String,
+    pub from_date: String,
+    pub to_date: String,
+    pub days: f64,
+    pub reason: Option<String>,
+    /// `pending`, `approved`, `rejected` or `cancelled`
+    pub status: String,
+    pub decided_on: Option<String>,
+    pub decision_remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveApplicationInput {
+    pub employee_id: i64,
+    pub leave_type_id: i64,
+    pub from_date: String,
+    pub to_date: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveAdjustment {
+    pub id: i64,
+    pub employee_id: i64,
+    pub leave_type_id: i64,
+    pub adjusted_on: String,
+    pub days: f64,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveAdjustmentInput {
+    pub employee_id: i64,
+    pub leave_type_id: i64,
+    pub adjusted_on: String,
+    pub days: f64,
+    pub remarks: Option<String>,
+}
+
+/// Movements of one leave type in a `YYYY-MM` month
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveLedgerEntry {
+    pub month: String,
+    pub days_worked: i64,
+    pub accrued: f64,
+    /// Accrual above the maximum balance that was not credited
+    pub lapsed: f64,
+    pub adjusted: f64,
+    pub taken: f64,
+    pub balance: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveBalance {
+    pub leave_type_id: i64,
+    pub code: String,
+    pub name: String,
+    pub paid: bool,
+    pub encashable: bool,
+    pub accrued: f64,
+    pub adjusted: f64,
+    pub taken: f64,
+    pub balance: f64,
+    /// Days applied for and awaiting approval
+    pub pending: f64,
+}
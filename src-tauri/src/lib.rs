@@ -1,7 +1,38 @@
+mod advances;
+mod attachments;
+mod attendance;
+mod bank;
+mod biometric;
+mod bonus;
+mod clients;
+mod company;
+mod compliance;
+mod csv;
+mod dates;
 mod db;
+mod deployments;
 mod employees;
+mod employment_history;
+mod epf;
+mod esic;
 mod files;
+mod gratuity;
+mod inventory;
+mod invoices;
+mod leave;
+mod minimum_wages;
+mod overtime;
+mod payroll;
+mod payslips;
+mod pdf;
+mod police_verification;
+mod registers;
+mod roster;
+mod settlements;
 mod state;
+mod training;
+mod weapons;
+mod xlsx;
 
 use std::{path::PathBuf, sync::OnceLock};
 
@@ -50,7 +81,199 @@ pub fn run() {
             commands::get_db_info,
             commands::create_database_backup,
             commands::delete_employee_image,
-            commands::export_employees_csv
+            commands::export_employees_csv,
+            police_verification::commands::get_police_verifications,
+            police_verification::commands::create_police_verification,
+            police_verification::commands::update_police_verification,
+            police_verification::commands::delete_police_verification,
+            police_verification::commands::get_police_station_report,
+            clients::commands::get_clients,
+            clients::commands::create_client,
+            clients::commands::update_client,
+            clients::commands::delete_client,
+            clients::commands::get_sites,
+            clients::commands::create_site,
+            clients::commands::update_site,
+            clients::commands::delete_site,
+            deployments::commands::assign_employee,
+            deployments::commands::transfer_employee,
+            deployments::commands::release_employee,
+            deployments::commands::delete_deployment,
+            deployments::commands::get_employee_deployments,
+            deployments::commands::get_site_deployments,
+            deployments::commands::get_site_headcounts,
+            roster::commands::get_shift_templates,
+            roster::commands::create_shift_template,
+            roster::commands::update_shift_template,
+            roster::commands::delete_shift_template,
+            roster::commands::get_site_roster,
+            roster::commands::create_roster_assignment,
+            roster::commands::delete_roster_assignment,
+            roster::commands::get_roster_conflicts,
+            roster::commands::copy_previous_week_roster,
+            roster::commands::export_roster_csv,
+            roster::commands::export_roster_pdf,
+            attendance::commands::get_attendance,
+            attendance::commands::mark_site_attendance,
+            attendance::commands::delete_attendance,
+            attendance::commands::import_attendance_csv,
+            attendance::commands::get_monthly_attendance_summary,
+            biometric::commands::get_biometric_enrolments,
+            biometric::commands::save_biometric_enrolment,
+            biometric::commands::delete_biometric_enrolment,
+            biometric::commands::import_biometric_log,
+            payroll::commands::get_wage_structures,
+            payroll::commands::create_wage_structure,
+            payroll::commands::update_wage_structure,
+            payroll::commands::delete_wage_structure,
+            payroll::commands::get_payroll_settings,
+            payroll::commands::update_payroll_settings,
+            payroll::commands::get_pay_runs,
+            payroll::commands::get_pay_run_for_month,
+            payroll::commands::get_pay_run_details,
+            payroll::commands::run_payroll,
+            payroll::commands::lock_pay_run,
+            payroll::commands::delete_pay_run,
+            epf::commands::get_ecr_report,
+            epf::commands::export_ecr_file,
+            epf::commands::get_employees_without_uan,
+            esic::commands::get_esic_report,
+            esic::commands::export_esic_file,
+            company::commands::get_company_profile,
+            company::commands::update_company_profile,
+            attachments::commands::get_employee_attachments,
+            attachments::commands::add_employee_attachment,
+            attachments::commands::delete_employee_attachment,
+            payslips::commands::export_payslips_pdf,
+            payslips::commands::save_payslip_attachments,
+            bank::commands::get_bank_accounts,
+            bank::commands::get_employee_bank_account,
+            bank::commands::save_employee_bank_account,
+            bank::commands::delete_employee_bank_account,
+            bank::commands::get_bank_payment_layouts,
+            bank::commands::create_bank_payment_layout,
+            bank::commands::update_bank_payment_layout,
+            bank::commands::delete_bank_payment_layout,
+            bank::commands::get_bank_payment_preview,
+            bank::commands::export_bank_payment_file,
+            advances::commands::get_salary_advances,
+            advances::commands::create_salary_advance,
+            advances::commands::update_salary_advance,
+            advances::commands::delete_salary_advance,
+            advances::commands::add_advance_recovery,
+            advances::commands::get_advance_ledger,
+            advances::commands::get_past_employee_outstanding_advances,
+            settlements::commands::get_exit_settlements,
+            settlements::commands::get_exit_settlement,
+            settlements::commands::compute_exit_settlement,
+            settlements::commands::settle_exit_settlement,
+            settlements::commands::delete_exit_settlement,
+            settlements::commands::export_settlement_pdf,
+            gratuity::commands::get_gratuity_calculation,
+            gratuity::commands::get_gratuity_eligibility_due,
+            gratuity::commands::get_gratuity_liability,
+            bonus::commands::get_bonus_runs,
+            bonus::commands::get_bonus_run,
+            bonus::commands::compute_bonus,
+            bonus::commands::mark_bonus_paid,
+            bonus::commands::delete_bonus_run,
+            bonus::commands::export_bonus_form_c,
+            bonus::commands::export_bonus_disbursement_csv,
+            minimum_wages::commands::get_minimum_wage_rates,
+            minimum_wages::commands::create_minimum_wage_rate,
+            minimum_wages::commands::update_minimum_wage_rate,
+            minimum_wages::commands::delete_minimum_wage_rate,
+            minimum_wages::commands::import_minimum_wage_rates_csv,
+            minimum_wages::commands::get_job_post_wage_categories,
+            minimum_wages::commands::save_job_post_wage_category,
+            minimum_wages::commands::delete_job_post_wage_category,
+            minimum_wages::commands::check_minimum_wages,
+            leave::commands::get_leave_types,
+            leave::commands::create_leave_type,
+            leave::commands::update_leave_type,
+            leave::commands::delete_leave_type,
+            leave::commands::get_leave_applications,
+            leave::commands::apply_leave,
+            leave::commands::approve_leave,
+            leave::commands::reject_leave,
+            leave::commands::cancel_leave,
+            leave::commands::get_leave_adjustments,
+            leave::commands::add_leave_adjustment,
+            leave::commands::delete_leave_adjustment,
+            leave::commands::get_leave_balances,
+            leave::commands::get_leave_ledger,
+            overtime::commands::get_overtime_records,
+            overtime::commands::derive_overtime,
+            overtime::commands::create_overtime_record,
+            overtime::commands::update_overtime_record,
+            overtime::commands::delete_overtime_record,
+            overtime::commands::set_overtime_status,
+            overtime::commands::get_overtime_summary,
+            overtime::commands::export_overtime_register,
+            invoices::commands::get_client_contracts,
+            invoices::commands::create_client_contract,
+            invoices::commands::update_client_contract,
+            invoices::commands::delete_client_contract,
+            invoices::commands::preview_invoice,
+            invoices::commands::issue_invoice,
+            invoices::commands::get_invoices,
+            invoices::commands::get_invoice,
+            invoices::commands::cancel_invoice,
+            invoices::commands::export_invoice_pdf,
+            invoices::commands::export_invoice_csv,
+            deployments::commands::get_site_requirements,
+            deployments::commands::create_site_requirement,
+            deployments::commands::update_site_requirement,
+            deployments::commands::delete_site_requirement,
+            deployments::commands::get_strength_gaps,
+            deployments::commands::export_strength_gap_report,
+            registers::commands::get_statutory_register,
+            registers::commands::export_statutory_register_pdf,
+            registers::commands::export_statutory_registers_xlsx,
+            compliance::commands::get_employee_compliance,
+            compliance::commands::get_compliance_dashboard,
+            weapons::commands::get_weapons,
+            weapons::commands::create_weapon,
+            weapons::commands::update_weapon,
+            weapons::commands::delete_weapon,
+            weapons::commands::get_weapon_assignments,
+            weapons::commands::assign_weapon,
+            weapons::commands::return_weapon,
+            weapons::commands::get_ammunition_movements,
+            weapons::commands::record_ammunition_movement,
+            weapons::commands::get_armed_roles,
+            weapons::commands::save_armed_role,
+            weapons::commands::delete_armed_role,
+            weapons::commands::export_weapon_register_pdf,
+            weapons::commands::export_ammunition_register_pdf,
+            inventory::commands::get_inventory_items,
+            inventory::commands::create_inventory_item,
+            inventory::commands::update_inventory_item,
+            inventory::commands::delete_inventory_item,
+            inventory::commands::get_inventory_movements,
+            inventory::commands::record_inventory_movement,
+            inventory::commands::get_employee_outstanding_items,
+            inventory::commands::get_low_stock_items,
+            inventory::commands::export_low_stock_report,
+            training::commands::get_training_programs,
+            training::commands::create_training_program,
+            training::commands::update_training_program,
+            training::commands::delete_training_program,
+            training::commands::get_training_sessions,
+            training::commands::create_training_session,
+            training::commands::update_training_session,
+            training::commands::delete_training_session,
+            training::commands::record_training_attendance,
+            training::commands::delete_training_attendance,
+            training::commands::get_employee_certifications,
+            training::commands::get_refresher_due,
+            training::commands::export_refresher_due_report,
+            employment_history::commands::get_employment_history,
+            employment_history::commands::create_previous_employment,
+            employment_history::commands::update_previous_employment,
+            employment_history::commands::delete_previous_employment,
+            employment_history::commands::get_ex_servicemen,
+            employment_history::commands::export_ex_servicemen_report
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,443 @@
+use super::helpers::{
+    find_minimum_wage, get_rate_by_id, map_category, map_rate, upsert_rate, validate_rate,
+    RATE_SELECT,
+};
+use super::types::{
+    JobPostWageCategory, MinimumWageCheck, MinimumWageImportResult, MinimumWageIssue,
+    MinimumWageRate, MinimumWageRateInput,
+};
+use crate::attendance::types::ImportError;
+use crate::clients::helpers::get_site_by_id;
+use crate::csv::{header_index, parse_csv};
+use crate::dates::{format_date, parse_date, parse_date_flexible, today};
+use crate::db;
+use crate::deployments::helpers::get_active_deployment;
+use crate::payroll::helpers::{find_wage_structure, round2};
+use crate::state::AppState;
+use rusqlite::{params, Connection, Result};
+use std::fs;
+use tauri::State;
+
+fn map_rate_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "A rate for this state, zone and category already starts on that date".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+fn present(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+#[tauri::command]
+pub fn get_minimum_wage_rates(
+    _state: State<AppState>,
+    state: Option<String>,
+    category: Option<String>,
+) -> Result<Vec<MinimumWageRate>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{RATE_SELECT}
+            WHERE (?1 IS NULL OR state = ?1) AND (?2 IS NULL OR category = ?2)
+            ORDER BY state COLLATE NOCASE, zone COLLATE NOCASE, category COLLATE NOCASE,
+                effective_from DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rates = stmt
+        .query_map(
+            params![present(state.as_deref()), present(category.as_deref())],
+            map_rate,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rates)
+}
+
+#[tauri::command]
+pub fn create_minimum_wage_rate(
+    _state: State<AppState>,
+    rate: MinimumWageRateInput,
+) -> Result<MinimumWageRate, String> {
+    let conn = db::get_connection()?;
+
+    let (state, zone, category, effective_from, rate_basis) = validate_rate(&rate)?;
+
+    conn.execute(
+        "INSERT INTO minimum_wage_rates (
+            state, zone, category, effective_from, basic, vda, rate_basis
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            state,
+            zone,
+            category,
+            effective_from,
+            rate.basic,
+            rate.vda,
+            rate_basis
+        ],
+    )
+    .map_err(map_rate_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(rate)) = get_rate_by_id(&conn, id) {
+        Ok(rate)
+    } else {
+        Err("Failed to retrieve minimum wage rate".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_minimum_wage_rate(
+    _state: State<AppState>,
+    id: i64,
+    rate: MinimumWageRateInput,
+) -> Result<MinimumWageRate, String> {
+    let conn = db::get_connection()?;
+
+    let (state, zone, category, effective_from, rate_basis) = validate_rate(&rate)?;
+
+    conn.execute(
+        "UPDATE minimum_wage_rates SET
+            state = ?1, zone = ?2, category = ?3, effective_from = ?4, basic = ?5, vda = ?6,
+            rate_basis = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            state,
+            zone,
+            category,
+            effective_from,
+            rate.basic,
+            rate.vda,
+            rate_basis,
+            id
+        ],
+    )
+    .map_err(map_rate_error)?;
+
+    if let Ok(Some(rate)) = get_rate_by_id(&conn, id) {
+        Ok(rate)
+    } else {
+        Err("Failed to retrieve minimum wage rate".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_minimum_wage_rate(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM minimum_wage_rates WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+struct RateColumns {
+    state: usize,
+    zone: Option<usize>,
+    category: usize,
+    effective_from: usize,
+    basic: usize,
+    vda: Option<usize>,
+    rate_basis: Option<usize>,
+}
+
+fn import_rate_row(conn: &Connection, columns: &RateColumns, row: &[String]) -> Result<(), String> {
+    let field = |index: usize| row.get(index).map(|v| v.trim()).unwrap_or_default();
+    let optional = |index: Option<usize>| index.map(field).unwrap_or_default();
+    let amount = |value: &str, label: &str| -> Result<f64, String> {
+        match value {
+            "" => Ok(0.0),
+            value => value
+                .replace(',', "")
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid {label} '{value}'")),
+        }
+    };
+
+    upsert_rate(
+        conn,
+        &MinimumWageRateInput {
+            state: field(columns.state).to_string(),
+            zone: Some(optional(columns.zone).to_string()),
+            category: field(columns.category).to_string(),
+            effective_from: format_date(parse_date_flexible(
+                field(columns.effective_from),
+                "effective from date",
+            )?),
+            basic: amount(field(columns.basic), "basic")?,
+            vda: amount(optional(columns.vda), "VDA")?,
+            rate_basis: Some(optional(columns.rate_basis).to_string()),
+        },
+    )
+}
+
+/// Import rates from a CSV with `State`, `Category`, `Effective From` and
+/// `Basic` columns and optional `Zone`, `VDA` and `Rate Basis` (`day` or
+/// `month`) columns. A row for a state,
+/// zone, category and date that already exists replaces its amounts, so a
+/// revised notification can be imported again.
+#[tauri::command]
+pub fn import_minimum_wage_rates_csv(
+    _state: State<AppState>,
+    file_path: String,
+) -> Result<MinimumWageImportResult, String> {
+    let mut conn = db::get_connection()?;
+
+    let content =
+        fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+    let mut rows = parse_csv(&content).into_iter();
+    let (_, header) = rows.next().ok_or("The CSV file is empty")?;
+
+    let columns = RateColumns {
+        state: header_index(&header, &["state"]).ok_or("The CSV file has no State column")?,
+        zone: header_index(&header, &["zone", "area"]),
+        category: header_index(&header, &["category", "wage category", "class"])
+            .ok_or("The CSV file has no Category column")?,
+        effective_from: header_index(&header, &["effective from", "effective date", "from"])
+            .ok_or("The CSV file has no Effective From column")?,
+        basic: header_index(&header, &["basic", "basic wage", "minimum wage"])
+            .ok_or("The CSV file has no Basic column")?,
+        vda: header_index(&header, &["vda", "da", "variable da"]),
+        rate_basis: header_index(&header, &["rate basis", "basis", "per"]),
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut imported = 0;
+    let mut errors = Vec::new();
+
+    for (line, row) in rows {
+        match import_rate_row(&tx, &columns, &row) {
+            Ok(()) => imported += 1,
+            Err(message) => errors.push(ImportError { line, message }),
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(MinimumWageImportResult { imported, errors })
+}
+
+#[tauri::command]
+pub fn get_job_post_wage_categories(
+    _state: State<AppState>,
+) -> Result<Vec<JobPostWageCategory>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, job_post, category, created_at, updated_at
+            FROM job_post_wage_categories
+            ORDER BY job_post COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let categories = stmt
+        .query_map([], map_category)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(categories)
+}
+
+/// Set the minimum wage category of a job post, replacing any earlier one
+#[tauri::command]
+pub fn save_job_post_wage_category(
+    _state: State<AppState>,
+    job_post: String,
+    category: String,
+) -> Result<JobPostWageCategory, String> {
+    let conn = db::get_connection()?;
+
+    let job_post = job_post.trim();
+    let category = category.trim();
+    if job_post.is_empty() {
+        return Err("Job post is required".to_string());
+    }
+    if category.is_empty() {
+        return Err("Category is required".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO job_post_wage_categories (job_post, category) VALUES (?1, ?2)
+        ON CONFLICT (job_post) DO UPDATE SET
+            category = excluded.category, updated_at = CURRENT_TIMESTAMP",
+        params![job_post, category],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT id, job_post, category, created_at, updated_at
+        FROM job_post_wage_categories WHERE job_post = ?1",
+        [job_post],
+        map_category,
+    )
+    .map_err(|_| "Failed to retrieve job post category".to_string())
+}
+
+#[tauri::command]
+pub fn delete_job_post_wage_category(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM job_post_wage_categories WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Compare the basic and DA of every current employee's wage structure with
+/// the monthly minimum wage for their job post's category in the state and zone of
+/// the site they are deployed at on `as_on` (today when empty). Employees
+/// without a deployment are checked against `default_state` and
+/// `default_zone`, usually where the company is registered. Employees that
+/// cannot be checked are listed with the reason.
+#[tauri::command]
+pub fn check_minimum_wages(
+    _state: State<AppState>,
+    as_on: Option<String>,
+    default_state: Option<String>,
+    default_zone: Option<String>,
+) -> Result<MinimumWageCheck, String> {
+    let conn = db::get_connection()?;
+
+    let as_on = match present(as_on.as_deref()) {
+        Some(date) => parse_date(date, "date")?,
+        None => today(),
+    };
+    let as_on_text = format_date(as_on);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.name, e.essid, e.job_post, c.category
+            FROM employees e
+            LEFT JOIN job_post_wage_categories c ON c.job_post = TRIM(e.job_post)
+            WHERE e.employment_status = 'current' AND IFNULL(e.joining_date, '') <= ?1
+            ORDER BY e.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let employees = stmt
+        .query_map(params![as_on_text], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut checked = 0;
+    let mut below_minimum = Vec::new();
+    let mut unchecked = Vec::new();
+
+    for (employee_id, employee_name, essid, job_post, category) in employees {
+        let mut issue = MinimumWageIssue {
+            employee_id,
+            employee_name,
+            essid,
+            job_post: present(job_post.as_deref()).map(str::to_string),
+            category,
+            site_name: None,
+            state: None,
+            zone: None,
+            configured_wage: None,
+            minimum_wage: None,
+            shortfall: None,
+            rate_effective_from: None,
+            message: String::new(),
+        };
+
+        let Some(job_post) = issue.job_post.clone() else {
+            issue.message = "No job post is recorded".to_string();
+            unchecked.push(issue);
+            continue;
+        };
+        let Some(category) = issue.category.clone() else {
+            issue.message = format!("Job post '{job_post}' has no minimum wage category");
+            unchecked.push(issue);
+            continue;
+        };
+
+        // The site's own state and zone win over the defaults
+        let site = match get_active_deployment(&conn, employee_id, &as_on_text)? {
+            Some(deployment) => get_site_by_id(&conn, deployment.site_id)?,
+            None => None,
+        };
+        issue.site_name = site.as_ref().map(|s| s.name.clone());
+        let site_state = site.as_ref().and_then(|s| present(s.state.as_deref()));
+        let (state, zone) = match site_state {
+            Some(state) => (
+                Some(state),
+                site.as_ref().and_then(|s| present(s.wage_zone.as_deref())),
+            ),
+            None => (
+                present(default_state.as_deref()),
+                present(default_zone.as_deref()),
+            ),
+        };
+        issue.state = state.map(str::to_string);
+        issue.zone = zone.map(str::to_string);
+        let Some(state) = state else {
+            issue.message = match &issue.site_name {
+                Some(site_name) => format!("Site '{site_name}' has no state"),
+                None => "Not deployed at a site and no default state is given".to_string(),
+            };
+            unchecked.push(issue);
+            continue;
+        };
+
+        let Some(structure) = find_wage_structure(&conn, &job_post, as_on)? else {
+            issue.message = format!("No wage structure for '{job_post}' on {as_on_text}");
+            unchecked.push(issue);
+            continue;
+        };
+        let configured = round2(structure.basic + structure.da);
+        issue.configured_wage = Some(configured);
+
+        let Some(rate) = find_minimum_wage(
+            &conn,
+            state,
+            zone.unwrap_or_default(),
+            &category,
+            &as_on_text,
+        )?
+        else {
+            issue.message = format!("No minimum wage rate for {category} in {state}");
+            unchecked.push(issue);
+            continue;
+        };
+        let minimum = round2(rate.monthly_total);
+        issue.minimum_wage = Some(minimum);
+        issue.rate_effective_from = Some(rate.effective_from);
+
+        checked += 1;
+        if configured < minimum {
+            let shortfall = round2(minimum - configured);
+            issue.shortfall = Some(shortfall);
+            issue.message = format!("Basic and DA are {shortfall} below the minimum wage");
+            below_minimum.push(issue);
+        }
+    }
+
+    Ok(MinimumWageCheck {
+        as_on: as_on_text,
+        checked,
+        below_minimum,
+        unchecked,
+    })
+}
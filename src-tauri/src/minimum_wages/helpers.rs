@@ -0,0 +1,206 @@
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::types::{JobPostWageCategory, MinimumWageRate, MinimumWageRateInput};
+use crate::dates::{format_date, parse_date};
+use crate::payroll::helpers::{round2, WORKING_DAYS_PER_MONTH};
+
+pub const RATE_SELECT: &str = "SELECT id, state, zone, category, effective_from, basic, vda,
+        rate_basis, created_at, updated_at
+    FROM minimum_wage_rates";
+
+pub const RATE_BASES: [&str; 2] = ["day", "month"];
+
+pub fn map_rate(row: &Row) -> rusqlite::Result<MinimumWageRate> {
+    let basic: f64 = row.get(5)?;
+    let vda: f64 = row.get(6)?;
+    let rate_basis: String = row.get(7)?;
+    let monthly_total = if rate_basis == "day" {
+        round2((basic + vda) * WORKING_DAYS_PER_MONTH)
+    } else {
+        basic + vda
+    };
+    Ok(MinimumWageRate {
+        id: row.get(0)?,
+        state: row.get(1)?,
+        zone: row.get(2)?,
+        category: row.get(3)?,
+        effective_from: row.get(4)?,
+        basic,
+        vda,
+        total: basic + vda,
+        rate_basis,
+        monthly_total,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+pub fn map_category(row: &Row) -> rusqlite::Result<JobPostWageCategory> {
+    Ok(JobPostWageCategory {
+        id: row.get(0)?,
+        job_post: row.get(1)?,
+        category: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+pub fn get_rate_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<MinimumWageRate>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{RATE_SELECT} WHERE id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_rate) {
+        Ok(rate) => Ok(Some(rate)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Rate in force on `date` for a category in a state, preferring the zone's
+/// own rate over a statewide one
+pub fn find_minimum_wage(
+    conn: &rusqlite::Connection,
+    state: &str,
+    zone: &str,
+    category: &str,
+    date: &str,
+) -> Result<Option<MinimumWageRate>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{RATE_SELECT}
+            WHERE state = ?1 AND category = ?3 AND effective_from <= ?4
+              AND (zone = ?2 OR zone = '')
+            ORDER BY zone = ?2 DESC, effective_from DESC
+            LIMIT 1"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    stmt.query_row(params![state, zone, category, date], map_rate)
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Check a rate and return its state, zone, category, effective date and
+/// basis trimmed and in canonical form
+pub fn validate_rate(
+    rate: &MinimumWageRateInput,
+) -> Result<(String, String, String, String, String), String> {
+    let state = rate.state.trim();
+    let zone = rate.zone.as_deref().unwrap_or_default().trim();
+    let category = rate.category.trim();
+    if state.is_empty() {
+        return Err("State is required".to_string());
+    }
+    if category.is_empty() {
+        return Err("Category is required".to_string());
+    }
+    if !rate.basic.is_finite() || !rate.vda.is_finite() || rate.basic <= 0.0 || rate.vda < 0.0 {
+        return Err("Basic must be greater than zero and VDA cannot be negative".to_string());
+    }
+    let effective_from = format_date(parse_date(&rate.effective_from, "effective from date")?);
+    let rate_basis = match rate.rate_basis.as_deref().map(str::trim) {
+        None | Some("") => "month".to_string(),
+        Some(basis) => {
+            let basis = basis.to_lowercase();
+            if !RATE_BASES.contains(&basis.as_str()) {
+                return Err(format!(
+                    "Invalid rate basis '{basis}', expected one of: {}",
+                    RATE_BASES.join(", ")
+                ));
+            }
+            basis
+        }
+    };
+
+    Ok((
+        state.to_string(),
+        zone.to_string(),
+        category.to_string(),
+        effective_from,
+        rate_basis,
+    ))
+}
+
+/// Insert a rate, or replace the amounts of the rate with the same state,
+/// zone, category and effective date
+pub fn upsert_rate(conn: &rusqlite::Connection, rate: &MinimumWageRateInput) -> Result<(), String> {
+    let (state, zone, category, effective_from, rate_basis) = validate_rate(rate)?;
+
+    conn.execute(
+        "INSERT INTO minimum_wage_rates (
+            state, zone, category, effective_from, basic, vda, rate_basis
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (state, zone, category, effective_from) DO UPDATE SET
+            basic = excluded.basic, vda = excluded.vda, rate_basis = excluded.rate_basis,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            state,
+            zone,
+            category,
+            effective_from,
+            rate.basic,
+            rate.vda,
+            rate_basis
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn rate(zone: Option<&str>, basic: f64, vda: f64, basis: Option<&str>) -> MinimumWageRateInput {
+        MinimumWageRateInput {
+            state: " Kerala ".to_string(),
+            zone: zone.map(str::to_string),
+            category: "Unskilled".to_string(),
+            effective_from: "2024-04-01".to_string(),
+            basic,
+            vda,
+            rate_basis: basis.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn daily_rates_compare_as_26_working_days() {
+        let conn = open_test_db();
+        upsert_rate(&conn, &rate(None, 12000.0, 1500.0, None)).unwrap();
+        upsert_rate(&conn, &rate(Some("A"), 600.0, 45.5, Some(" Day "))).unwrap();
+
+        let statewide = find_minimum_wage(&conn, "Kerala", "B", "Unskilled", "2024-07-01")
+            .unwrap()
+            .unwrap();
+        assert_eq!(statewide.rate_basis, "month");
+        assert_eq!(statewide.monthly_total, 13500.0);
+
+        let zone_a = find_minimum_wage(&conn, "Kerala", "A", "Unskilled", "2024-07-01")
+            .unwrap()
+            .unwrap();
+        assert_eq!(zone_a.rate_basis, "day");
+        assert_eq!((zone_a.total, zone_a.monthly_total), (645.5, 16783.0));
+
+        assert!(
+            find_minimum_wage(&conn, "Kerala", "A", "Unskilled", "2024-03-31")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn invalid_rates_are_refused() {
+        assert!(validate_rate(&rate(None, 0.0, 100.0, None)).is_err());
+        assert!(validate_rate(&rate(None, 500.0, -1.0, None)).is_err());
+        assert_eq!(
+            validate_rate(&rate(None, 500.0, 0.0, Some("hour"))),
+            Err("Invalid rate basis 'hour', expected one of: day, month".to_string())
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::attendance::types::ImportError;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimumWageRate {
+    pub id: i64,
+    pub state: String,
+    /// Empty for a rate that applies to the whole state
+    pub zone: String,
+    pub category: String,
+    pub effective_from: String,
+    pub basic: f64,
+    /// Variable dearness allowance, revised every six months
+    pub vda: f64,
+    pub total: f64,
+    /// `day` or `month`, as the rate is notified
+    pub rate_basis: String,
+    /// The total for a month, taking a daily rate for 26 working days
+    pub monthly_total: f64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimumWageRateInput {
+    pub state: String,
+    pub zone: Option<String>,
+    pub category: String,
+    pub effective_from: String,
+    pub basic: f64,
+    pub vda: f64,
+    /// `day` or `month`; `month` when empty
+    pub rate_basis: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobPostWageCategory {
+    pub id: i64,
+    pub job_post: String,
+    pub category: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimumWageImportResult {
+    pub imported: i64,
+    pub errors: Vec<ImportError>,
+}
+
+/// An employee whose wage is below the minimum or who could not be checked
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimumWageIssue {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub category: Option<String>,
+    pub site_name: Option<String>,
+    pub state: Option<String>,
+    pub zone: Option<String>,
+    /// Basic and DA of the employee's wage structure
+    pub configured_wage: Option<f64>,
+    /// Basic and VDA of the applicable minimum wage rate for a month
+    pub minimum_wage: Option<f64>,
+    pub shortfall: Option<f64>,
+    pub rate_effective_from: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimumWageCheck {
+    pub as_on: String,
+    pub checked: i64,
+    pub below_minimum: Vec<MinimumWageIssue>,
+    pub unchecked: Vec<MinimumWageIssue>,
+}
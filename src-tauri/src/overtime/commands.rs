@@ -0,0 +1,535 @@
+use super::helpers::{
+    attendance_worked_days, get_overtime_by_id, map_overtime, overtime_hours, roster_worked_days,
+    week_start, OVERTIME_SELECT,
+};
+use super::types::{
+    EmployeeOvertimeTotal, OvertimeDerivation, OvertimeRecord, OvertimeRecordInput,
+    OvertimeSummary, SiteOvertimeTotal,
+};
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, month_bounds, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payroll::helpers::{
+    ensure_month_unlocked, find_wage_structure, get_pay_run_by_month, get_pay_run_lines,
+    line_item_amount, load_payroll_settings, overtime_hourly_rate, round2,
+};
+use crate::payslips::helpers::{format_amount, month_title};
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use tauri::State;
+
+/// Validate a manual record and return its date and overtime hours
+fn validate_overtime(
+    conn: &rusqlite::Connection,
+    record: &OvertimeRecordInput,
+) -> Result<(String, f64), String> {
+    let settings = load_payroll_settings(conn)?;
+    let work_date = parse_date(&record.work_date, "work date")?;
+    ensure_month_unlocked(conn, &work_date.format("%Y-%m").to_string())?;
+
+    if !record.hours_worked.is_finite() || record.hours_worked <= 0.0 || record.hours_worked > 24.0
+    {
+        return Err("Hours worked must be between 0 and 24".to_string());
+    }
+    let ot_hours = record
+        .ot_hours
+        .unwrap_or((record.hours_worked - settings.ot_daily_hours).max(0.0));
+    if !ot_hours.is_finite() || ot_hours <= 0.0 || ot_hours > record.hours_worked {
+        return Err("Overtime must be more than zero and within the hours worked".to_string());
+    }
+
+    Ok((format_date(work_date), round2(ot_hours)))
+}
+
+fn map_overtime_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Overtime is already recorded for this employee on that date".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+/// Overtime records of a `YYYY-MM` month
+#[tauri::command]
+pub fn get_overtime_records(
+    _state: State<AppState>,
+    month: String,
+    employee_id: Option<i64>,
+    site_id: Option<i64>,
+    status: Option<String>,
+) -> Result<Vec<OvertimeRecord>, String> {
+    let conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{OVERTIME_SELECT}
+            WHERE o.work_date BETWEEN ?1 AND ?2
+              AND (?3 IS NULL OR o.employee_id = ?3)
+              AND (?4 IS NULL OR o.site_id = ?4)
+              AND (?5 IS NULL OR o.status = ?5)
+            ORDER BY o.work_date, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let records = stmt
+        .query_map(
+            params![
+                format_date(first),
+                format_date(last),
+                employee_id,
+                site_id,
+                status
+            ],
+            map_overtime,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(records)
+}
+
+/// Work out the overtime of a `YYYY-MM` month from `attendance` or the
+/// `roster` with the daily and weekly limits in the payroll settings. Pending
+/// records derived earlier from the same source are replaced; days that
+/// already have an approved, rejected or manual record are left alone.
+#[tauri::command]
+pub fn derive_overtime(
+    _state: State<AppState>,
+    month: String,
+    source: String,
+) -> Result<OvertimeDerivation, String> {
+    let mut conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let month = first.format("%Y-%m").to_string();
+    ensure_month_unlocked(&conn, &month)?;
+    let settings = load_payroll_settings(&conn)?;
+
+    // Weekly hours of the first week include its days in the previous month
+    let from = week_start(first);
+    let worked = match source.as_str() {
+        "attendance" => attendance_worked_days(&conn, &settings, from, last)?,
+        "roster" => roster_worked_days(&conn, from, last)?,
+        other => return Err(format!("Unknown overtime source '{other}'")),
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM overtime_records
+        WHERE source = ?1 AND status = 'pending' AND work_date BETWEEN ?2 AND ?3",
+        params![source, format_date(first), format_date(last)],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut created = 0;
+    let mut kept = 0;
+    for (employee_id, days) in &worked {
+        for (day, ot_hours) in days.iter().zip(overtime_hours(days, &settings)) {
+            if day.date < first || ot_hours <= 0.0 {
+                continue;
+            }
+            let inserted = tx
+                .execute(
+                    "INSERT INTO overtime_records (
+                        employee_id, work_date, site_id, hours_worked, ot_hours, source
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    ON CONFLICT (employee_id, work_date) DO NOTHING",
+                    params![
+                        employee_id,
+                        format_date(day.date),
+                        day.site_id,
+                        round2(day.hours),
+                        ot_hours,
+                        source,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if inserted > 0 {
+                created += 1;
+            } else {
+                kept += 1;
+            }
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(OvertimeDerivation {
+        month,
+        created,
+        kept,
+    })
+}
+
+#[tauri::command]
+pub fn create_overtime_record(
+    _state: State<AppState>,
+    record: OvertimeRecordInput,
+) -> Result<OvertimeRecord, String> {
+    let conn = db::get_connection()?;
+
+    get_employee_by_id(&conn, record.employee_id)?.ok_or("Employee not found")?;
+    let (work_date, ot_hours) = validate_overtime(&conn, &record)?;
+
+    conn.execute(
+        "INSERT INTO overtime_records (
+            employee_id, work_date, site_id, hours_worked, ot_hours, source, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, 'manual', ?6)",
+        params![
+            record.employee_id,
+            work_date,
+            record.site_id,
+            round2(record.hours_worked),
+            ot_hours,
+            record.remarks,
+        ],
+    )
+    .map_err(map_overtime_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(record)) = get_overtime_by_id(&conn, id) {
+        Ok(record)
+    } else {
+        Err("Failed to retrieve overtime record".to_string())
+    }
+}
+
+/// Correct a record before it is approved. The record becomes manual so that
+/// deriving overtime again does not overwrite it.
+#[tauri::command]
+pub fn update_overtime_record(
+    _state: State<AppState>,
+    id: i64,
+    record: OvertimeRecordInput,
+) -> Result<OvertimeRecord, String> {
+    let conn = db::get_connection()?;
+
+    let existing = get_overtime_by_id(&conn, id)?.ok_or("Overtime record not found")?;
+    if existing.status == "approved" {
+        return Err("Approved overtime cannot be changed".to_string());
+    }
+    let (work_date, ot_hours) = validate_overtime(&conn, &record)?;
+
+    conn.execute(
+        "UPDATE overtime_records SET
+            employee_id = ?1, work_date = ?2, site_id = ?3, hours_worked = ?4, ot_hours = ?5,
+            source = 'manual', status = 'pending', remarks = ?6, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?7",
+        params![
+            record.employee_id,
+            work_date,
+            record.site_id,
+            round2(record.hours_worked),
+            ot_hours,
+            record.remarks,
+            id,
+        ],
+    )
+    .map_err(map_overtime_error)?;
+
+    if let Ok(Some(record)) = get_overtime_by_id(&conn, id) {
+        Ok(record)
+    } else {
+        Err("Failed to retrieve overtime record".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_overtime_record(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let record = get_overtime_by_id(&conn, id)?.ok_or("Overtime record not found")?;
+    ensure_month_unlocked(&conn, &record.work_date[..7])?;
+
+    conn.execute("DELETE FROM overtime_records WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Set the status of records to `approved` or `rejected`. Approved overtime
+/// is paid by the next payroll run of its month.
+#[tauri::command]
+pub fn set_overtime_status(
+    _state: State<AppState>,
+    ids: Vec<i64>,
+    status: String,
+    remarks: Option<String>,
+) -> Result<Vec<OvertimeRecord>, String> {
+    let mut conn = db::get_connection()?;
+
+    if !matches!(status.as_str(), "approved" | "rejected" | "pending") {
+        return Err(format!("Unknown overtime status '{status}'"));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut records = Vec::new();
+    for id in ids {
+        let record = get_overtime_by_id(&tx, id)?.ok_or("Overtime record not found")?;
+        ensure_month_unlocked(&tx, &record.work_date[..7])?;
+
+        tx.execute(
+            "UPDATE overtime_records SET
+                status = ?1, approved_on = ?2, remarks = IFNULL(?3, remarks),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?4",
+            params![
+                status,
+                (status == "approved").then(|| format_date(today())),
+                remarks,
+                id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        records.push(get_overtime_by_id(&tx, id)?.ok_or("Failed to retrieve overtime record")?);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(records)
+}
+
+/// Overtime hours of a `YYYY-MM` month per employee and per site, with the
+/// pay for the approved hours at the month's wage structures
+#[tauri::command]
+pub fn get_overtime_summary(
+    _state: State<AppState>,
+    month: String,
+) -> Result<OvertimeSummary, String> {
+    let conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let settings = load_payroll_settings(&conn)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{OVERTIME_SELECT}
+            WHERE o.work_date BETWEEN ?1 AND ?2 AND o.status <> 'rejected'
+            ORDER BY e.name COLLATE NOCASE, o.work_date"
+        ))
+        .map_err(|e| e.to_string())?;
+    let records = stmt
+        .query_map(params![format_date(first), format_date(last)], map_overtime)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let split = |record: &OvertimeRecord| {
+        if record.status == "approved" {
+            (record.ot_hours, 0.0)
+        } else {
+            (0.0, record.ot_hours)
+        }
+    };
+
+    let mut by_employee: Vec<EmployeeOvertimeTotal> = Vec::new();
+    let mut sites: BTreeMap<Option<i64>, (SiteOvertimeTotal, HashSet<i64>)> = BTreeMap::new();
+    for record in &records {
+        let (approved, pending) = split(record);
+
+        match by_employee.last_mut() {
+            Some(total) if total.employee_id == record.employee_id => {
+                total.days += 1;
+                total.approved_hours += approved;
+                total.pending_hours += pending;
+            }
+            _ => by_employee.push(EmployeeOvertimeTotal {
+                employee_id: record.employee_id,
+                employee_name: record.employee_name.clone(),
+                essid: record.essid.clone(),
+                days: 1,
+                approved_hours: approved,
+                pending_hours: pending,
+                amount: None,
+            }),
+        }
+
+        let (site, employees) = sites.entry(record.site_id).or_insert_with(|| {
+            (
+                SiteOvertimeTotal {
+                    site_id: record.site_id,
+                    site_name: record.site_name.clone(),
+                    employees: 0,
+                    days: 0,
+                    approved_hours: 0.0,
+                    pending_hours: 0.0,
+                },
+                HashSet::new(),
+            )
+        });
+        employees.insert(record.employee_id);
+        site.employees = employees.len() as i64;
+        site.days += 1;
+        site.approved_hours = round2(site.approved_hours + approved);
+        site.pending_hours = round2(site.pending_hours + pending);
+    }
+
+    for total in &mut by_employee {
+        total.approved_hours = round2(total.approved_hours);
+        total.pending_hours = round2(total.pending_hours);
+        let employee = get_employee_by_id(&conn, total.employee_id)?;
+        let job_post = employee.and_then(|e| e.job_post).unwrap_or_default();
+        if let Some(structure) = find_wage_structure(&conn, &job_post, last)? {
+            let rate = overtime_hourly_rate(&settings, &structure) * settings.ot_rate_multiplier;
+            total.amount = Some((rate * total.approved_hours).round());
+        }
+    }
+
+    Ok(OvertimeSummary {
+        month: first.format("%Y-%m").to_string(),
+        approved_hours: round2(by_employee.iter().map(|t| t.approved_hours).sum()),
+        pending_hours: round2(by_employee.iter().map(|t| t.pending_hours).sum()),
+        by_employee,
+        by_site: sites.into_values().map(|(site, _)| site).collect(),
+    })
+}
+
+/// Write the register of overtime (Form IV) of a `YYYY-MM` month with one row
+/// per day of approved overtime. Earnings come from the month's pay run once
+/// payroll has been run.
+#[tauri::command]
+pub fn export_overtime_register(
+    _state: State<AppState>,
+    month: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let month = first.format("%Y-%m").to_string();
+    let settings = load_payroll_settings(&conn)?;
+    let profile = load_company_profile(&conn)?;
+    let pay_run = get_pay_run_by_month(&conn, &month)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{OVERTIME_SELECT}
+            WHERE o.work_date BETWEEN ?1 AND ?2 AND o.status = 'approved'
+            ORDER BY e.name COLLATE NOCASE, o.employee_id, o.work_date"
+        ))
+        .map_err(|e| e.to_string())?;
+    let records = stmt
+        .query_map(params![format_date(first), format_date(last)], map_overtime)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if records.is_empty() {
+        return Err(format!("No approved overtime in {}", month_title(&month)));
+    }
+
+    let columns = vec![
+        TableColumn::new("Sl", 20.0, Align::Right),
+        TableColumn::new("Name", 90.0, Align::Left),
+        TableColumn::new("Father's name", 80.0, Align::Left),
+        TableColumn::new("Designation", 75.0, Align::Left),
+        TableColumn::new("Date", 52.0, Align::Left),
+        TableColumn::new("OT hours", 40.0, Align::Right),
+        TableColumn::new("Total OT", 40.0, Align::Right),
+        TableColumn::new("Normal hrs", 54.0, Align::Right),
+        TableColumn::new("Normal rate", 58.0, Align::Right),
+        TableColumn::new("OT rate", 44.0, Align::Right),
+        TableColumn::new("Normal pay", 56.0, Align::Right),
+        TableColumn::new("OT pay", 50.0, Align::Right),
+        TableColumn::new("Total pay", 56.0, Align::Right),
+        TableColumn::new("Date paid", 52.0, Align::Left),
+    ];
+    let mut report = TableReport::new("Form IV - Register of Overtime", columns);
+    report.subtitle_lines = vec![
+        format!(
+            "Name of the establishment: {}    Month: {}",
+            profile.name,
+            month_title(&month)
+        ),
+        format!(
+            "Overtime is work beyond {} hours a day or {} hours a week, paid at {} times the ordinary rate",
+            settings.ot_daily_hours, settings.ot_weekly_hours, settings.ot_rate_multiplier
+        ),
+    ];
+
+    let paid_on = pay_run
+        .as_ref()
+        .filter(|p| p.status == "locked")
+        .and_then(|p| p.locked_at.as_deref())
+        .map(|at| at.chars().take(10).collect::<String>())
+        .unwrap_or_default();
+    let mut serial = 0;
+    let mut total_hours = 0.0;
+    let mut employee = None;
+    for (index, record) in records.iter().enumerate() {
+        let first_of_employee = index == 0 || records[index - 1].employee_id != record.employee_id;
+        let last_of_employee = records
+            .get(index + 1)
+            .is_none_or(|next| next.employee_id != record.employee_id);
+        total_hours += record.ot_hours;
+
+        let mut row = vec![String::new(); 14];
+        if first_of_employee {
+            serial += 1;
+            employee = get_employee_by_id(&conn, record.employee_id)?;
+            row[0] = serial.to_string();
+            row[1] = record.employee_name.clone();
+            row[2] = employee
+                .as_ref()
+                .and_then(|e| e.father_name.clone())
+                .unwrap_or_default();
+            row[3] = employee
+                .as_ref()
+                .and_then(|e| e.job_post.clone())
+                .unwrap_or_default();
+        }
+        row[4] = record.work_date.clone();
+        row[5] = record.ot_hours.to_string();
+
+        if last_of_employee {
+            let employee_hours: f64 = records
+                .iter()
+                .filter(|r| r.employee_id == record.employee_id)
+                .map(|r| r.ot_hours)
+                .sum();
+            row[6] = round2(employee_hours).to_string();
+            row[7] = settings.ot_daily_hours.to_string();
+
+            let job_post = employee
+                .as_ref()
+                .and_then(|e| e.job_post.as_deref())
+                .unwrap_or_default();
+            if let Some(structure) = find_wage_structure(&conn, job_post, last)? {
+                let rate = overtime_hourly_rate(&settings, &structure);
+                row[8] = format_amount(rate);
+                row[9] = format_amount(round2(rate * settings.ot_rate_multiplier));
+            }
+            let line = match &pay_run {
+                Some(pay_run) => get_pay_run_lines(&conn, pay_run.id, Some(record.employee_id))?
+                    .into_iter()
+                    .next(),
+                None => None,
+            };
+            if let Some(line) = line {
+                let ot_pay = line_item_amount(&line, "OT");
+                row[10] = format_amount(line.gross_pay - ot_pay);
+                row[11] = format_amount(ot_pay);
+                row[12] = format_amount(line.gross_pay);
+                row[13] = paid_on.clone();
+            }
+        }
+        report.rows.push(row);
+    }
+    report.footer_lines = vec![
+        format!(
+            "Employees: {serial}    Total overtime: {} hours",
+            round2(total_hours)
+        ),
+        "OT pay includes overtime shifts paid at the wage structure's OT rate.".to_string(),
+    ];
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
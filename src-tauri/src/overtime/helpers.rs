@@ -0,0 +1,255 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::{params, Row};
+use std::collections::BTreeMap;
+
+use super::types::OvertimeRecord;
+use crate::dates::{format_date, parse_date};
+use crate::payroll::helpers::round2;
+use crate::payroll::types::PayrollSettings;
+use crate::roster::helpers::{get_roster_between, shift_interval};
+
+pub const OVERTIME_SELECT: &str = "SELECT o.id, o.employee_id, e.name, e.essid, o.work_date,
+        o.site_id, s.name, o.hours_worked, o.ot_hours, o.source, o.status, o.approved_on,
+        o.remarks, o.created_at, o.updated_at
+    FROM overtime_records o
+    JOIN employees e ON e.id = o.employee_id
+    LEFT JOIN sites s ON s.id = o.site_id";
+
+/// Hours worked by one employee on one day and the site they worked at
+pub struct WorkedDay {
+    pub date: NaiveDate,
+    pub hours: f64,
+    pub site_id: Option<i64>,
+}
+
+pub fn map_overtime(row: &Row) -> rusqlite::Result<OvertimeRecord> {
+    Ok(OvertimeRecord {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        work_date: row.get(4)?,
+        site_id: row.get(5)?,
+        site_name: row.get(6)?,
+        hours_worked: row.get(7)?,
+        ot_hours: row.get(8)?,
+        source: row.get(9)?,
+        status: row.get(10)?,
+        approved_on: row.get(11)?,
+        remarks: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+    })
+}
+
+pub fn get_overtime_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<OvertimeRecord>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{OVERTIME_SELECT} WHERE o.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_overtime) {
+        Ok(record) => Ok(Some(record)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Monday of the week a date falls in
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Overtime of each day of `days`, sorted by date: hours beyond the daily
+/// limit, plus ordinary hours that take the week beyond the weekly limit.
+/// Weeks run Monday to Sunday.
+pub fn overtime_hours(days: &[WorkedDay], settings: &PayrollSettings) -> Vec<f64> {
+    let mut week = None;
+    let mut week_ordinary = 0.0;
+
+    days.iter()
+        .map(|day| {
+            if week != Some(week_start(day.date)) {
+                week = Some(week_start(day.date));
+                week_ordinary = 0.0;
+            }
+            let daily = (day.hours - settings.ot_daily_hours).max(0.0);
+            let ordinary = day.hours - daily;
+            let over_week = |hours: f64| (hours - settings.ot_weekly_hours).max(0.0);
+            let weekly = over_week(week_ordinary + ordinary) - over_week(week_ordinary);
+            week_ordinary += ordinary;
+            round2(daily + weekly)
+        })
+        .collect()
+}
+
+/// Days worked per employee between two dates according to attendance. A
+/// present day lasts as long as its shift, or the daily limit when no shift
+/// is recorded. OT shifts are left out as they are paid at the structure's
+/// OT rate.
+pub fn attendance_worked_days(
+    conn: &rusqlite::Connection,
+    settings: &PayrollSettings,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BTreeMap<i64, Vec<WorkedDay>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.employee_id, a.attendance_date, a.site_id, t.start_time, t.end_time
+            FROM attendance a
+            LEFT JOIN shift_templates t ON t.id = a.shift_template_id
+            WHERE a.status = 'present' AND a.attendance_date BETWEEN ?1 AND ?2
+            ORDER BY a.employee_id, a.attendance_date",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![format_date(from), format_date(to)], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut worked: BTreeMap<i64, Vec<WorkedDay>> = BTreeMap::new();
+    for (employee_id, date, site_id, start_time, end_time) in rows {
+        let date = parse_date(&date, "attendance date")?;
+        let hours = match (start_time, end_time) {
+            (Some(start), Some(end)) => {
+                let (start, end) = shift_interval(date, &start, &end)?;
+                (end - start).num_minutes() as f64 / 60.0
+            }
+            _ => settings.ot_daily_hours,
+        };
+        worked.entry(employee_id).or_default().push(WorkedDay {
+            date,
+            hours,
+            site_id,
+        });
+    }
+
+    Ok(worked)
+}
+
+/// Days worked per employee between two dates according to the roster. Only
+/// the first shift rostered on a day counts; further shifts of a double shift
+/// are OT shifts paid at the structure's OT rate, as with attendance.
+pub fn roster_worked_days(
+    conn: &rusqlite::Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BTreeMap<i64, Vec<WorkedDay>>, String> {
+    let mut worked: BTreeMap<i64, BTreeMap<NaiveDate, WorkedDay>> = BTreeMap::new();
+    for assignment in get_roster_between(conn, from, to, None, None)? {
+        let date = parse_date(&assignment.roster_date, "roster date")?;
+        let days = worked.entry(assignment.employee_id).or_default();
+        if days.contains_key(&date) {
+            continue;
+        }
+        let (start, end) = shift_interval(date, &assignment.start_time, &assignment.end_time)?;
+        days.insert(
+            date,
+            WorkedDay {
+                date,
+                hours: (end - start).num_minutes() as f64 / 60.0,
+                site_id: Some(assignment.site_id),
+            },
+        );
+    }
+
+    Ok(worked
+        .into_iter()
+        .map(|(employee_id, days)| (employee_id, days.into_values().collect()))
+        .collect())
+}
+
+pub fn approved_ot_hours(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT IFNULL(SUM(ot_hours), 0) FROM overtime_records
+        WHERE employee_id = ?1 AND status = 'approved' AND work_date BETWEEN ?2 AND ?3",
+        params![employee_id, format_date(from), format_date(to)],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+    use crate::payroll::helpers::load_payroll_settings;
+
+    fn settings() -> PayrollSettings {
+        let mut settings = load_payroll_settings(&open_test_db()).unwrap();
+        settings.ot_daily_hours = 8.0;
+        settings.ot_weekly_hours = 48.0;
+        settings
+    }
+
+    /// Consecutive days from `first` with the given hours
+    fn days(first: &str, hours: &[f64]) -> Vec<WorkedDay> {
+        let first = parse_date(first, "date").unwrap();
+        hours
+            .iter()
+            .enumerate()
+            .map(|(offset, hours)| WorkedDay {
+                date: first + Duration::days(offset as i64),
+                hours: *hours,
+                site_id: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hours_beyond_the_daily_limit() {
+        let hours = overtime_hours(&days("2024-01-01", &[12.0, 8.0, 7.5, 9.25]), &settings());
+        assert_eq!(hours, vec![4.0, 0.0, 0.0, 1.25]);
+    }
+
+    #[test]
+    fn seven_eight_hour_days() {
+        // 2024-01-01 is a Monday; Sunday takes the week past 48 hours and the
+        // next Monday starts a new week
+        let hours = overtime_hours(
+            &days("2024-01-01", &[8.0, 8.0, 8.0, 8.0, 8.0, 8.0, 8.0, 8.0]),
+            &settings(),
+        );
+        assert_eq!(hours, vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 8.0, 0.0]);
+    }
+
+    #[test]
+    fn daily_overtime_is_not_counted_again_for_the_week() {
+        let hours = overtime_hours(
+            &days("2024-01-01", &[12.0, 12.0, 12.0, 12.0, 12.0, 12.0, 10.0]),
+            &settings(),
+        );
+        assert_eq!(hours, vec![4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 10.0]);
+    }
+
+    #[test]
+    fn week_crossing_the_month_start() {
+        let first = parse_date("2024-06-01", "date").unwrap();
+        assert_eq!(format_date(week_start(first)), "2024-05-27");
+
+        // Monday 27 May to Sunday 2 June
+        let week = days("2024-05-27", &[8.0, 8.0, 8.0, 8.0, 8.0, 10.0, 8.0]);
+        let hours = overtime_hours(&week, &settings());
+        assert_eq!(&hours[5..], &[2.0, 8.0]);
+
+        // Without the days in May the weekly limit is never reached
+        let hours = overtime_hours(&week[5..], &settings());
+        assert_eq!(hours, vec![2.0, 0.0]);
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OvertimeRecord {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub work_date: String,
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub hours_worked: f64,
+    pub ot_hours: f64,
+    /// `attendance`, `roster` or `manual`
+    pub source: String,
+    /// `pending`, `approved` or `rejected`
+    pub status: String,
+    pub approved_on: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OvertimeRecordInput {
+    pub employee_id: i64,
+    pub work_date: String,
+    pub site_id: Option<i64>,
+    pub hours_worked: f64,
+    /// Hours beyond the daily limit when empty
+    pub ot_hours: Option<f64>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OvertimeDerivation {
+    pub month: String,
+    pub created: i64,
+    /// Days with overtime that already had an approved, rejected or manual
+    /// record, which is left as it is
+    pub kept: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmployeeOvertimeTotal {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub days: i64,
+    pub approved_hours: f64,
+    pub pending_hours: f64,
+    /// Pay for the approved hours, empty without a wage structure
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteOvertimeTotal {
+    pub site_id: Option<i64>,
+    pub site_name: Option<String>,
+    pub employees: i64,
+    pub days: i64,
+    pub approved_hours: f64,
+    pub pending_hours: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OvertimeSummary {
+    pub month: String,
+    pub approved_hours: f64,
+    pub pending_hours: f64,
+    pub by_employee: Vec<EmployeeOvertimeTotal>,
+    pub by_site: Vec<SiteOvertimeTotal>,
+}
@@ -0,0 +1,506 @@
+use super::helpers::{
+    compute_pay, ensure_month_unlocked, eps_eligible, find_wage_structure, get_pay_run_by_id,
+    get_pay_run_by_month, get_pay_run_lines, get_wage_structure_by_id, load_payroll_settings,
+    map_pay_run, map_wage_structure, round2, PAY_RUN_SELECT,
+};
+use super::types::{
+    PayRun, PayRunLine, PayrollRunResult, PayrollSettings, PayrollSkip, WageStructure,
+    WageStructureInput,
+};
+use crate::advances::helpers::{advance_instalments, insert_recovery, RECOVERY_CODE};
+use crate::attendance::helpers::attendance_summaries;
+use crate::dates::{format_date, month_bounds, parse_date};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::overtime::helpers::approved_ot_hours;
+use crate::settlements::helpers::is_settled;
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use std::collections::HashMap;
+use tauri::State;
+
+fn validate_wage_structure(structure: &WageStructureInput) -> Result<String, String> {
+    if structure.job_post.trim().is_empty() {
+        return Err("Job post is required".to_string());
+    }
+    let amounts = [
+        structure.basic,
+        structure.da,
+        structure.hra,
+        structure.other_allowances,
+        structure.ot_rate,
+    ];
+    if amounts
+        .iter()
+        .any(|amount| !amount.is_finite() || *amount < 0.0)
+    {
+        return Err("Wage amounts cannot be negative".to_string());
+    }
+    Ok(format_date(parse_date(
+        &structure.effective_from,
+        "effective from date",
+    )?))
+}
+
+fn map_wage_structure_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "A wage structure for this job post already starts on that date".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_wage_structures(
+    _state: State<AppState>,
+    job_post: Option<String>,
+) -> Result<Vec<WageStructure>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM wage_structures
+            WHERE ?1 IS NULL OR job_post = ?1
+            ORDER BY job_post, effective_from DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let structures = stmt
+        .query_map([job_post.as_deref().map(str::trim)], map_wage_structure)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(structures)
+}
+
+#[tauri::command]
+pub fn create_wage_structure(
+    _state: State<AppState>,
+    structure: WageStructureInput,
+) -> Result<WageStructure, String> {
+    let conn = db::get_connection()?;
+
+    let effective_from = validate_wage_structure(&structure)?;
+
+    conn.execute(
+        "INSERT INTO wage_structures (
+            job_post, effective_from, basic, da, hra, other_allowances, ot_rate
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            structure.job_post.trim(),
+            effective_from,
+            structure.basic,
+            structure.da,
+            structure.hra,
+            structure.other_allowances,
+            structure.ot_rate,
+        ],
+    )
+    .map_err(map_wage_structure_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(structure)) = get_wage_structure_by_id(&conn, id) {
+        Ok(structure)
+    } else {
+        Err("Failed to retrieve wage structure".to_string())
+    }
+}
+
+/// Rate changes only affect pay runs computed afterwards, locked months keep
+/// the amounts they were computed with
+#[tauri::command]
+pub fn update_wage_structure(
+    _state: State<AppState>,
+    id: i64,
+    structure: WageStructureInput,
+) -> Result<WageStructure, String> {
+    let conn = db::get_connection()?;
+
+    let effective_from = validate_wage_structure(&structure)?;
+
+    conn.execute(
+        "UPDATE wage_structures SET
+            job_post = ?1, effective_from = ?2, basic = ?3, da = ?4, hra = ?5,
+            other_allowances = ?6, ot_rate = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            structure.job_post.trim(),
+            effective_from,
+            structure.basic,
+            structure.da,
+            structure.hra,
+            structure.other_allowances,
+            structure.ot_rate,
+            id,
+        ],
+    )
+    .map_err(map_wage_structure_error)?;
+
+    if let Ok(Some(structure)) = get_wage_structure_by_id(&conn, id) {
+        Ok(structure)
+    } else {
+        Err("Failed to retrieve wage structure".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_wage_structure(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM wage_structures WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_payroll_settings(_state: State<AppState>) -> Result<PayrollSettings, String> {
+    let conn = db::get_connection()?;
+    load_payroll_settings(&conn)
+}
+
+/// Save the statutory rates and replace the professional tax slabs
+#[tauri::command]
+pub fn update_payroll_settings(
+    _state: State<AppState>,
+    settings: PayrollSettings,
+) -> Result<PayrollSettings, String> {
+    let mut conn = db::get_connection()?;
+
+    let rates = [
+        settings.pf_employee_rate,
+        settings.pf_employer_rate,
+        settings.eps_rate,
+        settings.esi_employee_rate,
+        settings.esi_employer_rate,
+    ];
+    if rates
+        .iter()
+        .any(|rate| !rate.is_finite() || *rate < 0.0 || *rate > 100.0)
+    {
+        return Err("Contribution rates must be percentages between 0 and 100".to_string());
+    }
+    if settings.eps_rate > settings.pf_employer_rate {
+        return Err("Pension rate cannot exceed the employer PF rate".to_string());
+    }
+    if settings.pf_wage_ceiling <= 0.0
+        || settings.esi_wage_ceiling <= 0.0
+        || settings.gratuity_ceiling <= 0.0
+    {
+        return Err("Ceilings must be greater than zero".to_string());
+    }
+    if !(settings.ot_daily_hours > 0.0 && settings.ot_daily_hours <= 24.0)
+        || settings.ot_weekly_hours <= 0.0
+    {
+        return Err("Overtime hour limits must be greater than zero".to_string());
+    }
+    if !settings.ot_rate_multiplier.is_finite() || settings.ot_rate_multiplier < 1.0 {
+        return Err("Overtime rate multiplier cannot be less than 1".to_string());
+    }
+    for slab in &settings.professional_tax_slabs {
+        if slab.min_gross < 0.0 || slab.amount < 0.0 {
+            return Err("Professional tax slabs cannot have negative amounts".to_string());
+        }
+        if slab.max_gross.is_some_and(|max| max < slab.min_gross) {
+            return Err(format!(
+                "Professional tax slab starting at {} ends before it starts",
+                slab.min_gross
+            ));
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE payroll_settings SET
+            pf_employee_rate = ?1, pf_employer_rate = ?2, eps_rate = ?3, pf_wage_ceiling = ?4,
+            restrict_pf_to_ceiling = ?5, esi_employee_rate = ?6, esi_employer_rate = ?7,
+            esi_wage_ceiling = ?8, gratuity_ceiling = ?9, ot_daily_hours = ?10,
+            ot_weekly_hours = ?11, ot_rate_multiplier = ?12, updated_at = CURRENT_TIMESTAMP
+        WHERE id = 1",
+        params![
+            settings.pf_employee_rate,
+            settings.pf_employer_rate,
+            settings.eps_rate,
+            settings.pf_wage_ceiling,
+            settings.restrict_pf_to_ceiling,
+            settings.esi_employee_rate,
+            settings.esi_employer_rate,
+            settings.esi_wage_ceiling,
+            settings.gratuity_ceiling,
+            settings.ot_daily_hours,
+            settings.ot_weekly_hours,
+            settings.ot_rate_multiplier,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM professional_tax_slabs", [])
+        .map_err(|e| e.to_string())?;
+    for slab in &settings.professional_tax_slabs {
+        tx.execute(
+            "INSERT INTO professional_tax_slabs (min_gross, max_gross, amount)
+             VALUES (?1, ?2, ?3)",
+            params![slab.min_gross, slab.max_gross, slab.amount],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_payroll_settings(&conn)
+}
+
+#[tauri::command]
+pub fn get_pay_runs(_state: State<AppState>) -> Result<Vec<PayRun>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{PAY_RUN_SELECT} GROUP BY p.id ORDER BY p.month DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let pay_runs = stmt
+        .query_map([], map_pay_run)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pay_runs)
+}
+
+#[tauri::command]
+pub fn get_pay_run_details(
+    _state: State<AppState>,
+    pay_run_id: i64,
+    employee_id: Option<i64>,
+) -> Result<Vec<PayRunLine>, String> {
+    let conn = db::get_connection()?;
+
+    get_pay_run_by_id(&conn, pay_run_id)?.ok_or("Pay run not found")?;
+    get_pay_run_lines(&conn, pay_run_id, employee_id)
+}
+
+/// Compute the pay of every current employee and everyone with attendance in
+/// a `YYYY-MM` month. Running a month again replaces its draft figures.
+#[tauri::command]
+pub fn run_payroll(_state: State<AppState>, month: String) -> Result<PayrollRunResult, String> {
+    let mut conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let month = first.format("%Y-%m").to_string();
+    ensure_month_unlocked(&conn, &month)?;
+
+    let settings = load_payroll_settings(&conn)?;
+    let days_in_month = (last - first).num_days() + 1;
+
+    let attendance: HashMap<i64, _> = attendance_summaries(&conn, first, last, None, None)?
+        .into_iter()
+        .map(|summary| (summary.employee_id, summary))
+        .collect();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM employees
+            WHERE (employment_status = 'current' AND IFNULL(joining_date, '') <= ?2)
+               OR id IN (SELECT employee_id FROM attendance WHERE attendance_date BETWEEN ?1 AND ?2)
+            ORDER BY name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let employee_ids = stmt
+        .query_map(params![format_date(first), format_date(last)], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    drop(stmt);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO pay_runs (month) VALUES (?1)
+         ON CONFLICT (month) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
+        params![month],
+    )
+    .map_err(|e| e.to_string())?;
+    let pay_run_id: i64 = tx
+        .query_row(
+            "SELECT id FROM pay_runs WHERE month = ?1",
+            [&month],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM pay_run_lines WHERE pay_run_id = ?1",
+        params![pay_run_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut skipped = Vec::new();
+    for employee_id in employee_ids {
+        let employee = get_employee_by_id(&tx, employee_id)?.ok_or("Employee not found")?;
+        let skip = |reason: String| PayrollSkip {
+            employee_id,
+            employee_name: employee.name.clone(),
+            essid: employee.essid.clone(),
+            reason,
+        };
+
+        if is_settled(&tx, employee_id)? {
+            skipped.push(skip("Full and final settlement is done".to_string()));
+            continue;
+        }
+        let Some(job_post) = employee
+            .job_post
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+        else {
+            skipped.push(skip("No job post".to_string()));
+            continue;
+        };
+        let Some(structure) = find_wage_structure(&tx, job_post, last)? else {
+            skipped.push(skip(format!("No wage structure for {job_post} on {last}")));
+            continue;
+        };
+
+        let (paid_days, ot_shifts) = attendance
+            .get(&employee_id)
+            .map(|a| {
+                (
+                    a.present_days + a.leave_days + a.weekly_offs + a.holidays,
+                    a.ot_shifts,
+                )
+            })
+            .unwrap_or((0, 0));
+        if paid_days == 0 && ot_shifts == 0 {
+            skipped.push(skip("No paid days in the month".to_string()));
+            continue;
+        }
+
+        let mut pay = compute_pay(
+            &settings,
+            &structure,
+            days_in_month,
+            paid_days.min(days_in_month),
+            ot_shifts,
+            approved_ot_hours(&tx, employee_id, first, last)?,
+            eps_eligible(employee.date_of_birth.as_deref(), first),
+        );
+        let instalments = advance_instalments(&tx, employee_id, &month, pay.net_pay)?;
+        pay.add_deduction(
+            RECOVERY_CODE,
+            "Salary advance recovery",
+            round2(instalments.iter().map(|(_, amount)| amount).sum()),
+        );
+
+        tx.execute(
+            "INSERT INTO pay_run_lines (
+                pay_run_id, employee_id, employee_name, essid, job_post, uan, esiip,
+                days_in_month, paid_days, ot_shifts, gross_pay, pf_wages, eps_wages, esi_wages,
+                total_deductions, net_pay
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                pay_run_id,
+                employee_id,
+                employee.name,
+                employee.essid,
+                job_post,
+                employee.uan,
+                employee.esiip,
+                pay.days_in_month,
+                pay.paid_days,
+                pay.ot_shifts,
+                pay.gross_pay,
+                pay.pf_wages,
+                pay.eps_wages,
+                pay.esi_wages,
+                pay.total_deductions,
+                pay.net_pay,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let line_id = tx.last_insert_rowid();
+
+        for item in &pay.items {
+            tx.execute(
+                "INSERT INTO pay_run_line_items (line_id, kind, code, label, amount)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![line_id, item.kind, item.code, item.label, item.amount],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for (advance_id, amount) in instalments {
+            insert_recovery(
+                &tx,
+                advance_id,
+                Some(line_id),
+                &format_date(last),
+                amount,
+                None,
+            )?;
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let pay_run = get_pay_run_by_id(&conn, pay_run_id)?.ok_or("Failed to retrieve pay run")?;
+    let lines = get_pay_run_lines(&conn, pay_run_id, None)?;
+
+    Ok(PayrollRunResult {
+        pay_run,
+        lines,
+        skipped,
+    })
+}
+
+/// Freeze a pay run so that it can no longer be recomputed or deleted
+#[tauri::command]
+pub fn lock_pay_run(_state: State<AppState>, id: i64) -> Result<PayRun, String> {
+    let conn = db::get_connection()?;
+
+    let pay_run = get_pay_run_by_id(&conn, id)?.ok_or("Pay run not found")?;
+    if pay_run.status == "locked" {
+        return Err(format!("Payroll for {} is already locked", pay_run.month));
+    }
+
+    conn.execute(
+        "UPDATE pay_runs SET
+            status = 'locked', locked_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    get_pay_run_by_id(&conn, id)?.ok_or_else(|| "Failed to retrieve pay run".to_string())
+}
+
+#[tauri::command]
+pub fn delete_pay_run(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let pay_run = get_pay_run_by_id(&conn, id)?.ok_or("Pay run not found")?;
+    ensure_month_unlocked(&conn, &pay_run.month)?;
+
+    conn.execute("DELETE FROM pay_runs WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Pay run of a `YYYY-MM` month, if it has been computed
+#[tauri::command]
+pub fn get_pay_run_for_month(
+    _state: State<AppState>,
+    month: String,
+) -> Result<Option<PayRun>, String> {
+    let conn = db::get_connection()?;
+
+    let (first, _) = month_bounds(&month)?;
+    get_pay_run_by_month(&conn, &first.format("%Y-%m").to_string())
+}
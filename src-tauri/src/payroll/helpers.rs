@@ -0,0 +1,688 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Row};
+use std::collections::HashMap;
+
+use super::types::{
+    PayLineItem, PayRun, PayRunLine, PayrollSettings, ProfessionalTaxSlab, WageStructure,
+};
+use crate::dates::{format_date, parse_date};
+
+pub const PAY_RUN_SELECT: &str = "SELECT p.id, p.month, p.status, p.locked_at, COUNT(l.id),
+        IFNULL(SUM(l.gross_pay), 0), IFNULL(SUM(l.total_deductions), 0),
+        IFNULL(SUM(l.net_pay), 0), p.created_at, p.updated_at
+    FROM pay_runs p
+    LEFT JOIN pay_run_lines l ON l.pay_run_id = p.id";
+
+/// Employees stop contributing to the pension scheme at this age
+const EPS_AGE_LIMIT: u32 = 58;
+
+/// Working days a monthly wage is taken to cover when working out daily and
+/// hourly rates, for overtime, leave encashment, gratuity and daily minimum
+/// wages alike
+pub const WORKING_DAYS_PER_MONTH: f64 = 26.0;
+
+/// Amounts of one employee's pay for a month, before it is stored
+pub struct ComputedPay {
+    pub days_in_month: i64,
+    pub paid_days: i64,
+    pub ot_shifts: i64,
+    pub gross_pay: f64,
+    pub pf_wages: f64,
+    pub eps_wages: f64,
+    pub esi_wages: f64,
+    pub total_deductions: f64,
+    pub net_pay: f64,
+    pub items: Vec<PayLineItem>,
+}
+
+impl ComputedPay {
+    /// Deduct a further amount, such as a recovery, from the net pay
+    pub fn add_deduction(&mut self, code: &str, label: &str, amount: f64) {
+        if amount <= 0.0 {
+            return;
+        }
+        let position = self
+            .items
+            .iter()
+            .rposition(|i| i.kind == "deduction")
+            .map_or(self.items.len(), |p| p + 1);
+        self.items
+            .insert(position, item("deduction", code, label, amount));
+        self.total_deductions = round2(self.total_deductions + amount);
+        self.net_pay = round2(self.gross_pay - self.total_deductions);
+    }
+}
+
+pub fn map_wage_structure(row: &Row) -> rusqlite::Result<WageStructure> {
+    Ok(WageStructure {
+        id: row.get(0)?,
+        job_post: row.get(1)?,
+        effective_from: row.get(2)?,
+        basic: row.get(3)?,
+        da: row.get(4)?,
+        hra: row.get(5)?,
+        other_allowances: row.get(6)?,
+        ot_rate: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+pub fn map_pay_run(row: &Row) -> rusqlite::Result<PayRun> {
+    Ok(PayRun {
+        id: row.get(0)?,
+        month: row.get(1)?,
+        status: row.get(2)?,
+        locked_at: row.get(3)?,
+        employee_count: row.get(4)?,
+        total_gross: row.get(5)?,
+        total_deductions: row.get(6)?,
+        total_net: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+pub fn get_wage_structure_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<WageStructure>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM wage_structures WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_wage_structure) {
+        Ok(structure) => Ok(Some(structure)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The wage structure of a job post in force on `date`
+pub fn find_wage_structure(
+    conn: &rusqlite::Connection,
+    job_post: &str,
+    date: NaiveDate,
+) -> Result<Option<WageStructure>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM wage_structures
+            WHERE job_post = ?1 AND effective_from <= ?2
+            ORDER BY effective_from DESC
+            LIMIT 1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row(
+        params![job_post.trim(), format_date(date)],
+        map_wage_structure,
+    ) {
+        Ok(structure) => Ok(Some(structure)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_pay_run_by_id(conn: &rusqlite::Connection, id: i64) -> Result<Option<PayRun>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{PAY_RUN_SELECT} WHERE p.id = ?1 GROUP BY p.id"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_pay_run) {
+        Ok(pay_run) => Ok(Some(pay_run)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_pay_run_by_month(
+    conn: &rusqlite::Connection,
+    month: &str,
+) -> Result<Option<PayRun>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{PAY_RUN_SELECT} WHERE p.month = ?1 GROUP BY p.id"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([month], map_pay_run) {
+        Ok(pay_run) => Ok(Some(pay_run)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Lines of a pay run with their items, optionally for a single employee
+pub fn get_pay_run_lines(
+    conn: &rusqlite::Connection,
+    pay_run_id: i64,
+    employee_id: Option<i64>,
+) -> Result<Vec<PayRunLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.line_id, i.kind, i.code, i.label, i.amount
+            FROM pay_run_line_items i
+            JOIN pay_run_lines l ON l.id = i.line_id
+            WHERE l.pay_run_id = ?1 AND (?2 IS NULL OR l.employee_id = ?2)
+            ORDER BY i.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut items: HashMap<i64, Vec<PayLineItem>> = HashMap::new();
+    let rows = stmt
+        .query_map(params![pay_run_id, employee_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                PayLineItem {
+                    kind: row.get(1)?,
+                    code: row.get(2)?,
+                    label: row.get(3)?,
+                    amount: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (line_id, item) in rows {
+        items.entry(line_id).or_default().push(item);
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, pay_run_id, employee_id, employee_name, essid, job_post, uan, esiip,
+                days_in_month, paid_days, ot_shifts, gross_pay, pf_wages, eps_wages, esi_wages,
+                total_deductions, net_pay
+            FROM pay_run_lines
+            WHERE pay_run_id = ?1 AND (?2 IS NULL OR employee_id = ?2)
+            ORDER BY employee_name COLLATE NOCASE, essid",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let lines = stmt
+        .query_map(params![pay_run_id, employee_id], |row| {
+            let id: i64 = row.get(0)?;
+            Ok(PayRunLine {
+                id,
+                pay_run_id: row.get(1)?,
+                employee_id: row.get(2)?,
+                employee_name: row.get(3)?,
+                essid: row.get(4)?,
+                job_post: row.get(5)?,
+                uan: row.get(6)?,
+                esiip: row.get(7)?,
+                days_in_month: row.get(8)?,
+                paid_days: row.get(9)?,
+                ot_shifts: row.get(10)?,
+                gross_pay: row.get(11)?,
+                pf_wages: row.get(12)?,
+                eps_wages: row.get(13)?,
+                esi_wages: row.get(14)?,
+                total_deductions: row.get(15)?,
+                net_pay: row.get(16)?,
+                items: items.remove(&id).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(lines)
+}
+
+pub fn load_payroll_settings(conn: &rusqlite::Connection) -> Result<PayrollSettings, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT min_gross, max_gross, amount FROM professional_tax_slabs ORDER BY min_gross",
+        )
+        .map_err(|e| e.to_string())?;
+    let professional_tax_slabs = stmt
+        .query_map([], |row| {
+            Ok(ProfessionalTaxSlab {
+                min_gross: row.get(0)?,
+                max_gross: row.get(1)?,
+                amount: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT pf_employee_rate, pf_employer_rate, eps_rate, pf_wage_ceiling,
+            restrict_pf_to_ceiling, esi_employee_rate, esi_employer_rate, esi_wage_ceiling,
+            gratuity_ceiling, ot_daily_hours, ot_weekly_hours, ot_rate_multiplier
+        FROM payroll_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(PayrollSettings {
+                pf_employee_rate: row.get(0)?,
+                pf_employer_rate: row.get(1)?,
+                eps_rate: row.get(2)?,
+                pf_wage_ceiling: row.get(3)?,
+                restrict_pf_to_ceiling: row.get(4)?,
+                esi_employee_rate: row.get(5)?,
+                esi_employer_rate: row.get(6)?,
+                esi_wage_ceiling: row.get(7)?,
+                gratuity_ceiling: row.get(8)?,
+                ot_daily_hours: row.get(9)?,
+                ot_weekly_hours: row.get(10)?,
+                ot_rate_multiplier: row.get(11)?,
+                professional_tax_slabs,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to load payroll settings: {e}"))
+}
+
+/// Refuse changes to a month whose pay run has been locked
+pub fn ensure_month_unlocked(conn: &rusqlite::Connection, month: &str) -> Result<(), String> {
+    match get_pay_run_by_month(conn, month)? {
+        Some(pay_run) if pay_run.status == "locked" => {
+            Err(format!("Payroll for {month} is locked"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Total of a pay run line's items with the given code
+pub fn line_item_amount(line: &PayRunLine, code: &str) -> f64 {
+    line.items
+        .iter()
+        .filter(|item| item.code == code)
+        .map(|item| item.amount)
+        .sum()
+}
+
+pub fn round2(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn percent(amount: f64, rate: f64) -> f64 {
+    round2(amount * rate / 100.0)
+}
+
+/// Whether an employee born on `date_of_birth` still contributes to the
+/// pension scheme in the month starting on `month_start`. Unknown birth dates
+/// are treated as eligible.
+pub fn eps_eligible(date_of_birth: Option<&str>, month_start: NaiveDate) -> bool {
+    let Some(dob) = date_of_birth.and_then(|d| parse_date(d, "date of birth").ok()) else {
+        return true;
+    };
+    month_start
+        .years_since(dob)
+        .is_none_or(|age| age < EPS_AGE_LIMIT)
+}
+
+fn item(kind: &str, code: &str, label: &str, amount: f64) -> PayLineItem {
+    PayLineItem {
+        kind: kind.to_string(),
+        code: code.to_string(),
+        label: label.to_string(),
+        amount,
+    }
+}
+
+/// Ordinary hourly rate that overtime is a multiple of, the monthly basic and
+/// DA spread over 26 working days of the daily hour limit
+pub fn overtime_hourly_rate(settings: &PayrollSettings, structure: &WageStructure) -> f64 {
+    round2((structure.basic + structure.da) / (WORKING_DAYS_PER_MONTH * settings.ot_daily_hours))
+}
+
+/// Prorate the monthly wage structure over the paid days and work out the
+/// statutory contributions. Overtime covers OT shifts at the structure's rate
+/// and approved OT hours at the settings' multiple of the hourly rate.
+/// Earnings are rounded to the rupee, PF to the nearest rupee and ESI up to
+/// the next rupee as the portals expect.
+pub fn compute_pay(
+    settings: &PayrollSettings,
+    structure: &WageStructure,
+    days_in_month: i64,
+    paid_days: i64,
+    ot_shifts: i64,
+    ot_hours: f64,
+    eps_eligible: bool,
+) -> ComputedPay {
+    let prorate = |amount: f64| (amount * paid_days as f64 / days_in_month as f64).round();
+
+    let basic = prorate(structure.basic);
+    let da = prorate(structure.da);
+    let hra = prorate(structure.hra);
+    let other_allowances = prorate(structure.other_allowances);
+    let ot_pay = (structure.ot_rate * ot_shifts as f64
+        + overtime_hourly_rate(settings, structure) * settings.ot_rate_multiplier * ot_hours)
+        .round();
+    let gross_pay = basic + da + hra + other_allowances + ot_pay;
+
+    let mut items: Vec<PayLineItem> = [
+        ("BASIC", "Basic", basic),
+        ("DA", "Dearness allowance", da),
+        ("HRA", "House rent allowance", hra),
+        ("ALLOW", "Other allowances", other_allowances),
+        ("OT", "Overtime", ot_pay),
+    ]
+    .into_iter()
+    .filter(|(_, _, amount)| *amount > 0.0)
+    .map(|(code, label, amount)| item("earning", code, label, amount))
+    .collect();
+
+    // PF is due on basic and DA, optionally only up to the wage ceiling. The
+    // pension share is always limited to the ceiling.
+    let pf_base = basic + da;
+    let pf_wages = if settings.restrict_pf_to_ceiling != 0 {
+        pf_base.min(settings.pf_wage_ceiling)
+    } else {
+        pf_base
+    };
+    let eps_wages = if eps_eligible {
+        pf_wages.min(settings.pf_wage_ceiling)
+    } else {
+        0.0
+    };
+    let pf_employee = percent(pf_wages, settings.pf_employee_rate).round();
+    let pf_employer = percent(pf_wages, settings.pf_employer_rate).round();
+    let eps = percent(eps_wages, settings.eps_rate)
+        .round()
+        .min(pf_employer);
+
+    // ESI covers employees whose full monthly wage is within the ceiling
+    let monthly_gross = structure.basic + structure.da + structure.hra + structure.other_allowances;
+    let esi_wages = if monthly_gross <= settings.esi_wage_ceiling {
+        gross_pay
+    } else {
+        0.0
+    };
+    let esi_employee = percent(esi_wages, settings.esi_employee_rate).ceil();
+    let esi_employer = percent(esi_wages, settings.esi_employer_rate).ceil();
+
+    let professional_tax = settings
+        .professional_tax_slabs
+        .iter()
+        .find(|slab| {
+            gross_pay >= slab.min_gross && slab.max_gross.is_none_or(|max| gross_pay <= max)
+        })
+        .map_or(0.0, |slab| slab.amount);
+
+    let deductions = [
+        ("PF", "Provident fund", pf_employee),
+        ("ESI", "ESI", esi_employee),
+        ("PT", "Professional tax", professional_tax),
+    ];
+    let total_deductions: f64 = deductions.iter().map(|(_, _, amount)| amount).sum();
+    items.extend(
+        deductions
+            .into_iter()
+            .filter(|(_, _, amount)| *amount > 0.0)
+            .map(|(code, label, amount)| item("deduction", code, label, amount)),
+    );
+
+    items.extend(
+        [
+            ("EPS", "Employer pension (EPS)", eps),
+            ("EPF_ER", "Employer provident fund", pf_employer - eps),
+            ("ESI_ER", "Employer ESI", esi_employer),
+        ]
+        .into_iter()
+        .filter(|(_, _, amount)| *amount > 0.0)
+        .map(|(code, label, amount)| item("employer", code, label, amount)),
+    );
+
+    ComputedPay {
+        days_in_month,
+        paid_days,
+        ot_shifts,
+        gross_pay,
+        pf_wages,
+        eps_wages,
+        esi_wages,
+        total_deductions,
+        net_pay: gross_pay - total_deductions,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> PayrollSettings {
+        PayrollSettings {
+            pf_employee_rate: 12.0,
+            pf_employer_rate: 12.0,
+            eps_rate: 8.33,
+            pf_wage_ceiling: 15000.0,
+            restrict_pf_to_ceiling: 1,
+            esi_employee_rate: 0.75,
+            esi_employer_rate: 3.25,
+            esi_wage_ceiling: 21000.0,
+            gratuity_ceiling: 2000000.0,
+            ot_daily_hours: 8.0,
+            ot_weekly_hours: 48.0,
+            ot_rate_multiplier: 2.0,
+            professional_tax_slabs: vec![
+                ProfessionalTaxSlab {
+                    min_gross: 0.0,
+                    max_gross: Some(11999.0),
+                    amount: 0.0,
+                },
+                ProfessionalTaxSlab {
+                    min_gross: 12000.0,
+                    max_gross: Some(17999.0),
+                    amount: 120.0,
+                },
+                ProfessionalTaxSlab {
+                    min_gross: 18000.0,
+                    max_gross: None,
+                    amount: 208.0,
+                },
+            ],
+        }
+    }
+
+    fn structure(basic: f64, da: f64, hra: f64) -> WageStructure {
+        WageStructure {
+            id: 1,
+            job_post: "Security Guard".to_string(),
+            effective_from: "2024-01-01".to_string(),
+            basic,
+            da,
+            hra,
+            other_allowances: 0.0,
+            ot_rate: 150.0,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn amount(pay: &ComputedPay, code: &str) -> f64 {
+        pay.items
+            .iter()
+            .filter(|item| item.code == code)
+            .map(|item| item.amount)
+            .sum()
+    }
+
+    #[test]
+    fn earnings_are_prorated_over_paid_days() {
+        let pay = compute_pay(
+            &settings(),
+            &structure(12000.0, 3000.0, 1000.0),
+            30,
+            15,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(amount(&pay, "BASIC"), 6000.0);
+        assert_eq!(amount(&pay, "DA"), 1500.0);
+        assert_eq!(amount(&pay, "HRA"), 500.0);
+        assert_eq!(pay.gross_pay, 8000.0);
+        assert_eq!(pay.pf_wages, 7500.0);
+        assert_eq!(amount(&pay, "PF"), 900.0);
+        assert_eq!(pay.total_deductions, 960.0);
+        assert_eq!(pay.net_pay, 7040.0);
+
+        // Each earning is rounded to the rupee
+        let pay = compute_pay(
+            &settings(),
+            &structure(12000.0, 0.0, 0.0),
+            31,
+            10,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(amount(&pay, "BASIC"), 3871.0);
+    }
+
+    #[test]
+    fn overtime_shifts_and_hours() {
+        // 2 shifts at 150, and 3 hours at twice 15,000 / (26 x 8) = 72.12
+        let pay = compute_pay(
+            &settings(),
+            &structure(12000.0, 3000.0, 0.0),
+            30,
+            30,
+            2,
+            3.0,
+            true,
+        );
+        assert_eq!(amount(&pay, "OT"), 733.0);
+        assert_eq!(pay.gross_pay, 15733.0);
+    }
+
+    #[test]
+    fn pf_is_limited_to_the_ceiling() {
+        let pay = compute_pay(
+            &settings(),
+            &structure(20000.0, 5000.0, 0.0),
+            30,
+            30,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(pay.pf_wages, 15000.0);
+        assert_eq!(amount(&pay, "PF"), 1800.0);
+        assert_eq!(amount(&pay, "EPS"), 1250.0);
+        assert_eq!(amount(&pay, "EPF_ER"), 550.0);
+
+        // Without the restriction PF is due on the full wage, but the pension
+        // share still stops at the ceiling
+        let mut settings = settings();
+        settings.restrict_pf_to_ceiling = 0;
+        let pay = compute_pay(
+            &settings,
+            &structure(20000.0, 5000.0, 0.0),
+            30,
+            30,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(pay.pf_wages, 25000.0);
+        assert_eq!(pay.eps_wages, 15000.0);
+        assert_eq!(amount(&pay, "PF"), 3000.0);
+        assert_eq!(amount(&pay, "EPS"), 1250.0);
+        assert_eq!(amount(&pay, "EPF_ER"), 1750.0);
+    }
+
+    #[test]
+    fn pension_stops_at_fifty_eight() {
+        let may = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert!(eps_eligible(Some("1966-05-02"), may));
+        assert!(!eps_eligible(Some("1966-05-01"), may));
+        assert!(!eps_eligible(Some("1960-01-01"), may));
+        assert!(eps_eligible(None, may));
+
+        // The employer's whole share goes to the provident fund
+        let pay = compute_pay(
+            &settings(),
+            &structure(12000.0, 3000.0, 0.0),
+            30,
+            30,
+            0,
+            0.0,
+            false,
+        );
+        assert_eq!(pay.eps_wages, 0.0);
+        assert_eq!(amount(&pay, "EPS"), 0.0);
+        assert_eq!(amount(&pay, "EPF_ER"), 1800.0);
+    }
+
+    #[test]
+    fn esi_is_rounded_up() {
+        let pay = compute_pay(
+            &settings(),
+            &structure(10001.0, 0.0, 0.0),
+            30,
+            30,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(pay.esi_wages, 10001.0);
+        // 75.01 and 325.03
+        assert_eq!(amount(&pay, "ESI"), 76.0);
+        assert_eq!(amount(&pay, "ESI_ER"), 326.0);
+
+        let pay = compute_pay(
+            &settings(),
+            &structure(10000.0, 0.0, 0.0),
+            30,
+            30,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(amount(&pay, "ESI"), 75.0);
+    }
+
+    #[test]
+    fn esi_covers_wages_within_the_ceiling() {
+        let covered = compute_pay(
+            &settings(),
+            &structure(21000.0, 0.0, 0.0),
+            30,
+            15,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(covered.esi_wages, 10500.0);
+
+        // The full monthly wage decides, not what was paid for the days worked
+        let pay = compute_pay(
+            &settings(),
+            &structure(21001.0, 0.0, 0.0),
+            30,
+            15,
+            0,
+            0.0,
+            true,
+        );
+        assert_eq!(pay.esi_wages, 0.0);
+        assert_eq!(amount(&pay, "ESI"), 0.0);
+    }
+
+    #[test]
+    fn professional_tax_slabs() {
+        let pt = |gross: f64| {
+            let pay = compute_pay(
+                &settings(),
+                &structure(gross, 0.0, 0.0),
+                30,
+                30,
+                0,
+                0.0,
+                true,
+            );
+            amount(&pay, "PT")
+        };
+        assert_eq!(pt(11999.0), 0.0);
+        assert_eq!(pt(12000.0), 120.0);
+        assert_eq!(pt(17999.0), 120.0);
+        assert_eq!(pt(18000.0), 208.0);
+        assert_eq!(pt(50000.0), 208.0);
+    }
+}
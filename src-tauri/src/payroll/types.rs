@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WageStructure {
+    pub id: i64,
+    pub job_post: String,
+    pub effective_from: String,
+    pub basic: f64,
+    pub da: f64,
+    pub hra: f64,
+    pub other_allowances: f64,
+    /// Paid per OT shift on top of the monthly wage
+    pub ot_rate: f64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WageStructureInput {
+    pub job_post: String,
+    pub effective_from: String,
+    pub basic: f64,
+    pub da: f64,
+    pub hra: f64,
+    pub other_allowances: f64,
+    pub ot_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfessionalTaxSlab {
+    pub min_gross: f64,
+    /// No upper bound when empty
+    pub max_gross: Option<f64>,
+    pub amount: f64,
+}
+
+/// Statutory rates as percentages and wage ceilings in rupees
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollSettings {
+    pub pf_employee_rate: f64,
+    pub pf_employer_rate: f64,
+    pub eps_rate: f64,
+    pub pf_wage_ceiling: f64,
+    pub restrict_pf_to_ceiling: i32,
+    pub esi_employee_rate: f64,
+    pub esi_employer_rate: f64,
+    pub esi_wage_ceiling: f64,
+    pub gratuity_ceiling: f64,
+    /// Hours a day and a week beyond which work is overtime
+    pub ot_daily_hours: f64,
+    pub ot_weekly_hours: f64,
+    /// Overtime is paid at this multiple of the ordinary hourly rate
+    pub ot_rate_multiplier: f64,
+    pub professional_tax_slabs: Vec<ProfessionalTaxSlab>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayRun {
+    pub id: i64,
+    pub month: String,
+    pub status: String,
+    pub locked_at: Option<String>,
+    pub employee_count: i64,
+    pub total_gross: f64,
+    pub total_deductions: f64,
+    pub total_net: f64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayLineItem {
+    /// earning, deduction or employer (contributions not taken from pay)
+    pub kind: String,
+    pub code: String,
+    pub label: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayRunLine {
+    pub id: i64,
+    pub pay_run_id: i64,
+    pub employee_id: Option<i64>,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub uan: Option<String>,
+    pub esiip: Option<String>,
+    pub days_in_month: i64,
+    pub paid_days: i64,
+    pub ot_shifts: i64,
+    pub gross_pay: f64,
+    pub pf_wages: f64,
+    pub eps_wages: f64,
+    pub esi_wages: f64,
+    pub total_deductions: f64,
+    pub net_pay: f64,
+    pub items: Vec<PayLineItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollSkip {
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollRunResult {
+    pub pay_run: PayRun,
+    pub lines: Vec<PayRunLine>,
+    pub skipped: Vec<PayrollSkip>,
+}
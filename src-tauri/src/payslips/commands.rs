@@ -0,0 +1,81 @@
+use super::helpers::{draw_payslip, month_title};
+use crate::attachments::helpers::replace_generated_attachment;
+use crate::attachments::types::EmployeeAttachment;
+use crate::company::helpers::load_company_profile;
+use crate::db;
+use crate::payroll::helpers::{get_pay_run_by_id, get_pay_run_lines};
+use crate::pdf::{Orientation, PdfDocument};
+use crate::state::AppState;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Attachment category of generated payslips
+const PAYSLIP_CATEGORY: &str = "payslip";
+
+/// Write the payslips of a pay run, or of one employee in it, to a single PDF
+/// with one page per employee
+#[tauri::command]
+pub fn export_payslips_pdf(
+    _state: State<AppState>,
+    pay_run_id: i64,
+    employee_id: Option<i64>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let pay_run = get_pay_run_by_id(&conn, pay_run_id)?.ok_or("Pay run not found")?;
+    let profile = load_company_profile(&conn)?;
+    let lines = get_pay_run_lines(&conn, pay_run_id, employee_id)?;
+    if lines.is_empty() {
+        return Err("No payslips to print for this pay run".to_string());
+    }
+
+    let mut doc = PdfDocument::new(
+        &format!("Payslips - {}", month_title(&pay_run.month)),
+        Orientation::Portrait,
+    );
+    for line in &lines {
+        draw_payslip(&mut doc, &profile, &pay_run.month, line);
+    }
+
+    doc.save(&PathBuf::from(&export_path))
+}
+
+/// Generate the payslip of every employee in a pay run, or of one employee,
+/// and store it as an attachment on the employee record. Generating a month
+/// again replaces its earlier payslip.
+#[tauri::command]
+pub fn save_payslip_attachments(
+    _state: State<AppState>,
+    pay_run_id: i64,
+    employee_id: Option<i64>,
+) -> Result<Vec<EmployeeAttachment>, String> {
+    let conn = db::get_connection()?;
+
+    let pay_run = get_pay_run_by_id(&conn, pay_run_id)?.ok_or("Pay run not found")?;
+    let profile = load_company_profile(&conn)?;
+
+    let mut attachments = Vec::new();
+    for line in get_pay_run_lines(&conn, pay_run_id, employee_id)? {
+        // Lines of deleted employees have nowhere to be attached
+        let Some(line_employee_id) = line.employee_id else {
+            continue;
+        };
+
+        let title = format!("Payslip for {}", month_title(&pay_run.month));
+        let mut doc = PdfDocument::new(&title, Orientation::Portrait);
+        draw_payslip(&mut doc, &profile, &pay_run.month, &line);
+
+        attachments.push(replace_generated_attachment(
+            &conn,
+            line_employee_id,
+            PAYSLIP_CATEGORY,
+            &pay_run.month,
+            &title,
+            &format!("payslip-{}-{}.pdf", line.essid, pay_run.month),
+            &doc.to_bytes(),
+        )?);
+    }
+
+    Ok(attachments)
+}
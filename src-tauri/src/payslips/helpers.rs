@@ -0,0 +1,350 @@
+use chrono::NaiveDate;
+
+use crate::company::helpers::draw_letterhead;
+use crate::company::types::CompanyProfile;
+use crate::payroll::helpers::line_item_amount;
+use crate::payroll::types::PayRunLine;
+use crate::pdf::{text_width, Align, Font, PdfDocument, MARGIN};
+
+const ONES: [&str; 20] = [
+    "",
+    "One",
+    "Two",
+    "Three",
+    "Four",
+    "Five",
+    "Six",
+    "Seven",
+    "Eight",
+    "Nine",
+    "Ten",
+    "Eleven",
+    "Twelve",
+    "Thirteen",
+    "Fourteen",
+    "Fifteen",
+    "Sixteen",
+    "Seventeen",
+    "Eighteen",
+    "Nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+];
+
+/// Amount with two decimals and Indian digit grouping, e.g. `12,34,567.50`
+pub fn format_amount(amount: f64) -> String {
+    let formatted = format!("{:.2}", amount.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+
+    let mut grouped = String::new();
+    let digits: Vec<char> = whole.chars().collect();
+    let (head, last_three) = digits.split_at(digits.len().saturating_sub(3));
+    for (i, c) in head.iter().enumerate() {
+        if i > 0 && (head.len() - i) % 2 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(*c);
+    }
+    if !head.is_empty() {
+        grouped.push(',');
+    }
+    grouped.extend(last_three);
+
+    let sign = if amount < 0.0 { "-" } else { "" };
+    format!("{sign}{grouped}.{fraction}")
+}
+
+fn below_hundred(n: u64) -> String {
+    if n < 20 {
+        ONES[n as usize].to_string()
+    } else {
+        [TENS[(n / 10) as usize], ONES[(n % 10) as usize]]
+            .iter()
+            .filter(|w| !w.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn number_in_words(n: u64) -> String {
+    if n == 0 {
+        return "Zero".to_string();
+    }
+
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (unit, name) in [
+        (10_000_000, "Crore"),
+        (100_000, "Lakh"),
+        (1_000, "Thousand"),
+    ] {
+        if rest >= unit {
+            parts.push(format!("{} {name}", number_in_words(rest / unit)));
+            rest %= unit;
+        }
+    }
+    if rest >= 100 {
+        parts.push(format!("{} Hundred", ONES[(rest / 100) as usize]));
+        rest %= 100;
+    }
+    if rest > 0 {
+        parts.push(below_hundred(rest));
+    }
+    parts.join(" ")
+}
+
+/// Amount in words as printed on Indian payslips and invoices, e.g.
+/// `Rupees One Thousand Two Hundred Only`
+pub fn amount_in_words(amount: f64) -> String {
+    let paise_total = (amount.abs() * 100.0).round() as u64;
+    let (rupees, paise) = (paise_total / 100, paise_total % 100);
+
+    let mut words = format!("Rupees {}", number_in_words(rupees));
+    if paise > 0 {
+        words.push_str(&format!(" and {} Paise", below_hundred(paise)));
+    }
+    words.push_str(" Only");
+    words
+}
+
+/// `YYYY-MM` as `March 2024`
+pub fn month_title(month: &str) -> String {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map(|d| d.format("%B %Y").to_string())
+        .unwrap_or_else(|_| month.to_string())
+}
+
+/// Add a page with the payslip of one pay run line to `doc`
+pub fn draw_payslip(
+    doc: &mut PdfDocument,
+    profile: &CompanyProfile,
+    month: &str,
+    line: &PayRunLine,
+) {
+    let width = doc.width;
+    let height = doc.height;
+    let printable_width = width - 2.0 * MARGIN;
+    let page = doc.add_page();
+
+    let mut y = draw_letterhead(page, width, profile);
+
+    let title = format!("Payslip for {}", month_title(month));
+    y += 8.0;
+    page.text(
+        (width - text_width(&title, Font::Bold, 12.0)) / 2.0,
+        y,
+        Font::Bold,
+        12.0,
+        &title,
+    );
+    y += 16.0;
+
+    let text_or_dash = |value: &Option<String>| {
+        value
+            .as_deref()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or("-")
+            .to_string()
+    };
+    let details = [
+        (
+            ("Employee name", line.employee_name.clone()),
+            ("ESI IP number", text_or_dash(&line.esiip)),
+        ),
+        (
+            ("ESSID", line.essid.clone()),
+            ("Days in month", line.days_in_month.to_string()),
+        ),
+        (
+            ("Designation", text_or_dash(&line.job_post)),
+            ("Paid days", line.paid_days.to_string()),
+        ),
+        (
+            ("UAN", text_or_dash(&line.uan)),
+            ("OT shifts", line.ot_shifts.to_string()),
+        ),
+    ];
+    let half = printable_width / 2.0;
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+    y += 4.0;
+    for (left, right) in details {
+        y += 13.0;
+        for (x, (label, value)) in [(MARGIN, left), (MARGIN + half, right)] {
+            page.text(x + 4.0, y, Font::Regular, 9.0, label);
+            page.text_in_box(
+                x + 95.0,
+                y,
+                half - 100.0,
+                Align::Left,
+                Font::Bold,
+                9.0,
+                &value,
+            );
+        }
+    }
+    y += 8.0;
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+    y += 14.0;
+
+    // Earnings and deductions side by side
+    let earnings: Vec<_> = line.items.iter().filter(|i| i.kind == "earning").collect();
+    let deductions: Vec<_> = line
+        .items
+        .iter()
+        .filter(|i| i.kind == "deduction")
+        .collect();
+    let label_width = half * 0.62;
+    let amount_width = half - label_width;
+    let row_height = 15.0;
+
+    page.fill_rect(MARGIN, y, printable_width, row_height, 0.88);
+    for (x, title) in [(MARGIN, "Earnings"), (MARGIN + half, "Deductions")] {
+        page.text(x + 4.0, y + 10.5, Font::Bold, 9.0, title);
+        page.text_in_box(
+            x + label_width,
+            y + 10.5,
+            amount_width - 4.0,
+            Align::Right,
+            Font::Bold,
+            9.0,
+            "Amount (Rs.)",
+        );
+    }
+    y += row_height;
+
+    for i in 0..earnings.len().max(deductions.len()).max(1) {
+        for (x, item) in [
+            (MARGIN, earnings.get(i)),
+            (MARGIN + half, deductions.get(i)),
+        ] {
+            if let Some(item) = item {
+                page.text_in_box(
+                    x + 4.0,
+                    y + 10.5,
+                    label_width - 8.0,
+                    Align::Left,
+                    Font::Regular,
+                    9.0,
+                    &item.label,
+                );
+                page.text_in_box(
+                    x + label_width,
+                    y + 10.5,
+                    amount_width - 4.0,
+                    Align::Right,
+                    Font::Regular,
+                    9.0,
+                    &format_amount(item.amount),
+                );
+            }
+        }
+        y += row_height;
+    }
+
+    page.line(MARGIN, y, width - MARGIN, y, 0.6);
+    for (x, label, amount) in [
+        (MARGIN, "Gross earnings", line.gross_pay),
+        (MARGIN + half, "Total deductions", line.total_deductions),
+    ] {
+        page.text(x + 4.0, y + 10.5, Font::Bold, 9.0, label);
+        page.text_in_box(
+            x + label_width,
+            y + 10.5,
+            amount_width - 4.0,
+            Align::Right,
+            Font::Bold,
+            9.0,
+            &format_amount(amount),
+        );
+    }
+    y += row_height;
+    page.line(MARGIN, y, width - MARGIN, y, 0.6);
+    page.line(MARGIN + half, y - row_height * 2.0, MARGIN + half, y, 0.2);
+
+    y += 22.0;
+    page.text(
+        MARGIN + 4.0,
+        y,
+        Font::Bold,
+        12.0,
+        &format!("Net pay: Rs. {}", format_amount(line.net_pay)),
+    );
+    y += 14.0;
+    page.text(
+        MARGIN + 4.0,
+        y,
+        Font::Regular,
+        9.0,
+        &format!("({})", amount_in_words(line.net_pay)),
+    );
+
+    let employer: Vec<String> = [("PF", ["EPS", "EPF_ER"].as_slice()), ("ESI", &["ESI_ER"])]
+        .iter()
+        .map(|(label, codes)| {
+            let amount: f64 = codes.iter().map(|c| line_item_amount(line, c)).sum();
+            (label, amount)
+        })
+        .filter(|(_, amount)| *amount > 0.0)
+        .map(|(label, amount)| format!("{label} Rs. {}", format_amount(amount)))
+        .collect();
+    if !employer.is_empty() {
+        y += 18.0;
+        page.text(
+            MARGIN + 4.0,
+            y,
+            Font::Regular,
+            8.0,
+            &format!(
+                "Employer contributions, not deducted from pay: {}",
+                employer.join(", ")
+            ),
+        );
+    }
+
+    page.text(
+        MARGIN,
+        height - MARGIN,
+        Font::Regular,
+        7.5,
+        "This is a computer generated payslip and does not require a signature.",
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_use_indian_digit_grouping() {
+        assert_eq!(format_amount(0.0), "0.00");
+        assert_eq!(format_amount(999.5), "999.50");
+        assert_eq!(format_amount(1000.0), "1,000.00");
+        assert_eq!(format_amount(1234567.5), "12,34,567.50");
+        assert_eq!(format_amount(-25000.456), "-25,000.46");
+    }
+
+    #[test]
+    fn amounts_are_written_in_lakhs_and_crores() {
+        assert_eq!(amount_in_words(0.0), "Rupees Zero Only");
+        assert_eq!(
+            amount_in_words(1200.0),
+            "Rupees One Thousand Two Hundred Only"
+        );
+        assert_eq!(
+            amount_in_words(18780.45),
+            "Rupees Eighteen Thousand Seven Hundred Eighty and Forty Five Paise Only"
+        );
+        assert_eq!(
+            amount_in_words(120500019.0),
+            "Rupees Twelve Crore Five Lakh Nineteen Only"
+        );
+    }
+
+    #[test]
+    fn month_title_spells_out_the_month() {
+        assert_eq!(month_title("2024-03"), "March 2024");
+        assert_eq!(month_title("2024-13"), "2024-13");
+    }
+}
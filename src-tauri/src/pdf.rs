@@ -0,0 +1,524 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// A4 in PDF points
+const A4_SHORT: f32 = 595.0;
+const A4_LONG: f32 = 842.0;
+
+pub const MARGIN: f32 = 36.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Font {
+    fn resource_name(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+// Glyph widths of the standard Helvetica fonts for ASCII 32..=126, in 1/1000 em
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Width of `text` in points when set in `font` at `size`
+pub fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let widths = match font {
+        Font::Regular => &HELVETICA_WIDTHS,
+        Font::Bold => &HELVETICA_BOLD_WIDTHS,
+    };
+    let units: u32 = text
+        .chars()
+        .map(|c| match c as u32 {
+            code @ 32..=126 => widths[(code - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Shorten `text` with an ellipsis so that it fits in `max_width`
+pub fn fit_text(text: &str, font: Font, size: f32, max_width: f32) -> String {
+    if text_width(text, font, size) <= max_width {
+        return text.to_string();
+    }
+
+    let mut fitted = String::new();
+    for c in text.chars() {
+        let candidate = format!("{fitted}{c}...");
+        if text_width(&candidate, font, size) > max_width {
+            break;
+        }
+        fitted.push(c);
+    }
+    format!("{fitted}...")
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// A single page. Coordinates are in points measured from the top-left corner.
+pub struct Page {
+    height: f32,
+    content: String,
+}
+
+impl Page {
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.content.push_str(&format!(
+            "BT /{} {size:.1} Tf {x:.2} {:.2} Td ({}) Tj ET\n",
+            font.resource_name(),
+            self.height - y,
+            escape_text(text)
+        ));
+    }
+
+    /// Draw text inside a box of `width` starting at `x`, truncating if needed
+    #[allow(clippy::too_many_arguments)]
+    pub fn text_in_box(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        align: Align,
+        font: Font,
+        size: f32,
+        text: &str,
+    ) {
+        let text = fit_text(text, font, size, width);
+        let text_x = match align {
+            Align::Left => x,
+            Align::Center => x + (width - text_width(&text, font, size)) / 2.0,
+            Align::Right => x + width - text_width(&text, font, size),
+        };
+        self.text(text_x, y, font, size, &text);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32) {
+        self.content.push_str(&format!(
+            "{thickness:.2} w {x1:.2} {:.2} m {x2:.2} {:.2} l S\n",
+            self.height - y1,
+            self.height - y2
+        ));
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, gray: f32) {
+        self.content.push_str(&format!(
+            "q {gray:.2} g {x:.2} {:.2} {width:.2} {height:.2} re f Q\n",
+            self.height - y - height
+        ));
+    }
+}
+
+/// Minimal PDF writer using the built-in Helvetica fonts, enough for
+/// printable registers, rosters and statements without extra dependencies
+pub struct PdfDocument {
+    pub width: f32,
+    pub height: f32,
+    title: String,
+    pages: Vec<Page>,
+}
+
+impl PdfDocument {
+    pub fn new(title: &str, orientation: Orientation) -> Self {
+        let (width, height) = match orientation {
+            Orientation::Portrait => (A4_SHORT, A4_LONG),
+            Orientation::Landscape => (A4_LONG, A4_SHORT),
+        };
+
+        PdfDocument {
+            width,
+            height,
+            title: title.to_string(),
+            pages: Vec::new(),
+        }
+    }
+
+    pub fn add_page(&mut self) -> &mut Page {
+        self.pages.push(Page {
+            height: self.height,
+            content: String::new(),
+        });
+        self.pages.last_mut().expect("page was just pushed")
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let page_count = self.pages.len().max(1);
+        // 1: catalog, 2: pages, 3-4: fonts, 5: info, then a page and its
+        // content stream for every page
+        let mut objects: Vec<String> = Vec::new();
+
+        let kids = (0..page_count)
+            .map(|i| format!("{} 0 R", 6 + i * 2))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+        objects.push(format!(
+            "<< /Type /Pages /Kids [{kids}] /Count {page_count} >>"
+        ));
+        objects.push(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        );
+        objects.push(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        );
+        objects.push(format!(
+            "<< /Title ({}) /Producer (ExGroup) >>",
+            escape_text(&self.title)
+        ));
+
+        let empty = String::new();
+        for i in 0..page_count {
+            let content = self.pages.get(i).map(|p| &p.content).unwrap_or(&empty);
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.0} {:.0}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                self.width,
+                self.height,
+                7 + i * 2
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ));
+        }
+
+        let mut bytes: Vec<u8> = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(bytes.len());
+            bytes.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+        }
+
+        let xref_offset = bytes.len();
+        bytes.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+        bytes.extend_from_slice(b"0000000000 65535 f \n");
+        for offset in offsets {
+            bytes.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        bytes.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+
+        bytes
+    }
+
+    pub fn save(&self, path: &Path) -> Result<String, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create file: {e}"))?;
+        let mut writer = BufWriter::new(file);
+
+        writer
+            .write_all(&self.to_bytes())
+            .map_err(|e| format!("Failed to write PDF: {e}"))?;
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush: {e}"))?;
+
+        Ok(path.to_string_lossy().to_string())
+    }
+}
+
+pub struct TableColumn {
+    pub title: String,
+    pub width: f32,
+    pub align: Align,
+}
+
+impl TableColumn {
+    pub fn new(title: &str, width: f32, align: Align) -> Self {
+        TableColumn {
+            title: title.to_string(),
+            width,
+            align,
+        }
+    }
+}
+
+/// A titled report with a single table that flows over as many pages as it
+/// needs, repeating the header row on every page with rows
+pub struct TableReport {
+    pub title: String,
+    pub subtitle_lines: Vec<String>,
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<Vec<String>>,
+    pub footer_lines: Vec<String>,
+    pub font_size: f32,
+}
+
+impl TableReport {
+    pub fn new(title: &str, columns: Vec<TableColumn>) -> Self {
+        TableReport {
+            title: title.to_string(),
+            subtitle_lines: Vec::new(),
+            columns,
+            rows: Vec::new(),
+            footer_lines: Vec::new(),
+            font_size: 8.0,
+        }
+    }
+
+    /// Scale the column widths so that the table spans the printable width
+    fn column_widths(&self, printable_width: f32) -> Vec<f32> {
+        let total: f32 = self.columns.iter().map(|c| c.width).sum();
+        if total <= 0.0 {
+            return vec![0.0; self.columns.len()];
+        }
+        self.columns
+            .iter()
+            .map(|c| c.width / total * printable_width)
+            .collect()
+    }
+
+    pub fn render(&self, orientation: Orientation) -> PdfDocument {
+        let mut doc = PdfDocument::new(&self.title, orientation);
+        let printable_width = doc.width - 2.0 * MARGIN;
+        let widths = self.column_widths(printable_width);
+        let row_height = self.font_size + 6.0;
+        let bottom = doc.height - MARGIN - 14.0;
+        let doc_height = doc.height;
+
+        let mut rows = self.rows.iter().peekable();
+        let mut footer_lines = self.footer_lines.iter().peekable();
+        let mut page_number = 0;
+        loop {
+            page_number += 1;
+            let page = doc.add_page();
+            let mut y = MARGIN + 12.0;
+
+            page.text(MARGIN, y, Font::Bold, 13.0, &self.title);
+            y += 14.0;
+            if page_number == 1 {
+                for line in &self.subtitle_lines {
+                    page.text(MARGIN, y, Font::Regular, 9.0, line);
+                    y += 12.0;
+                }
+            }
+            y += 4.0;
+
+            // Header row, left out of pages that only carry the footer
+            if page_number == 1 || rows.peek().is_some() {
+                page.fill_rect(MARGIN, y, printable_width, row_height, 0.88);
+                let mut x = MARGIN;
+                for (column, width) in self.columns.iter().zip(&widths) {
+                    page.text_in_box(
+                        x + 2.0,
+                        y + self.font_size + 1.5,
+                        width - 4.0,
+                        column.align,
+                        Font::Bold,
+                        self.font_size,
+                        &column.title,
+                    );
+                    x += width;
+                }
+                y += row_height;
+                page.line(MARGIN, y, MARGIN + printable_width, y, 0.6);
+            }
+
+            while let Some(row) = rows.peek() {
+                if y + row_height > bottom {
+                    break;
+                }
+                let mut x = MARGIN;
+                for ((column, width), cell) in self.columns.iter().zip(&widths).zip(row.iter()) {
+                    page.text_in_box(
+                        x + 2.0,
+                        y + self.font_size + 1.5,
+                        width - 4.0,
+                        column.align,
+                        Font::Regular,
+                        self.font_size,
+                        cell,
+                    );
+                    x += width;
+                }
+                y += row_height;
+                page.line(MARGIN, y, MARGIN + printable_width, y, 0.2);
+                rows.next();
+            }
+
+            // The footer follows the last row, carrying over to a new page
+            // when it does not fit
+            if rows.peek().is_none() {
+                y += 8.0;
+                while let Some(line) = footer_lines.peek() {
+                    if y + 12.0 > bottom {
+                        break;
+                    }
+                    page.text(MARGIN, y + 9.0, Font::Regular, 9.0, line);
+                    y += 12.0;
+                    footer_lines.next();
+                }
+            }
+
+            page.text(
+                MARGIN,
+                doc_height - MARGIN,
+                Font::Regular,
+                7.0,
+                &format!("Page {page_number}"),
+            );
+
+            if rows.peek().is_none() && footer_lines.peek().is_none() {
+                break;
+            }
+        }
+
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    #[test]
+    fn xref_offsets_point_at_their_objects() {
+        let mut doc = PdfDocument::new("Muster (July)", Orientation::Landscape);
+        doc.add_page()
+            .text(MARGIN, MARGIN, Font::Bold, 12.0, "Attendance");
+        let page = doc.add_page();
+        page.line(MARGIN, 50.0, 200.0, 50.0, 0.5);
+        page.text(MARGIN, 60.0, Font::Regular, 9.0, "Total: 26");
+        let bytes = doc.to_bytes();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|offset| offset.parse().ok())
+            .unwrap();
+        assert!(text[startxref..].starts_with("xref\n"));
+
+        // Catalog, pages, two fonts, info, then a page and a stream per page
+        let mut lines = text[startxref..].lines().skip(1);
+        assert_eq!(lines.next(), Some("0 10"));
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for number in 1..=9 {
+            let entry = lines.next().unwrap();
+            assert_eq!(entry.len(), 19, "entries are 20 bytes with the newline");
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                text[offset..].starts_with(&format!("{number} 0 obj\n")),
+                "object {number}"
+            );
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        assert!(text.contains("/Size 10 /Root 1 0 R"));
+    }
+
+    #[test]
+    fn stream_lengths_match_their_content() {
+        let mut doc = PdfDocument::new("Report", Orientation::Portrait);
+        doc.add_page()
+            .text(MARGIN, MARGIN, Font::Regular, 10.0, "Café (1)");
+        let bytes = doc.to_bytes();
+
+        let mut rest = &bytes[..];
+        let mut streams = 0;
+        while let Some(at) = find(rest, b"/Length ") {
+            rest = &rest[at + 8..];
+            let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+            let length: usize = std::str::from_utf8(&rest[..digits])
+                .unwrap()
+                .parse()
+                .unwrap();
+            let start = find(rest, b"stream\n").unwrap() + 7;
+            assert_eq!(&rest[start + length..start + length + 9], b"endstream");
+            streams += 1;
+        }
+        assert_eq!(streams, 1);
+    }
+
+    #[test]
+    fn footer_moves_to_a_new_page_after_a_full_page_of_rows() {
+        let mut report = TableReport::new(
+            "Register",
+            vec![
+                TableColumn::new("No", 1.0, Align::Right),
+                TableColumn::new("Name", 4.0, Align::Left),
+            ],
+        );
+        // 50 rows fill a portrait page with no subtitle
+        report.rows = (1..=50)
+            .map(|n| vec![n.to_string(), format!("Guard {n}")])
+            .collect();
+        report.footer_lines = vec!["Total: 50".to_string(), "Checked by".to_string()];
+
+        let doc = report.render(Orientation::Portrait);
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.pages[0].content.contains("(Guard 50)"));
+        assert!(!doc.pages[0].content.contains("(Total: 50)"));
+        assert!(!doc.pages[1].content.contains("(Guard"));
+        assert!(!doc.pages[1].content.contains("(Name)"));
+        assert!(doc.pages[1].content.contains("(Total: 50)"));
+        assert!(doc.pages[1].content.contains("(Checked by)"));
+
+        // One row fewer leaves room for the first footer line only
+        report.rows.pop();
+        let doc = report.render(Orientation::Portrait);
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.pages[0].content.contains("(Total: 50)"));
+        assert!(doc.pages[1].content.contains("(Checked by)"));
+    }
+}
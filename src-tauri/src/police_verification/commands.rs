@@ -0,0 +1,234 @@
+use super::helpers::{
+    get_police_verification_by_id, map_police_verification, validate_dates, validate_status,
+};
+use super::types::{
+    PoliceStationGroup, PoliceStationReportFilter, PoliceStationReportRow, PoliceVerification,
+    PoliceVerificationInput,
+};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::state::AppState;
+use rusqlite::{params, Result};
+use tauri::State;
+
+#[tauri::command]
+pub fn get_police_verifications(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Vec<PoliceVerification>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM police_verifications WHERE employee_id = ?1
+             ORDER BY COALESCE(application_date, created_at) DESC, id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let verifications = stmt
+        .query_map([employee_id], map_police_verification)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(verifications)
+}
+
+#[tauri::command]
+pub fn create_police_verification(
+    _state: State<AppState>,
+    mut verification: PoliceVerificationInput,
+) -> Result<PoliceVerification, String> {
+    let conn = db::get_connection()?;
+
+    validate_status(&verification.status)?;
+    validate_dates(&mut verification)?;
+
+    let employee =
+        get_employee_by_id(&conn, verification.employee_id)?.ok_or("Employee not found")?;
+
+    // Fall back to the station recorded on the employee profile
+    let police_station = verification
+        .police_station
+        .filter(|s| !s.trim().is_empty())
+        .or(employee.police_station);
+
+    conn.execute(
+        "INSERT INTO police_verifications (
+            employee_id, application_date, police_station, reference_number, status,
+            certificate_date, renewal_due_date, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            verification.employee_id,
+            verification.application_date,
+            police_station,
+            verification.reference_number,
+            verification.status,
+            verification.certificate_date,
+            verification.renewal_due_date,
+            verification.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(verification)) = get_police_verification_by_id(&conn, id) {
+        Ok(verification)
+    } else {
+        Err("Failed to retrieve police verification".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_police_verification(
+    _state: State<AppState>,
+    id: i64,
+    mut verification: PoliceVerificationInput,
+) -> Result<PoliceVerification, String> {
+    let conn = db::get_connection()?;
+
+    validate_status(&verification.status)?;
+    validate_dates(&mut verification)?;
+
+    let existing =
+        get_police_verification_by_id(&conn, id)?.ok_or("Police verification not found")?;
+    if existing.employee_id != verification.employee_id {
+        return Err("Police verification belongs to a different employee".to_string());
+    }
+
+    conn.execute(
+        "UPDATE police_verifications SET
+            application_date = ?1, police_station = ?2, reference_number = ?3, status = ?4,
+            certificate_date = ?5, renewal_due_date = ?6, remarks = ?7,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            verification.application_date,
+            verification.police_station,
+            verification.reference_number,
+            verification.status,
+            verification.certificate_date,
+            verification.renewal_due_date,
+            verification.remarks,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(verification)) = get_police_verification_by_id(&conn, id) {
+        Ok(verification)
+    } else {
+        Err("Failed to retrieve police verification".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_police_verification(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute(
+        "DELETE FROM police_verifications WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Latest verification of every employee, grouped by police station so that
+/// follow-ups can be done one station at a time
+#[tauri::command]
+pub fn get_police_station_report(
+    _state: State<AppState>,
+    filter: PoliceStationReportFilter,
+) -> Result<Vec<PoliceStationGroup>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT
+                COALESCE(NULLIF(TRIM(lpv.police_station), ''), NULLIF(TRIM(e.police_station), '')) AS station,
+                e.id, e.name, e.essid, e.employment_status,
+                lpv.id, lpv.status, lpv.application_date, lpv.reference_number,
+                lpv.certificate_date, lpv.renewal_due_date
+            FROM employees e
+            LEFT JOIN latest_police_verifications lpv ON lpv.employee_id = e.id
+            WHERE (?1 IS NULL OR e.employment_status = ?1)
+              AND (?2 IS NULL
+                   OR (?2 = 'none' AND lpv.id IS NULL)
+                   OR (?2 = 'not_cleared' AND (lpv.id IS NULL OR lpv.status != 'cleared'))
+                   OR lpv.status = ?2)
+            ORDER BY station IS NULL, station COLLATE NOCASE, e.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let employment_status = filter.employment_status.filter(|s| !s.trim().is_empty());
+    let verification_status = filter.verification_status.filter(|s| !s.trim().is_empty());
+    let station_query = filter
+        .police_station
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_lowercase());
+
+    let rows = stmt
+        .query_map(params![employment_status, verification_status], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                PoliceStationReportRow {
+                    employee_id: row.get(1)?,
+                    name: row.get(2)?,
+                    essid: row.get(3)?,
+                    employment_status: row.get(4)?,
+                    verification_id: row.get(5)?,
+                    status: row.get(6)?,
+                    application_date: row.get(7)?,
+                    reference_number: row.get(8)?,
+                    certificate_date: row.get(9)?,
+                    renewal_due_date: row.get(10)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut groups: Vec<PoliceStationGroup> = Vec::new();
+    for (station, row) in rows {
+        if let Some(query) = &station_query {
+            let matches = station
+                .as_deref()
+                .map(|s| s.to_lowercase().contains(query.as_str()))
+                .unwrap_or(false);
+            if !matches {
+                continue;
+            }
+        }
+
+        let same_station = groups.last().is_some_and(|group| {
+            group.police_station.as_deref().map(str::to_lowercase)
+                == station.as_deref().map(str::to_lowercase)
+        });
+        if !same_station {
+            groups.push(PoliceStationGroup {
+                police_station: station,
+                total: 0,
+                not_applied: 0,
+                pending: 0,
+                cleared: 0,
+                adverse: 0,
+                employees: Vec::new(),
+            });
+        }
+
+        let group = groups.last_mut().expect("group was just pushed");
+        group.total += 1;
+        match row.status.as_deref() {
+            None => group.not_applied += 1,
+            Some("cleared") => group.cleared += 1,
+            Some("adverse") => group.adverse += 1,
+            Some(_) => group.pending += 1,
+        }
+        group.employees.push(row);
+    }
+
+    Ok(groups)
+}
@@ -0,0 +1,133 @@
+use rusqlite::Row;
+
+use super::types::{PoliceVerification, PoliceVerificationInput};
+use crate::dates::{format_date, parse_date};
+
+pub const VERIFICATION_STATUSES: [&str; 3] = ["pending", "cleared", "adverse"];
+
+pub fn validate_status(status: &str) -> Result<(), String> {
+    if VERIFICATION_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid verification status '{status}', expected one of: {}",
+            VERIFICATION_STATUSES.join(", ")
+        ))
+    }
+}
+
+/// Check that the application, certificate and renewal due dates are valid
+/// and follow one another, storing them as `YYYY-MM-DD`
+pub fn validate_dates(verification: &mut PoliceVerificationInput) -> Result<(), String> {
+    let parse = |value: &Option<String>, field: &str| {
+        value
+            .as_deref()
+            .filter(|d| !d.trim().is_empty())
+            .map(|d| parse_date(d, field))
+            .transpose()
+    };
+    let application_date = parse(&verification.application_date, "application date")?;
+    let certificate_date = parse(&verification.certificate_date, "certificate date")?;
+    let renewal_due_date = parse(&verification.renewal_due_date, "renewal due date")?;
+
+    if let (Some(application), Some(certificate)) = (application_date, certificate_date) {
+        if certificate < application {
+            return Err("Certificate date must not be before the application date".to_string());
+        }
+    }
+    if let Some(renewal) = renewal_due_date {
+        if certificate_date
+            .or(application_date)
+            .is_some_and(|d| renewal <= d)
+        {
+            return Err(
+                "Renewal due date must be after the certificate and application dates".to_string(),
+            );
+        }
+    }
+
+    verification.application_date = application_date.map(format_date);
+    verification.certificate_date = certificate_date.map(format_date);
+    verification.renewal_due_date = renewal_due_date.map(format_date);
+    Ok(())
+}
+
+pub fn map_police_verification(row: &Row) -> rusqlite::Result<PoliceVerification> {
+    Ok(PoliceVerification {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        application_date: row.get(2)?,
+        police_station: row.get(3)?,
+        reference_number: row.get(4)?,
+        status: row.get(5)?,
+        certificate_date: row.get(6)?,
+        renewal_due_date: row.get(7)?,
+        remarks: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn get_police_verification_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<PoliceVerification>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM police_verifications WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_police_verification) {
+        Ok(verification) => Ok(Some(verification)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verification(
+        application: Option<&str>,
+        certificate: Option<&str>,
+        renewal: Option<&str>,
+    ) -> PoliceVerificationInput {
+        PoliceVerificationInput {
+            employee_id: 1,
+            application_date: application.map(str::to_string),
+            police_station: None,
+            reference_number: None,
+            status: "cleared".to_string(),
+            certificate_date: certificate.map(str::to_string),
+            renewal_due_date: renewal.map(str::to_string),
+            remarks: None,
+        }
+    }
+
+    #[test]
+    fn dates_are_parsed_and_kept_in_order() {
+        let mut valid = verification(Some(" 2024-01-10"), Some(""), Some("2027-01-10"));
+        validate_dates(&mut valid).unwrap();
+        assert_eq!(valid.application_date.as_deref(), Some("2024-01-10"));
+        assert_eq!(valid.certificate_date, None);
+
+        assert_eq!(
+            validate_dates(&mut verification(Some("10-01-2024"), None, None)),
+            Err("Invalid application date '10-01-2024', expected YYYY-MM-DD".to_string())
+        );
+        assert_eq!(
+            validate_dates(&mut verification(
+                Some("2024-02-01"),
+                Some("2024-01-31"),
+                None
+            )),
+            Err("Certificate date must not be before the application date".to_string())
+        );
+        assert!(validate_dates(&mut verification(
+            Some("2024-01-10"),
+            Some("2024-03-01"),
+            Some("2024-02-01")
+        ))
+        .is_err());
+    }
+}
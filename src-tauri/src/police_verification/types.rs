@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoliceVerification {
+    pub id: i64,
+    pub employee_id: i64,
+    pub application_date: Option<String>,
+    pub police_station: Option<String>,
+    pub reference_number: Option<String>,
+    pub status: String,
+    pub certificate_date: Option<String>,
+    pub renewal_due_date: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoliceVerificationInput {
+    pub employee_id: i64,
+    pub application_date: Option<String>,
+    pub police_station: Option<String>,
+    pub reference_number: Option<String>,
+    pub status: String,
+    pub certificate_date: Option<String>,
+    pub renewal_due_date: Option<String>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoliceStationReportFilter {
+    pub employment_status: Option<String>,
+    pub verification_status: Option<String>,
+    pub police_station: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoliceStationReportRow {
+    pub employee_id: i64,
+    pub name: String,
+    pub essid: String,
+    pub employment_status: String,
+    pub verification_id: Option<i64>,
+    pub status: Option<String>,
+    pub application_date: Option<String>,
+    pub reference_number: Option<String>,
+    pub certificate_date: Option<String>,
+    pub renewal_due_date: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoliceStationGroup {
+    pub police_station: Option<String>,
+    pub total: i64,
+    pub not_applied: i64,
+    pub pending: i64,
+    pub cleared: i64,
+    pub adverse: i64,
+    pub employees: Vec<PoliceStationReportRow>,
+}
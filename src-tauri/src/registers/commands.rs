@@ -0,0 +1,61 @@
+use super::helpers::{build_register, register_report, register_sheet, REGISTERS};
+use super::types::StatutoryRegister;
+use crate::db;
+use crate::pdf::Orientation;
+use crate::state::AppState;
+use crate::xlsx::Workbook;
+use std::path::PathBuf;
+use tauri::State;
+
+/// One of the Contract Labour Act registers for a month, for a site or for
+/// all sites. `register` is `workmen`, `muster_roll`, `wages` or `deductions`.
+#[tauri::command]
+pub fn get_statutory_register(
+    _state: State<AppState>,
+    register: String,
+    month: String,
+    site_id: Option<i64>,
+) -> Result<StatutoryRegister, String> {
+    let conn = db::get_connection()?;
+    build_register(&conn, &register, &month, site_id)
+}
+
+#[tauri::command]
+pub fn export_statutory_register_pdf(
+    _state: State<AppState>,
+    register: String,
+    month: String,
+    site_id: Option<i64>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let register = build_register(&conn, &register, &month, site_id)?;
+    register_report(&register)
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
+
+/// Export a register, or every register as one sheet each when none is given
+#[tauri::command]
+pub fn export_statutory_registers_xlsx(
+    _state: State<AppState>,
+    register: Option<String>,
+    month: String,
+    site_id: Option<i64>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let registers: Vec<&str> = match register.as_deref() {
+        Some(register) => vec![register],
+        None => REGISTERS.iter().map(|(register, _, _)| *register).collect(),
+    };
+
+    let mut workbook = Workbook::new();
+    for register in registers {
+        let register = build_register(&conn, register, &month, site_id)?;
+        workbook.add_sheet(register_sheet(&register));
+    }
+    workbook.save(&PathBuf::from(&export_path))
+}
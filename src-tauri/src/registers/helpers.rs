@@ -0,0 +1,750 @@
+use chrono::NaiveDate;
+use rusqlite::params;
+use std::collections::HashSet;
+
+use super::types::{RegisterColumn, StatutoryRegister};
+use crate::clients::helpers::{get_client_by_id, get_site_by_id};
+use crate::company::helpers::load_company_profile;
+use crate::dates::{age_on, format_date, month_bounds};
+use crate::payroll::helpers::{get_pay_run_by_month, get_pay_run_lines, line_item_amount, round2};
+use crate::payslips::helpers::{format_amount, month_title};
+use crate::pdf::{Align, TableColumn, TableReport};
+use crate::xlsx::{Cell, Sheet};
+
+/// Registers the Contract Labour (Regulation and Abolition) Central Rules
+/// require a contractor to keep, as `(register, form, title)`
+pub const REGISTERS: [(&str, &str, &str); 4] = [
+    (
+        "workmen",
+        "Form XIII",
+        "Register of Workmen Employed by Contractor",
+    ),
+    ("muster_roll", "Form XVI", "Muster Roll"),
+    ("wages", "Form XVII", "Register of Wages"),
+    (
+        "deductions",
+        "Forms XX, XXI and XXII",
+        "Register of Deductions for Damage or Loss, Fines and Advances",
+    ),
+];
+
+/// A workman on a register, with the period of employment at the site or,
+/// for registers of all sites, with the contractor
+struct Workman {
+    employee_id: i64,
+    name: String,
+    essid: String,
+    father_name: Option<String>,
+    date_of_birth: Option<String>,
+    designation: Option<String>,
+    permanent_address: Option<String>,
+    local_address: Option<String>,
+    commencement: Option<String>,
+    termination: Option<String>,
+    termination_reason: Option<String>,
+}
+
+/// Columns, rows and footer lines of a register
+type RegisterBody = (Vec<RegisterColumn>, Vec<Vec<String>>, Vec<String>);
+
+fn column(title: &str, width: f32, kind: &str) -> RegisterColumn {
+    RegisterColumn {
+        title: title.to_string(),
+        width,
+        kind: kind.to_string(),
+    }
+}
+
+fn or_blank(value: &Option<String>) -> String {
+    value
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Address on a single line
+fn one_line(address: Option<&str>) -> String {
+    address
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Workmen employed at a site during a month, from their deployments, or
+/// everyone employed during the month when no site is given
+fn register_workmen(
+    conn: &rusqlite::Connection,
+    site_id: Option<i64>,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<Vec<Workman>, String> {
+    let sql = match site_id {
+        Some(_) => {
+            "SELECT e.id, e.name, e.essid, e.father_name, e.date_of_birth,
+                IFNULL((
+                    SELECT NULLIF(TRIM(l.role), '') FROM deployments l
+                    WHERE l.employee_id = e.id AND l.site_id = ?3 AND l.start_date <= ?2
+                    ORDER BY l.start_date DESC LIMIT 1
+                ), e.job_post),
+                e.permanent_same_as_current, e.permanent_address, e.current_address,
+                MIN(d.start_date), MAX(IFNULL(d.end_date, '9999-12-31')),
+                e.employment_status, e.exit_date
+            FROM deployments d
+            JOIN employees e ON e.id = d.employee_id
+            WHERE d.site_id = ?3 AND d.start_date <= ?2
+              AND (d.end_date IS NULL OR d.end_date >= ?1)
+            GROUP BY e.id
+            ORDER BY e.name COLLATE NOCASE"
+        }
+        None => {
+            "SELECT e.id, e.name, e.essid, e.father_name, e.date_of_birth, e.job_post,
+                e.permanent_same_as_current, e.permanent_address, e.current_address,
+                e.joining_date, IFNULL(e.exit_date, '9999-12-31'),
+                e.employment_status, e.exit_date
+            FROM employees e
+            WHERE ?3 IS NULL
+              AND ((e.employment_status = 'current' AND IFNULL(e.joining_date, '') <= ?2)
+                OR (e.employment_status = 'past' AND e.exit_date >= ?1
+                    AND IFNULL(e.joining_date, '') <= ?2)
+                OR e.id IN (
+                    SELECT employee_id FROM attendance WHERE attendance_date BETWEEN ?1 AND ?2
+                ))
+            ORDER BY e.name COLLATE NOCASE"
+        }
+    };
+
+    let last_date = format_date(last);
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let workmen = stmt
+        .query_map(params![format_date(first), last_date, site_id], |row| {
+            let same_as_current: Option<i32> = row.get(6)?;
+            let permanent: Option<String> = row.get(7)?;
+            let current: Option<String> = row.get(8)?;
+            let end: String = row.get(10)?;
+            let status: Option<String> = row.get(11)?;
+            let exit_date: Option<String> = row.get(12)?;
+
+            // Only a termination on or before the month end is recorded
+            let termination = Some(end).filter(|end| *end <= last_date);
+            let termination_reason = termination.as_ref().map(|end| {
+                if status.as_deref() == Some("past") && exit_date.as_ref() == Some(end) {
+                    "Left service".to_string()
+                } else {
+                    "Transferred".to_string()
+                }
+            });
+            Ok(Workman {
+                employee_id: row.get(0)?,
+                name: row.get(1)?,
+                essid: row.get(2)?,
+                father_name: row.get(3)?,
+                date_of_birth: row.get(4)?,
+                designation: row.get(5)?,
+                permanent_address: if same_as_current.unwrap_or(0) != 0 {
+                    current.clone()
+                } else {
+                    permanent
+                },
+                local_address: current,
+                commencement: row.get(9)?,
+                termination,
+                termination_reason,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(workmen)
+}
+
+/// Form XIII, one row per workman
+fn workmen_register(
+    workmen: &[Workman],
+    last: NaiveDate,
+) -> (Vec<RegisterColumn>, Vec<Vec<String>>) {
+    let columns = vec![
+        column("Sl.", 20.0, "number"),
+        column("Name of workman", 90.0, "text"),
+        column("ESSID", 45.0, "text"),
+        column("Age", 25.0, "number"),
+        column("Father's/husband's name", 110.0, "text"),
+        column("Designation", 70.0, "text"),
+        column("Permanent home address", 125.0, "text"),
+        column("Local address", 120.0, "text"),
+        column("Commenced on", 60.0, "text"),
+        column("Terminated on", 60.0, "text"),
+        column("Reason", 55.0, "text"),
+        column("Signature", 55.0, "text"),
+    ];
+
+    let rows = workmen
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            vec![
+                (i + 1).to_string(),
+                w.name.clone(),
+                w.essid.clone(),
+                age_on(w.date_of_birth.as_deref(), last)
+                    .map(|age| age.to_string())
+                    .unwrap_or_default(),
+                or_blank(&w.father_name),
+                or_blank(&w.designation),
+                one_line(w.permanent_address.as_deref()),
+                one_line(w.local_address.as_deref()),
+                or_blank(&w.commencement),
+                or_blank(&w.termination),
+                or_blank(&w.termination_reason),
+                String::new(),
+            ]
+        })
+        .collect();
+
+    (columns, rows)
+}
+
+/// Attendance mark of a day in the muster roll
+fn attendance_mark(status: &str, ot_shifts: i64) -> String {
+    let mark = match status {
+        "present" => "P",
+        "absent" => "A",
+        "leave" => "L",
+        "weekly_off" => "WO",
+        "holiday" => "H",
+        _ => "",
+    };
+    if ot_shifts > 0 {
+        format!("{mark}+{ot_shifts}")
+    } else {
+        mark.to_string()
+    }
+}
+
+/// Form XVI, one row per workman with a mark for every day of the month. At
+/// a site only the days worked there are marked: attendance at the site, or
+/// without a site during the workman's deployment there.
+fn muster_roll(
+    conn: &rusqlite::Connection,
+    workmen: &[Workman],
+    site_id: Option<i64>,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<(Vec<RegisterColumn>, Vec<Vec<String>>), String> {
+    let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+    let mut columns = vec![
+        column("Sl.", 18.0, "number"),
+        column("Name of workman", 90.0, "text"),
+        column("Father's name", 80.0, "text"),
+    ];
+    for date in &dates {
+        columns.push(column(&date.format("%d").to_string(), 16.0, "text"));
+    }
+    columns.push(column("Days", 24.0, "number"));
+    columns.push(column("OT", 20.0, "number"));
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT attendance_date, status, site_id, ot_shifts FROM attendance
+            WHERE employee_id = ?1 AND attendance_date BETWEEN ?2 AND ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = Vec::new();
+    for (i, workman) in workmen.iter().enumerate() {
+        let attendance = stmt
+            .query_map(
+                params![workman.employee_id, format_date(first), format_date(last)],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, i64>(3)?,
+                    ))
+                },
+            )
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let at_site = |date: &str, attendance_site: Option<i64>| match site_id {
+            None => true,
+            Some(site_id) => match attendance_site {
+                Some(attendance_site) => attendance_site == site_id,
+                None => {
+                    workman.commencement.as_deref().is_some_and(|c| c <= date)
+                        && workman.termination.as_deref().is_none_or(|t| t >= date)
+                }
+            },
+        };
+
+        let mut row = vec![
+            (i + 1).to_string(),
+            workman.name.clone(),
+            or_blank(&workman.father_name),
+        ];
+        let (mut worked, mut ot) = (0, 0);
+        for date in &dates {
+            let date = format_date(*date);
+            let mark = attendance
+                .iter()
+                .find(|(d, _, s, _)| *d == date && at_site(d, *s))
+                .map(|(_, status, _, ot_shifts)| {
+                    if status == "present" {
+                        worked += 1;
+                        ot += ot_shifts;
+                    }
+                    attendance_mark(status, *ot_shifts)
+                })
+                .unwrap_or_default();
+            row.push(mark);
+        }
+        row.push(worked.to_string());
+        row.push(ot.to_string());
+        rows.push(row);
+    }
+
+    Ok((columns, rows))
+}
+
+/// Form XVII from the month's pay run. The wages are those of the whole
+/// month's pay run line.
+fn wages_register(
+    conn: &rusqlite::Connection,
+    workmen: &[Workman],
+    month: &str,
+) -> Result<RegisterBody, String> {
+    let pay_run = get_pay_run_by_month(conn, month)?
+        .ok_or_else(|| format!("Payroll for {} has not been run", month_title(month)))?;
+
+    let columns = vec![
+        column("Sl.", 20.0, "number"),
+        column("Name of workman", 90.0, "text"),
+        column("ESSID", 40.0, "text"),
+        column("Designation", 65.0, "text"),
+        column("Days worked", 58.0, "number"),
+        column("Daily rate", 50.0, "amount"),
+        column("Basic", 55.0, "amount"),
+        column("DA", 55.0, "amount"),
+        column("Overtime", 50.0, "amount"),
+        column("Other cash", 55.0, "amount"),
+        column("Total", 60.0, "amount"),
+        column("Deductions", 55.0, "amount"),
+        column("Net paid", 60.0, "amount"),
+        column("Signature", 55.0, "text"),
+    ];
+
+    let on_register: HashSet<i64> = workmen.iter().map(|w| w.employee_id).collect();
+    let mut rows = Vec::new();
+    let mut totals = [0.0; 7];
+    for line in get_pay_run_lines(conn, pay_run.id, None)?
+        .into_iter()
+        .filter(|l| l.employee_id.is_some_and(|id| on_register.contains(&id)))
+    {
+        let basic = line_item_amount(&line, "BASIC");
+        let da = line_item_amount(&line, "DA");
+        let overtime = line_item_amount(&line, "OT");
+        let other = round2(line.gross_pay - basic - da - overtime);
+        let daily_rate = if line.paid_days > 0 {
+            round2((line.gross_pay - overtime) / line.paid_days as f64)
+        } else {
+            0.0
+        };
+        let amounts = [
+            basic,
+            da,
+            overtime,
+            other,
+            line.gross_pay,
+            line.total_deductions,
+            line.net_pay,
+        ];
+        for (total, amount) in totals.iter_mut().zip(amounts) {
+            *total += amount;
+        }
+
+        let mut row = vec![
+            (rows.len() + 1).to_string(),
+            line.employee_name.clone(),
+            line.essid.clone(),
+            or_blank(&line.job_post),
+            line.paid_days.to_string(),
+            format!("{daily_rate:.2}"),
+        ];
+        row.extend(amounts.iter().map(|a| format!("{a:.2}")));
+        row.push(String::new());
+        rows.push(row);
+    }
+
+    let mut footer = vec![format!(
+        "Totals: basic {}, DA {}, overtime {}, other {}, gross {}, deductions {}, net {}",
+        format_amount(totals[0]),
+        format_amount(totals[1]),
+        format_amount(totals[2]),
+        format_amount(totals[3]),
+        format_amount(totals[4]),
+        format_amount(totals[5]),
+        format_amount(totals[6]),
+    )];
+    if pay_run.status != "locked" {
+        footer.push("Payroll for the month is not locked yet and may change".to_string());
+    }
+
+    Ok((columns, rows, footer))
+}
+
+/// Forms XX to XXII combined: advances given or recovered during the month.
+/// Fines and deductions for damage or loss are not imposed through payroll,
+/// so the register records them as nil.
+fn deductions_register(
+    conn: &rusqlite::Connection,
+    workmen: &[Workman],
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<RegisterBody, String> {
+    let columns = vec![
+        column("Sl.", 20.0, "number"),
+        column("Name of workman", 90.0, "text"),
+        column("Father's/husband's name", 110.0, "text"),
+        column("Designation", 65.0, "text"),
+        column("Nature", 50.0, "text"),
+        column("Particulars", 110.0, "text"),
+        column("Date", 55.0, "text"),
+        column("Amount", 55.0, "amount"),
+        column("Recovered", 60.0, "amount"),
+        column("Outstanding", 55.0, "amount"),
+        column("Remarks", 90.0, "text"),
+    ];
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.disbursed_on, a.amount, a.reason,
+                IFNULL((SELECT SUM(r.amount) FROM advance_recoveries r
+                    WHERE r.advance_id = a.id AND r.recovered_on BETWEEN ?2 AND ?3), 0),
+                IFNULL((SELECT SUM(r.amount) FROM advance_recoveries r
+                    WHERE r.advance_id = a.id AND r.recovered_on <= ?3), 0),
+                a.recovery_type, a.recovery_value
+            FROM salary_advances a
+            WHERE a.employee_id = ?1 AND a.disbursed_on <= ?3
+              AND (a.disbursed_on >= ?2 OR EXISTS (
+                SELECT 1 FROM advance_recoveries r
+                WHERE r.advance_id = a.id AND r.recovered_on BETWEEN ?2 AND ?3
+              ))
+            ORDER BY a.disbursed_on, a.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = Vec::new();
+    for workman in workmen {
+        let advances = stmt
+            .query_map(
+                params![workman.employee_id, format_date(first), format_date(last)],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, f64>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, f64>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, f64>(6)?,
+                    ))
+                },
+            )
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        for (disbursed_on, amount, reason, recovered, recovered_to_date, kind, value) in advances {
+            let schedule = if kind == "percentage" {
+                format!("{}% of net pay", value)
+            } else {
+                format!("Rs. {} a month", format_amount(value))
+            };
+            rows.push(vec![
+                (rows.len() + 1).to_string(),
+                workman.name.clone(),
+                or_blank(&workman.father_name),
+                or_blank(&workman.designation),
+                "Advance".to_string(),
+                reason
+                    .filter(|r| !r.trim().is_empty())
+                    .unwrap_or_else(|| "Salary advance".to_string()),
+                disbursed_on,
+                format!("{amount:.2}"),
+                format!("{:.2}", round2(recovered)),
+                format!("{:.2}", round2(amount - recovered_to_date)),
+                schedule,
+            ]);
+        }
+    }
+
+    let footer = vec!["Fines imposed: Nil    Deductions for damage or loss: Nil".to_string()];
+    Ok((columns, rows, footer))
+}
+
+/// Build one of the `REGISTERS` for a month, for a site or for all sites
+pub fn build_register(
+    conn: &rusqlite::Connection,
+    register: &str,
+    month: &str,
+    site_id: Option<i64>,
+) -> Result<StatutoryRegister, String> {
+    let (_, form, title) = REGISTERS
+        .iter()
+        .find(|(r, _, _)| *r == register)
+        .ok_or_else(|| format!("Unknown register '{register}'"))?;
+    let (first, last) = month_bounds(month)?;
+    let month = first.format("%Y-%m").to_string();
+
+    let profile = load_company_profile(conn)?;
+    let mut header_lines = vec![format!(
+        "Name and address of contractor: {}{}",
+        profile.name,
+        Some(one_line(profile.address.as_deref()))
+            .filter(|a| !a.is_empty())
+            .map(|a| format!(", {a}"))
+            .unwrap_or_default()
+    )];
+    match site_id {
+        Some(site_id) => {
+            let site = get_site_by_id(conn, site_id)?.ok_or("Site not found")?;
+            let client = get_client_by_id(conn, site.client_id)?.ok_or("Client not found")?;
+            let site_address = one_line(site.address.as_deref().or(site.place.as_deref()));
+            let client_address = one_line(client.address.as_deref());
+            header_lines.push(format!(
+                "Name and address of establishment in/under which contract is carried on: {}{}",
+                site.name,
+                Some(site_address)
+                    .filter(|a| !a.is_empty())
+                    .map(|a| format!(", {a}"))
+                    .unwrap_or_default()
+            ));
+            header_lines.push(format!(
+                "Name and address of principal employer: {}{}",
+                client.name,
+                Some(client_address)
+                    .filter(|a| !a.is_empty())
+                    .map(|a| format!(", {a}"))
+                    .unwrap_or_default()
+            ));
+        }
+        None => header_lines.push("Establishments: all sites".to_string()),
+    }
+    let period = if register == "workmen" {
+        "Month"
+    } else {
+        "Wage period"
+    };
+    header_lines.push(format!(
+        "{period}: {} ({} to {})",
+        month_title(&month),
+        format_date(first),
+        format_date(last)
+    ));
+
+    let workmen = register_workmen(conn, site_id, first, last)?;
+    let (columns, rows, footer_lines) = match register {
+        "workmen" => {
+            let (columns, rows) = workmen_register(&workmen, last);
+            (columns, rows, Vec::new())
+        }
+        "muster_roll" => {
+            let (columns, rows) = muster_roll(conn, &workmen, site_id, first, last)?;
+            let legend =
+                "P = present, A = absent, L = leave, WO = weekly off, H = holiday, +n = OT shifts";
+            (columns, rows, vec![legend.to_string()])
+        }
+        "wages" => wages_register(conn, &workmen, &month)?,
+        _ => deductions_register(conn, &workmen, first, last)?,
+    };
+
+    Ok(StatutoryRegister {
+        register: register.to_string(),
+        form: form.to_string(),
+        title: title.to_string(),
+        month,
+        site_id,
+        header_lines,
+        columns,
+        rows,
+        footer_lines,
+    })
+}
+
+/// The register as a printable table, with amounts in Indian digit grouping
+pub fn register_report(register: &StatutoryRegister) -> TableReport {
+    let columns = register
+        .columns
+        .iter()
+        .map(|c| {
+            let align = if c.kind == "text" {
+                Align::Left
+            } else {
+                Align::Right
+            };
+            TableColumn::new(&c.title, c.width, align)
+        })
+        .collect();
+
+    let mut report = TableReport::new(&format!("{} - {}", register.form, register.title), columns);
+    report.subtitle_lines = register.header_lines.clone();
+    report.rows = register
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&register.columns)
+                .map(|(value, column)| match value.parse::<f64>() {
+                    Ok(amount) if column.kind == "amount" => format_amount(amount),
+                    _ => value.clone(),
+                })
+                .collect()
+        })
+        .collect();
+    if report.rows.is_empty() {
+        report.rows.push(vec!["Nil".to_string()]);
+    }
+    report.footer_lines = register.footer_lines.clone();
+    report.footer_lines.push(String::new());
+    report
+        .footer_lines
+        .push("Signature of contractor: ____________________".to_string());
+    report
+}
+
+/// The register as a worksheet, with numbers and amounts as numeric cells
+pub fn register_sheet(register: &StatutoryRegister) -> Sheet {
+    let mut sheet = Sheet::new(&register.form);
+    sheet.set_column_widths(
+        &register
+            .columns
+            .iter()
+            .map(|c| (c.width / 5.0).max(4.0) as f64)
+            .collect::<Vec<_>>(),
+    );
+
+    sheet.add_bold_row(vec![Cell::text(format!(
+        "{} - {}",
+        register.form, register.title
+    ))]);
+    for line in &register.header_lines {
+        sheet.add_row(vec![Cell::text(line.clone())]);
+    }
+    sheet.add_row(Vec::new());
+    sheet.add_bold_row(
+        register
+            .columns
+            .iter()
+            .map(|c| Cell::text(c.title.clone()))
+            .collect(),
+    );
+    for row in &register.rows {
+        sheet.add_row(
+            row.iter()
+                .zip(&register.columns)
+                .map(|(value, column)| match value.parse::<f64>() {
+                    Ok(number) if column.kind != "text" => Cell::number(number),
+                    _ if value.is_empty() => Cell::Empty,
+                    _ => Cell::text(value.clone()),
+                })
+                .collect(),
+        );
+    }
+    if register.rows.is_empty() {
+        sheet.add_row(vec![Cell::text("Nil")]);
+    }
+    if !register.footer_lines.is_empty() {
+        sheet.add_row(Vec::new());
+        for line in &register.footer_lines {
+            sheet.add_row(vec![Cell::text(line.clone())]);
+        }
+    }
+
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    /// Anil left service on 20 July; Ravi was transferred from the gate to
+    /// the yard on 16 July
+    fn with_workmen() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute_batch(
+            "INSERT INTO employees (name, essid, employment_status, exit_date, date_of_birth)
+                VALUES ('Anil', 'E2', 'past', '2024-07-20', '1990-08-01');
+            INSERT INTO employees (name, essid, employment_status, father_name)
+                VALUES ('Ravi', 'E1', 'current', 'Mohan');
+            INSERT INTO clients (name) VALUES ('Acme');
+            INSERT INTO sites (client_id, name) VALUES (1, 'Gate'), (1, 'Yard');
+            INSERT INTO deployments (employee_id, site_id, role, start_date, end_date) VALUES
+                (1, 1, 'Supervisor', '2024-03-01', '2024-07-20'),
+                (2, 1, 'Guard', '2024-01-01', '2024-07-15'),
+                (2, 2, 'Guard', '2024-07-16', NULL);
+            INSERT INTO attendance (employee_id, attendance_date, status, site_id, ot_shifts) VALUES
+                (1, '2024-07-03', 'absent', 1, 0),
+                (2, '2024-07-01', 'present', NULL, 1),
+                (2, '2024-07-02', 'present', 2, 0),
+                (2, '2024-07-16', 'present', NULL, 0);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn workmen_register_records_why_service_ended() {
+        let conn = with_workmen();
+
+        let register = build_register(&conn, "workmen", "2024-07", Some(1)).unwrap();
+        let rows: Vec<_> = register
+            .rows
+            .iter()
+            .map(|r| {
+                (
+                    r[1].as_str(),
+                    r[3].as_str(),
+                    r[5].as_str(),
+                    r[9].as_str(),
+                    r[10].as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Anil", "33", "Supervisor", "2024-07-20", "Left service"),
+                ("Ravi", "", "Guard", "2024-07-15", "Transferred"),
+            ]
+        );
+        assert!(build_register(&conn, "fines", "2024-07", None).is_err());
+    }
+
+    #[test]
+    fn muster_roll_at_a_site_marks_only_the_days_worked_there() {
+        let conn = with_workmen();
+
+        let register = build_register(&conn, "muster_roll", "2024-07", Some(1)).unwrap();
+        assert_eq!(register.columns.len(), 3 + 31 + 2);
+        let ravi = &register.rows[1];
+        assert_eq!(ravi[1], "Ravi");
+        // 1 July unassigned during the gate deployment, 2 July at the yard,
+        // 16 July after the transfer
+        assert_eq!((ravi[3].as_str(), ravi[4].as_str()), ("P+1", ""));
+        assert_eq!(ravi[18], "");
+        assert_eq!((ravi[34].as_str(), ravi[35].as_str()), ("1", "1"));
+        assert_eq!(register.rows[0][5], "A");
+
+        let all_sites = build_register(&conn, "muster_roll", "2024-07", None).unwrap();
+        let ravi = &all_sites.rows[1];
+        assert_eq!((ravi[4].as_str(), ravi[18].as_str()), ("P", "P"));
+        assert_eq!(ravi[34], "3");
+    }
+}
@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterColumn {
+    pub title: String,
+    /// Relative width in the printed register
+    pub width: f32,
+    /// `text`, `number` or `amount`
+    pub kind: String,
+}
+
+/// A statutory register ready to be shown, printed or exported. Number and
+/// amount cells hold plain decimals and are formatted when printed.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatutoryRegister {
+    /// `workmen`, `muster_roll`, `wages` or `deductions`
+    pub register: String,
+    pub form: String,
+    pub title: String,
+    pub month: String,
+    pub site_id: Option<i64>,
+    pub header_lines: Vec<String>,
+    pub columns: Vec<RegisterColumn>,
+    pub rows: Vec<Vec<String>>,
+    pub footer_lines: Vec<String>,
+}
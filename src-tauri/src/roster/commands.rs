@@ -0,0 +1,606 @@
+use super::helpers::{
+    detect_conflicts, find_overlapping_assignment, find_short_rest, get_roster_assignment_by_id,
+    get_roster_between, get_shift_template_by_id, map_shift_template, parse_time, week_dates,
+};
+use super::types::{
+    CopyRosterResult, RosterAssignment, RosterAssignmentInput, RosterConflict, ShiftTemplate,
+    ShiftTemplateInput,
+};
+use crate::clients::helpers::get_site_by_id;
+use crate::csv::write_csv;
+use crate::dates::{format_date, parse_date};
+use crate::db;
+use crate::deployments::helpers::get_active_deployment;
+use crate::employees::helpers::get_employee_by_id;
+use crate::employees::types::Employee;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use crate::weapons::helpers::{armed_licence_problem, ensure_armed_licence};
+use chrono::Duration;
+use rusqlite::{params, Connection, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::State;
+
+const DEFAULT_MIN_REST_HOURS: i64 = 8;
+
+/// Role a rostered shift is worked in: its own role, else the role of the
+/// employee's deployment on the day, else their job post
+fn shift_role(
+    conn: &Connection,
+    employee: &Employee,
+    role: Option<&str>,
+    date: &str,
+) -> Result<String, String> {
+    if let Some(role) = role.filter(|r| !r.trim().is_empty()) {
+        return Ok(role.to_string());
+    }
+    let deployment_role = get_active_deployment(conn, employee.id, date)?.and_then(|d| d.role);
+    Ok(deployment_role
+        .filter(|r| !r.trim().is_empty())
+        .or_else(|| employee.job_post.clone())
+        .unwrap_or_default())
+}
+
+fn validate_shift_template(template: &ShiftTemplateInput) -> Result<(), String> {
+    if template.name.trim().is_empty() || template.code.trim().is_empty() {
+        return Err("Shift name and code are required".to_string());
+    }
+    parse_time(&template.start_time, "start time")?;
+    parse_time(&template.end_time, "end time")?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_shift_templates(
+    _state: State<AppState>,
+    include_inactive: Option<bool>,
+) -> Result<Vec<ShiftTemplate>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM shift_templates WHERE ?1 OR is_active = 1 ORDER BY start_time")
+        .map_err(|e| e.to_string())?;
+
+    let templates = stmt
+        .query_map([include_inactive.unwrap_or(false)], map_shift_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(templates)
+}
+
+#[tauri::command]
+pub fn create_shift_template(
+    _state: State<AppState>,
+    template: ShiftTemplateInput,
+) -> Result<ShiftTemplate, String> {
+    let conn = db::get_connection()?;
+
+    validate_shift_template(&template)?;
+
+    conn.execute(
+        "INSERT INTO shift_templates (name, code, start_time, end_time, is_active)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            template.name.trim(),
+            template.code.trim(),
+            template.start_time.trim(),
+            template.end_time.trim(),
+            template.is_active,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Shift with the same name or code already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(template)) = get_shift_template_by_id(&conn, id) {
+        Ok(template)
+    } else {
+        Err("Failed to retrieve shift template".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_shift_template(
+    _state: State<AppState>,
+    id: i64,
+    template: ShiftTemplateInput,
+) -> Result<ShiftTemplate, String> {
+    let conn = db::get_connection()?;
+
+    validate_shift_template(&template)?;
+
+    conn.execute(
+        "UPDATE shift_templates SET
+            name = ?1, code = ?2, start_time = ?3, end_time = ?4, is_active = ?5,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?6",
+        params![
+            template.name.trim(),
+            template.code.trim(),
+            template.start_time.trim(),
+            template.end_time.trim(),
+            template.is_active,
+            id,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "Shift with the same name or code already exists".to_string()
+        }
+        e => e.to_string(),
+    })?;
+
+    if let Ok(Some(template)) = get_shift_template_by_id(&conn, id) {
+        Ok(template)
+    } else {
+        Err("Failed to retrieve shift template".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_shift_template(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let usage: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM roster_assignments WHERE shift_template_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if usage > 0 {
+        return Err("Shift is used in the roster, mark it inactive instead".to_string());
+    }
+
+    conn.execute("DELETE FROM shift_templates WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Roster of a site for the seven days starting on `week_start`
+#[tauri::command]
+pub fn get_site_roster(
+    _state: State<AppState>,
+    site_id: i64,
+    week_start: String,
+) -> Result<Vec<RosterAssignment>, String> {
+    let conn = db::get_connection()?;
+
+    let week_start = parse_date(&week_start, "week start")?;
+    get_roster_between(
+        &conn,
+        week_start,
+        week_start + Duration::days(6),
+        Some(site_id),
+        None,
+    )
+}
+
+#[tauri::command]
+pub fn create_roster_assignment(
+    _state: State<AppState>,
+    assignment: RosterAssignmentInput,
+) -> Result<RosterAssignment, String> {
+    let conn = db::get_connection()?;
+
+    let roster_date = parse_date(&assignment.roster_date, "roster date")?;
+    let employee =
+        get_employee_by_id(&conn, assignment.employee_id)?.ok_or("Employee not found")?;
+    if employee.employment_status != "current" {
+        return Err(format!("{} is not a current employee", employee.name));
+    }
+    get_site_by_id(&conn, assignment.site_id)?.ok_or("Site not found")?;
+    let shift =
+        get_shift_template_by_id(&conn, assignment.shift_template_id)?.ok_or("Shift not found")?;
+
+    let role = shift_role(
+        &conn,
+        &employee,
+        assignment.role.as_deref(),
+        &format_date(roster_date),
+    )?;
+    ensure_armed_licence(
+        &conn,
+        employee.id,
+        &employee.name,
+        &role,
+        &format_date(roster_date),
+    )?;
+
+    if let Some(existing) = find_overlapping_assignment(
+        &conn,
+        assignment.employee_id,
+        roster_date,
+        &shift.start_time,
+        &shift.end_time,
+        None,
+    )? {
+        return Err(format!(
+            "{} is already rostered for the {} shift at {} on {}",
+            employee.name, existing.shift_name, existing.site_name, existing.roster_date
+        ));
+    }
+    if let Some((existing, rest)) = find_short_rest(
+        &conn,
+        assignment.employee_id,
+        roster_date,
+        &shift.start_time,
+        &shift.end_time,
+        assignment.min_rest_hours.unwrap_or(DEFAULT_MIN_REST_HOURS),
+    )? {
+        return Err(format!(
+            "{} would only have {}h {}m rest next to the {} shift at {} on {}",
+            employee.name,
+            rest.num_hours(),
+            rest.num_minutes() % 60,
+            existing.shift_name,
+            existing.site_name,
+            existing.roster_date
+        ));
+    }
+
+    conn.execute(
+        "INSERT INTO roster_assignments (
+            site_id, shift_template_id, employee_id, roster_date, role, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            assignment.site_id,
+            assignment.shift_template_id,
+            assignment.employee_id,
+            format_date(roster_date),
+            assignment.role,
+            assignment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(assignment)) = get_roster_assignment_by_id(&conn, id) {
+        Ok(assignment)
+    } else {
+        Err("Failed to retrieve roster assignment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_roster_assignment(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM roster_assignments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Double bookings, insufficient rest, shifts at sites the employee is not
+/// deployed to and armed shifts without a valid weapon licence, for the week
+/// starting on `week_start`
+#[tauri::command]
+pub fn get_roster_conflicts(
+    _state: State<AppState>,
+    site_id: Option<i64>,
+    week_start: String,
+    min_rest_hours: Option<i64>,
+) -> Result<Vec<RosterConflict>, String> {
+    let conn = db::get_connection()?;
+
+    let week_start = parse_date(&week_start, "week start")?;
+    let week_end = week_start + Duration::days(6);
+
+    let scope = get_roster_between(&conn, week_start, week_end, site_id, None)?;
+    let scope_ids: Vec<i64> = scope.iter().map(|a| a.id).collect();
+
+    // Shifts at other sites and just outside the week still count against
+    // the rostered employees
+    let mut employee_ids: Vec<i64> = scope.iter().map(|a| a.employee_id).collect();
+    employee_ids.sort_unstable();
+    employee_ids.dedup();
+    let context = get_roster_between(
+        &conn,
+        week_start - Duration::days(1),
+        week_end + Duration::days(1),
+        None,
+        None,
+    )?
+    .into_iter()
+    .filter(|a| employee_ids.binary_search(&a.employee_id).is_ok())
+    .collect::<Vec<_>>();
+
+    let mut conflicts = detect_conflicts(
+        &context,
+        &scope_ids,
+        min_rest_hours.unwrap_or(DEFAULT_MIN_REST_HOURS),
+    )?;
+
+    for assignment in &scope {
+        let employee =
+            get_employee_by_id(&conn, assignment.employee_id)?.ok_or("Employee not found")?;
+        let role = shift_role(
+            &conn,
+            &employee,
+            assignment.role.as_deref(),
+            &assignment.roster_date,
+        )?;
+        if let Some(problem) = armed_licence_problem(
+            &conn,
+            employee.id,
+            &employee.name,
+            &role,
+            &assignment.roster_date,
+        )? {
+            conflicts.push(RosterConflict {
+                kind: "armed_licence".to_string(),
+                employee_id: assignment.employee_id,
+                employee_name: assignment.employee_name.clone(),
+                essid: assignment.essid.clone(),
+                roster_date: assignment.roster_date.clone(),
+                assignment_id: assignment.id,
+                other_assignment_id: None,
+                message: problem,
+            });
+        }
+
+        let deployment =
+            get_active_deployment(&conn, assignment.employee_id, &assignment.roster_date)?;
+        if deployment.map(|d| d.site_id) != Some(assignment.site_id) {
+            conflicts.push(RosterConflict {
+                kind: "not_deployed".to_string(),
+                employee_id: assignment.employee_id,
+                employee_name: assignment.employee_name.clone(),
+                essid: assignment.essid.clone(),
+                roster_date: assignment.roster_date.clone(),
+                assignment_id: assignment.id,
+                other_assignment_id: None,
+                message: format!(
+                    "{} is not deployed at {} on {}",
+                    assignment.employee_name, assignment.site_name, assignment.roster_date
+                ),
+            });
+        }
+    }
+
+    conflicts.sort_by(|a, b| a.roster_date.cmp(&b.roster_date));
+    Ok(conflicts)
+}
+
+/// Copy the site's roster of the previous week into the week starting on
+/// `week_start`, skipping shifts that already exist, would double-book or are
+/// armed without a valid weapon licence
+#[tauri::command]
+pub fn copy_previous_week_roster(
+    _state: State<AppState>,
+    site_id: i64,
+    week_start: String,
+) -> Result<CopyRosterResult, String> {
+    let mut conn = db::get_connection()?;
+
+    let week_start = parse_date(&week_start, "week start")?;
+    let previous_week = get_roster_between(
+        &conn,
+        week_start - Duration::days(7),
+        week_start - Duration::days(1),
+        Some(site_id),
+        None,
+    )?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut copied = 0;
+    let mut skipped = Vec::new();
+
+    for assignment in previous_week {
+        let target_date = parse_date(&assignment.roster_date, "roster date")? + Duration::days(7);
+        let target = format_date(target_date);
+
+        let employee =
+            get_employee_by_id(&tx, assignment.employee_id)?.ok_or("Employee not found")?;
+        if employee.employment_status != "current" {
+            skipped.push(format!(
+                "{} on {target}: no longer a current employee",
+                assignment.employee_name
+            ));
+            continue;
+        }
+        let role = shift_role(&tx, &employee, assignment.role.as_deref(), &target)?;
+        if let Some(problem) =
+            armed_licence_problem(&tx, employee.id, &employee.name, &role, &target)?
+        {
+            skipped.push(format!(
+                "{} on {target}: {problem}",
+                assignment.employee_name
+            ));
+            continue;
+        }
+
+        if let Some(existing) = find_overlapping_assignment(
+            &tx,
+            assignment.employee_id,
+            target_date,
+            &assignment.start_time,
+            &assignment.end_time,
+            None,
+        )? {
+            skipped.push(format!(
+                "{} on {target}: already rostered for the {} shift at {}",
+                assignment.employee_name, existing.shift_name, existing.site_name
+            ));
+            continue;
+        }
+
+        tx.execute(
+            "INSERT INTO roster_assignments (
+                site_id, shift_template_id, employee_id, roster_date, role, remarks
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                assignment.site_id,
+                assignment.shift_template_id,
+                assignment.employee_id,
+                target,
+                assignment.role,
+                assignment.remarks,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        copied += 1;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(CopyRosterResult { copied, skipped })
+}
+
+/// One row per employee and role with the shift codes for each day of the week
+fn roster_grid(
+    assignments: &[RosterAssignment],
+    week_start: chrono::NaiveDate,
+) -> Vec<Vec<String>> {
+    let dates: Vec<String> = week_dates(week_start)
+        .into_iter()
+        .map(format_date)
+        .collect();
+
+    let mut grid: BTreeMap<(String, String, String), Vec<Vec<String>>> = BTreeMap::new();
+    for assignment in assignments {
+        let key = (
+            assignment.employee_name.clone(),
+            assignment.essid.clone(),
+            assignment.role.clone().unwrap_or_default(),
+        );
+        let days = grid.entry(key).or_insert_with(|| vec![Vec::new(); 7]);
+        if let Some(day) = dates.iter().position(|d| *d == assignment.roster_date) {
+            days[day].push(assignment.shift_code.clone());
+        }
+    }
+
+    grid.into_iter()
+        .map(|((name, essid, role), days)| {
+            let mut row = vec![essid, name, role];
+            row.extend(days.into_iter().map(|codes| {
+                if codes.is_empty() {
+                    "OFF".to_string()
+                } else {
+                    codes.join("+")
+                }
+            }));
+            row
+        })
+        .collect()
+}
+
+fn roster_header(week_start: chrono::NaiveDate) -> Vec<String> {
+    let mut header = vec!["ESSID".to_string(), "Name".to_string(), "Role".to_string()];
+    header.extend(
+        week_dates(week_start)
+            .into_iter()
+            .map(|d| d.format("%a %d-%m").to_string()),
+    );
+    header
+}
+
+#[tauri::command]
+pub fn export_roster_csv(
+    _state: State<AppState>,
+    site_id: i64,
+    week_start: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let week_start = parse_date(&week_start, "week start")?;
+    get_site_by_id(&conn, site_id)?.ok_or("Site not found")?;
+    let assignments = get_roster_between(
+        &conn,
+        week_start,
+        week_start + Duration::days(6),
+        Some(site_id),
+        None,
+    )?;
+
+    let header = roster_header(week_start);
+    let header: Vec<&str> = header.iter().map(String::as_str).collect();
+    write_csv(
+        &PathBuf::from(&export_path),
+        &header,
+        &roster_grid(&assignments, week_start),
+    )
+}
+
+#[tauri::command]
+pub fn export_roster_pdf(
+    _state: State<AppState>,
+    site_id: i64,
+    week_start: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let week_start = parse_date(&week_start, "week start")?;
+    let site = get_site_by_id(&conn, site_id)?.ok_or("Site not found")?;
+    let assignments = get_roster_between(
+        &conn,
+        week_start,
+        week_start + Duration::days(6),
+        Some(site_id),
+        None,
+    )?;
+
+    let mut columns = vec![
+        TableColumn::new("ESSID", 60.0, Align::Left),
+        TableColumn::new("Name", 140.0, Align::Left),
+        TableColumn::new("Role", 80.0, Align::Left),
+    ];
+    for title in roster_header(week_start).iter().skip(3) {
+        columns.push(TableColumn::new(title, 55.0, Align::Center));
+    }
+
+    let mut report = TableReport::new(&format!("Duty Roster - {}", site.name), columns);
+    report.subtitle_lines.push(format!(
+        "Client: {}    Week: {} to {}",
+        site.client_name,
+        format_date(week_start),
+        format_date(week_start + Duration::days(6))
+    ));
+
+    let mut shifts: Vec<(String, String, String, String)> = assignments
+        .iter()
+        .map(|a| {
+            (
+                a.shift_code.clone(),
+                a.shift_name.clone(),
+                a.start_time.clone(),
+                a.end_time.clone(),
+            )
+        })
+        .collect();
+    shifts.sort();
+    shifts.dedup();
+    let legend = shifts
+        .iter()
+        .map(|(code, name, start, end)| format!("{code} = {name} ({start}-{end})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !legend.is_empty() {
+        report.subtitle_lines.push(format!("Shifts: {legend}"));
+    }
+
+    report.rows = roster_grid(&assignments, week_start);
+    report.footer_lines.push(String::new());
+    report.footer_lines.push(
+        "Prepared by: ____________________        Approved by: ____________________".to_string(),
+    );
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
@@ -0,0 +1,349 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{params, Row};
+
+use super::types::{RosterAssignment, RosterConflict, ShiftTemplate};
+use crate::dates::{format_date, parse_date};
+
+pub const ROSTER_SELECT: &str = "SELECT r.id, r.site_id, s.name, r.shift_template_id, t.name,
+        t.code, t.start_time, t.end_time, r.employee_id, e.name, e.essid, r.roster_date,
+        r.role, r.remarks
+    FROM roster_assignments r
+    JOIN sites s ON s.id = r.site_id
+    JOIN shift_templates t ON t.id = r.shift_template_id
+    JOIN employees e ON e.id = r.employee_id";
+
+pub fn map_shift_template(row: &Row) -> rusqlite::Result<ShiftTemplate> {
+    Ok(ShiftTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        code: row.get(2)?,
+        start_time: row.get(3)?,
+        end_time: row.get(4)?,
+        is_active: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn map_roster_assignment(row: &Row) -> rusqlite::Result<RosterAssignment> {
+    Ok(RosterAssignment {
+        id: row.get(0)?,
+        site_id: row.get(1)?,
+        site_name: row.get(2)?,
+        shift_template_id: row.get(3)?,
+        shift_name: row.get(4)?,
+        shift_code: row.get(5)?,
+        start_time: row.get(6)?,
+        end_time: row.get(7)?,
+        employee_id: row.get(8)?,
+        employee_name: row.get(9)?,
+        essid: row.get(10)?,
+        roster_date: row.get(11)?,
+        role: row.get(12)?,
+        remarks: row.get(13)?,
+    })
+}
+
+pub fn get_shift_template_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<ShiftTemplate>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM shift_templates WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_shift_template) {
+        Ok(template) => Ok(Some(template)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_roster_assignment_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<RosterAssignment>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ROSTER_SELECT} WHERE r.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_roster_assignment) {
+        Ok(assignment) => Ok(Some(assignment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Roster assignments between two dates (inclusive), optionally limited to a
+/// site and/or an employee
+pub fn get_roster_between(
+    conn: &rusqlite::Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    site_id: Option<i64>,
+    employee_id: Option<i64>,
+) -> Result<Vec<RosterAssignment>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ROSTER_SELECT}
+            WHERE r.roster_date BETWEEN ?1 AND ?2
+              AND (?3 IS NULL OR r.site_id = ?3)
+              AND (?4 IS NULL OR r.employee_id = ?4)
+            ORDER BY r.roster_date, t.start_time, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let assignments = stmt
+        .query_map(
+            params![format_date(from), format_date(to), site_id, employee_id],
+            map_roster_assignment,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(assignments)
+}
+
+pub fn parse_time(value: &str, field: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid {field} '{value}', expected HH:MM"))
+}
+
+/// Start and end of a shift on a date. Shifts ending at or before their start
+/// time run past midnight into the next day.
+pub fn shift_interval(
+    date: NaiveDate,
+    start_time: &str,
+    end_time: &str,
+) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+    let start = date.and_time(parse_time(start_time, "shift start time")?);
+    let mut end = date.and_time(parse_time(end_time, "shift end time")?);
+    if end <= start {
+        end += Duration::days(1);
+    }
+    Ok((start, end))
+}
+
+fn assignment_interval(
+    assignment: &RosterAssignment,
+) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+    shift_interval(
+        parse_date(&assignment.roster_date, "roster date")?,
+        &assignment.start_time,
+        &assignment.end_time,
+    )
+}
+
+/// An existing assignment of the employee that overlaps the given shift
+pub fn find_overlapping_assignment(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    date: NaiveDate,
+    start_time: &str,
+    end_time: &str,
+    exclude_id: Option<i64>,
+) -> Result<Option<RosterAssignment>, String> {
+    let (start, end) = shift_interval(date, start_time, end_time)?;
+
+    let nearby = get_roster_between(
+        conn,
+        date - Duration::days(1),
+        date + Duration::days(1),
+        None,
+        Some(employee_id),
+    )?;
+
+    for assignment in nearby {
+        if Some(assignment.id) == exclude_id {
+            continue;
+        }
+        let (other_start, other_end) = assignment_interval(&assignment)?;
+        if other_start < end && start < other_end {
+            return Ok(Some(assignment));
+        }
+    }
+
+    Ok(None)
+}
+
+/// An existing assignment of the employee ending less than `min_rest_hours`
+/// before the given shift starts or starting that soon after it ends, with
+/// the rest left between them
+pub fn find_short_rest(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    date: NaiveDate,
+    start_time: &str,
+    end_time: &str,
+    min_rest_hours: i64,
+) -> Result<Option<(RosterAssignment, Duration)>, String> {
+    let (start, end) = shift_interval(date, start_time, end_time)?;
+    let min_rest = Duration::hours(min_rest_hours);
+
+    let nearby = get_roster_between(
+        conn,
+        date - Duration::days(2),
+        date + Duration::days(2),
+        None,
+        Some(employee_id),
+    )?;
+
+    for assignment in nearby {
+        let (other_start, other_end) = assignment_interval(&assignment)?;
+        let rest = if other_end <= start {
+            start - other_end
+        } else if end <= other_start {
+            other_start - end
+        } else {
+            continue;
+        };
+        if rest < min_rest {
+            return Ok(Some((assignment, rest)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Double bookings and insufficient rest among `assignments`, checked in
+/// order of shift start per employee. Only pairs involving an assignment in
+/// `scope` are reported.
+pub fn detect_conflicts(
+    assignments: &[RosterAssignment],
+    scope: &[i64],
+    min_rest_hours: i64,
+) -> Result<Vec<RosterConflict>, String> {
+    let mut timed = assignments
+        .iter()
+        .map(|a| assignment_interval(a).map(|(start, end)| (a, start, end)))
+        .collect::<Result<Vec<_>, _>>()?;
+    timed.sort_by_key(|a| (a.0.employee_id, a.1));
+
+    let min_rest = Duration::hours(min_rest_hours);
+    let mut conflicts = Vec::new();
+
+    // Latest-ending shift seen so far for the current employee
+    let mut previous: Option<(&RosterAssignment, NaiveDateTime)> = None;
+
+    for (next, next_start, next_end) in timed {
+        let Some((last, last_end)) = previous.filter(|(p, _)| p.employee_id == next.employee_id)
+        else {
+            previous = Some((next, next_end));
+            continue;
+        };
+
+        if scope.contains(&last.id) || scope.contains(&next.id) {
+            if next_start < last_end {
+                conflicts.push(RosterConflict {
+                    kind: "double_booking".to_string(),
+                    employee_id: next.employee_id,
+                    employee_name: next.employee_name.clone(),
+                    essid: next.essid.clone(),
+                    roster_date: next.roster_date.clone(),
+                    assignment_id: next.id,
+                    other_assignment_id: Some(last.id),
+                    message: format!(
+                        "{} shift at {} on {} overlaps {} shift at {} on {}",
+                        next.shift_name,
+                        next.site_name,
+                        next.roster_date,
+                        last.shift_name,
+                        last.site_name,
+                        last.roster_date
+                    ),
+                });
+            } else if next_start - last_end < min_rest {
+                let rest = next_start - last_end;
+                conflicts.push(RosterConflict {
+                    kind: "insufficient_rest".to_string(),
+                    employee_id: next.employee_id,
+                    employee_name: next.employee_name.clone(),
+                    essid: next.essid.clone(),
+                    roster_date: next.roster_date.clone(),
+                    assignment_id: next.id,
+                    other_assignment_id: Some(last.id),
+                    message: format!(
+                        "Only {}h {}m rest between the {} shift ending {} and the {} shift starting {}",
+                        rest.num_hours(),
+                        rest.num_minutes() % 60,
+                        last.shift_name,
+                        last_end.format("%Y-%m-%d %H:%M"),
+                        next.shift_name,
+                        next_start.format("%Y-%m-%d %H:%M")
+                    ),
+                });
+            }
+        }
+
+        if next_end > last_end {
+            previous = Some((next, next_end));
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// The seven dates of the roster week starting on `week_start`
+pub fn week_dates(week_start: NaiveDate) -> Vec<NaiveDate> {
+    (0..7).map(|i| week_start + Duration::days(i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_date(value, "date").unwrap()
+    }
+
+    #[test]
+    fn rest_is_checked_on_both_sides_of_a_shift() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO clients (name, is_active) VALUES ('Acme', 1)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO sites (client_id, name, is_active) VALUES (1, 'Gate', 1)",
+            [],
+        )
+        .unwrap();
+        // Night shift from 20:00 on 1 July to 08:00 on 2 July
+        conn.execute(
+            "INSERT INTO roster_assignments (site_id, shift_template_id, employee_id, roster_date)
+            SELECT 1, id, 1, '2024-07-01' FROM shift_templates WHERE code = 'N'",
+            [],
+        )
+        .unwrap();
+
+        let (before, rest) = find_short_rest(&conn, 1, date("2024-07-01"), "09:00", "18:00", 8)
+            .unwrap()
+            .unwrap();
+        assert_eq!(before.shift_code, "N");
+        assert_eq!(rest, Duration::hours(2));
+
+        let (_, rest) = find_short_rest(&conn, 1, date("2024-07-02"), "12:00", "18:00", 8)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rest, Duration::hours(4));
+
+        assert!(
+            find_short_rest(&conn, 1, date("2024-07-02"), "16:00", "22:00", 8)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            find_short_rest(&conn, 1, date("2024-07-02"), "12:00", "18:00", 4)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
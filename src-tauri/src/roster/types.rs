@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShiftTemplate {
+    pub id: i64,
+    pub name: String,
+    pub code: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub is_active: i32,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShiftTemplateInput {
+    pub name: String,
+    pub code: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub is_active: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterAssignment {
+    pub id: i64,
+    pub site_id: i64,
+    pub site_name: String,
+    pub shift_template_id: i64,
+    pub shift_name: String,
+    pub shift_code: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub roster_date: String,
+    pub role: Option<String>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterAssignmentInput {
+    pub site_id: i64,
+    pub shift_template_id: i64,
+    pub employee_id: i64,
+    pub roster_date: String,
+    pub role: Option<String>,
+    pub remarks: Option<String>,
+    /// Least rest in hours the employee must have between shifts, 8 when
+    /// not given
+    pub min_rest_hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterConflict {
+    pub kind: String,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub roster_date: String,
+    pub assignment_id: i64,
+    pub other_assignment_id: Option<i64>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyRosterResult {
+    pub copied: i64,
+    pub skipped: Vec<String>,
+}
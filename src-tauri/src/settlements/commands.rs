@@ -0,0 +1,353 @@
+use super::helpers::{
+    draw_settlement, get_settlement_by_employee, get_settlement_by_id, leave_encashment_item,
+    load_settlement_items, map_settlement, outstanding_value, pending_salary_items,
+    settlement_exit_date, settlement_item, store_settlement_items, SETTLEMENT_SELECT,
+};
+use super::types::{ExitSettlement, SettlementInput};
+use crate::advances::helpers::{insert_recovery, open_advances, RECOVERY_CODE};
+use crate::bonus::helpers::unpaid_bonus;
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, parse_date};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::gratuity::helpers::calculate_gratuity;
+use crate::inventory::helpers::close_outstanding_items;
+use crate::leave::helpers::encashable_leave_days;
+use crate::payroll::helpers::{load_payroll_settings, round2};
+use crate::payroll::types::PayLineItem;
+use crate::pdf::{Orientation, PdfDocument};
+use crate::state::AppState;
+use rusqlite::{params, OptionalExtension, Result};
+use std::path::PathBuf;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_exit_settlements(
+    _state: State<AppState>,
+    status: Option<String>,
+) -> Result<Vec<ExitSettlement>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{SETTLEMENT_SELECT}
+            WHERE (?1 IS NULL OR s.status = ?1)
+            ORDER BY s.exit_date DESC, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut settlements = stmt
+        .query_map(params![status], map_settlement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for settlement in &mut settlements {
+        load_settlement_items(&conn, settlement)?;
+    }
+
+    Ok(settlements)
+}
+
+#[tauri::command]
+pub fn get_exit_settlement(
+    _state: State<AppState>,
+    employee_id: i64,
+) -> Result<Option<ExitSettlement>, String> {
+    let conn = db::get_connection()?;
+    get_settlement_by_employee(&conn, employee_id)
+}
+
+/// Compute, or recompute, the draft settlement of an employee marked past.
+/// Unpaid salary up to the exit date, gratuity, unpaid bonus and outstanding
+/// advances come from the records; the other amounts are taken from `input`.
+/// Leave encashment defaults to the encashable leave balance on the exit date
+/// and the uniform deduction to the cost of the items not returned.
+#[tauri::command]
+pub fn compute_exit_settlement(
+    _state: State<AppState>,
+    employee_id: i64,
+    input: SettlementInput,
+) -> Result<ExitSettlement, String> {
+    let mut conn = db::get_connection()?;
+
+    let employee = get_employee_by_id(&conn, employee_id)?.ok_or("Employee not found")?;
+    let exit_date = settlement_exit_date(&employee)?;
+    if let Some(existing) = get_settlement_by_employee(&conn, employee_id)? {
+        if existing.status == "settled" {
+            return Err(format!("{} has already been settled", employee.name));
+        }
+    }
+
+    let amounts = [
+        input.leave_encashment_days,
+        input.uniform_deduction,
+        input.other_earnings,
+        input.other_deductions,
+    ];
+    if amounts
+        .iter()
+        .flatten()
+        .any(|amount| !amount.is_finite() || *amount < 0.0)
+    {
+        return Err("Settlement amounts cannot be negative".to_string());
+    }
+
+    let mut items = pending_salary_items(&conn, &employee, exit_date)?;
+    items.extend(leave_encashment_item(
+        &conn,
+        &employee,
+        exit_date,
+        match input.leave_encashment_days {
+            Some(days) => days,
+            None => encashable_leave_days(&conn, employee_id, exit_date)?,
+        },
+    )?);
+    let gratuity = calculate_gratuity(&conn, &load_payroll_settings(&conn)?, &employee, exit_date)?;
+    if gratuity.payable_amount > 0.0 {
+        items.push(settlement_item(
+            "earning",
+            "GRATUITY",
+            &format!("Gratuity for {} years of service", gratuity.counted_years),
+            gratuity.payable_amount,
+        ));
+    }
+    for (_, accounting_year, amount) in unpaid_bonus(&conn, employee_id)? {
+        items.push(settlement_item(
+            "earning",
+            "BONUS",
+            &format!("Bonus for {accounting_year}"),
+            amount,
+        ));
+    }
+    if let Some(amount) = input.other_earnings.filter(|a| *a > 0.0) {
+        items.push(settlement_item(
+            "earning",
+            "OTHER_EARN",
+            "Other dues",
+            round2(amount),
+        ));
+    }
+
+    let outstanding_items_value = outstanding_value(&conn, employee_id)?;
+    let uniform_deduction = input.uniform_deduction.unwrap_or(outstanding_items_value);
+    for (code, label, amount) in [
+        (
+            "UNIFORM",
+            "Uniform and equipment not returned",
+            Some(uniform_deduction),
+        ),
+        ("OTHER_DED", "Other recoveries", input.other_deductions),
+    ] {
+        if let Some(amount) = amount.filter(|a| *a > 0.0) {
+            items.push(settlement_item("deduction", code, label, round2(amount)));
+        }
+    }
+
+    // Advances are recovered from whatever remains payable
+    let payable: f64 = items
+        .iter()
+        .map(|i| {
+            if i.kind == "earning" {
+                i.amount
+            } else {
+                -i.amount
+            }
+        })
+        .sum();
+    let outstanding: f64 = open_advances(&conn, employee_id)?
+        .iter()
+        .map(|a| a.outstanding)
+        .sum();
+    let advance_recovery = round2(outstanding.min(payable.max(0.0)));
+    if advance_recovery > 0.0 {
+        items.push(settlement_item(
+            "deduction",
+            RECOVERY_CODE,
+            "Salary advance recovery",
+            advance_recovery,
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO exit_settlements (employee_id, exit_date, remarks, outstanding_items_value)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (employee_id) DO UPDATE SET
+            exit_date = excluded.exit_date, remarks = excluded.remarks,
+            outstanding_items_value = excluded.outstanding_items_value",
+        params![
+            employee_id,
+            format_date(exit_date),
+            input.remarks,
+            outstanding_items_value
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let settlement_id: i64 = tx
+        .query_row(
+            "SELECT id FROM exit_settlements WHERE employee_id = ?1",
+            [employee_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    store_settlement_items(&tx, settlement_id, &items)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if let Ok(Some(settlement)) = get_settlement_by_id(&conn, settlement_id) {
+        Ok(settlement)
+    } else {
+        Err("Failed to retrieve settlement".to_string())
+    }
+}
+
+/// Mark a settlement as paid. Advances deducted in it are closed in the
+/// ledger, bonus arrears are marked paid and payroll no longer includes the
+/// employee.
+#[tauri::command]
+pub fn settle_exit_settlement(_state: State<AppState>, id: i64) -> Result<ExitSettlement, String> {
+    let mut conn = db::get_connection()?;
+
+    let settlement = get_settlement_by_id(&conn, id)?.ok_or("Settlement not found")?;
+    if settlement.status == "settled" {
+        return Err(format!(
+            "{} has already been settled",
+            settlement.employee_name
+        ));
+    }
+
+    // Earlier months have to be final before the history is closed
+    let draft_month: Option<String> = conn
+        .query_row(
+            "SELECT p.month FROM pay_runs p JOIN pay_run_lines l ON l.pay_run_id = p.id
+            WHERE l.employee_id = ?1 AND p.status = 'draft'
+            ORDER BY p.month LIMIT 1",
+            [settlement.employee_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(month) = draft_month {
+        return Err(format!(
+            "Payroll for {month} includes {} and has to be locked first",
+            settlement.employee_name
+        ));
+    }
+
+    // A pay run or attendance change since the draft would pay the salary
+    // twice or pay the wrong days
+    let employee =
+        get_employee_by_id(&conn, settlement.employee_id)?.ok_or("Employee not found")?;
+    let exit_date = parse_date(&settlement.exit_date, "exit date")?;
+    let salary = |items: &[PayLineItem]| -> Vec<(String, f64)> {
+        items
+            .iter()
+            .filter(|i| i.code == "SALARY")
+            .map(|i| (i.label.clone(), i.amount))
+            .collect()
+    };
+    if salary(&pending_salary_items(&conn, &employee, exit_date)?) != salary(&settlement.items) {
+        return Err(format!(
+            "Salary due to {} has changed since the settlement was computed, recompute it first",
+            settlement.employee_name
+        ));
+    }
+
+    // Items are closed as they stand when settling, so they have to be the
+    // ones valued in the draft
+    let valued_items: f64 = conn
+        .query_row(
+            "SELECT outstanding_items_value FROM exit_settlements WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if (outstanding_value(&conn, settlement.employee_id)? - valued_items).abs() > 0.005 {
+        return Err(format!(
+            "Items held by {} have changed since the settlement was computed, recompute it first",
+            settlement.employee_name
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut to_recover: f64 = settlement
+        .items
+        .iter()
+        .filter(|i| i.code == RECOVERY_CODE)
+        .map(|i| i.amount)
+        .sum();
+    for advance in open_advances(&tx, settlement.employee_id)? {
+        if to_recover <= 0.0 {
+            break;
+        }
+        let amount = round2(advance.outstanding.min(to_recover));
+        insert_recovery(
+            &tx,
+            advance.id,
+            None,
+            &settlement.exit_date,
+            amount,
+            Some("Full and final settlement"),
+        )?;
+        to_recover -= amount;
+    }
+
+    // Bonus arrears are paid with the settlement
+    if settlement.items.iter().any(|i| i.code == "BONUS") {
+        for (line_id, _, _) in unpaid_bonus(&tx, settlement.employee_id)? {
+            tx.execute(
+                "UPDATE bonus_run_lines SET paid_on = ?1 WHERE id = ?2",
+                params![settlement.exit_date, line_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    // Items not returned are deducted above and no longer held
+    close_outstanding_items(&tx, settlement.employee_id, &settlement.exit_date)?;
+
+    tx.execute(
+        "UPDATE exit_settlements SET
+            status = 'settled', settled_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_settlement_by_id(&conn, id)?.ok_or_else(|| "Failed to retrieve settlement".to_string())
+}
+
+#[tauri::command]
+pub fn delete_exit_settlement(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    let settlement = get_settlement_by_id(&conn, id)?.ok_or("Settlement not found")?;
+    if settlement.status == "settled" {
+        return Err("Settled statements cannot be deleted".to_string());
+    }
+
+    conn.execute("DELETE FROM exit_settlements WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn export_settlement_pdf(
+    _state: State<AppState>,
+    id: i64,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let settlement = get_settlement_by_id(&conn, id)?.ok_or("Settlement not found")?;
+    let profile = load_company_profile(&conn)?;
+
+    let mut doc = PdfDocument::new(
+        &format!("Full and final settlement - {}", settlement.employee_name),
+        Orientation::Portrait,
+    );
+    draw_settlement(&mut doc, &profile, &settlement);
+
+    doc.save(&PathBuf::from(&export_path))
+}
@@ -0,0 +1,629 @@
+use chrono::{Datelike, Months, NaiveDate};
+use rusqlite::{params, Row};
+
+use super::types::ExitSettlement;
+use crate::attendance::helpers::attendance_summaries;
+use crate::company::helpers::draw_letterhead;
+use crate::company::types::CompanyProfile;
+use crate::dates::{month_bounds, parse_date};
+use crate::employees::types::Employee;
+use crate::gratuity::helpers::service_length;
+use crate::inventory::helpers::outstanding_items;
+use crate::overtime::helpers::approved_ot_hours;
+use crate::payroll::helpers::{
+    compute_pay, eps_eligible, find_wage_structure, load_payroll_settings, round2,
+    WORKING_DAYS_PER_MONTH,
+};
+use crate::payroll::types::PayLineItem;
+use crate::payslips::helpers::{amount_in_words, format_amount, month_title};
+use crate::pdf::{text_width, Align, Font, PdfDocument, MARGIN};
+
+pub const SETTLEMENT_SELECT: &str = "SELECT s.id, s.employee_id, e.name, e.essid, e.job_post,
+        e.joining_date, s.exit_date, s.status, s.total_earnings, s.total_deductions,
+        s.net_payable, s.remarks, s.settled_at, s.created_at, s.updated_at
+    FROM exit_settlements s
+    JOIN employees e ON e.id = s.employee_id";
+
+pub fn map_settlement(row: &Row) -> rusqlite::Result<ExitSettlement> {
+    Ok(ExitSettlement {
+        id: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        essid: row.get(3)?,
+        job_post: row.get(4)?,
+        joining_date: row.get(5)?,
+        exit_date: row.get(6)?,
+        status: row.get(7)?,
+        total_earnings: row.get(8)?,
+        total_deductions: row.get(9)?,
+        net_payable: row.get(10)?,
+        remarks: row.get(11)?,
+        settled_at: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+        items: Vec::new(),
+    })
+}
+
+pub fn settlement_item(kind: &str, code: &str, label: &str, amount: f64) -> PayLineItem {
+    PayLineItem {
+        kind: kind.to_string(),
+        code: code.to_string(),
+        label: label.to_string(),
+        amount,
+    }
+}
+
+pub fn load_settlement_items(
+    conn: &rusqlite::Connection,
+    settlement: &mut ExitSettlement,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, code, label, amount FROM exit_settlement_items
+            WHERE settlement_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    settlement.items = stmt
+        .query_map([settlement.id], |row| {
+            Ok(settlement_item(
+                &row.get::<_, String>(0)?,
+                &row.get::<_, String>(1)?,
+                &row.get::<_, String>(2)?,
+                row.get(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn get_settlement_where(
+    conn: &rusqlite::Connection,
+    condition: &str,
+    id: i64,
+) -> Result<Option<ExitSettlement>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{SETTLEMENT_SELECT} WHERE {condition} = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    let mut settlement = match stmt.query_row([id], map_settlement) {
+        Ok(settlement) => settlement,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    load_settlement_items(conn, &mut settlement)?;
+    Ok(Some(settlement))
+}
+
+pub fn get_settlement_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<ExitSettlement>, String> {
+    get_settlement_where(conn, "s.id", id)
+}
+
+pub fn get_settlement_by_employee(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+) -> Result<Option<ExitSettlement>, String> {
+    get_settlement_where(conn, "s.employee_id", employee_id)
+}
+
+/// Whether the employee's final dues have been paid, after which payroll no
+/// longer touches them
+pub fn is_settled(conn: &rusqlite::Connection, employee_id: i64) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS (
+            SELECT 1 FROM exit_settlements WHERE employee_id = ?1 AND status = 'settled'
+        )",
+        [employee_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Cost of the uniform and equipment the employee has not returned
+pub fn outstanding_value(conn: &rusqlite::Connection, employee_id: i64) -> Result<f64, String> {
+    Ok(round2(
+        outstanding_items(conn, employee_id)?
+            .iter()
+            .map(|i| i.amount)
+            .sum(),
+    ))
+}
+
+/// Salary of the exit month and the month before it for the days worked that
+/// no pay run has paid yet, with the statutory deductions on it
+pub fn pending_salary_items(
+    conn: &rusqlite::Connection,
+    employee: &Employee,
+    exit_date: NaiveDate,
+) -> Result<Vec<PayLineItem>, String> {
+    let settings = load_payroll_settings(conn)?;
+    let job_post = employee.job_post.as_deref().unwrap_or_default();
+    let exit_month_start = exit_date.with_day(1).unwrap_or(exit_date);
+    let months = [
+        exit_month_start.checked_sub_months(Months::new(1)),
+        Some(exit_month_start),
+    ];
+
+    let mut items = Vec::new();
+    for month_start in months.into_iter().flatten() {
+        let month = month_start.format("%Y-%m").to_string();
+        let (first, last) = month_bounds(&month)?;
+
+        let already_paid: bool = conn
+            .query_row(
+                "SELECT EXISTS (
+                    SELECT 1 FROM pay_run_lines l JOIN pay_runs p ON p.id = l.pay_run_id
+                    WHERE l.employee_id = ?1 AND p.month = ?2
+                )",
+                params![employee.id, month],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if already_paid {
+            continue;
+        }
+
+        let worked_until = last.min(exit_date);
+        let Some(summary) =
+            attendance_summaries(conn, first, worked_until, None, Some(employee.id))?
+                .into_iter()
+                .next()
+        else {
+            continue;
+        };
+        let paid_days =
+            summary.present_days + summary.leave_days + summary.weekly_offs + summary.holidays;
+        if paid_days == 0 && summary.ot_shifts == 0 {
+            continue;
+        }
+
+        let structure = find_wage_structure(conn, job_post, worked_until)?
+            .ok_or_else(|| format!("No wage structure for {job_post} on {worked_until}"))?;
+        let days_in_month = (last - first).num_days() + 1;
+        let pay = compute_pay(
+            &settings,
+            &structure,
+            days_in_month,
+            paid_days.min(days_in_month),
+            summary.ot_shifts,
+            approved_ot_hours(conn, employee.id, first, worked_until)?,
+            eps_eligible(employee.date_of_birth.as_deref(), first),
+        );
+
+        let title = month_title(&month);
+        items.push(settlement_item(
+            "earning",
+            "SALARY",
+            &format!("Salary for {paid_days} days of {title}"),
+            pay.gross_pay,
+        ));
+        items.extend(pay.items.iter().filter(|i| i.kind == "deduction").map(|i| {
+            settlement_item(
+                "deduction",
+                &i.code,
+                &format!("{}, {title}", i.label),
+                i.amount,
+            )
+        }));
+    }
+
+    Ok(items)
+}
+
+/// Encashment of `days` of leave at the daily basic and DA on the exit date
+pub fn leave_encashment_item(
+    conn: &rusqlite::Connection,
+    employee: &Employee,
+    exit_date: NaiveDate,
+    days: f64,
+) -> Result<Option<PayLineItem>, String> {
+    if days <= 0.0 {
+        return Ok(None);
+    }
+    let job_post = employee.job_post.as_deref().unwrap_or_default();
+    let structure = find_wage_structure(conn, job_post, exit_date)?
+        .ok_or_else(|| format!("No wage structure for {job_post} on {exit_date}"))?;
+    let daily_rate = round2((structure.basic + structure.da) / WORKING_DAYS_PER_MONTH);
+
+    Ok(Some(settlement_item(
+        "earning",
+        "LEAVE_ENC",
+        &format!(
+            "Leave encashment, {days} days at {}",
+            format_amount(daily_rate)
+        ),
+        (daily_rate * days).round(),
+    )))
+}
+
+/// Years and months between joining and exit, e.g. `6 years 2 months`
+pub fn service_period(joining_date: Option<&str>, exit_date: &str) -> Option<String> {
+    let joining = parse_date(joining_date?, "joining date").ok()?;
+    let exit = parse_date(exit_date, "exit date").ok()?;
+    if exit < joining {
+        return None;
+    }
+
+    let (years, months, _) = service_length(joining, exit);
+    let plural = |n: i32, unit: &str| format!("{n} {unit}{}", if n == 1 { "" } else { "s" });
+    Some(match (years, months) {
+        (0, m) => plural(m, "month"),
+        (y, 0) => plural(y, "year"),
+        (y, m) => format!("{} {}", plural(y, "year"), plural(m, "month")),
+    })
+}
+
+/// Add the settlement statement of an employee as a page of `doc`
+pub fn draw_settlement(
+    doc: &mut PdfDocument,
+    profile: &CompanyProfile,
+    settlement: &ExitSettlement,
+) {
+    let width = doc.width;
+    let height = doc.height;
+    let printable_width = width - 2.0 * MARGIN;
+    let page = doc.add_page();
+
+    let mut y = draw_letterhead(page, width, profile);
+
+    let title = "Full and Final Settlement";
+    y += 8.0;
+    page.text(
+        (width - text_width(title, Font::Bold, 12.0)) / 2.0,
+        y,
+        Font::Bold,
+        12.0,
+        title,
+    );
+    y += 16.0;
+
+    let or_dash = |value: Option<&str>| {
+        value
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or("-")
+            .to_string()
+    };
+    let details = [
+        ("Employee name", settlement.employee_name.clone()),
+        ("ESSID", settlement.essid.clone()),
+        ("Designation", or_dash(settlement.job_post.as_deref())),
+        (
+            "Date of joining",
+            or_dash(settlement.joining_date.as_deref()),
+        ),
+        ("Date of exit", settlement.exit_date.clone()),
+        (
+            "Length of service",
+            or_dash(
+                service_period(settlement.joining_date.as_deref(), &settlement.exit_date)
+                    .as_deref(),
+            ),
+        ),
+    ];
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+    y += 4.0;
+    for (label, value) in details {
+        y += 13.0;
+        page.text(MARGIN + 4.0, y, Font::Regular, 9.0, label);
+        page.text_in_box(
+            MARGIN + 110.0,
+            y,
+            printable_width - 114.0,
+            Align::Left,
+            Font::Bold,
+            9.0,
+            &value,
+        );
+    }
+    y += 8.0;
+    page.line(MARGIN, y, width - MARGIN, y, 0.4);
+    y += 14.0;
+
+    let amount_width = 110.0;
+    let row_height = 15.0;
+    for (kind, heading, total_label, total) in [
+        (
+            "earning",
+            "Dues payable",
+            "Total dues",
+            settlement.total_earnings,
+        ),
+        (
+            "deduction",
+            "Recoveries",
+            "Total recoveries",
+            settlement.total_deductions,
+        ),
+    ] {
+        page.fill_rect(MARGIN, y, printable_width, row_height, 0.88);
+        page.text(MARGIN + 4.0, y + 10.5, Font::Bold, 9.0, heading);
+        page.text_in_box(
+            width - MARGIN - amount_width,
+            y + 10.5,
+            amount_width - 4.0,
+            Align::Right,
+            Font::Bold,
+            9.0,
+            "Amount (Rs.)",
+        );
+        y += row_height;
+
+        let items: Vec<_> = settlement.items.iter().filter(|i| i.kind == kind).collect();
+        if items.is_empty() {
+            page.text(MARGIN + 4.0, y + 10.5, Font::Regular, 9.0, "None");
+            y += row_height;
+        }
+        for item in items {
+            page.text_in_box(
+                MARGIN + 4.0,
+                y + 10.5,
+                printable_width - amount_width - 8.0,
+                Align::Left,
+                Font::Regular,
+                9.0,
+                &item.label,
+            );
+            page.text_in_box(
+                width - MARGIN - amount_width,
+                y + 10.5,
+                amount_width - 4.0,
+                Align::Right,
+                Font::Regular,
+                9.0,
+                &format_amount(item.amount),
+            );
+            y += row_height;
+        }
+
+        page.line(MARGIN, y, width - MARGIN, y, 0.6);
+        page.text(MARGIN + 4.0, y + 10.5, Font::Bold, 9.0, total_label);
+        page.text_in_box(
+            width - MARGIN - amount_width,
+            y + 10.5,
+            amount_width - 4.0,
+            Align::Right,
+            Font::Bold,
+            9.0,
+            &format_amount(total),
+        );
+        y += row_height + 12.0;
+    }
+
+    let net_label = if settlement.net_payable < 0.0 {
+        "Net amount recoverable from employee"
+    } else {
+        "Net amount payable to employee"
+    };
+    y += 6.0;
+    page.text(
+        MARGIN + 4.0,
+        y,
+        Font::Bold,
+        12.0,
+        &format!(
+            "{net_label}: Rs. {}",
+            format_amount(settlement.net_payable.abs())
+        ),
+    );
+    y += 14.0;
+    page.text(
+        MARGIN + 4.0,
+        y,
+        Font::Regular,
+        9.0,
+        &format!("({})", amount_in_words(settlement.net_payable)),
+    );
+
+    if let Some(remarks) = settlement
+        .remarks
+        .as_deref()
+        .filter(|r| !r.trim().is_empty())
+    {
+        y += 18.0;
+        page.text_in_box(
+            MARGIN + 4.0,
+            y,
+            printable_width - 8.0,
+            Align::Left,
+            Font::Regular,
+            9.0,
+            &format!("Remarks: {remarks}"),
+        );
+    }
+
+    y += 30.0;
+    page.text_in_box(
+        MARGIN + 4.0,
+        y,
+        printable_width - 8.0,
+        Align::Left,
+        Font::Regular,
+        8.5,
+        "I have received the above amount in full and final settlement of all my dues and have no further claims against the company.",
+    );
+
+    let signature_y = (y + 70.0).min(height - MARGIN - 20.0);
+    for (x, label) in [
+        (MARGIN + 4.0, "Employee signature"),
+        (width - MARGIN - 150.0, "Authorised signatory"),
+    ] {
+        page.line(x, signature_y - 12.0, x + 146.0, signature_y - 12.0, 0.4);
+        page.text(x, signature_y, Font::Regular, 9.0, label);
+    }
+
+    let footer = if settlement.status == "settled" {
+        format!(
+            "Settled on {}",
+            settlement.settled_at.as_deref().unwrap_or_default()
+        )
+    } else {
+        "Draft statement, subject to change until settled".to_string()
+    };
+    page.text(MARGIN, height - MARGIN, Font::Regular, 7.5, &footer);
+}
+
+/// Write the items of a settlement and update its totals
+pub fn store_settlement_items(
+    conn: &rusqlite::Connection,
+    settlement_id: i64,
+    items: &[PayLineItem],
+) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM exit_settlement_items WHERE settlement_id = ?1",
+        params![settlement_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for item in items {
+        conn.execute(
+            "INSERT INTO exit_settlement_items (settlement_id, kind, code, label, amount)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![settlement_id, item.kind, item.code, item.label, item.amount],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let total = |kind: &str| -> f64 {
+        round2(
+            items
+                .iter()
+                .filter(|i| i.kind == kind)
+                .map(|i| i.amount)
+                .sum(),
+        )
+    };
+    let (earnings, deductions) = (total("earning"), total("deduction"));
+    conn.execute(
+        "UPDATE exit_settlements SET
+            total_earnings = ?1, total_deductions = ?2, net_payable = ?3,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?4",
+        params![
+            earnings,
+            deductions,
+            round2(earnings - deductions),
+            settlement_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+pub fn settlement_exit_date(employee: &Employee) -> Result<NaiveDate, String> {
+    if employee.employment_status != "past" {
+        return Err(format!(
+            "{} is not marked as past, set the exit date first",
+            employee.name
+        ));
+    }
+    let exit_date = employee
+        .exit_date
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| format!("{} has no exit date", employee.name))?;
+    parse_date(exit_date, "exit date")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+    use crate::employees::helpers::get_employee_by_id;
+
+    fn exited_employee() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (
+                name, essid, employment_status, job_post, joining_date, exit_date
+            ) VALUES ('Ravi', 'E1', 'past', 'Guard', '2018-05-10', '2024-07-20')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn totals_are_stored_with_the_items() {
+        let conn = exited_employee();
+        conn.execute(
+            "INSERT INTO exit_settlements (employee_id, exit_date) VALUES (1, '2024-07-20')",
+            [],
+        )
+        .unwrap();
+
+        store_settlement_items(
+            &conn,
+            1,
+            &[
+                settlement_item("earning", "SALARY", "Salary", 10000.5),
+                settlement_item("earning", "GRATUITY", "Gratuity", 2500.25),
+                settlement_item("deduction", "PF", "PF", 1200.1),
+            ],
+        )
+        .unwrap();
+        let settlement = get_settlement_by_id(&conn, 1).unwrap().unwrap();
+        assert_eq!(settlement.items.len(), 3);
+        assert_eq!(
+            (
+                settlement.total_earnings,
+                settlement.total_deductions,
+                settlement.net_payable
+            ),
+            (12500.75, 1200.1, 11300.65)
+        );
+
+        // Recomputing replaces the earlier items
+        store_settlement_items(
+            &conn,
+            1,
+            &[settlement_item("deduction", "UNIFORM", "Uniform", 800.0)],
+        )
+        .unwrap();
+        let settlement = get_settlement_by_employee(&conn, 1).unwrap().unwrap();
+        assert_eq!(settlement.items.len(), 1);
+        assert_eq!(settlement.net_payable, -800.0);
+    }
+
+    #[test]
+    fn leave_is_encashed_at_the_daily_basic_and_da() {
+        let conn = exited_employee();
+        conn.execute(
+            "INSERT INTO wage_structures (job_post, effective_from, basic, da, hra)
+            VALUES ('Guard', '2024-04-01', 13000, 2600, 2000)",
+            [],
+        )
+        .unwrap();
+        let employee = get_employee_by_id(&conn, 1).unwrap().unwrap();
+        let exit_date = settlement_exit_date(&employee).unwrap();
+
+        let item = leave_encashment_item(&conn, &employee, exit_date, 7.5)
+            .unwrap()
+            .unwrap();
+        assert_eq!((item.code.as_str(), item.amount), ("LEAVE_ENC", 4500.0));
+        assert!(leave_encashment_item(&conn, &employee, exit_date, 0.0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn service_period_reads_in_years_and_months() {
+        assert_eq!(
+            service_period(Some("2018-05-10"), "2024-07-20").as_deref(),
+            Some("6 years 2 months")
+        );
+        assert_eq!(
+            service_period(Some("2023-07-20"), "2024-07-20").as_deref(),
+            Some("1 year")
+        );
+        assert_eq!(service_period(Some("2024-08-01"), "2024-07-20"), None);
+        assert_eq!(service_period(None, "2024-07-20"), None);
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::payroll::types::PayLineItem;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitSettlement {
+    pub id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub joining_date: Option<String>,
+    pub exit_date: String,
+    /// `draft` while amounts can be recomputed, `settled` once paid
+    pub status: String,
+    pub total_earnings: f64,
+    pub total_deductions: f64,
+    /// Negative when the employee owes the company
+    pub net_payable: f64,
+    pub remarks: Option<String>,
+    pub settled_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub items: Vec<PayLineItem>,
+}
+
+/// Amounts of the settlement that are not derived from attendance, wages,
+/// service, bonus runs or the advances ledger
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementInput {
+    /// Encashable leave balance when empty
+    pub leave_encashment_days: Option<f64>,
+    /// Cost of the uniform and equipment not returned when empty
+    pub uniform_deduction: Option<f64>,
+    pub other_earnings: Option<f64>,
+    pub other_deductions: Option<f64>,
+    pub remarks: Option<String>,
+}
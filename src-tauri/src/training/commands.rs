@@ -0,0 +1,575 @@
+use super::helpers::{
+    get_attendance_by_id, get_program_by_id, get_session_by_id, map_certification, map_program,
+    map_session, session_attendance, validate_attendance_status, CERTIFICATION_SELECT,
+    PROGRAM_SELECT, SESSION_SELECT,
+};
+use super::types::{
+    Certification, TrainingAttendance, TrainingAttendanceInput, TrainingProgram,
+    TrainingProgramInput, TrainingSession, TrainingSessionInput,
+};
+use crate::company::helpers::load_company_profile;
+use crate::compliance::helpers::TRAINING_CATEGORY;
+use crate::dates::{format_date, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::files::{delete_attachment_file, save_attachment_file};
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use chrono::{Duration, Months, NaiveDate};
+use rusqlite::{params, Connection, Result};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+fn validate_program(program: &TrainingProgramInput) -> Result<(), String> {
+    if program.code.trim().is_empty() {
+        return Err("Program code is required".to_string());
+    }
+    if program.name.trim().is_empty() {
+        return Err("Program name is required".to_string());
+    }
+    if program.validity_months.is_some_and(|m| m <= 0) {
+        return Err("Validity must be at least one month".to_string());
+    }
+    Ok(())
+}
+
+fn map_program_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "A training program with this code already exists".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_training_programs(
+    _state: State<AppState>,
+    active_only: Option<bool>,
+) -> Result<Vec<TrainingProgram>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{PROGRAM_SELECT} WHERE (?1 = 0 OR is_active = 1) ORDER BY name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let programs = stmt
+        .query_map(params![active_only.unwrap_or(false)], map_program)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(programs)
+}
+
+#[tauri::command]
+pub fn create_training_program(
+    _state: State<AppState>,
+    program: TrainingProgramInput,
+) -> Result<TrainingProgram, String> {
+    let conn = db::get_connection()?;
+
+    validate_program(&program)?;
+
+    conn.execute(
+        "INSERT INTO training_programs (code, name, description, validity_months, is_active)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            program.code.trim(),
+            program.name.trim(),
+            program.description,
+            program.validity_months,
+            program.is_active.unwrap_or(1),
+        ],
+    )
+    .map_err(map_program_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(program)) = get_program_by_id(&conn, id) {
+        Ok(program)
+    } else {
+        Err("Failed to retrieve training program".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_training_program(
+    _state: State<AppState>,
+    id: i64,
+    program: TrainingProgramInput,
+) -> Result<TrainingProgram, String> {
+    let conn = db::get_connection()?;
+
+    validate_program(&program)?;
+    let existing = get_program_by_id(&conn, id)?.ok_or("Training program not found")?;
+
+    conn.execute(
+        "UPDATE training_programs SET
+            code = ?1, name = ?2, description = ?3, validity_months = ?4, is_active = ?5,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?6",
+        params![
+            program.code.trim(),
+            program.name.trim(),
+            program.description,
+            program.validity_months,
+            program.is_active.unwrap_or(existing.is_active),
+            id,
+        ],
+    )
+    .map_err(map_program_error)?;
+
+    if let Ok(Some(program)) = get_program_by_id(&conn, id) {
+        Ok(program)
+    } else {
+        Err("Failed to retrieve training program".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_training_program(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM training_programs WHERE id = ?1", params![id])
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _)
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                "The program has sessions, mark it inactive instead".to_string()
+            }
+            e => e.to_string(),
+        })?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_training_sessions(
+    _state: State<AppState>,
+    program_id: Option<i64>,
+    from_date: Option<String>,
+    to_date: Option<String>,
+) -> Result<Vec<TrainingSession>, String> {
+    let conn = db::get_connection()?;
+
+    let from_date = from_date
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| parse_date(&d, "from date").map(format_date))
+        .transpose()?;
+    let to_date = to_date
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| parse_date(&d, "to date").map(format_date))
+        .transpose()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{SESSION_SELECT}
+            WHERE (?1 IS NULL OR s.program_id = ?1) AND (?2 IS NULL OR s.end_date >= ?2)
+              AND (?3 IS NULL OR s.start_date <= ?3)
+            ORDER BY s.start_date DESC, s.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut sessions = stmt
+        .query_map(params![program_id, from_date, to_date], map_session)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for session in &mut sessions {
+        session.attendance = session_attendance(&conn, session.id)?;
+    }
+
+    Ok(sessions)
+}
+
+fn validate_session(
+    conn: &Connection,
+    session: &TrainingSessionInput,
+) -> Result<(String, String), String> {
+    get_program_by_id(conn, session.program_id)?.ok_or("Training program not found")?;
+    let start_date = parse_date(&session.start_date, "start date")?;
+    let end_date = match session.end_date.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(end_date) => parse_date(end_date, "end date")?,
+        None => start_date,
+    };
+    if end_date < start_date {
+        return Err("End date must not be before the start date".to_string());
+    }
+    Ok((format_date(start_date), format_date(end_date)))
+}
+
+#[tauri::command]
+pub fn create_training_session(
+    _state: State<AppState>,
+    session: TrainingSessionInput,
+) -> Result<TrainingSession, String> {
+    let conn = db::get_connection()?;
+
+    let (start_date, end_date) = validate_session(&conn, &session)?;
+
+    conn.execute(
+        "INSERT INTO training_sessions (
+            program_id, start_date, end_date, trainer, venue, is_refresher, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            session.program_id,
+            start_date,
+            end_date,
+            session.trainer,
+            session.venue,
+            session.is_refresher.unwrap_or(0),
+            session.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(session)) = get_session_by_id(&conn, id) {
+        Ok(session)
+    } else {
+        Err("Failed to retrieve training session".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_training_session(
+    _state: State<AppState>,
+    id: i64,
+    session: TrainingSessionInput,
+) -> Result<TrainingSession, String> {
+    let conn = db::get_connection()?;
+
+    let (start_date, end_date) = validate_session(&conn, &session)?;
+    get_session_by_id(&conn, id)?.ok_or("Training session not found")?;
+
+    conn.execute(
+        "UPDATE training_sessions SET
+            program_id = ?1, start_date = ?2, end_date = ?3, trainer = ?4, venue = ?5,
+            is_refresher = ?6, remarks = ?7, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?8",
+        params![
+            session.program_id,
+            start_date,
+            end_date,
+            session.trainer,
+            session.venue,
+            session.is_refresher.unwrap_or(0),
+            session.remarks,
+            id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(session)) = get_session_by_id(&conn, id) {
+        Ok(session)
+    } else {
+        Err("Failed to retrieve training session".to_string())
+    }
+}
+
+/// Delete a session with its attendance. Certificates stay with the
+/// employees' attachments.
+#[tauri::command]
+pub fn delete_training_session(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM training_sessions WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Record an employee's attendance at a session, adding or replacing their
+/// entry. A completed session certifies the employee from the last day of the
+/// session until the program's validity runs out, unless dates are given.
+/// A certificate file is stored with the employee's attachments.
+#[tauri::command]
+pub fn record_training_attendance(
+    _state: State<AppState>,
+    session_id: i64,
+    attendance: TrainingAttendanceInput,
+) -> Result<TrainingAttendance, String> {
+    let mut conn = db::get_connection()?;
+
+    let status = attendance.status.trim().to_lowercase();
+    validate_attendance_status(&status)?;
+    let session = get_session_by_id(&conn, session_id)?.ok_or("Training session not found")?;
+    let program =
+        get_program_by_id(&conn, session.program_id)?.ok_or("Training program not found")?;
+    let employee =
+        get_employee_by_id(&conn, attendance.employee_id)?.ok_or("Employee not found")?;
+
+    let certificate_path = attendance
+        .certificate_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty());
+    let (certified_on, expiry_date) = if status == "completed" {
+        let certified_on = match attendance
+            .certified_on
+            .as_deref()
+            .filter(|d| !d.trim().is_empty())
+        {
+            Some(date) => parse_date(date, "certification date")?,
+            None => parse_date(&session.end_date, "end date")?,
+        };
+        let expiry_date = match attendance
+            .expiry_date
+            .as_deref()
+            .filter(|d| !d.trim().is_empty())
+        {
+            Some(date) => Some(parse_date(date, "expiry date")?),
+            None => program.validity_months.and_then(|months| {
+                certified_on
+                    .checked_add_months(Months::new(months as u32))
+                    .and_then(|d| d.pred_opt())
+            }),
+        };
+        if expiry_date.is_some_and(|expiry| expiry < certified_on) {
+            return Err("Expiry date must not be before the certification date".to_string());
+        }
+        (
+            Some(format_date(certified_on)),
+            expiry_date.map(format_date),
+        )
+    } else {
+        if certificate_path.is_some() {
+            return Err("A certificate can only be attached to a completed session".to_string());
+        }
+        (None, None)
+    };
+
+    let stored_path = certificate_path
+        .map(|path| save_attachment_file(Path::new(path), employee.id))
+        .transpose()?;
+
+    let result = (|| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let attachment_id = match (&stored_path, certificate_path) {
+            (Some(stored), Some(source)) => {
+                let file_name = Path::new(source)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                tx.execute(
+                    "INSERT INTO employee_attachments (
+                        employee_id, category, title, file_path, file_name, reference
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        employee.id,
+                        TRAINING_CATEGORY,
+                        format!("{} certificate", program.name),
+                        stored.to_string_lossy(),
+                        file_name,
+                        attendance.certificate_number,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+                Some(tx.last_insert_rowid())
+            }
+            _ => None,
+        };
+
+        tx.execute(
+            "INSERT INTO training_attendance (
+                session_id, employee_id, status, certificate_number, certified_on, expiry_date,
+                attachment_id, remarks
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (session_id, employee_id) DO UPDATE SET
+                status = excluded.status, certificate_number = excluded.certificate_number,
+                certified_on = excluded.certified_on, expiry_date = excluded.expiry_date,
+                attachment_id = CASE WHEN excluded.status = 'completed'
+                    THEN COALESCE(excluded.attachment_id, attachment_id) END,
+                remarks = excluded.remarks, updated_at = CURRENT_TIMESTAMP",
+            params![
+                session_id,
+                employee.id,
+                status,
+                attendance
+                    .certificate_number
+                    .filter(|_| status == "completed"),
+                certified_on,
+                expiry_date,
+                attachment_id,
+                attendance.remarks,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let id: i64 = tx
+            .query_row(
+                "SELECT id FROM training_attendance WHERE session_id = ?1 AND employee_id = ?2",
+                params![session_id, employee.id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(id)
+    })();
+
+    let id = match result {
+        Ok(id) => id,
+        Err(e) => {
+            if let Some(stored) = &stored_path {
+                delete_attachment_file(stored)?;
+            }
+            return Err(e);
+        }
+    };
+
+    if let Ok(Some(attendance)) = get_attendance_by_id(&conn, id) {
+        Ok(attendance)
+    } else {
+        Err("Failed to retrieve training attendance".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_training_attendance(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM training_attendance WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn report_date(date: Option<String>) -> Result<NaiveDate, String> {
+    match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => parse_date(&date, "date"),
+        None => Ok(today()),
+    }
+}
+
+/// Latest certification of the employee in every program, valid or expired
+/// on the given date (today by default)
+#[tauri::command]
+pub fn get_employee_certifications(
+    _state: State<AppState>,
+    employee_id: i64,
+    date: Option<String>,
+) -> Result<Vec<Certification>, String> {
+    let conn = db::get_connection()?;
+
+    let date = report_date(date)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{CERTIFICATION_SELECT} WHERE lc.employee_id = ?1 ORDER BY p.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let certifications = stmt
+        .query_map([employee_id], |row| map_certification(row, date))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(certifications)
+}
+
+fn refresher_due(
+    conn: &Connection,
+    date: NaiveDate,
+    within_days: i64,
+    program_id: Option<i64>,
+) -> Result<Vec<Certification>, String> {
+    let due_by = format_date(date + Duration::days(within_days.max(0)));
+    let mut stmt = conn
+        .prepare(&format!(
+            "{CERTIFICATION_SELECT}
+            WHERE e.employment_status = 'current' AND p.is_active = 1
+              AND lc.expiry_date IS NOT NULL AND lc.expiry_date <= ?1
+              AND (?2 IS NULL OR lc.program_id = ?2)
+            ORDER BY lc.expiry_date, e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let certifications = stmt
+        .query_map(params![due_by, program_id], |row| {
+            map_certification(row, date)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(certifications)
+}
+
+/// Current employees whose certification has expired or expires within
+/// `within_days` (30 by default) of the given date, for refresher training
+#[tauri::command]
+pub fn get_refresher_due(
+    _state: State<AppState>,
+    date: Option<String>,
+    within_days: Option<i64>,
+    program_id: Option<i64>,
+) -> Result<Vec<Certification>, String> {
+    let conn = db::get_connection()?;
+
+    let date = report_date(date)?;
+    refresher_due(&conn, date, within_days.unwrap_or(30), program_id)
+}
+
+#[tauri::command]
+pub fn export_refresher_due_report(
+    _state: State<AppState>,
+    date: Option<String>,
+    within_days: Option<i64>,
+    program_id: Option<i64>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let date = report_date(date)?;
+    let within_days = within_days.unwrap_or(30);
+    let due = refresher_due(&conn, date, within_days, program_id)?;
+    if due.is_empty() {
+        return Err("No employee is due for refresher training".to_string());
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Refresher Training Due",
+        vec![
+            TableColumn::new("Employee", 110.0, Align::Left),
+            TableColumn::new("ESSID", 50.0, Align::Left),
+            TableColumn::new("Job post", 70.0, Align::Left),
+            TableColumn::new("Program", 100.0, Align::Left),
+            TableColumn::new("Certified", 58.0, Align::Left),
+            TableColumn::new("Expires", 58.0, Align::Left),
+            TableColumn::new("Status", 77.0, Align::Left),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    report.subtitle_lines.push(format!(
+        "As on {}, expiring within {within_days} days",
+        format_date(date)
+    ));
+
+    for certification in &due {
+        let days = certification.days_to_expiry.unwrap_or_default();
+        report.rows.push(vec![
+            certification.employee_name.clone(),
+            certification.essid.clone(),
+            certification.job_post.clone().unwrap_or_default(),
+            certification.program_name.clone(),
+            certification.certified_on.clone().unwrap_or_default(),
+            certification.expiry_date.clone().unwrap_or_default(),
+            if days < 0 {
+                format!("Expired {} days", -days)
+            } else {
+                format!("Due in {days} days")
+            },
+        ]);
+    }
+    let expired = due.iter().filter(|c| c.status == "expired").count();
+    report
+        .footer_lines
+        .push(format!("Due: {}    Already expired: {expired}", due.len()));
+
+    report
+        .render(Orientation::Portrait)
+        .save(&PathBuf::from(&export_path))
+}
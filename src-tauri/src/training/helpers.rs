@@ -0,0 +1,183 @@
+use chrono::NaiveDate;
+use rusqlite::Row;
+
+use super::types::{Certification, TrainingAttendance, TrainingProgram, TrainingSession};
+
+pub const ATTENDANCE_STATUSES: [&str; 4] = ["enrolled", "completed", "absent", "failed"];
+
+pub const PROGRAM_SELECT: &str = "SELECT id, code, name, description, validity_months, is_active,
+        created_at, updated_at
+    FROM training_programs";
+
+pub const SESSION_SELECT: &str = "SELECT s.id, s.program_id, p.code, p.name, s.start_date,
+        s.end_date, s.trainer, s.venue, s.is_refresher, s.remarks, s.created_at, s.updated_at
+    FROM training_sessions s
+    JOIN training_programs p ON p.id = s.program_id";
+
+pub const ATTENDANCE_SELECT: &str = "SELECT a.id, a.session_id, a.employee_id, e.name, e.essid,
+        a.status, a.certificate_number, a.certified_on, a.expiry_date, a.attachment_id,
+        a.remarks, a.created_at, a.updated_at
+    FROM training_attendance a
+    JOIN employees e ON e.id = a.employee_id";
+
+pub const CERTIFICATION_SELECT: &str = "SELECT lc.id, lc.session_id, lc.employee_id, e.name,
+        e.essid, e.job_post, lc.program_id, p.code, p.name, lc.certificate_number,
+        lc.certified_on, lc.expiry_date, lc.attachment_id
+    FROM latest_certifications lc
+    JOIN employees e ON e.id = lc.employee_id
+    JOIN training_programs p ON p.id = lc.program_id";
+
+pub fn validate_attendance_status(status: &str) -> Result<(), String> {
+    if ATTENDANCE_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid attendance status '{status}', expected one of: {}",
+            ATTENDANCE_STATUSES.join(", ")
+        ))
+    }
+}
+
+pub fn map_program(row: &Row) -> rusqlite::Result<TrainingProgram> {
+    Ok(TrainingProgram {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        validity_months: row.get(4)?,
+        is_active: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn get_program_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<TrainingProgram>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{PROGRAM_SELECT} WHERE id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_program) {
+        Ok(program) => Ok(Some(program)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn map_session(row: &Row) -> rusqlite::Result<TrainingSession> {
+    Ok(TrainingSession {
+        id: row.get(0)?,
+        program_id: row.get(1)?,
+        program_code: row.get(2)?,
+        program_name: row.get(3)?,
+        start_date: row.get(4)?,
+        end_date: row.get(5)?,
+        trainer: row.get(6)?,
+        venue: row.get(7)?,
+        is_refresher: row.get(8)?,
+        remarks: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+        attendance: Vec::new(),
+    })
+}
+
+pub fn map_attendance(row: &Row) -> rusqlite::Result<TrainingAttendance> {
+    Ok(TrainingAttendance {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        employee_id: row.get(2)?,
+        employee_name: row.get(3)?,
+        essid: row.get(4)?,
+        status: row.get(5)?,
+        certificate_number: row.get(6)?,
+        certified_on: row.get(7)?,
+        expiry_date: row.get(8)?,
+        attachment_id: row.get(9)?,
+        remarks: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+pub fn session_attendance(
+    conn: &rusqlite::Connection,
+    session_id: i64,
+) -> Result<Vec<TrainingAttendance>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ATTENDANCE_SELECT} WHERE a.session_id = ?1 ORDER BY e.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+    let attendance = stmt
+        .query_map([session_id], map_attendance)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(attendance)
+}
+
+pub fn get_session_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<TrainingSession>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{SESSION_SELECT} WHERE s.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_session) {
+        Ok(mut session) => {
+            session.attendance = session_attendance(conn, id)?;
+            Ok(Some(session))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_attendance_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<TrainingAttendance>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ATTENDANCE_SELECT} WHERE a.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_attendance) {
+        Ok(attendance) => Ok(Some(attendance)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Map a certification, judging its validity on `date`
+pub fn map_certification(row: &Row, date: NaiveDate) -> rusqlite::Result<Certification> {
+    let expiry_date: Option<String> = row.get(11)?;
+    let days_to_expiry = expiry_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|expiry| (expiry - date).num_days());
+    Ok(Certification {
+        attendance_id: row.get(0)?,
+        session_id: row.get(1)?,
+        employee_id: row.get(2)?,
+        employee_name: row.get(3)?,
+        essid: row.get(4)?,
+        job_post: row.get(5)?,
+        program_id: row.get(6)?,
+        program_code: row.get(7)?,
+        program_name: row.get(8)?,
+        certificate_number: row.get(9)?,
+        certified_on: row.get(10)?,
+        expiry_date,
+        attachment_id: row.get(12)?,
+        days_to_expiry,
+        status: if days_to_expiry.is_some_and(|days| days < 0) {
+            "expired".to_string()
+        } else {
+            "valid".to_string()
+        },
+    })
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingProgram {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Months a certification stays valid, empty when it never lapses
+    pub validity_months: Option<i64>,
+    pub is_active: i32,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingProgramInput {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub validity_months: Option<i64>,
+    pub is_active: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingAttendance {
+    pub id: i64,
+    pub session_id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    /// enrolled, completed, absent or failed
+    pub status: String,
+    pub certificate_number: Option<String>,
+    pub certified_on: Option<String>,
+    pub expiry_date: Option<String>,
+    /// Certificate stored with the employee's attachments
+    pub attachment_id: Option<i64>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingAttendanceInput {
+    pub employee_id: i64,
+    pub status: String,
+    pub certificate_number: Option<String>,
+    /// Last day of the session when empty
+    pub certified_on: Option<String>,
+    /// Derived from the program's validity when empty
+    pub expiry_date: Option<String>,
+    /// Certificate file to store as an attachment
+    pub certificate_path: Option<String>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingSession {
+    pub id: i64,
+    pub program_id: i64,
+    pub program_code: String,
+    pub program_name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub trainer: Option<String>,
+    pub venue: Option<String>,
+    pub is_refresher: i32,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub attendance: Vec<TrainingAttendance>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingSessionInput {
+    pub program_id: i64,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub trainer: Option<String>,
+    pub venue: Option<String>,
+    pub is_refresher: Option<i32>,
+    pub remarks: Option<String>,
+}
+
+/// Latest completion of a program by an employee
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Certification {
+    pub attendance_id: i64,
+    pub session_id: i64,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub job_post: Option<String>,
+    pub program_id: i64,
+    pub program_code: String,
+    pub program_name: String,
+    pub certificate_number: Option<String>,
+    pub certified_on: Option<String>,
+    pub expiry_date: Option<String>,
+    pub attachment_id: Option<i64>,
+    /// Days from the report date to expiry, negative once expired
+    pub days_to_expiry: Option<i64>,
+    /// valid or expired
+    pub status: String,
+}
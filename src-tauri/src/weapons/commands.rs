@@ -0,0 +1,699 @@
+use super::helpers::{
+    assignment_on_date, get_assignment_by_id, get_movement_by_id, get_weapon_by_id, map_assignment,
+    map_movement, map_weapon, rounds_outstanding, ASSIGNMENT_SELECT, MOVEMENTS, MOVEMENT_SELECT,
+    WEAPON_SELECT,
+};
+use super::types::{
+    AmmunitionInput, AmmunitionMovement, ArmedRole, Weapon, WeaponAssignment,
+    WeaponAssignmentInput, WeaponInput,
+};
+use crate::clients::helpers::get_site_by_id;
+use crate::company::helpers::load_company_profile;
+use crate::dates::{format_date, month_bounds, parse_date, today};
+use crate::db;
+use crate::employees::helpers::get_employee_by_id;
+use crate::payslips::helpers::month_title;
+use crate::pdf::{Align, Orientation, TableColumn, TableReport};
+use crate::state::AppState;
+use chrono::Duration;
+use rusqlite::{params, Connection, Result, Row};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::State;
+
+fn validate_weapon(weapon: &WeaponInput) -> Result<String, String> {
+    if weapon.weapon_type.trim().is_empty() {
+        return Err("Weapon type is required".to_string());
+    }
+    if weapon.weapon_number.trim().is_empty() {
+        return Err("Weapon number is required".to_string());
+    }
+    if weapon.licence_number.trim().is_empty() {
+        return Err("Licence number is required".to_string());
+    }
+    Ok(format_date(parse_date(
+        &weapon.licence_expiry,
+        "licence expiry",
+    )?))
+}
+
+fn map_weapon_error(e: rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            "A weapon with this number already exists".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+/// Weapons with their current holder. `expiring_within_days` limits the list
+/// to weapons whose licence has expired or expires within that many days.
+#[tauri::command]
+pub fn get_weapons(
+    _state: State<AppState>,
+    active_only: Option<bool>,
+    expiring_within_days: Option<i64>,
+) -> Result<Vec<Weapon>, String> {
+    let conn = db::get_connection()?;
+
+    let expiring_by =
+        expiring_within_days.map(|days| format_date(today() + Duration::days(days.max(0))));
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{WEAPON_SELECT}
+            WHERE (?1 = 0 OR w.is_active = 1) AND (?2 IS NULL OR w.licence_expiry <= ?2)
+            ORDER BY w.weapon_type COLLATE NOCASE, w.weapon_number COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let weapons = stmt
+        .query_map(
+            params![active_only.unwrap_or(false), expiring_by],
+            map_weapon,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(weapons)
+}
+
+#[tauri::command]
+pub fn create_weapon(_state: State<AppState>, weapon: WeaponInput) -> Result<Weapon, String> {
+    let conn = db::get_connection()?;
+
+    let licence_expiry = validate_weapon(&weapon)?;
+
+    conn.execute(
+        "INSERT INTO weapons (
+            weapon_type, weapon_number, calibre, licence_number, issuing_authority,
+            licence_expiry, is_active, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            weapon.weapon_type.trim(),
+            weapon.weapon_number.trim(),
+            weapon.calibre,
+            weapon.licence_number.trim(),
+            weapon.issuing_authority,
+            licence_expiry,
+            weapon.is_active.unwrap_or(1),
+            weapon.remarks,
+        ],
+    )
+    .map_err(map_weapon_error)?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(weapon)) = get_weapon_by_id(&conn, id) {
+        Ok(weapon)
+    } else {
+        Err("Failed to retrieve weapon".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_weapon(
+    _state: State<AppState>,
+    id: i64,
+    weapon: WeaponInput,
+) -> Result<Weapon, String> {
+    let conn = db::get_connection()?;
+
+    let licence_expiry = validate_weapon(&weapon)?;
+    let existing = get_weapon_by_id(&conn, id)?.ok_or("Weapon not found")?;
+    if weapon.is_active == Some(0) && existing.assignment_id.is_some() {
+        return Err("Take the weapon back before marking it inactive".to_string());
+    }
+
+    conn.execute(
+        "UPDATE weapons SET
+            weapon_type = ?1, weapon_number = ?2, calibre = ?3, licence_number = ?4,
+            issuing_authority = ?5, licence_expiry = ?6, is_active = ?7, remarks = ?8,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?9",
+        params![
+            weapon.weapon_type.trim(),
+            weapon.weapon_number.trim(),
+            weapon.calibre,
+            weapon.licence_number.trim(),
+            weapon.issuing_authority,
+            licence_expiry,
+            weapon.is_active.unwrap_or(existing.is_active),
+            weapon.remarks,
+            id,
+        ],
+    )
+    .map_err(map_weapon_error)?;
+
+    if let Ok(Some(weapon)) = get_weapon_by_id(&conn, id) {
+        Ok(weapon)
+    } else {
+        Err("Failed to retrieve weapon".to_string())
+    }
+}
+
+/// Only weapons that never appeared in the register can be deleted, others
+/// are marked inactive
+#[tauri::command]
+pub fn delete_weapon(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM weapons WHERE id = ?1", params![id])
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _)
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                "The weapon has entries in the register, mark it inactive instead".to_string()
+            }
+            e => e.to_string(),
+        })?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_weapon_assignments(
+    _state: State<AppState>,
+    weapon_id: Option<i64>,
+    employee_id: Option<i64>,
+) -> Result<Vec<WeaponAssignment>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{ASSIGNMENT_SELECT}
+            WHERE (?1 IS NULL OR a.weapon_id = ?1) AND (?2 IS NULL OR a.employee_id = ?2)
+            ORDER BY a.issued_on DESC, a.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let assignments = stmt
+        .query_map(params![weapon_id, employee_id], map_assignment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(assignments)
+}
+
+/// Issue a weapon to an employee for duty at a site. The weapon must be
+/// active, free and licensed on the issue date.
+#[tauri::command]
+pub fn assign_weapon(
+    _state: State<AppState>,
+    assignment: WeaponAssignmentInput,
+) -> Result<WeaponAssignment, String> {
+    let conn = db::get_connection()?;
+
+    let issued_on = format_date(parse_date(&assignment.issued_on, "issue date")?);
+
+    let weapon = get_weapon_by_id(&conn, assignment.weapon_id)?.ok_or("Weapon not found")?;
+    if weapon.is_active == 0 {
+        return Err(format!("Weapon {} is inactive", weapon.weapon_number));
+    }
+    if weapon.licence_expiry < issued_on {
+        return Err(format!(
+            "Licence {} of weapon {} expired on {}, renew it before issuing the weapon",
+            weapon.licence_number, weapon.weapon_number, weapon.licence_expiry
+        ));
+    }
+
+    let employee =
+        get_employee_by_id(&conn, assignment.employee_id)?.ok_or("Employee not found")?;
+    if employee.employment_status != "current" {
+        return Err(format!("{} is not a current employee", employee.name));
+    }
+    let site = get_site_by_id(&conn, assignment.site_id)?.ok_or("Site not found")?;
+    if site.is_active == 0 {
+        return Err(format!("Site {} is inactive", site.name));
+    }
+
+    let overlapping: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM weapon_assignments
+             WHERE weapon_id = ?1 AND (returned_on IS NULL OR returned_on >= ?2)",
+            params![assignment.weapon_id, issued_on],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if overlapping > 0 {
+        return Err(
+            "Weapon is already issued on or after this date, take it back first".to_string(),
+        );
+    }
+
+    conn.execute(
+        "INSERT INTO weapon_assignments (weapon_id, employee_id, site_id, issued_on, remarks)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            assignment.weapon_id,
+            assignment.employee_id,
+            assignment.site_id,
+            issued_on,
+            assignment.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(assignment)) = get_assignment_by_id(&conn, id) {
+        Ok(assignment)
+    } else {
+        Err("Failed to retrieve weapon assignment".to_string())
+    }
+}
+
+/// Take a weapon back from its holder once all issued ammunition is returned
+#[tauri::command]
+pub fn return_weapon(
+    _state: State<AppState>,
+    id: i64,
+    returned_on: String,
+    remarks: Option<String>,
+) -> Result<WeaponAssignment, String> {
+    let conn = db::get_connection()?;
+
+    let returned_on = format_date(parse_date(&returned_on, "return date")?);
+
+    let assignment = get_assignment_by_id(&conn, id)?.ok_or("Weapon assignment not found")?;
+    if assignment.returned_on.is_some() {
+        return Err("Weapon has already been returned".to_string());
+    }
+    if returned_on < assignment.issued_on {
+        return Err(format!(
+            "Return date must not be before the issue date {}",
+            assignment.issued_on
+        ));
+    }
+    let outstanding = rounds_outstanding(&conn, assignment.weapon_id, assignment.employee_id)?;
+    if outstanding > 0 {
+        return Err(format!(
+            "{} still holds {outstanding} rounds for this weapon, record their return first",
+            assignment.employee_name
+        ));
+    }
+
+    conn.execute(
+        "UPDATE weapon_assignments SET
+            returned_on = ?1, remarks = COALESCE(?2, remarks), updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?3",
+        params![returned_on, remarks, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(Some(assignment)) = get_assignment_by_id(&conn, id) {
+        Ok(assignment)
+    } else {
+        Err("Failed to retrieve weapon assignment".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_ammunition_movements(
+    _state: State<AppState>,
+    weapon_id: Option<i64>,
+    employee_id: Option<i64>,
+    month: Option<String>,
+) -> Result<Vec<AmmunitionMovement>, String> {
+    let conn = db::get_connection()?;
+
+    let bounds = match month.filter(|m| !m.trim().is_empty()) {
+        Some(month) => {
+            let (first, last) = month_bounds(&month)?;
+            Some((format_date(first), format_date(last)))
+        }
+        None => None,
+    };
+    let (from, to) = bounds.unzip();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{MOVEMENT_SELECT}
+            WHERE (?1 IS NULL OR m.weapon_id = ?1) AND (?2 IS NULL OR m.employee_id = ?2)
+              AND (?3 IS NULL OR m.movement_date BETWEEN ?3 AND ?4)
+            ORDER BY m.movement_date DESC, m.id DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let movements = stmt
+        .query_map(params![weapon_id, employee_id, from, to], map_movement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(movements)
+}
+
+/// Log rounds issued with a weapon to its holder, or returned by them
+#[tauri::command]
+pub fn record_ammunition_movement(
+    _state: State<AppState>,
+    movement: AmmunitionInput,
+) -> Result<AmmunitionMovement, String> {
+    let conn = db::get_connection()?;
+
+    let movement_date = format_date(parse_date(&movement.movement_date, "date")?);
+    let kind = movement.movement.trim().to_lowercase();
+    if !MOVEMENTS.contains(&kind.as_str()) {
+        return Err(format!(
+            "Invalid ammunition movement '{}', expected one of: {}",
+            movement.movement,
+            MOVEMENTS.join(", ")
+        ));
+    }
+    if movement.rounds <= 0 {
+        return Err("Rounds must be at least one".to_string());
+    }
+
+    let weapon = get_weapon_by_id(&conn, movement.weapon_id)?.ok_or("Weapon not found")?;
+    let employee = get_employee_by_id(&conn, movement.employee_id)?.ok_or("Employee not found")?;
+    if kind == "issue" {
+        if assignment_on_date(&conn, weapon.id, employee.id, &movement_date)?.is_none() {
+            return Err(format!(
+                "Weapon {} is not issued to {} on {movement_date}",
+                weapon.weapon_number, employee.name
+            ));
+        }
+    } else {
+        let outstanding = rounds_outstanding(&conn, weapon.id, employee.id)?;
+        if movement.rounds > outstanding {
+            return Err(format!(
+                "{} holds only {outstanding} rounds for weapon {}",
+                employee.name, weapon.weapon_number
+            ));
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO ammunition_movements (
+            weapon_id, employee_id, movement_date, movement, rounds, remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            weapon.id,
+            employee.id,
+            movement_date,
+            kind,
+            movement.rounds,
+            movement.remarks,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    if let Ok(Some(movement)) = get_movement_by_id(&conn, id) {
+        Ok(movement)
+    } else {
+        Err("Failed to retrieve ammunition entry".to_string())
+    }
+}
+
+fn map_armed_role(row: &Row) -> rusqlite::Result<ArmedRole> {
+    Ok(ArmedRole {
+        id: row.get(0)?,
+        role: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+#[tauri::command]
+pub fn get_armed_roles(_state: State<AppState>) -> Result<Vec<ArmedRole>, String> {
+    let conn = db::get_connection()?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, role, created_at FROM armed_roles ORDER BY role COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+
+    let roles = stmt
+        .query_map([], map_armed_role)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(roles)
+}
+
+/// Mark a deployment role as armed, so that deploying to it needs a licensed
+/// weapon
+#[tauri::command]
+pub fn save_armed_role(_state: State<AppState>, role: String) -> Result<ArmedRole, String> {
+    let conn = db::get_connection()?;
+
+    let role = role.trim();
+    if role.is_empty() {
+        return Err("Role is required".to_string());
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO armed_roles (role) VALUES (?1)",
+        [role],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT id, role, created_at FROM armed_roles WHERE role = ?1",
+        [role],
+        map_armed_role,
+    )
+    .map_err(|_| "Failed to retrieve armed role".to_string())
+}
+
+#[tauri::command]
+pub fn delete_armed_role(_state: State<AppState>, id: i64) -> Result<(), String> {
+    let conn = db::get_connection()?;
+
+    conn.execute("DELETE FROM armed_roles WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Register of active weapons with their licence and the holder and rounds
+/// outstanding on `date` (today by default)
+#[tauri::command]
+pub fn export_weapon_register_pdf(
+    _state: State<AppState>,
+    date: Option<String>,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let date = match date.filter(|d| !d.trim().is_empty()) {
+        Some(date) => format_date(parse_date(&date, "date")?),
+        None => format_date(today()),
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.weapon_type, w.weapon_number, w.calibre, w.licence_number,
+                w.issuing_authority, w.licence_expiry, e.name, e.essid, s.name, a.issued_on,
+                (SELECT IFNULL(SUM(CASE m.movement WHEN 'issue' THEN m.rounds ELSE -m.rounds END), 0)
+                 FROM ammunition_movements m
+                 WHERE m.weapon_id = w.id AND m.movement_date <= ?1)
+            FROM weapons w
+            LEFT JOIN weapon_assignments a ON a.weapon_id = w.id AND a.issued_on <= ?1
+                AND (a.returned_on IS NULL OR a.returned_on >= ?1)
+            LEFT JOIN employees e ON e.id = a.employee_id
+            LEFT JOIN sites s ON s.id = a.site_id
+            WHERE w.is_active = 1
+            ORDER BY w.weapon_type COLLATE NOCASE, w.weapon_number COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let weapons = stmt
+        .query_map([&date], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<String>>(8)?,
+                row.get::<_, Option<String>>(9)?,
+                row.get::<_, i64>(10)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if weapons.is_empty() {
+        return Err("No active weapons in the register".to_string());
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Register of Weapons",
+        vec![
+            TableColumn::new("Sl.", 20.0, Align::Right),
+            TableColumn::new("Weapon", 90.0, Align::Left),
+            TableColumn::new("Number", 70.0, Align::Left),
+            TableColumn::new("Calibre", 45.0, Align::Left),
+            TableColumn::new("Licence no.", 80.0, Align::Left),
+            TableColumn::new("Issuing authority", 90.0, Align::Left),
+            TableColumn::new("Valid till", 55.0, Align::Left),
+            TableColumn::new("Licence", 50.0, Align::Left),
+            TableColumn::new("Issued to", 90.0, Align::Left),
+            TableColumn::new("Site", 85.0, Align::Left),
+            TableColumn::new("Since", 55.0, Align::Left),
+            TableColumn::new("Rounds", 40.0, Align::Right),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    report.subtitle_lines.push(format!("As on {date}"));
+
+    let mut expired = 0;
+    let mut issued = 0;
+    for (index, weapon) in weapons.iter().enumerate() {
+        let (kind, number, calibre, licence, authority, expiry, name, essid, site, since, rounds) =
+            weapon;
+        if expiry.as_str() < date.as_str() {
+            expired += 1;
+        }
+        if name.is_some() {
+            issued += 1;
+        }
+        report.rows.push(vec![
+            (index + 1).to_string(),
+            kind.clone(),
+            number.clone(),
+            calibre.clone().unwrap_or_default(),
+            licence.clone(),
+            authority.clone().unwrap_or_default(),
+            expiry.clone(),
+            if expiry.as_str() < date.as_str() {
+                "EXPIRED".to_string()
+            } else {
+                "Valid".to_string()
+            },
+            match (name, essid) {
+                (Some(name), Some(essid)) => format!("{name} ({essid})"),
+                _ => "In armoury".to_string(),
+            },
+            site.clone().unwrap_or_default(),
+            since.clone().unwrap_or_default(),
+            rounds.to_string(),
+        ]);
+    }
+    report.footer_lines.push(format!(
+        "Weapons: {}    Issued: {issued}    Licence expired: {expired}",
+        weapons.len()
+    ));
+    report
+        .footer_lines
+        .push("Signature of licensee/manager: ____________________".to_string());
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
+
+/// Ammunition issued and returned in a month, with the rounds held by guards
+/// per weapon after each entry
+#[tauri::command]
+pub fn export_ammunition_register_pdf(
+    _state: State<AppState>,
+    month: String,
+    export_path: String,
+) -> Result<String, String> {
+    let conn = db::get_connection()?;
+
+    let (first, last) = month_bounds(&month)?;
+    let (first, last) = (format_date(first), format_date(last));
+
+    let mut balances = opening_balances(&conn, &first)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{MOVEMENT_SELECT}
+            WHERE m.movement_date BETWEEN ?1 AND ?2
+            ORDER BY m.movement_date, m.id"
+        ))
+        .map_err(|e| e.to_string())?;
+    let movements = stmt
+        .query_map(params![first, last], map_movement)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if movements.is_empty() {
+        return Err(format!(
+            "No ammunition was issued or returned in {}",
+            month_title(month.trim())
+        ));
+    }
+    let profile = load_company_profile(&conn)?;
+
+    let mut report = TableReport::new(
+        "Register of Ammunition Issued and Returned",
+        vec![
+            TableColumn::new("Date", 55.0, Align::Left),
+            TableColumn::new("Weapon", 90.0, Align::Left),
+            TableColumn::new("Number", 75.0, Align::Left),
+            TableColumn::new("Guard", 110.0, Align::Left),
+            TableColumn::new("ESSID", 50.0, Align::Left),
+            TableColumn::new("Issued", 45.0, Align::Right),
+            TableColumn::new("Returned", 50.0, Align::Right),
+            TableColumn::new("Held by guards", 70.0, Align::Right),
+            TableColumn::new("Remarks", 180.0, Align::Left),
+        ],
+    );
+    report.subtitle_lines.push(profile.name.clone());
+    report
+        .subtitle_lines
+        .push(format!("Month: {}", month_title(month.trim())));
+
+    let (mut total_issued, mut total_returned) = (0, 0);
+    for movement in &movements {
+        let balance = balances.entry(movement.weapon_id).or_insert(0);
+        let (issued, returned) = if movement.movement == "issue" {
+            *balance += movement.rounds;
+            total_issued += movement.rounds;
+            (movement.rounds.to_string(), String::new())
+        } else {
+            *balance -= movement.rounds;
+            total_returned += movement.rounds;
+            (String::new(), movement.rounds.to_string())
+        };
+        report.rows.push(vec![
+            movement.movement_date.clone(),
+            movement.weapon_type.clone(),
+            movement.weapon_number.clone(),
+            movement.employee_name.clone(),
+            movement.essid.clone(),
+            issued,
+            returned,
+            balance.to_string(),
+            movement.remarks.clone().unwrap_or_default(),
+        ]);
+    }
+    report.footer_lines.push(format!(
+        "Rounds issued: {total_issued}    Rounds returned: {total_returned}"
+    ));
+    report
+        .footer_lines
+        .push("Signature of licensee/manager: ____________________".to_string());
+
+    report
+        .render(Orientation::Landscape)
+        .save(&PathBuf::from(&export_path))
+}
+
+/// Rounds held by guards per weapon before `date`
+fn opening_balances(conn: &Connection, date: &str) -> Result<HashMap<i64, i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT weapon_id,
+                SUM(CASE movement WHEN 'issue' THEN rounds ELSE -rounds END)
+            FROM ammunition_movements WHERE movement_date < ?1
+            GROUP BY weapon_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let balances = stmt
+        .query_map([date], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(balances)
+}
@@ -0,0 +1,323 @@
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::types::{AmmunitionMovement, Weapon, WeaponAssignment};
+
+pub const MOVEMENTS: [&str; 2] = ["issue", "return"];
+
+/// Weapons with their open assignment and the rounds outstanding against them
+pub const WEAPON_SELECT: &str = "SELECT w.id, w.weapon_type, w.weapon_number, w.calibre,
+        w.licence_number, w.issuing_authority, w.licence_expiry, w.is_active, w.remarks,
+        a.id, a.employee_id, e.name, s.name,
+        (SELECT IFNULL(SUM(CASE m.movement WHEN 'issue' THEN m.rounds ELSE -m.rounds END), 0)
+         FROM ammunition_movements m WHERE m.weapon_id = w.id),
+        w.created_at, w.updated_at
+    FROM weapons w
+    LEFT JOIN weapon_assignments a ON a.weapon_id = w.id AND a.returned_on IS NULL
+    LEFT JOIN employees e ON e.id = a.employee_id
+    LEFT JOIN sites s ON s.id = a.site_id";
+
+pub const ASSIGNMENT_SELECT: &str = "SELECT a.id, a.weapon_id, w.weapon_type, w.weapon_number,
+        a.employee_id, e.name, e.essid, a.site_id, s.name, c.name,
+        a.issued_on, a.returned_on, a.remarks, a.created_at, a.updated_at
+    FROM weapon_assignments a
+    JOIN weapons w ON w.id = a.weapon_id
+    JOIN employees e ON e.id = a.employee_id
+    JOIN sites s ON s.id = a.site_id
+    JOIN clients c ON c.id = s.client_id";
+
+pub const MOVEMENT_SELECT: &str = "SELECT m.id, m.weapon_id, w.weapon_type, w.weapon_number,
+        m.employee_id, e.name, e.essid, m.movement_date, m.movement, m.rounds, m.remarks,
+        m.created_at
+    FROM ammunition_movements m
+    JOIN weapons w ON w.id = m.weapon_id
+    JOIN employees e ON e.id = m.employee_id";
+
+/// Assignment covering `?2` of weapon or employee `?1`
+const ASSIGNED_ON_DATE: &str =
+    "a.issued_on <= ?2 AND (a.returned_on IS NULL OR a.returned_on >= ?2)";
+
+pub fn map_weapon(row: &Row) -> rusqlite::Result<Weapon> {
+    Ok(Weapon {
+        id: row.get(0)?,
+        weapon_type: row.get(1)?,
+        weapon_number: row.get(2)?,
+        calibre: row.get(3)?,
+        licence_number: row.get(4)?,
+        issuing_authority: row.get(5)?,
+        licence_expiry: row.get(6)?,
+        is_active: row.get(7)?,
+        remarks: row.get(8)?,
+        assignment_id: row.get(9)?,
+        holder_employee_id: row.get(10)?,
+        holder_name: row.get(11)?,
+        holder_site_name: row.get(12)?,
+        rounds_outstanding: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+pub fn get_weapon_by_id(conn: &rusqlite::Connection, id: i64) -> Result<Option<Weapon>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{WEAPON_SELECT} WHERE w.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_weapon) {
+        Ok(weapon) => Ok(Some(weapon)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn map_assignment(row: &Row) -> rusqlite::Result<WeaponAssignment> {
+    Ok(WeaponAssignment {
+        id: row.get(0)?,
+        weapon_id: row.get(1)?,
+        weapon_type: row.get(2)?,
+        weapon_number: row.get(3)?,
+        employee_id: row.get(4)?,
+        employee_name: row.get(5)?,
+        essid: row.get(6)?,
+        site_id: row.get(7)?,
+        site_name: row.get(8)?,
+        client_name: row.get(9)?,
+        issued_on: row.get(10)?,
+        returned_on: row.get(11)?,
+        remarks: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+    })
+}
+
+pub fn get_assignment_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<WeaponAssignment>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{ASSIGNMENT_SELECT} WHERE a.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_assignment) {
+        Ok(assignment) => Ok(Some(assignment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn map_movement(row: &Row) -> rusqlite::Result<AmmunitionMovement> {
+    Ok(AmmunitionMovement {
+        id: row.get(0)?,
+        weapon_id: row.get(1)?,
+        weapon_type: row.get(2)?,
+        weapon_number: row.get(3)?,
+        employee_id: row.get(4)?,
+        employee_name: row.get(5)?,
+        essid: row.get(6)?,
+        movement_date: row.get(7)?,
+        movement: row.get(8)?,
+        rounds: row.get(9)?,
+        remarks: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+pub fn get_movement_by_id(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<AmmunitionMovement>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{MOVEMENT_SELECT} WHERE m.id = ?1"))
+        .map_err(|e| e.to_string())?;
+
+    match stmt.query_row([id], map_movement) {
+        Ok(movement) => Ok(Some(movement)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Weapon of `weapon_id` issued to `employee_id` on `date`, as the assignment id
+pub fn assignment_on_date(
+    conn: &rusqlite::Connection,
+    weapon_id: i64,
+    employee_id: i64,
+    date: &str,
+) -> Result<Option<i64>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT a.id FROM weapon_assignments a
+            WHERE a.weapon_id = ?1 AND {ASSIGNED_ON_DATE} AND a.employee_id = ?3"
+        ),
+        params![weapon_id, date, employee_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Rounds of the weapon issued to the employee and not yet returned
+pub fn rounds_outstanding(
+    conn: &rusqlite::Connection,
+    weapon_id: i64,
+    employee_id: i64,
+) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT IFNULL(SUM(CASE movement WHEN 'issue' THEN rounds ELSE -rounds END), 0)
+        FROM ammunition_movements WHERE weapon_id = ?1 AND employee_id = ?2",
+        params![weapon_id, employee_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn is_armed_role(conn: &rusqlite::Connection, role: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM armed_roles WHERE role = ?1)",
+        [role.trim()],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Why the employee may not work an armed role on `date`, if they may not:
+/// they need a weapon issued to them whose licence is valid on that date
+pub fn armed_licence_problem(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    employee_name: &str,
+    role: &str,
+    date: &str,
+) -> Result<Option<String>, String> {
+    if role.trim().is_empty() || !is_armed_role(conn, role)? {
+        return Ok(None);
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT w.weapon_number, w.licence_number, w.licence_expiry
+            FROM weapon_assignments a
+            JOIN weapons w ON w.id = a.weapon_id
+            WHERE a.employee_id = ?1 AND {ASSIGNED_ON_DATE}
+            ORDER BY w.licence_expiry DESC"
+        ))
+        .map_err(|e| e.to_string())?;
+    let weapons = stmt
+        .query_map(params![employee_id, date], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(match weapons.first() {
+        None => Some(format!(
+            "{} is an armed role but no weapon is issued to {employee_name} on {date}",
+            role.trim()
+        )),
+        Some((number, licence, expiry)) if expiry.as_str() < date => Some(format!(
+            "Licence {licence} of weapon {number} expired on {expiry}, {employee_name} cannot work as {}",
+            role.trim()
+        )),
+        Some(_) => None,
+    })
+}
+
+/// An armed role may only be deployed or rostered on `date` when the employee
+/// holds a weapon whose licence is valid on that date
+pub fn ensure_armed_licence(
+    conn: &rusqlite::Connection,
+    employee_id: i64,
+    employee_name: &str,
+    role: &str,
+    date: &str,
+) -> Result<(), String> {
+    match armed_licence_problem(conn, employee_id, employee_name, role, date)? {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    /// Ravi holds a pistol from June 2024 until it is returned at the end of
+    /// September; its licence expires in August
+    fn with_weapon() -> rusqlite::Connection {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO employees (name, essid, employment_status) VALUES ('Ravi', 'E1', 'current')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO clients (name) VALUES ('Acme')", [])
+            .unwrap();
+        conn.execute("INSERT INTO sites (client_id, name) VALUES (1, 'Bank')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO weapons (weapon_type, weapon_number, licence_number, licence_expiry)
+            VALUES ('Pistol', 'P-101', 'LIC/77', '2024-08-31')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO weapon_assignments (weapon_id, employee_id, site_id, issued_on, returned_on)
+            VALUES (1, 1, 1, '2024-06-01', '2024-09-30')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn unarmed_roles_need_no_weapon() {
+        let conn = with_weapon();
+        assert!(ensure_armed_licence(&conn, 1, "Ravi", "Guard", "2025-01-01").is_ok());
+        assert!(ensure_armed_licence(&conn, 1, "Ravi", " ", "2025-01-01").is_ok());
+        assert!(is_armed_role(&conn, " armed guard ").unwrap());
+    }
+
+    #[test]
+    fn armed_roles_need_a_weapon_with_a_valid_licence() {
+        let conn = with_weapon();
+
+        assert!(ensure_armed_licence(&conn, 1, "Ravi", "Gunman", "2024-07-15").is_ok());
+        assert!(ensure_armed_licence(&conn, 1, "Ravi", "Gunman", "2024-08-31").is_ok());
+        assert_eq!(
+            armed_licence_problem(&conn, 1, "Ravi", "Gunman", "2024-09-01").unwrap(),
+            Some(
+                "Licence LIC/77 of weapon P-101 expired on 2024-08-31, Ravi cannot work as Gunman"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            armed_licence_problem(&conn, 1, "Ravi", "Gunman", "2024-05-31").unwrap(),
+            Some(
+                "Gunman is an armed role but no weapon is issued to Ravi on 2024-05-31".to_string()
+            )
+        );
+        assert!(ensure_armed_licence(&conn, 1, "Ravi", "Gunman", "2024-10-01").is_err());
+    }
+
+    #[test]
+    fn rounds_outstanding_nets_returns_against_issues() {
+        let conn = with_weapon();
+        conn.execute(
+            "INSERT INTO ammunition_movements (weapon_id, employee_id, movement_date, movement, rounds)
+            VALUES (1, 1, '2024-06-01', 'issue', 10), (1, 1, '2024-06-30', 'return', 4)",
+            [],
+        )
+        .unwrap();
+        assert_eq!(rounds_outstanding(&conn, 1, 1).unwrap(), 6);
+        assert_eq!(
+            assignment_on_date(&conn, 1, 1, "2024-06-15").unwrap(),
+            Some(1)
+        );
+        assert_eq!(assignment_on_date(&conn, 1, 1, "2024-10-01").unwrap(), None);
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Weapon {
+    pub id: i64,
+    /// Kind of firearm, e.g. 12 bore DBBL gun or .32 revolver
+    pub weapon_type: String,
+    pub weapon_number: String,
+    pub calibre: Option<String>,
+    pub licence_number: String,
+    pub issuing_authority: Option<String>,
+    pub licence_expiry: String,
+    pub is_active: i32,
+    pub remarks: Option<String>,
+    /// Open assignment, if the weapon is with an employee now
+    pub assignment_id: Option<i64>,
+    pub holder_employee_id: Option<i64>,
+    pub holder_name: Option<String>,
+    pub holder_site_name: Option<String>,
+    /// Rounds issued with the weapon and not yet returned
+    pub rounds_outstanding: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponInput {
+    pub weapon_type: String,
+    pub weapon_number: String,
+    pub calibre: Option<String>,
+    pub licence_number: String,
+    pub issuing_authority: Option<String>,
+    pub licence_expiry: String,
+    pub is_active: Option<i32>,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponAssignment {
+    pub id: i64,
+    pub weapon_id: i64,
+    pub weapon_type: String,
+    pub weapon_number: String,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub site_id: i64,
+    pub site_name: String,
+    pub client_name: String,
+    pub issued_on: String,
+    pub returned_on: Option<String>,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponAssignmentInput {
+    pub weapon_id: i64,
+    pub employee_id: i64,
+    pub site_id: i64,
+    pub issued_on: String,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmmunitionMovement {
+    pub id: i64,
+    pub weapon_id: i64,
+    pub weapon_type: String,
+    pub weapon_number: String,
+    pub employee_id: i64,
+    pub employee_name: String,
+    pub essid: String,
+    pub movement_date: String,
+    /// issue or return
+    pub movement: String,
+    pub rounds: i64,
+    pub remarks: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmmunitionInput {
+    pub weapon_id: i64,
+    pub employee_id: i64,
+    pub movement_date: String,
+    pub movement: String,
+    pub rounds: i64,
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmedRole {
+    pub id: i64,
+    pub role: String,
+    pub created_at: Option<String>,
+}
@@ -0,0 +1,428 @@
+use std::fs;
+use std::path::Path;
+
+/// Excel limits sheet names to 31 characters
+const MAX_SHEET_NAME: usize = 31;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl Cell {
+    pub fn text(value: impl Into<String>) -> Self {
+        Cell::Text(value.into())
+    }
+
+    pub fn number(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+struct Row {
+    cells: Vec<Cell>,
+    bold: bool,
+}
+
+pub struct Sheet {
+    name: String,
+    column_widths: Vec<f64>,
+    rows: Vec<Row>,
+}
+
+impl Sheet {
+    pub fn new(name: &str) -> Self {
+        let name: String = name
+            .chars()
+            .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+            .take(MAX_SHEET_NAME)
+            .collect();
+        Self {
+            name: if name.trim().is_empty() {
+                "Sheet".to_string()
+            } else {
+                name
+            },
+            column_widths: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Column widths in characters, from the first column onwards
+    pub fn set_column_widths(&mut self, widths: &[f64]) {
+        self.column_widths = widths.to_vec();
+    }
+
+    pub fn add_row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(Row { cells, bold: false });
+    }
+
+    pub fn add_bold_row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(Row { cells, bold: true });
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">",
+        );
+
+        if !self.column_widths.is_empty() {
+            xml.push_str("<cols>");
+            for (i, width) in self.column_widths.iter().enumerate() {
+                xml.push_str(&format!(
+                    "<col min=\"{0}\" max=\"{0}\" width=\"{width}\" customWidth=\"1\"/>",
+                    i + 1
+                ));
+            }
+            xml.push_str("</cols>");
+        }
+
+        xml.push_str("<sheetData>");
+        for (r, row) in self.rows.iter().enumerate() {
+            xml.push_str(&format!("<row r=\"{}\">", r + 1));
+            let style = if row.bold { " s=\"1\"" } else { "" };
+            for (c, cell) in row.cells.iter().enumerate() {
+                let reference = format!("{}{}", column_name(c), r + 1);
+                match cell {
+                    Cell::Text(text) => xml.push_str(&format!(
+                        "<c r=\"{reference}\" t=\"inlineStr\"{style}><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        escape_xml(text)
+                    )),
+                    Cell::Number(value) if value.is_finite() => xml.push_str(&format!(
+                        "<c r=\"{reference}\"{style}><v>{value}</v></c>"
+                    )),
+                    Cell::Number(_) | Cell::Empty => {}
+                }
+            }
+            xml.push_str("</row>");
+        }
+        xml.push_str("</sheetData></worksheet>");
+
+        xml
+    }
+}
+
+/// A minimal XLSX workbook: text and number cells, bold rows and column widths
+#[derive(Default)]
+pub struct Workbook {
+    sheets: Vec<Sheet>,
+}
+
+impl Workbook {
+    pub fn new() -> Self {
+        Self { sheets: Vec::new() }
+    }
+
+    pub fn add_sheet(&mut self, sheet: Sheet) {
+        self.sheets.push(sheet);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sheet_count = self.sheets.len().max(1);
+
+        let mut content_types = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             <Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+        );
+        let mut workbook = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets>",
+        );
+        let mut workbook_rels = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        );
+
+        let mut sheet_files = Vec::new();
+        for i in 1..=sheet_count {
+            let (name, xml) = match self.sheets.get(i - 1) {
+                Some(sheet) => (sheet.name.clone(), sheet.to_xml()),
+                None => ("Sheet1".to_string(), Sheet::new("Sheet1").to_xml()),
+            };
+            content_types.push_str(&format!(
+                "<Override PartName=\"/xl/worksheets/sheet{i}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+            ));
+            workbook.push_str(&format!(
+                "<sheet name=\"{}\" sheetId=\"{i}\" r:id=\"rId{i}\"/>",
+                escape_xml(&name)
+            ));
+            workbook_rels.push_str(&format!(
+                "<Relationship Id=\"rId{i}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet{i}.xml\"/>"
+            ));
+            sheet_files.push((format!("xl/worksheets/sheet{i}.xml"), xml));
+        }
+
+        content_types.push_str("</Types>");
+        workbook.push_str("</sheets></workbook>");
+        workbook_rels.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/></Relationships>",
+            sheet_count + 1
+        ));
+
+        let root_rels = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+             </Relationships>";
+
+        // Style 0 is the default, style 1 is bold
+        let styles = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+             <fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font>\
+             <font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>\
+             <fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill>\
+             <fill><patternFill patternType=\"gray125\"/></fill></fills>\
+             <borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+             <cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+             <cellXfs count=\"2\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+             <xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/></cellXfs>\
+             </styleSheet>";
+
+        let mut zip = ZipWriter::new();
+        zip.add("[Content_Types].xml", content_types.as_bytes());
+        zip.add("_rels/.rels", root_rels.as_bytes());
+        zip.add("xl/workbook.xml", workbook.as_bytes());
+        zip.add("xl/_rels/workbook.xml.rels", workbook_rels.as_bytes());
+        zip.add("xl/styles.xml", styles.as_bytes());
+        for (name, xml) in &sheet_files {
+            zip.add(name, xml.as_bytes());
+        }
+        zip.finish()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<String, String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("Failed to write XLSX: {e}"))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+}
+
+/// Spreadsheet column letters for a zero-based index: A..Z, AA..
+fn column_name(index: usize) -> String {
+    let mut index = index + 1;
+    let mut name = Vec::new();
+    while index > 0 {
+        let rem = (index - 1) % 26;
+        name.push(b'A' + rem as u8);
+        index = (index - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .fold(String::with_capacity(text.len()), |mut out, c| {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                c => out.push(c),
+            }
+            out
+        })
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Just enough of the ZIP format for an XLSX package: uncompressed entries
+/// with a fixed timestamp
+struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    // 1980-01-01 00:00 in MS-DOS format
+    const DOS_TIME: u16 = 0;
+    const DOS_DATE: u16 = 0x21;
+
+    fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            central_directory: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    fn add(&mut self, name: &str, content: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32(content);
+        let size = content.len() as u32;
+        let name = name.as_bytes();
+
+        self.data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.data.extend_from_slice(&Self::DOS_TIME.to_le_bytes());
+        self.data.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        self.data.extend_from_slice(&crc.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        self.data.extend_from_slice(name);
+        self.data.extend_from_slice(content);
+
+        let cd = &mut self.central_directory;
+        cd.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        cd.extend_from_slice(&0u16.to_le_bytes()); // flags
+        cd.extend_from_slice(&0u16.to_le_bytes()); // stored
+        cd.extend_from_slice(&Self::DOS_TIME.to_le_bytes());
+        cd.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        cd.extend_from_slice(&crc.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        cd.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        cd.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        cd.extend_from_slice(&offset.to_le_bytes());
+        cd.extend_from_slice(name);
+
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let cd_offset = self.data.len() as u32;
+        let cd_size = self.central_directory.len() as u32;
+        self.data.extend_from_slice(&self.central_directory);
+
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // disk with central directory
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&cd_size.to_le_bytes());
+        self.data.extend_from_slice(&cd_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], at: usize) -> usize {
+        u16::from_le_bytes([data[at], data[at + 1]]) as usize
+    }
+
+    fn u32_at(data: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+    }
+
+    /// Read every entry of a stored ZIP archive through its central directory,
+    /// checking each local header and CRC on the way
+    fn read_zip(data: &[u8]) -> Vec<(String, String)> {
+        let end = data.len() - 22;
+        assert_eq!(u32_at(data, end), 0x0605_4b50, "end of central directory");
+        let entries = u16_at(data, end + 10);
+        let cd_size = u32_at(data, end + 12) as usize;
+        let mut at = u32_at(data, end + 16) as usize;
+        assert_eq!(at + cd_size, end, "central directory ends the archive");
+
+        let mut files = Vec::new();
+        for _ in 0..entries {
+            assert_eq!(u32_at(data, at), 0x0201_4b50, "central directory header");
+            let crc = u32_at(data, at + 16);
+            let size = u32_at(data, at + 20) as usize;
+            assert_eq!(u32_at(data, at + 24) as usize, size);
+            let name_len = u16_at(data, at + 28);
+            let skip = name_len + u16_at(data, at + 30) + u16_at(data, at + 32);
+            let offset = u32_at(data, at + 42) as usize;
+            let name = &data[at + 46..at + 46 + name_len];
+
+            assert_eq!(u32_at(data, offset), 0x0403_4b50, "local file header");
+            assert_eq!(u32_at(data, offset + 14), crc);
+            assert_eq!(u32_at(data, offset + 18) as usize, size);
+            assert_eq!(&data[offset + 30..offset + 30 + name_len], name);
+            let start = offset + 30 + name_len + u16_at(data, offset + 28);
+            let content = &data[start..start + size];
+            assert_eq!(
+                crc32(content),
+                crc,
+                "CRC of {}",
+                String::from_utf8_lossy(name)
+            );
+
+            files.push((
+                String::from_utf8(name.to_vec()).unwrap(),
+                String::from_utf8(content.to_vec()).unwrap(),
+            ));
+            at += 46 + skip;
+        }
+        assert_eq!(at, end);
+        files
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn workbook_is_a_valid_archive() {
+        let mut sheet = Sheet::new("Muster: July");
+        sheet.add_bold_row(vec![Cell::text("Name"), Cell::text("Days")]);
+        sheet.add_row(vec![Cell::text("A & B"), Cell::number(26.0)]);
+        let mut workbook = Workbook::new();
+        workbook.add_sheet(sheet);
+        workbook.add_sheet(Sheet::new("Summary"));
+
+        let files = read_zip(&workbook.to_bytes());
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/styles.xml",
+                "xl/worksheets/sheet1.xml",
+                "xl/worksheets/sheet2.xml",
+            ]
+        );
+        assert!(files[2]
+            .1
+            .contains("<sheet name=\"Muster July\" sheetId=\"1\""));
+        assert!(files[5].1.contains(">A &amp; B</t>"));
+        assert!(files[5].1.contains("<c r=\"B2\"><v>26</v></c>"));
+    }
+
+    #[test]
+    fn column_names() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
+}
@@ -77,4 +77,9 @@ export interface FilterOptions {
   joiningDateTo?: string;
   joiningDateFrom?: string;
   employmentStatus?: string;
+  policeVerification?: string;
+  certification?: string;
+  minExperienceMonths?: number;
+  experienceRole?: string;
+  exServiceman?: boolean;
 }